@@ -42,6 +42,21 @@ setReferralFee() {
     --gas-limit=10000000 --send --proxy=${PROXY} --chain=1
 }
 
+# Update the fee of several referrals in one call (only owner)
+# Usage: setReferralFeeBatch <referral_id1> <fee1> [<referral_id2> <fee2> ...]
+# An unknown referral id reverts the whole batch
+setReferralFeeBatch() {
+    args=""
+    while [ $# -gt 1 ]; do
+        args="${args} $1 $2"
+        shift 2
+    done
+    mxpy contract call ${ADDRESS} --function=setReferralFeeBatch \
+    --arguments ${args} \
+    --ledger \
+    --gas-limit=20000000 --send --proxy=${PROXY} --chain=1
+}
+
 # Enable or disable a referral (only owner)
 # Usage: setReferralActive <referral_id> <active>
 # active: true or false
@@ -65,6 +80,108 @@ setReferralOwner() {
     --gas-limit=10000000 --send --proxy=${PROXY} --chain=1
 }
 
+# Set the minimum output amount before a referral earns its fee share (only owner)
+# Usage: setReferralMinTrade <referral_id> <min_trade>
+setReferralMinTrade() {
+    referral_id=$1
+    min_trade=$2
+    mxpy contract call ${ADDRESS} --function=setReferralMinTrade \
+    --arguments ${referral_id} ${min_trade} \
+    --ledger \
+    --gas-limit=10000000 --send --proxy=${PROXY} --chain=1
+}
+
+# Set progressive referral fees by trade size (only owner)
+# Usage: setReferralFeeTiers <referral_id> [<min_output1> <fee1> <min_output2> <fee2> ...]
+# Thresholds must be ascending; without tiers the flat referral fee applies again
+setReferralFeeTiers() {
+    referral_id=$1
+    shift
+    args=""
+    while [ $# -gt 1 ]; do
+        args="${args} $1 $2"
+        shift 2
+    done
+    mxpy contract call ${ADDRESS} --function=setReferralFeeTiers \
+    --arguments ${referral_id} ${args} \
+    --ledger \
+    --gas-limit=10000000 --send --proxy=${PROXY} --chain=1
+}
+
+# Boost a referral's fee between two epochs, both inclusive (only owner)
+# Usage: setReferralCampaign <referral_id> <fee> <start_epoch> <end_epoch>
+setReferralCampaign() {
+    referral_id=$1
+    fee=$2
+    start_epoch=$3
+    end_epoch=$4
+    mxpy contract call ${ADDRESS} --function=setReferralCampaign \
+    --arguments ${referral_id} ${fee} ${start_epoch} ${end_epoch} \
+    --ledger \
+    --gas-limit=10000000 --send --proxy=${PROXY} --chain=1
+}
+
+# End a referral's campaign early (only owner)
+# Usage: clearReferralCampaign <referral_id>
+clearReferralCampaign() {
+    referral_id=$1
+    mxpy contract call ${ADDRESS} --function=clearReferralCampaign \
+    --arguments ${referral_id} \
+    --ledger \
+    --gas-limit=10000000 --send --proxy=${PROXY} --chain=1
+}
+
+# Restrict a referral's fee share to specific output tokens (only owner)
+# Usage: addReferralAllowedTokens <referral_id> <token1> [<token2> ...]
+addReferralAllowedTokens() {
+    referral_id=$1
+    shift
+    args=""
+    for token in "$@"; do
+        args="${args} str:${token}"
+    done
+    mxpy contract call ${ADDRESS} --function=addReferralAllowedTokens \
+    --arguments ${referral_id} ${args} \
+    --ledger \
+    --gas-limit=10000000 --send --proxy=${PROXY} --chain=1
+}
+
+# Remove output tokens from a referral's allowed set (only owner)
+# Usage: removeReferralAllowedTokens <referral_id> <token1> [<token2> ...]
+# The last token cannot be removed, use clearReferralAllowedTokens instead
+removeReferralAllowedTokens() {
+    referral_id=$1
+    shift
+    args=""
+    for token in "$@"; do
+        args="${args} str:${token}"
+    done
+    mxpy contract call ${ADDRESS} --function=removeReferralAllowedTokens \
+    --arguments ${referral_id} ${args} \
+    --ledger \
+    --gas-limit=10000000 --send --proxy=${PROXY} --chain=1
+}
+
+# Lift a referral's token restriction, so it earns on every output token (only owner)
+# Usage: clearReferralAllowedTokens <referral_id>
+clearReferralAllowedTokens() {
+    referral_id=$1
+    mxpy contract call ${ADDRESS} --function=clearReferralAllowedTokens \
+    --arguments ${referral_id} \
+    --ledger \
+    --gas-limit=10000000 --send --proxy=${PROXY} --chain=1
+}
+
+# Set the inactivity period, in epochs, after which referral balances can be swept (only owner)
+# Usage: setReferralStaleEpochs <epochs>   (0 = never)
+setReferralStaleEpochs() {
+    epochs=$1
+    mxpy contract call ${ADDRESS} --function=setReferralStaleEpochs \
+    --arguments ${epochs} \
+    --ledger \
+    --gas-limit=10000000 --send --proxy=${PROXY} --chain=1
+}
+
 # Set the static fee for trades without a referral (only owner)
 # Usage: setStaticFee <fee>
 # fee is in basis points (e.g., 100 = 1%, 500 = 5%, 10000 = 100%)
@@ -76,6 +193,333 @@ setStaticFee() {
     --gas-limit=10000000 --send --proxy=${PROXY} --chain=1
 }
 
+# Override the static fee for one output token (only owner)
+# Usage: setStaticFeeOverride <token> <fee>
+# fee is in basis points (e.g., 100 = 1%, 500 = 5%, 10000 = 100%)
+setStaticFeeOverride() {
+    token=$1
+    fee=$2
+    mxpy contract call ${ADDRESS} --function=setStaticFeeOverride \
+    --arguments str:${token} ${fee} \
+    --ledger \
+    --gas-limit=10000000 --send --proxy=${PROXY} --chain=1
+}
+
+# Remove a token's static fee override, falling back to the global fee (only owner)
+# Usage: clearStaticFeeOverride <token>
+clearStaticFeeOverride() {
+    token=$1
+    mxpy contract call ${ADDRESS} --function=clearStaticFeeOverride \
+    --arguments str:${token} \
+    --ledger \
+    --gas-limit=10000000 --send --proxy=${PROXY} --chain=1
+}
+
+# Route xExchange swaps of token pairs to local pairs, a zero pair address drops the override (only owner)
+# Usage: setPairOverridesBatch <first_token1> <second_token1> <pair1> [<first_token2> <second_token2> <pair2> ...]
+setPairOverridesBatch() {
+    args=""
+    while [ $# -ge 3 ]; do
+        args="${args} str:$1 str:$2 $3"
+        shift 3
+    done
+    mxpy contract call ${ADDRESS} --function=setPairOverridesBatch \
+    --arguments ${args} \
+    --ledger \
+    --gas-limit=20000000 --send --proxy=${PROXY} --chain=1
+}
+
+# Route Hatom calls of hTokens to local markets, a zero market address drops the override (only owner)
+# Usage: setMarketOverridesBatch <h_token1> <market1> [<h_token2> <market2> ...]
+setMarketOverridesBatch() {
+    args=""
+    while [ $# -ge 2 ]; do
+        args="${args} str:$1 $2"
+        shift 2
+    done
+    mxpy contract call ${ADDRESS} --function=setMarketOverridesBatch \
+    --arguments ${args} \
+    --ledger \
+    --gas-limit=20000000 --send --proxy=${PROXY} --chain=1
+}
+
+# Whitelist contract endpoints that xoCallback may call (only owner)
+# Usage: addCallbackTargets <address1> <endpoint1> [<address2> <endpoint2> ...]
+addCallbackTargets() {
+    args=""
+    while [ $# -ge 2 ]; do
+        args="${args} addr:${1} str:${2}"
+        shift 2
+    done
+    mxpy contract call ${ADDRESS} --function=addCallbackTargets \
+    --arguments ${args} \
+    --ledger \
+    --gas-limit=10000000 --send --proxy=${PROXY} --chain=1
+}
+
+# Remove contract endpoints from the xoCallback whitelist (only owner)
+# Usage: removeCallbackTargets <address1> <endpoint1> [<address2> <endpoint2> ...]
+removeCallbackTargets() {
+    args=""
+    while [ $# -ge 2 ]; do
+        args="${args} addr:${1} str:${2}"
+        shift 2
+    done
+    mxpy contract call ${ADDRESS} --function=removeCallbackTargets \
+    --arguments ${args} \
+    --ledger \
+    --gas-limit=10000000 --send --proxy=${PROXY} --chain=1
+}
+
+# Enable or disable free mode, taking no fees regardless of fee settings (only owner)
+# Usage: setFreeMode <enabled>
+# enabled: true or false
+setFreeMode() {
+    enabled=$1
+    mxpy contract call ${ADDRESS} --function=setFreeMode \
+    --arguments ${enabled} \
+    --ledger \
+    --gas-limit=10000000 --send --proxy=${PROXY} --chain=1
+}
+
+# Take fees from the payment tokens before the path runs instead of from the output (only owner)
+# Usage: setFeeOnInput <enabled>
+# enabled: true or false
+setFeeOnInput() {
+    enabled=$1
+    mxpy contract call ${ADDRESS} --function=setFeeOnInput \
+    --arguments ${enabled} \
+    --ledger \
+    --gas-limit=10000000 --send --proxy=${PROXY} --chain=1
+}
+
+# Enable or disable drain mode: referral fee shares go to admin, referral balances stay claimable (only owner)
+# Usage: setDrainMode <enabled>
+# enabled: true or false
+setDrainMode() {
+    enabled=$1
+    mxpy contract call ${ADDRESS} --function=setDrainMode \
+    --arguments ${enabled} \
+    --ledger \
+    --gas-limit=10000000 --send --proxy=${PROXY} --chain=1
+}
+
+# Enable or disable per-instruction gas profiling events (only owner)
+# Usage: setGasProfiling <enabled>
+# enabled: true or false
+setGasProfiling() {
+    enabled=$1
+    mxpy contract call ${ADDRESS} --function=setGasProfiling \
+    --arguments ${enabled} \
+    --ledger \
+    --gas-limit=10000000 --send --proxy=${PROXY} --chain=1
+}
+
+# Enable or disable automatic EGLD/WEGLD conversion of instruction inputs (only owner)
+# Usage: setAutoWrapEnabled <enabled>
+# enabled: true or false
+setAutoWrapEnabled() {
+    enabled=$1
+    mxpy contract call ${ADDRESS} --function=setAutoWrapEnabled \
+    --arguments ${enabled} \
+    --ledger \
+    --gas-limit=10000000 --send --proxy=${PROXY} --chain=1
+}
+
+# Enable or disable counting distinct callers in getUniqueCallers (only owner)
+# Usage: setUniqueCallerTracking <enabled>
+# enabled: true or false; each new caller then costs one storage entry
+setUniqueCallerTracking() {
+    enabled=$1
+    mxpy contract call ${ADDRESS} --function=setUniqueCallerTracking \
+    --arguments ${enabled} \
+    --ledger \
+    --gas-limit=10000000 --send --proxy=${PROXY} --chain=1
+}
+
+# Mark tokens as fee-on-transfer so their vault balances follow the real holdings (only owner)
+# Usage: addFeeOnTransferTokens <token1> [<token2> ...]
+addFeeOnTransferTokens() {
+    args=""
+    for token in "$@"; do
+        args="${args} str:${token}"
+    done
+    mxpy contract call ${ADDRESS} --function=addFeeOnTransferTokens \
+    --arguments ${args} \
+    --ledger \
+    --gas-limit=10000000 --send --proxy=${PROXY} --chain=1
+}
+
+# Stop reconciling balances for fee-on-transfer tokens (only owner)
+# Usage: removeFeeOnTransferTokens <token1> [<token2> ...]
+removeFeeOnTransferTokens() {
+    args=""
+    for token in "$@"; do
+        args="${args} str:${token}"
+    done
+    mxpy contract call ${ADDRESS} --function=removeFeeOnTransferTokens \
+    --arguments ${args} \
+    --ledger \
+    --gas-limit=10000000 --send --proxy=${PROXY} --chain=1
+}
+
+# Cache token decimals from token/decimals pairs (only owner)
+# Usage: setTokenDecimals <token1> <decimals1> [<token2> <decimals2> ...]
+setTokenDecimals() {
+    args=""
+    while [ $# -ge 2 ]; do
+        args="${args} str:$1 $2"
+        shift 2
+    done
+    mxpy contract call ${ADDRESS} --function=setTokenDecimals \
+    --arguments ${args} \
+    --ledger \
+    --gas-limit=10000000 --send --proxy=${PROXY} --chain=1
+}
+
+# Usage: removeTokenDecimals <token1> [<token2> ...]
+removeTokenDecimals() {
+    args=""
+    for token in "$@"; do
+        args="${args} str:${token}"
+    done
+    mxpy contract call ${ADDRESS} --function=removeTokenDecimals \
+    --arguments ${args} \
+    --ledger \
+    --gas-limit=10000000 --send --proxy=${PROXY} --chain=1
+}
+
+# Cap the gas forwarded to DEX sub-calls for a compact action (only owner)
+# Usage: setActionGasLimit <action_byte> <gas_limit>
+# gas_limit of 0 removes the cap (all remaining gas is forwarded)
+setActionGasLimit() {
+    action=$1
+    gas_limit=$2
+    mxpy contract call ${ADDRESS} --function=setActionGasLimit \
+    --arguments ${action} ${gas_limit} \
+    --ledger \
+    --gas-limit=10000000 --send --proxy=${PROXY} --chain=1
+}
+
+# Set the minimum output of internal pool calls for a category, 0 = built-in default (only owner)
+# Usage: setInternalMinOutput <category> <min_amount>  (0 = Swap, 1 = Liquidity)
+setInternalMinOutput() {
+    category=$1
+    min_amount=$2
+    mxpy contract call ${ADDRESS} --function=setInternalMinOutput \
+    --arguments ${category} ${min_amount} \
+    --ledger \
+    --gas-limit=10000000 --send --proxy=${PROXY} --chain=1
+}
+
+# Limit how many instructions may withdraw the same token in one aggregation (only owner)
+# Usage: setMaxSplitsPerToken <max_splits>
+# max_splits of 0 disables the limit
+setMaxSplitsPerToken() {
+    max_splits=$1
+    mxpy contract call ${ADDRESS} --function=setMaxSplitsPerToken \
+    --arguments ${max_splits} \
+    --ledger \
+    --gas-limit=10000000 --send --proxy=${PROXY} --chain=1
+}
+
+# Reject trades whose min_amount_out is below a PPM of the input's spot value (only owner)
+# Usage: setMinOutputPpm <ppm>
+# ppm: 1_000_000 = 100%, 0 disables the check
+setMinOutputPpm() {
+    ppm=$1
+    mxpy contract call ${ADDRESS} --function=setMinOutputPpm \
+    --arguments ${ppm} \
+    --ledger \
+    --gas-limit=10000000 --send --proxy=${PROXY} --chain=1
+}
+
+# Check xExchange ZAP reserves against the safe price (TWAP) view (only owner)
+# Usage: setZapOracle <safe_price_view_address> <tolerance_ppm>
+# tolerance_ppm: 1_000_000 = 100%, e.g. 20000 = 2%
+setZapOracle() {
+    oracle=$1
+    tolerance_ppm=$2
+    mxpy contract call ${ADDRESS} --function=setZapOracle \
+    --arguments addr:${oracle} ${tolerance_ppm} \
+    --ledger \
+    --gas-limit=10000000 --send --proxy=${PROXY} --chain=1
+}
+
+# Stop checking ZAP reserves against the oracle (only owner)
+# Usage: clearZapOracle
+clearZapOracle() {
+    mxpy contract call ${ADDRESS} --function=clearZapOracle \
+    --ledger \
+    --gas-limit=10000000 --send --proxy=${PROXY} --chain=1
+}
+
+# Skip ZAP pre-swaps of a token below a minimum amount (only owner)
+# Usage: setMinZapSwap <token> <min_amount>
+# 0 swaps whenever the inputs are imbalanced
+setMinZapSwap() {
+    token=$1
+    min_amount=$2
+    mxpy contract call ${ADDRESS} --function=setMinZapSwap \
+    --arguments str:${token} ${min_amount} \
+    --ledger \
+    --gas-limit=10000000 --send --proxy=${PROXY} --chain=1
+}
+
+# Stake the path's last output when token_out is this liquid staking token (only owner)
+# Usage: setAutoStake <token> <action>  (20 = xEGLD, 21 = LXOXNO, 22 = Hatom sEGLD)
+setAutoStake() {
+    token=$1
+    action=$2
+    mxpy contract call ${ADDRESS} --function=setAutoStake \
+    --arguments str:${token} ${action} \
+    --ledger \
+    --gas-limit=10000000 --send --proxy=${PROXY} --chain=1
+}
+
+# Usage: clearAutoStake <token>
+clearAutoStake() {
+    token=$1
+    mxpy contract call ${ADDRESS} --function=clearAutoStake \
+    --arguments str:${token} \
+    --ledger \
+    --gas-limit=10000000 --send --proxy=${PROXY} --chain=1
+}
+
+# Set the global Jex stable swap min multiplier (only owner)
+# Usage: setJexStableMinMultiplier <multiplier>
+# 0 restores the built-in default
+setJexStableMinMultiplier() {
+    multiplier=$1
+    mxpy contract call ${ADDRESS} --function=setJexStableMinMultiplier \
+    --arguments ${multiplier} \
+    --ledger \
+    --gas-limit=10000000 --send --proxy=${PROXY} --chain=1
+}
+
+# Override the Jex stable swap min multiplier for one pool (only owner)
+# Usage: setJexStablePoolMinMultiplier <pool_address> <multiplier>
+# 0 falls back to the global multiplier
+setJexStablePoolMinMultiplier() {
+    pool=$1
+    multiplier=$2
+    mxpy contract call ${ADDRESS} --function=setJexStablePoolMinMultiplier \
+    --arguments addr:${pool} ${multiplier} \
+    --ledger \
+    --gas-limit=10000000 --send --proxy=${PROXY} --chain=1
+}
+
+# Cap the Jex stable swap and add minimums at a PPM fraction of the input (only owner)
+# Usage: setJexStableMinInputPpm <ppm>
+# ppm: 0-1000000, 0 disables the cap
+setJexStableMinInputPpm() {
+    ppm=$1
+    mxpy contract call ${ADDRESS} --function=setJexStableMinInputPpm \
+    --arguments ${ppm} \
+    --ledger \
+    --gas-limit=10000000 --send --proxy=${PROXY} --chain=1
+}
+
 # Claim accumulated admin fees (only owner)
 # Usage: claimAdminFees <recipient_address>
 claimAdminFees() {
@@ -85,6 +529,67 @@ claimAdminFees() {
     --ledger --send --proxy=${PROXY} --chain=1
 }
 
+# Claim accumulated admin fees for tokens above a per-token threshold (only owner)
+# Usage: claimAdminFeesAboveThreshold <recipient_address> <token1> <threshold1> [<token2> <threshold2> ...]
+claimAdminFeesAboveThreshold() {
+    recipient=$1
+    shift
+    args=""
+    while [ $# -gt 1 ]; do
+        args="${args} str:$1 $2"
+        shift 2
+    done
+    mxpy contract call ${ADDRESS} --function=claimAdminFeesAboveThreshold \
+    --arguments addr:${recipient} ${args} \
+    --ledger --send --proxy=${PROXY} --chain=1
+}
+
+# Seed the EGLD fee liability after upgrading, page by page (only owner)
+# Owner 0 is the admin fees, the others are referral ids; start at 0 and continue
+# from getEgldLiabilitySeedCursor until isEgldLiabilitySeeded
+# Usage: seedEgldFeeLiability <from> <count>
+seedEgldFeeLiability() {
+    from=$1
+    count=$2
+    mxpy contract call ${ADDRESS} --function=seedEgldFeeLiability \
+    --arguments ${from} ${count} \
+    --ledger \
+    --gas-limit=50000000 --send --proxy=${PROXY} --chain=1
+}
+
+# Send native EGLD left on the contract to a recipient (only owner)
+# Usage: rescueEgld <recipient_address> [amount]
+# Without amount all EGLD not owed as admin or referral fees is swept
+# Reverts until seedEgldFeeLiability has covered every referral
+rescueEgld() {
+    recipient=$1
+    amount=$2
+    mxpy contract call ${ADDRESS} --function=rescueEgld \
+    --arguments addr:${recipient} ${amount} \
+    --ledger --send --proxy=${PROXY} --chain=1
+}
+
+# Accept migrated balances from the aggregator being replaced (only owner, on the new contract)
+# Usage: setMigrationSource <old_contract_address>
+setMigrationSource() {
+    source=$1
+    mxpy contract call ${ADDRESS} --function=setMigrationSource \
+    --arguments ${source} \
+    --ledger \
+    --gas-limit=10000000 --send --proxy=${PROXY} --chain=1
+}
+
+# Move admin fees and referrals to a new aggregator in the same shard (only owner)
+# Usage: migrateBalances <new_contract_address> [<referral_id1> <referral_id2> ...]
+migrateBalances() {
+    new_contract=$1
+    shift
+    mxpy contract call ${ADDRESS} --function=migrateBalances \
+    --arguments ${new_contract} "$@" \
+    --ledger \
+    --gas-limit=300000000 --send --proxy=${PROXY} --chain=1
+}
+
 # Claim accumulated referral fees (can be called by referral owner)
 # Usage: claimReferralFees <referral_id>
 claimReferralFees() {
@@ -93,3 +598,24 @@ claimReferralFees() {
     --arguments ${referral_id} \
     --ledger --send --proxy=${PROXY} --chain=1
 }
+
+# Set the referral applied when a trade passes referral_id 0 (0 clears it)
+# Usage: setMyReferral <referral_id>
+setMyReferral() {
+    referral_id=$1
+    mxpy contract call ${ADDRESS} --function=setMyReferral \
+    --arguments ${referral_id} \
+    --ledger \
+    --gas-limit=10000000 --send --proxy=${PROXY} --chain=1
+}
+
+# Sweep a stale referral's balances to a treasury (only owner)
+# Usage: sweepStaleReferral <referral_id> <treasury>
+sweepStaleReferral() {
+    referral_id=$1
+    treasury=$2
+    mxpy contract call ${ADDRESS} --function=sweepStaleReferral \
+    --arguments ${referral_id} ${treasury} \
+    --ledger \
+    --gas-limit=30000000 --send --proxy=${PROXY} --chain=1
+}
@@ -6,6 +6,7 @@ multiversx_sc::derive_imports!();
 pub mod config;
 pub mod constants;
 pub mod errors;
+pub mod events;
 pub mod proxies;
 pub mod storage;
 pub mod types;
@@ -13,6 +14,17 @@ pub mod utils;
 pub mod vault;
 pub mod zap;
 
+use constants::{
+    DEFAULT_CALLBACK_GAS, JEX_STABLE_MIN_MULTIPLIER, MIN_INTERNAL_OUTPUT, ONE_DEX_ROUTER,
+    TOTAL_FEE, XEXCHANGE_FEE_DENOM,
+};
+use errors::{
+    ERR_ARB_NOT_ROUND_TRIP, ERR_CALLBACK_NOT_WHITELISTED, ERR_LP_TO_CALLER_CHARGES_FEES,
+    ERR_PAIR_FEE_NOT_FOUND, ERR_PAIR_FEE_NO_TIER, ERR_PRECISION_EXCEEDS_DECIMALS,
+};
+use types::{
+    ActionCategory, CompactAction, Instruction, PathOutput, PathStats, ZapMath, ZapVenueMath,
+};
 use utils::{AddressRegistry, AmountRegistry, TokenRegistry};
 use vault::Vault;
 
@@ -24,12 +36,31 @@ use vault::Vault;
 /// - LP to Token burning
 /// - LP to LP conversion
 #[multiversx_sc::contract]
-pub trait Aggregator: storage::Storage + config::Config + utils::Utils {
+pub trait Aggregator: storage::Storage + config::Config + utils::Utils + events::Events {
+    /// A fresh deployment owes no fees, so its EGLD fee liability starts seeded
     #[init]
-    fn init(&self) {}
+    fn init(&self) {
+        self.egld_liability_seeded().set(true);
+    }
 
+    /// Config mappers added since the initial deployment read empty as "disabled" or
+    /// "no limit", except those with a built-in default: the internal minimum output
+    /// per category, the Jex stable min multiplier and the callback gas limit are written
+    /// here, so their views report the value in force.
+    /// `set_if_empty` keeps operator values across upgrades.
+    ///
+    /// The EGLD fee liability is not seeded here, as walking every referral could run
+    /// out of gas; page through them with `seedEgldFeeLiability` after upgrading.
     #[upgrade]
-    fn upgrade(&self) {}
+    fn upgrade(&self) {
+        for category in [ActionCategory::Swap, ActionCategory::Liquidity] {
+            self.internal_min_output(category)
+                .set_if_empty(BigUint::from(MIN_INTERNAL_OUTPUT));
+        }
+        self.jex_stable_min_multiplier()
+            .set_if_empty(JEX_STABLE_MIN_MULTIPLIER);
+        self.callback_gas_limit().set_if_empty(DEFAULT_CALLBACK_GAS);
+    }
 
     // --- Main Aggregation Endpoint ---
 
@@ -39,15 +70,92 @@ pub trait Aggregator: storage::Storage + config::Config + utils::Utils {
     /// Each instruction is 6 bytes encoded as MultiValue6<u8,u8,u8,u8,u8,u8>:
     /// - Byte 0: action type (see CompactAction enum)
     /// - Byte 1: token1 index into tokens registry (or IDX_EGLD for EGLD, IDX_NONE for prev)
-    /// - Byte 2: mode1 (0=All, 1=Prev, 2-127=Fixed amounts[n], 128-255=PPM amounts[n])
+    /// - Byte 2: mode1 (0=All, 1=Prev, 2-95=Fixed amounts[n], 96-127=FixedOrAll amounts[n],
+    ///   128-255=PPM amounts[n])
     /// - Byte 3: token2 index (or IDX_NONE for single input)
     /// - Byte 4: mode2 (or 0 if single input)
     /// - Byte 5: address index (or IDX_AUTO for auto-resolved addresses)
     ///
+    /// A `ClearPrev` instruction (other bytes ignored, address IDX_AUTO) resets the
+    /// previous result so independent path segments cannot chain into each other.
+    ///
+    /// A `SetMinLpOut` instruction (byte 1 = amounts index, address IDX_AUTO) sets the
+    /// minimum LP amount the next add-liquidity instruction must mint.
+    ///
+    /// A `SetZapRatio` instruction (byte 1 = ratio amounts index, byte 3 = tolerance PPM
+    /// amounts index) makes the next ZAP add revert when the pool's second-per-first
+    /// reserve ratio (scaled by 1e18) is off by more than the tolerance, protecting the
+    /// value deposited behind an LP `token_out`.
+    ///
+    /// A `SetMinLpValue` instruction (byte 1 = reference token index, byte 3 = amounts
+    /// index, address IDX_AUTO) makes the next xExchange ZAP add revert unless the minted
+    /// LP is worth at least the amount in the reference token, one of the pool's tokens.
+    /// Shares are valued at the post-add reserves and supply, both halves priced at the
+    /// pool ratio, so an add minting too few shares fails even when it meets a raw
+    /// `SetMinLpOut`.
+    ///
+    /// An `ExpectOutputs` instruction (bytes 1-4 = token indices, IDX_NONE = unused)
+    /// declares the output tokens of the next multi-output call such as a remove
+    /// liquidity. The call then reverts on any other token or a missing one, and in the
+    /// next instruction with a `PrevAmount` input of a declared token, such as a ZAP add
+    /// of both legs, each such input withdraws exactly its leg. The legs are dropped once
+    /// that instruction has drawn on them, so a later `PrevAmount` reads the previous
+    /// single result and never a stale leg.
+    ///
+    /// An `UnwrapOutputs` instruction (other bytes ignored, address IDX_AUTO) unwraps
+    /// the WEGLD outputs of the next call, such as a remove liquidity on a WEGLD pair,
+    /// so they are deposited as EGLD. Declared outputs and `PrevAmount` then see EGLD,
+    /// and a `token_out` of EGLD (IDX_EGLD) receives them without an extra unwrap.
+    ///
+    /// An `AllowFailure` instruction (other bytes ignored, address IDX_AUTO) makes the
+    /// next instruction, which must be a swap, best-effort: if its pool call fails, its
+    /// input is refunded to the recipient after the path instead of reverting, so one
+    /// dry branch of a split route does not sink the others. `min_amount_out` still
+    /// applies to what the remaining branches produce.
+    ///
+    /// A `RequireFullInput` instruction (other bytes ignored, address IDX_AUTO), anywhere
+    /// in the path, makes it revert when any payment token other than `token_out` is
+    /// left in the vault after the last instruction or set aside as the refund of a
+    /// skipped `AllowFailure` swap, guaranteeing the full input was routed.
+    ///
+    /// An `LpToCaller` instruction (other bytes ignored, address IDX_AUTO) sends the LP of
+    /// the next xExchange, OneDex or Jex ZAP add straight to the caller. The pairs mint to
+    /// their sender only, so the contract forwards the LP right after the add; the LP of
+    /// all such adds is checked against `min_amount_out` once the path has run. As no fee
+    /// can be taken from an output that skipped the vault, it is only accepted for trades
+    /// charging no output fee (no active referral, free mode, or fee-on-input).
+    ///
+    /// A `HatomCompound` instruction (same layout as `HatomRedeem`) redeems the input
+    /// hTokens and supplies the underlying to the same market again, depositing the
+    /// refreshed hTokens.
+    ///
+    /// A `HatomEnterMarket` instruction (same layout as `HatomRedeem`) deposits the input
+    /// hTokens at the Hatom controller as collateral for the caller; like other actions
+    /// delivering straight to the caller, its output never enters the vault. It is only
+    /// accepted for fee-free trades, and the deposited hTokens are checked against
+    /// `min_amount_out` with any LP sent to the caller.
+    ///
+    /// An `AshSwapPoolSwapIndexed` instruction ([action, in_tok, in_mode, i, j, addr])
+    /// swaps AshSwap V1 stable coin `i` for coin `j`, for pools where the output token
+    /// alone is ambiguous (repeated or wrapped tokens).
+    ///
+    /// A `ClaimRewards` instruction sends its input to the Xoxno staking claim endpoint
+    /// (address IDX_AUTO) and deposits everything returned, so claimed rewards can be
+    /// routed by later instructions.
+    ///
+    /// A `ClaimAndRestake` instruction (same layout as `ClaimRewards`) claims the same
+    /// way, then delegates every claimed payment to the same staking contract and
+    /// deposits the staked tokens it mints, compounding the rewards.
+    ///
     /// # Arguments
+    /// * `format_version` - Compact format version of the remaining arguments
+    ///   (`FORMAT_VERSION`); any unsupported version is rejected before decoding
     /// * `min_amount_out` - Minimum expected output amount (slippage protection)
-    /// * `token_out` - Output token index into tokens registry (or IDX_EGLD for EGLD)
-    /// * `referral_id` - Referral ID for fee sharing (0 = no referral)
+    /// * `token_out` - Output token index into tokens registry (or IDX_EGLD for EGLD).
+    ///   A liquid staking token opted in with `setAutoStake` may be left out of the path:
+    ///   when the path ends short of it, its last output is staked (e.g. a path ending
+    ///   in EGLD with `token_out` xEGLD)
+    /// * `referral_id` - Referral ID for fee sharing (0 = the caller's `setMyReferral` default, if any)
     /// * `tokens` - Token registry (referenced by index in instructions and token_out)
     /// * `addresses` - Address registry (referenced by index in instructions)
     /// * `amounts` - Values registry (Fixed amounts or PPM values, referenced by mode)
@@ -60,6 +168,93 @@ pub trait Aggregator: storage::Storage + config::Config + utils::Utils {
     #[allow_multiple_var_args]
     fn aggregate(
         &self,
+        format_version: u8,
+        min_amount_out: BigUint<Self::Api>,
+        token_out: u8,
+        referral_id: u64,
+        tokens: MultiValueEncodedCounted<TokenIdentifier<Self::Api>>,
+        addresses: MultiValueEncodedCounted<ManagedAddress<Self::Api>>,
+        amounts: MultiValueEncodedCounted<BigUint<Self::Api>>,
+        instructions: MultiValueEncoded<MultiValue6<u8, u8, u8, u8, u8, u16>>,
+    ) {
+        self.require_format_version(format_version);
+        let referral_id = self.resolve_referral_id(referral_id);
+        self.aggregate_to_caller(
+            min_amount_out,
+            token_out,
+            referral_id,
+            tokens,
+            addresses,
+            amounts,
+            instructions,
+        );
+    }
+
+    /// `xo` with the minimum output given in token units instead of the raw amount
+    ///
+    /// The output token's decimals come from the owner-filled cache (`setTokenDecimals`),
+    /// and the raw minimum is `min_amount_out * 10^(decimals - precision)`: 2.5 USDC
+    /// (6 decimals) is `min_amount_out = 25, precision = 1`. Fails for a token out whose
+    /// decimals are not cached.
+    ///
+    /// # Arguments
+    /// * `precision` - Fractional digits of `min_amount_out`, at most the token decimals
+    /// * Remaining arguments as in `xo`
+    #[payable("*")]
+    #[endpoint(xoScaled)]
+    #[allow_multiple_var_args]
+    fn aggregate_scaled(
+        &self,
+        format_version: u8,
+        min_amount_out: BigUint<Self::Api>,
+        precision: u32,
+        token_out: u8,
+        referral_id: u64,
+        tokens: MultiValueEncodedCounted<TokenIdentifier<Self::Api>>,
+        addresses: MultiValueEncodedCounted<ManagedAddress<Self::Api>>,
+        amounts: MultiValueEncodedCounted<BigUint<Self::Api>>,
+        instructions: MultiValueEncoded<MultiValue6<u8, u8, u8, u8, u8, u16>>,
+    ) {
+        let token_registry: TokenRegistry<Self::Api> = tokens.clone().to_vec();
+        let token_out_id = self.resolve_token_to_id(token_out, &token_registry);
+        let decimals = self.get_decimals(&token_out_id);
+        require!(precision <= decimals, ERR_PRECISION_EXCEEDS_DECIMALS);
+        let raw_min_amount_out = min_amount_out * BigUint::from(10u64).pow(decimals - precision);
+
+        self.aggregate(
+            format_version,
+            raw_min_amount_out,
+            token_out,
+            referral_id,
+            tokens,
+            addresses,
+            amounts,
+            instructions,
+        );
+    }
+
+    /// `xo` followed by a call notifying `callback_address` of the result
+    ///
+    /// Once the output has been sent to the caller, `callback_name` is called on
+    /// `callback_address` with (token_out, amount_out). The aggregator is the sender of
+    /// that call, so the pair itself must be whitelisted, not just the contract. The
+    /// callback is strict: if it fails, the whole aggregation reverts. `amount_out` is 0
+    /// when the output was minted straight to the caller by a pool. The callback gets at
+    /// most `getCallbackGasLimit` gas.
+    ///
+    /// # Arguments
+    /// * `format_version` - As in `xo`
+    /// * `callback_address` - Contract to notify
+    /// * `callback_name` - Endpoint called on it, whitelisted together with the address
+    /// * Remaining arguments as in `xo`
+    #[payable("*")]
+    #[endpoint(xoCallback)]
+    #[allow_multiple_var_args]
+    fn aggregate_with_callback(
+        &self,
+        format_version: u8,
+        callback_address: ManagedAddress<Self::Api>,
+        callback_name: ManagedBuffer<Self::Api>,
         min_amount_out: BigUint<Self::Api>,
         token_out: u8,
         referral_id: u64,
@@ -68,6 +263,292 @@ pub trait Aggregator: storage::Storage + config::Config + utils::Utils {
         amounts: MultiValueEncodedCounted<BigUint<Self::Api>>,
         instructions: MultiValueEncoded<MultiValue6<u8, u8, u8, u8, u8, u16>>,
     ) {
+        self.require_format_version(format_version);
+        let target = types::CallbackTarget {
+            address: callback_address,
+            endpoint: callback_name,
+        };
+        require!(
+            self.callback_whitelist().contains(&target),
+            ERR_CALLBACK_NOT_WHITELISTED
+        );
+
+        let referral_id = self.resolve_referral_id(referral_id);
+        let (token_out_id, amount_out) = self.aggregate_to_caller(
+            min_amount_out,
+            token_out,
+            referral_id,
+            tokens,
+            addresses,
+            amounts,
+            instructions,
+        );
+
+        self.tx()
+            .to(&target.address)
+            .raw_call(target.endpoint)
+            .argument(&token_out_id)
+            .argument(&amount_out)
+            .gas(self.get_callback_gas())
+            .sync_call();
+    }
+
+    /// Body of `xo`: execute, take fees, check slippage and pay the caller
+    /// `referral_id` is already resolved (see `resolve_referral_id`)
+    /// Returns the output token and the amount sent to the caller
+    fn aggregate_to_caller(
+        &self,
+        min_amount_out: BigUint<Self::Api>,
+        token_out: u8,
+        referral_id: u64,
+        tokens: MultiValueEncodedCounted<TokenIdentifier<Self::Api>>,
+        addresses: MultiValueEncodedCounted<ManagedAddress<Self::Api>>,
+        amounts: MultiValueEncodedCounted<BigUint<Self::Api>>,
+        instructions: MultiValueEncoded<MultiValue6<u8, u8, u8, u8, u8, u16>>,
+    ) -> (TokenId<Self::Api>, BigUint<Self::Api>) {
+        let (mut vault, token_out_id, stats) = self.execute_path(
+            &min_amount_out,
+            token_out,
+            referral_id,
+            tokens,
+            addresses,
+            amounts,
+            instructions,
+        );
+
+        // LP minted straight to the caller: no fee applies (see `execute_path`), only
+        // leftover dust remains in the vault
+        if vault.is_output_delivered() && !vault.contains(&token_out_id) {
+            let delivered = vault.delivered_lp().clone();
+            self.emit_aggregate_event(&token_out_id, &delivered, &stats);
+            self.return_vault_to_caller(vault, &token_out_id);
+            return (token_out_id, delivered);
+        }
+
+        // 4. Apply fees before slippage check (0 = no referral)
+        self.apply_fees(&mut vault, &token_out_id, referral_id);
+
+        // 5. Verify minimum output amount AFTER fees
+        let current_balance = vault.balance_of(&token_out_id);
+
+        require!(
+            vault.has_minimum(&token_out_id, &min_amount_out),
+            "E: Slippage limit exceeded: have {}, need {}",
+            current_balance,
+            min_amount_out
+        );
+        self.emit_aggregate_event(&token_out_id, &current_balance, &stats);
+
+        // 6. Return only output token to caller, keep dust as protocol revenue
+        self.return_vault_to_caller(vault, &token_out_id);
+        (token_out_id, current_balance)
+    }
+
+    /// Owner-only, fee-free variant of `xo` for deploying protocol-owned liquidity
+    ///
+    /// Runs the same instructions (including the pre-balanced ZAP) but never calls
+    /// `apply_fees`, and sends the output token to `treasury` instead of the caller.
+    /// Actions that mint straight to the caller deliver to the owner.
+    ///
+    /// # Arguments
+    /// * `format_version` - As in `xo`
+    /// * `treasury` - Recipient of the output token
+    /// * Remaining arguments as in `xo`, without `referral_id`
+    #[only_owner]
+    #[payable("*")]
+    #[endpoint(xoOwner)]
+    #[allow_multiple_var_args]
+    fn aggregate_owner(
+        &self,
+        format_version: u8,
+        treasury: ManagedAddress<Self::Api>,
+        min_amount_out: BigUint<Self::Api>,
+        token_out: u8,
+        tokens: MultiValueEncodedCounted<TokenIdentifier<Self::Api>>,
+        addresses: MultiValueEncodedCounted<ManagedAddress<Self::Api>>,
+        amounts: MultiValueEncodedCounted<BigUint<Self::Api>>,
+        instructions: MultiValueEncoded<MultiValue6<u8, u8, u8, u8, u8, u16>>,
+    ) {
+        self.require_format_version(format_version);
+        let (vault, token_out_id, stats) = self.execute_path(
+            &min_amount_out,
+            token_out,
+            0,
+            tokens,
+            addresses,
+            amounts,
+            instructions,
+        );
+
+        // Output minted straight to the owner was already checked in `execute_path`
+        let delivered = vault.is_output_delivered() && !vault.contains(&token_out_id);
+        let amount_out = if delivered {
+            vault.delivered_lp().clone()
+        } else {
+            let current_balance = vault.balance_of(&token_out_id);
+            require!(
+                vault.has_minimum(&token_out_id, &min_amount_out),
+                "E: Slippage limit exceeded: have {}, need {}",
+                current_balance,
+                min_amount_out
+            );
+            current_balance
+        };
+        self.emit_aggregate_event(&token_out_id, &amount_out, &stats);
+
+        self.return_vault_to(vault, &token_out_id, &treasury);
+    }
+
+    /// Soft-fail variant of `xo` for swap-only paths
+    ///
+    /// The path is first quoted from the pools' reserves. When the quote after fees is
+    /// below `min_amount_out`, the payment is sent back untouched instead of reverting,
+    /// so no DEX call is made. Otherwise the path runs exactly like `xo` and still
+    /// reverts if the executed output falls short. In fee-on-input mode the quote starts
+    /// from the payments net of fees.
+    ///
+    /// Only single-input xExchange and Jex CPMM swaps can be quoted, alongside
+    /// `ClearPrev`, `AllowFailure`, `RequireFullInput` and `LpToCaller`. A path with
+    /// any other action (OneDex, AshSwap or stable swaps, liquidity, staking, lending,
+    /// wrapping) reverts with `ERR_SOFT_FAIL_SWAP_ONLY` before executing: use `xo`.
+    ///
+    /// # Returns
+    /// `true` when the path was executed, `false` when the payment was returned
+    #[payable("*")]
+    #[endpoint(xoSoft)]
+    #[allow_multiple_var_args]
+    fn aggregate_soft(
+        &self,
+        format_version: u8,
+        min_amount_out: BigUint<Self::Api>,
+        token_out: u8,
+        referral_id: u64,
+        tokens: MultiValueEncodedCounted<TokenIdentifier<Self::Api>>,
+        addresses: MultiValueEncodedCounted<ManagedAddress<Self::Api>>,
+        amounts: MultiValueEncodedCounted<BigUint<Self::Api>>,
+        instructions: MultiValueEncoded<MultiValue6<u8, u8, u8, u8, u8, u16>>,
+    ) -> bool {
+        self.require_format_version(format_version);
+        let referral_id = self.resolve_referral_id(referral_id);
+        let payment = self.call_value().all();
+        let token_registry: TokenRegistry<Self::Api> = tokens.clone().to_vec();
+        let token_out_id = self.resolve_token_to_id(token_out, &token_registry);
+
+        // Fee-on-input: quote what the path is left with after the fees
+        let fee_on_input = self.fee_on_input().get();
+        let quoted_payment = if fee_on_input {
+            self.net_of_input_fees(&payment, &token_out_id, referral_id)
+        } else {
+            payment.clone()
+        };
+        let mut quote = self.quote_swap_path(
+            &quoted_payment,
+            &token_out_id,
+            &token_registry,
+            &addresses.clone().to_vec(),
+            &amounts.clone().to_vec(),
+            instructions.clone(),
+        );
+        if !fee_on_input && self.charges_fees(referral_id) {
+            let (admin_fee, referral_fee) =
+                self.compute_fees(&token_out_id, &token_out_id, referral_id, &quote);
+            let fees = admin_fee + referral_fee;
+            quote = if quote > fees {
+                quote - fees
+            } else {
+                BigUint::zero()
+            };
+        }
+
+        if quote < min_amount_out {
+            self.tx()
+                .to(self.blockchain().get_caller())
+                .payment(payment.clone())
+                .transfer();
+            return false;
+        }
+
+        self.aggregate_to_caller(
+            min_amount_out,
+            token_out,
+            referral_id,
+            tokens,
+            addresses,
+            amounts,
+            instructions,
+        );
+        true
+    }
+
+    /// Round-trip variant of `xo` for arbitrage, where the payment is in `token_out`
+    ///
+    /// The output must return the paid amount of `token_out` plus `min_profit`, after
+    /// fees, so an unprofitable round trip reverts instead of settling at a loss.
+    ///
+    /// # Arguments
+    /// * `min_profit` - Minimum gain over the paid `token_out` amount
+    /// * Remaining arguments as in `xo`, `min_profit` replacing `min_amount_out`
+    #[payable("*")]
+    #[endpoint(xoArb)]
+    #[allow_multiple_var_args]
+    fn aggregate_arb(
+        &self,
+        format_version: u8,
+        min_profit: BigUint<Self::Api>,
+        token_out: u8,
+        referral_id: u64,
+        tokens: MultiValueEncodedCounted<TokenIdentifier<Self::Api>>,
+        addresses: MultiValueEncodedCounted<ManagedAddress<Self::Api>>,
+        amounts: MultiValueEncodedCounted<BigUint<Self::Api>>,
+        instructions: MultiValueEncoded<MultiValue6<u8, u8, u8, u8, u8, u16>>,
+    ) {
+        self.require_format_version(format_version);
+        let token_registry: TokenRegistry<Self::Api> = tokens.clone().to_vec();
+        let token_out_id = self.resolve_token_to_id(token_out, &token_registry);
+
+        let mut amount_in = BigUint::zero();
+        for payment in self.call_value().all().iter() {
+            if payment.token_identifier == token_out_id {
+                amount_in += payment.amount.as_big_uint();
+            }
+        }
+        require!(amount_in > 0u64, ERR_ARB_NOT_ROUND_TRIP);
+
+        let min_amount_out = amount_in + min_profit;
+        let referral_id = self.resolve_referral_id(referral_id);
+        let (_, amount_out) = self.aggregate_to_caller(
+            min_amount_out.clone(),
+            token_out,
+            referral_id,
+            tokens,
+            addresses,
+            amounts,
+            instructions,
+        );
+
+        // Output minted straight to the caller skips the slippage check and reports 0
+        require!(
+            amount_out >= min_amount_out,
+            "E: Slippage limit exceeded: have {}, need {}",
+            amount_out,
+            min_amount_out
+        );
+    }
+
+    /// Fund a vault from the call payments and run every compact instruction on it
+    /// Returns the vault, the resolved output token and a route summary,
+    /// before output fees and slippage checks
+    /// In fee-on-input mode the payments are charged first (`referral_id` 0 = no fee)
+    fn execute_path(
+        &self,
+        min_amount_out: &BigUint<Self::Api>,
+        token_out: u8,
+        referral_id: u64,
+        tokens: MultiValueEncodedCounted<TokenIdentifier<Self::Api>>,
+        addresses: MultiValueEncodedCounted<ManagedAddress<Self::Api>>,
+        amounts: MultiValueEncodedCounted<BigUint<Self::Api>>,
+        instructions: MultiValueEncoded<MultiValue6<u8, u8, u8, u8, u8, u16>>,
+    ) -> (Vault<Self::Api>, TokenId<Self::Api>, PathStats) {
         // 1. Initialize vault from incoming payments
         let payment = self.call_value().all();
         let mut vault = Vault::from_payment(&payment);
@@ -77,9 +558,40 @@ pub trait Aggregator: storage::Storage + config::Config + utils::Utils {
         let address_registry: AddressRegistry<Self::Api> = addresses.to_vec();
         let amount_registry: AmountRegistry<Self::Api> = amounts.to_vec();
 
+        // Each token must appear once, duplicates point at a broken route encoding
+        self.require_unique_tokens(&token_registry);
+
         // Resolve token_out from index
         let token_out_id = self.resolve_token_to_id(token_out, &token_registry);
 
+        if self.fee_on_input().get() {
+            self.apply_input_fees(&mut vault, &token_out_id, referral_id);
+        }
+
+        // Opt-in guard against near-zero minimums (0 = disabled), quoted from what the
+        // path actually routes, i.e. net of fee-on-input fees
+        let min_output_ppm = self.min_output_ppm().get();
+        if min_output_ppm > 0 {
+            self.require_slippage_floor(
+                &vault.get_all_payments(),
+                &token_out_id,
+                min_amount_out,
+                min_output_ppm,
+            );
+        }
+
+        // Guardrail against overly wide fan-outs of a single token (0 = unlimited)
+        let max_splits = self.max_splits_per_token().get();
+        let mut split_counts = ManagedMapEncoded::new();
+
+        // Route summary for the aggregate event
+        let mut touched_tokens = ManagedVec::<Self::Api, ManagedBuffer>::new();
+        touched_tokens.push(token_out_id.as_managed_buffer().clone());
+        let mut protocols = 0u32;
+        let mut instruction_count = 0u32;
+        let mut peak_vault_tokens = vault.len();
+        let gas_profiling = self.gas_profiling().get();
+
         // 3. Execute each compact instruction sequentially
         for compact_instr in instructions {
             let (action_byte, byte1, byte2, byte3, byte4, pair_id_or_addr) =
@@ -98,23 +610,501 @@ pub trait Aggregator: storage::Storage + config::Config + utils::Utils {
                 &amount_registry,
             );
 
-            self.execute_instruction(&mut vault, &instruction, &token_out_id);
+            if max_splits > 0 {
+                self.track_splits(&mut split_counts, &instruction, max_splits);
+            }
+
+            // An output that skips the vault cannot be charged, so it needs a fee-free trade
+            if matches!(
+                instruction.action,
+                types::ActionType::LpToCaller
+                    | types::ActionType::AshSwapPoolAddLiquidityToCaller
+                    | types::ActionType::HatomEnterMarket
+            ) {
+                require!(
+                    self.fee_on_input().get() || !self.charges_fees(referral_id),
+                    ERR_LP_TO_CALLER_CHARGES_FEES
+                );
+            }
+
+            self.track_tokens(&mut touched_tokens, &instruction);
+            protocols |= instruction.action.compact().protocol_bit();
+            self.execute_profiled(
+                &mut vault,
+                &instruction,
+                &token_out_id,
+                min_amount_out,
+                gas_profiling,
+                instruction_count,
+            );
+            instruction_count += 1;
+            peak_vault_tokens = core::cmp::max(peak_vault_tokens, vault.len());
         }
 
-        // 4. Apply fees before slippage check (0 = no referral)
-        self.apply_fees(&mut vault, &token_out_id, referral_id);
+        // 4. Buy-and-stake: a path ending short of an opted-in liquid staking token_out
+        // stakes its last output
+        if !vault.contains(&token_out_id) && vault.peek_prev_result().is_some() {
+            if let Some(instruction) = self.auto_stake_instruction(&token_out_id) {
+                protocols |= instruction.action.compact().protocol_bit();
+                self.execute_profiled(
+                    &mut vault,
+                    &instruction,
+                    &token_out_id,
+                    min_amount_out,
+                    gas_profiling,
+                    instruction_count,
+                );
+                instruction_count += 1;
+            }
+        }
 
-        // 5. Verify minimum output amount AFTER fees
-        let current_balance = vault.balance_of(&token_out_id);
+        // LP sent to the caller by split adds counts towards the minimum as a whole
+        let delivered_lp = vault.delivered_lp();
+        if *delivered_lp > 0u64 {
+            require!(
+                delivered_lp >= min_amount_out,
+                "E: LP output below minimum: have {}, need {}",
+                delivered_lp,
+                min_amount_out
+            );
+        }
 
-        require!(
-            vault.has_minimum(&token_out_id, &min_amount_out),
-            "Slippage limit exceeded: have {}, need {}",
-            current_balance,
-            min_amount_out
+        // 5. RequireFullInput: no payment token may be left over, unless it is the output;
+        // the input of a skipped AllowFailure swap is as unrouted as vault dust
+        if vault.is_full_input_required() {
+            for input in payment.iter() {
+                let token = &input.token_identifier;
+                if *token == token_out_id {
+                    continue;
+                }
+                let mut left = vault.refunded(token);
+                if vault.contains(token) {
+                    left += vault.balance_of(token);
+                }
+                if left > 0u64 {
+                    sc_panic!("E: Input token {} not fully consumed: {} left", token, left);
+                }
+            }
+        }
+
+        let stats = PathStats {
+            instructions: instruction_count,
+            tokens: touched_tokens.len() as u32,
+            protocols: protocols.count_ones(),
+            peak_vault_tokens: peak_vault_tokens as u32,
+        };
+        (vault, token_out_id, stats)
+    }
+
+    /// Run an instruction, emitting its gas use when profiling is enabled
+    fn execute_profiled(
+        &self,
+        vault: &mut Vault<Self::Api>,
+        instruction: &Instruction<Self::Api>,
+        token_out: &TokenId<Self::Api>,
+        min_amount_out: &BigUint<Self::Api>,
+        gas_profiling: bool,
+        index: u32,
+    ) {
+        if !gas_profiling {
+            self.execute_instruction(vault, instruction, token_out, min_amount_out);
+            return;
+        }
+
+        let gas_before = self.blockchain().get_gas_left();
+        self.execute_instruction(vault, instruction, token_out, min_amount_out);
+        let gas_used = gas_before - self.blockchain().get_gas_left();
+        self.gas_profile_event(index, instruction.action.compact().to_u8(), gas_used);
+    }
+
+    /// Emit the aggregate event of a successful path and count it in the usage metrics
+    fn emit_aggregate_event(
+        &self,
+        token_out: &TokenId<Self::Api>,
+        amount_out: &BigUint<Self::Api>,
+        stats: &PathStats,
+    ) {
+        let caller = self.blockchain().get_caller();
+        self.record_aggregation(&caller);
+        self.aggregate_event(
+            &caller,
+            token_out,
+            amount_out,
+            stats.instructions,
+            stats.tokens,
+            stats.protocols,
+            stats.peak_vault_tokens,
         );
+    }
 
-        // 6. Return only output token to caller, keep dust as protocol revenue
-        self.return_vault_to_caller(vault, &token_out_id);
+    // --- Views ---
+
+    /// Expected ZAP dust for a pair, using the same pre-swap and quote math as execution
+    ///
+    /// # Arguments
+    /// * `fee_on_output` - false for xExchange/OneDex (fee on input), true for Jex
+    /// * `split_fee_num` - special fee leaving the pool (fee on input) or LP fee staying
+    ///   in the pool (fee on output)
+    ///
+    /// # Returns
+    /// (dust_first, dust_second) refunded by add_liquidity
+    #[view(estimateZapDust)]
+    fn estimate_zap_dust(
+        &self,
+        balance_first: BigUint,
+        balance_second: BigUint,
+        reserve_first: BigUint,
+        reserve_second: BigUint,
+        fee_num: u64,
+        fee_denom: u64,
+        fee_on_output: bool,
+        split_fee_num: u64,
+    ) -> MultiValue2<BigUint, BigUint> {
+        let fee_mode = if fee_on_output {
+            zap::FeeMode::OnOutput {
+                lp_fee_num: split_fee_num,
+            }
+        } else {
+            zap::FeeMode::OnInput {
+                special_fee_num: split_fee_num,
+            }
+        };
+        zap::estimate_dust(
+            &balance_first,
+            &balance_second,
+            &reserve_first,
+            &reserve_second,
+            fee_num,
+            fee_denom,
+            fee_mode,
+        )
+        .into()
+    }
+
+    /// Constants of the deployed ZAP, so off-chain replicas can assert they match
+    ///
+    /// Lists the binary search iteration cap and, per zappable venue, its fee mode (as the
+    /// `fee_on_output` argument of `estimateZapDust`) and fee denominator.
+    #[view(getZapMath)]
+    fn get_zap_math(&self) -> ZapMath<Self::Api> {
+        let mut venues = ManagedVec::new();
+        venues.push(ZapVenueMath {
+            action: CompactAction::XExchangeAddLiquidity.to_u8(),
+            fee_on_output: false,
+            fee_denom: XEXCHANGE_FEE_DENOM,
+        });
+        venues.push(ZapVenueMath {
+            action: CompactAction::OneDexAddLiquidity.to_u8(),
+            fee_on_output: false,
+            fee_denom: TOTAL_FEE as u64,
+        });
+        venues.push(ZapVenueMath {
+            action: CompactAction::JexAddLiquidity.to_u8(),
+            fee_on_output: true,
+            fee_denom: TOTAL_FEE as u64,
+        });
+        ZapMath {
+            max_binary_search_iterations: zap::MAX_BINARY_SEARCH_ITERATIONS,
+            venues,
+        }
+    }
+
+    /// Decode a compact path and list the distinct tokens it can produce
+    ///
+    /// Static analysis only: nothing is executed. Actions whose output depends on
+    /// pool state are reported as `PathOutput::Unknown(action_byte)`.
+    ///
+    /// # Arguments
+    /// Same format version, registries and instructions as `xo`
+    #[view(pathOutputTokens)]
+    #[allow_multiple_var_args]
+    fn path_output_tokens(
+        &self,
+        format_version: u8,
+        tokens: MultiValueEncodedCounted<TokenIdentifier<Self::Api>>,
+        addresses: MultiValueEncodedCounted<ManagedAddress<Self::Api>>,
+        amounts: MultiValueEncodedCounted<BigUint<Self::Api>>,
+        instructions: MultiValueEncoded<MultiValue6<u8, u8, u8, u8, u8, u16>>,
+    ) -> MultiValueEncoded<PathOutput<Self::Api>> {
+        self.require_format_version(format_version);
+        let token_registry: TokenRegistry<Self::Api> = tokens.to_vec();
+        let address_registry: AddressRegistry<Self::Api> = addresses.to_vec();
+        let amount_registry: AmountRegistry<Self::Api> = amounts.to_vec();
+
+        let mut outputs = ManagedVec::<Self::Api, PathOutput<Self::Api>>::new();
+        for compact_instr in instructions {
+            let (action_byte, byte1, byte2, byte3, byte4, pair_id_or_addr) =
+                compact_instr.into_tuple();
+
+            let instruction = self.decode_compact_instruction(
+                action_byte,
+                byte1,
+                byte2,
+                byte3,
+                byte4,
+                pair_id_or_addr,
+                &token_registry,
+                &address_registry,
+                &amount_registry,
+            );
+
+            if let Some(output) = self.static_output(&instruction.action) {
+                if !outputs.contains(&output) {
+                    outputs.push(output);
+                }
+            }
+        }
+
+        outputs.into()
+    }
+
+    /// Run every decode and pre-execution check of `xo` on a path without executing it
+    ///
+    /// Covers the format version, registry uniqueness, `token_out` resolution, instruction decoding (indices,
+    /// modes, PPM bounds) and the split limit. No proxy calls are made.
+    ///
+    /// Like `xo`, a Ppm input is a share of the token's current balance, so only each
+    /// value is bounded by 100%: sequential legs such as 50% then 100% are valid.
+    ///
+    /// # Returns
+    /// `true` when the path is well-formed, otherwise fails with the same error `xo` would
+    #[view(validatePath)]
+    #[allow_multiple_var_args]
+    fn validate_path(
+        &self,
+        format_version: u8,
+        token_out: u8,
+        tokens: MultiValueEncodedCounted<TokenIdentifier<Self::Api>>,
+        addresses: MultiValueEncodedCounted<ManagedAddress<Self::Api>>,
+        amounts: MultiValueEncodedCounted<BigUint<Self::Api>>,
+        instructions: MultiValueEncoded<MultiValue6<u8, u8, u8, u8, u8, u16>>,
+    ) -> bool {
+        self.require_format_version(format_version);
+        let token_registry: TokenRegistry<Self::Api> = tokens.to_vec();
+        let address_registry: AddressRegistry<Self::Api> = addresses.to_vec();
+        let amount_registry: AmountRegistry<Self::Api> = amounts.to_vec();
+
+        self.require_unique_tokens(&token_registry);
+        self.resolve_token_to_id(token_out, &token_registry);
+
+        let max_splits = self.max_splits_per_token().get();
+        let mut split_counts = ManagedMapEncoded::new();
+
+        for compact_instr in instructions {
+            let (action_byte, byte1, byte2, byte3, byte4, pair_id_or_addr) =
+                compact_instr.into_tuple();
+
+            let instruction = self.decode_compact_instruction(
+                action_byte,
+                byte1,
+                byte2,
+                byte3,
+                byte4,
+                pair_id_or_addr,
+                &token_registry,
+                &address_registry,
+                &amount_registry,
+            );
+
+            if max_splits > 0 {
+                self.track_splits(&mut split_counts, &instruction, max_splits);
+            }
+        }
+
+        true
+    }
+
+    /// `PairFee` tier of a pool, read from the same pool storage the ZAP fee lookup uses
+    ///
+    /// OneDex pools store their tier. xExchange (base 100,000) and Jex (base 10,000)
+    /// pools store a raw fee, which must equal a tier exactly: a pool charging any other
+    /// fee, such as the standard 0.3% xExchange pair, reverts.
+    ///
+    /// # Arguments
+    /// * `pool` - xExchange or Jex pair address (ignored for OneDex)
+    /// * `opt_pair_id` - OneDex pair id; when given the tier is read from the OneDex router
+    #[view(getPairFee)]
+    fn get_pair_fee(
+        &self,
+        pool: ManagedAddress,
+        opt_pair_id: OptionalValue<usize>,
+    ) -> types::PairFee {
+        if let OptionalValue::Some(pair_id) = opt_pair_id {
+            let pair_fee = self.one_dex_pair_fee(ManagedAddress::from(ONE_DEX_ROUTER), pair_id);
+            require!(!pair_fee.is_empty(), ERR_PAIR_FEE_NOT_FOUND);
+            return pair_fee.get();
+        }
+
+        let xexchange_fee = self.xexchange_total_fee_percent(pool.clone());
+        let (fee_num, fee_denom) = if !xexchange_fee.is_empty() {
+            (xexchange_fee.get(), XEXCHANGE_FEE_DENOM)
+        } else {
+            let lp_fees = self.jex_liq_providers_fees(pool.clone());
+            require!(!lp_fees.is_empty(), ERR_PAIR_FEE_NOT_FOUND);
+            let jex_fee = lp_fees.get() + self.jex_platform_fees(pool).get();
+            (jex_fee as u64, TOTAL_FEE as u64)
+        };
+        types::PairFee::from_fee_fraction(fee_num, fee_denom)
+            .unwrap_or_else(|| sc_panic!(ERR_PAIR_FEE_NO_TIER))
+    }
+
+    /// Token amounts an xExchange pair pays out for burning `lp_amount` LP tokens
+    ///
+    /// Proportional share of each reserve, rounded down like the pair's own
+    /// `removeLiquidity`. Reads reserves and LP supply through the same view the ZAP
+    /// falls back to.
+    ///
+    /// # Returns
+    /// (first_token_payment, second_token_payment), zero amounts for an empty pair
+    #[view(previewRemoveLiquidity)]
+    fn preview_remove_liquidity(
+        &self,
+        pool: ManagedAddress,
+        lp_amount: BigUint,
+    ) -> MultiValue2<EsdtTokenPayment, EsdtTokenPayment> {
+        let (first_reserve, second_reserve, total_supply) =
+            self.get_xexchange_reserves_and_supply(&pool);
+
+        let (first_amount, second_amount) = if total_supply == 0u64 {
+            (BigUint::zero(), BigUint::zero())
+        } else {
+            (
+                &first_reserve * &lp_amount / &total_supply,
+                &second_reserve * &lp_amount / &total_supply,
+            )
+        };
+
+        let first_token = self.xexchange_first_token_id(pool.clone()).get();
+        let second_token = self.xexchange_second_token_id(pool).get();
+        (
+            EsdtTokenPayment::new(first_token, 0, first_amount),
+            EsdtTokenPayment::new(second_token, 0, second_amount),
+        )
+            .into()
+    }
+
+    /// CPMM venues holding a `token_in`/`token_out` pool, with the pool's reserves
+    ///
+    /// xExchange pairs are discovered through the router. OneDex and Jex keep no
+    /// on-chain pair index this contract can read, so their candidates are passed in
+    /// and kept only when they pair the two tokens. Routing stays off-chain: every
+    /// match is listed and the caller picks one.
+    ///
+    /// # Arguments
+    /// * `onedex_pair_ids` - OneDex pair ids to check
+    /// * `jex_pools` - Jex CPMM pool addresses to check
+    ///
+    /// # Returns
+    /// (venue, pool, reserve_in, reserve_out) per match, where venue is the compact swap
+    /// action byte (`XExchangeSwap`, `OneDexSwap`, `JexSwap`) and pool is the OneDex
+    /// router for OneDex
+    #[view(bestCpmmPoolFor)]
+    #[allow_multiple_var_args]
+    fn best_cpmm_pool_for(
+        &self,
+        token_in: TokenIdentifier<Self::Api>,
+        token_out: TokenIdentifier<Self::Api>,
+        onedex_pair_ids: MultiValueEncodedCounted<usize>,
+        jex_pools: MultiValueEncoded<ManagedAddress<Self::Api>>,
+    ) -> MultiValueEncoded<
+        MultiValue4<u8, ManagedAddress<Self::Api>, BigUint<Self::Api>, BigUint<Self::Api>>,
+    > {
+        let mut pools = MultiValueEncoded::new();
+
+        let xexchange_pool = self.get_pair_x(&token_in, &token_out);
+        if !xexchange_pool.is_zero() {
+            let reserve_in = self
+                .xexchange_pair_reserve(xexchange_pool.clone(), &token_in)
+                .get();
+            let reserve_out = self
+                .xexchange_pair_reserve(xexchange_pool.clone(), &token_out)
+                .get();
+            pools.push(
+                (
+                    CompactAction::XExchangeSwap.to_u8(),
+                    xexchange_pool,
+                    reserve_in,
+                    reserve_out,
+                )
+                    .into(),
+            );
+        }
+
+        let router = ManagedAddress::from(ONE_DEX_ROUTER);
+        for pair_id in onedex_pair_ids {
+            let first_token = self.onedex_first_token_id(router.clone(), pair_id);
+            let second_token = self.onedex_second_token_id(router.clone(), pair_id);
+            if first_token.is_empty() || second_token.is_empty() {
+                continue;
+            }
+            let (first_reserve, second_reserve) = self.get_onedex_reserves(&router, pair_id);
+            if let Some((reserve_in, reserve_out)) = self.orient_reserves(
+                &token_in,
+                &token_out,
+                &first_token.get(),
+                &second_token.get(),
+                first_reserve,
+                second_reserve,
+            ) {
+                pools.push(
+                    (
+                        CompactAction::OneDexSwap.to_u8(),
+                        router.clone(),
+                        reserve_in,
+                        reserve_out,
+                    )
+                        .into(),
+                );
+            }
+        }
+
+        for pool in jex_pools {
+            let first_token = self.jex_first_token_id(pool.clone());
+            let second_token = self.jex_second_token_id(pool.clone());
+            if first_token.is_empty() || second_token.is_empty() {
+                continue;
+            }
+            let (first_reserve, second_reserve) = self.get_jex_reserves(&pool);
+            if let Some((reserve_in, reserve_out)) = self.orient_reserves(
+                &token_in,
+                &token_out,
+                &first_token.get(),
+                &second_token.get(),
+                first_reserve,
+                second_reserve,
+            ) {
+                pools.push(
+                    (
+                        CompactAction::JexSwap.to_u8(),
+                        pool,
+                        reserve_in,
+                        reserve_out,
+                    )
+                        .into(),
+                );
+            }
+        }
+
+        pools
+    }
+
+    /// Pool reserves as (reserve_in, reserve_out), or None when the pool does not pair
+    /// `token_in` with `token_out`
+    fn orient_reserves(
+        &self,
+        token_in: &TokenIdentifier<Self::Api>,
+        token_out: &TokenIdentifier<Self::Api>,
+        first_token: &TokenIdentifier<Self::Api>,
+        second_token: &TokenIdentifier<Self::Api>,
+        first_reserve: BigUint<Self::Api>,
+        second_reserve: BigUint<Self::Api>,
+    ) -> Option<(BigUint<Self::Api>, BigUint<Self::Api>)> {
+        if first_token == token_in && second_token == token_out {
+            Some((first_reserve, second_reserve))
+        } else if first_token == token_out && second_token == token_in {
+            Some((second_reserve, first_reserve))
+        } else {
+            None
+        }
     }
 }
@@ -1,8 +1,16 @@
 multiversx_sc::imports!();
 
-use crate::constants::TOTAL_FEE;
-use crate::errors::{ERR_FEE_EXCEEDS_100, ERR_REFERRAL_FEE_EXCEEDS_50, ERR_REFERRAL_NOT_FOUND};
-use crate::types;
+use crate::constants::{MAX_TOKEN_DECIMALS, MIGRATION_CALL_GAS, TOTAL_FEE};
+use crate::errors::{
+    ERR_EGLD_LIABILITY_NOT_SEEDED, ERR_EGLD_LIABILITY_SEEDED, ERR_EGLD_LIABILITY_SEED_GAP,
+    ERR_FEE_EXCEEDS_100, ERR_INSUFFICIENT_EGLD_BALANCE, ERR_INVALID_ACTION, ERR_LAST_ALLOWED_TOKEN,
+    ERR_MIGRATION_REFERRAL_CONFLICT, ERR_NOT_MIGRATION_SOURCE, ERR_NOT_STAKING_ACTION,
+    ERR_PPM_EXCEEDS_100_PERCENT, ERR_REFERRAL_CAMPAIGN_WINDOW, ERR_REFERRAL_FEE_EXCEEDS_50,
+    ERR_REFERRAL_NOT_FOUND, ERR_REFERRAL_NOT_STALE, ERR_REFERRAL_SWEEP_DISABLED,
+    ERR_REFERRAL_SWEEP_DRAINING, ERR_REFERRAL_TIERS_NOT_ASCENDING, ERR_TOKEN_DECIMALS_TOO_LARGE,
+};
+use crate::types::{self, CompactAction};
+use multiversx_sc::chain_core::EGLD_000000_TOKEN_IDENTIFIER;
 
 /// Admin configuration module for referral and fee management
 #[multiversx_sc::module]
@@ -38,6 +46,17 @@ pub trait Config: crate::storage::Storage {
         self.referral_config(id).update(|c| c.fee = fee);
     }
 
+    /// Update the fees of many referrals at once from (id, fee) pairs
+    /// Each entry is validated like `setReferralFee`; an unknown id reverts the whole batch
+    #[only_owner]
+    #[endpoint(setReferralFeeBatch)]
+    fn set_referral_fee_batch(&self, fees: MultiValueEncoded<MultiValue2<u64, u32>>) {
+        for entry in fees {
+            let (id, fee) = entry.into_tuple();
+            self.set_referral_fee(id, fee);
+        }
+    }
+
     /// Enable or disable a referral
     #[only_owner]
     #[endpoint(setReferralActive)]
@@ -54,6 +73,107 @@ pub trait Config: crate::storage::Storage {
         self.referral_config(id).update(|c| c.owner = new_owner);
     }
 
+    /// Set the minimum output amount a trade needs before the referral earns its share
+    /// (the payment amount in fee-on-input mode)
+    /// Below it, the referral portion of the fee goes to admin instead
+    #[only_owner]
+    #[endpoint(setReferralMinTrade)]
+    fn set_referral_min_trade(&self, id: u64, min_trade: BigUint) {
+        require!(!self.referral_config(id).is_empty(), ERR_REFERRAL_NOT_FOUND);
+        self.referral_min_trade(id).set(min_trade);
+    }
+
+    /// Replace a referral's progressive fee tiers as (min_output, fee) pairs
+    /// Thresholds must be strictly ascending; trades below the first tier pay the flat fee
+    /// Calling without tiers restores the flat fee for every trade size
+    #[only_owner]
+    #[endpoint(setReferralFeeTiers)]
+    fn set_referral_fee_tiers(&self, id: u64, tiers: MultiValueEncoded<MultiValue2<BigUint, u32>>) {
+        require!(!self.referral_config(id).is_empty(), ERR_REFERRAL_NOT_FOUND);
+
+        let mut fee_tiers = ManagedVec::<Self::Api, types::ReferralFeeTier<Self::Api>>::new();
+        for tier in tiers {
+            let (min_output, fee) = tier.into_tuple();
+            require!(fee <= TOTAL_FEE / 2, ERR_REFERRAL_FEE_EXCEEDS_50);
+            if let Some(last) = fee_tiers.iter().last() {
+                require!(
+                    min_output > last.min_output,
+                    ERR_REFERRAL_TIERS_NOT_ASCENDING
+                );
+            }
+            fee_tiers.push(types::ReferralFeeTier { min_output, fee });
+        }
+
+        if fee_tiers.is_empty() {
+            self.referral_fee_tiers(id).clear();
+        } else {
+            self.referral_fee_tiers(id).set(fee_tiers);
+        }
+    }
+
+    /// Run a boosted-fee campaign for a referral between two epochs, both inclusive
+    /// Outside the window the tiered or flat fee applies; replaces any previous campaign
+    #[only_owner]
+    #[endpoint(setReferralCampaign)]
+    fn set_referral_campaign(&self, id: u64, fee: u32, start_epoch: u64, end_epoch: u64) {
+        require!(!self.referral_config(id).is_empty(), ERR_REFERRAL_NOT_FOUND);
+        require!(fee <= TOTAL_FEE / 2, ERR_REFERRAL_FEE_EXCEEDS_50);
+        require!(start_epoch <= end_epoch, ERR_REFERRAL_CAMPAIGN_WINDOW);
+
+        self.referral_campaign(id).set(types::ReferralCampaign {
+            fee,
+            start_epoch,
+            end_epoch,
+        });
+    }
+
+    /// End a referral's campaign early
+    #[only_owner]
+    #[endpoint(clearReferralCampaign)]
+    fn clear_referral_campaign(&self, id: u64) {
+        self.referral_campaign(id).clear();
+    }
+
+    /// Restrict a referral's fee share to the given output tokens
+    #[only_owner]
+    #[endpoint(addReferralAllowedTokens)]
+    fn add_referral_allowed_tokens(&self, id: u64, tokens: MultiValueEncoded<TokenId<Self::Api>>) {
+        require!(!self.referral_config(id).is_empty(), ERR_REFERRAL_NOT_FOUND);
+        let mut allowed_tokens = self.referral_allowed_tokens(id);
+        for token in tokens {
+            allowed_tokens.insert(token);
+        }
+    }
+
+    /// Remove output tokens from a referral's allowed set
+    /// The last token cannot be removed: an empty set means every token, so lifting the
+    /// restriction goes through `clearReferralAllowedTokens`
+    #[only_owner]
+    #[endpoint(removeReferralAllowedTokens)]
+    fn remove_referral_allowed_tokens(
+        &self,
+        id: u64,
+        tokens: MultiValueEncoded<TokenId<Self::Api>>,
+    ) {
+        require!(!self.referral_config(id).is_empty(), ERR_REFERRAL_NOT_FOUND);
+        let mut allowed_tokens = self.referral_allowed_tokens(id);
+        let was_restricted = !allowed_tokens.is_empty();
+        for token in tokens {
+            allowed_tokens.swap_remove(&token);
+        }
+        require!(
+            !was_restricted || !allowed_tokens.is_empty(),
+            ERR_LAST_ALLOWED_TOKEN
+        );
+    }
+
+    /// Lift a referral's token restriction, so it earns on every output token again
+    #[only_owner]
+    #[endpoint(clearReferralAllowedTokens)]
+    fn clear_referral_allowed_tokens(&self, id: u64) {
+        self.referral_allowed_tokens(id).clear();
+    }
+
     /// Set the static fee for trades without a referral
     #[only_owner]
     #[endpoint(setStaticFee)]
@@ -62,6 +182,320 @@ pub trait Config: crate::storage::Storage {
         self.static_fee().set(fee);
     }
 
+    /// Set a static fee for one output token, overriding the global static fee
+    #[only_owner]
+    #[endpoint(setStaticFeeOverride)]
+    fn set_static_fee_override(&self, token: TokenId<Self::Api>, fee: u32) {
+        require!(fee <= TOTAL_FEE, ERR_FEE_EXCEEDS_100);
+        self.static_fee_override(&token).set(fee);
+    }
+
+    /// Drop a token's static fee override, falling back to the global static fee
+    #[only_owner]
+    #[endpoint(clearStaticFeeOverride)]
+    fn clear_static_fee_override(&self, token: TokenId<Self::Api>) {
+        self.static_fee_override(&token).clear();
+    }
+
+    /// Route xExchange swaps between two tokens to local pairs from
+    /// (first_token, second_token, pair) entries; a zero pair drops the override
+    #[only_owner]
+    #[endpoint(setPairOverridesBatch)]
+    fn set_pair_overrides_batch(
+        &self,
+        entries: MultiValueEncoded<MultiValue3<TokenIdentifier, TokenIdentifier, ManagedAddress>>,
+    ) {
+        for entry in entries {
+            let (first_token_id, second_token_id, pair) = entry.into_tuple();
+            let pair_override = self.pair_override(&types::PairTokens {
+                first_token_id,
+                second_token_id,
+            });
+            if pair.is_zero() {
+                pair_override.clear();
+            } else {
+                pair_override.set(pair);
+            }
+        }
+    }
+
+    /// Route Hatom calls for hTokens to local markets from (h_token, market) entries;
+    /// a zero market drops the override
+    #[only_owner]
+    #[endpoint(setMarketOverridesBatch)]
+    fn set_market_overrides_batch(
+        &self,
+        entries: MultiValueEncoded<MultiValue2<TokenIdentifier, ManagedAddress>>,
+    ) {
+        for entry in entries {
+            let (h_token, market) = entry.into_tuple();
+            if market.is_zero() {
+                self.market_override(&h_token).clear();
+            } else {
+                self.market_override(&h_token).set(market);
+            }
+        }
+    }
+
+    /// Allow (contract, endpoint) pairs to be called through `xoCallback`
+    #[only_owner]
+    #[endpoint(addCallbackTargets)]
+    fn add_callback_targets(
+        &self,
+        targets: MultiValueEncoded<MultiValue2<ManagedAddress, ManagedBuffer>>,
+    ) {
+        let mut whitelist = self.callback_whitelist();
+        for target in targets {
+            let (address, endpoint) = target.into_tuple();
+            whitelist.insert(types::CallbackTarget { address, endpoint });
+        }
+    }
+
+    #[only_owner]
+    #[endpoint(removeCallbackTargets)]
+    fn remove_callback_targets(
+        &self,
+        targets: MultiValueEncoded<MultiValue2<ManagedAddress, ManagedBuffer>>,
+    ) {
+        let mut whitelist = self.callback_whitelist();
+        for target in targets {
+            let (address, endpoint) = target.into_tuple();
+            whitelist.swap_remove(&types::CallbackTarget { address, endpoint });
+        }
+    }
+
+    /// Set the gas forwarded to `xoCallback` targets (0 = DEFAULT_CALLBACK_GAS)
+    #[only_owner]
+    #[endpoint(setCallbackGasLimit)]
+    fn set_callback_gas_limit(&self, gas_limit: u64) {
+        self.callback_gas_limit().set(gas_limit);
+    }
+
+    /// Enable or disable free mode, which skips every fee regardless of configuration
+    #[only_owner]
+    #[endpoint(setFreeMode)]
+    fn set_free_mode(&self, enabled: bool) {
+        self.free_mode().set(enabled);
+    }
+
+    /// Take fees from the payment tokens before the path runs instead of from the output
+    /// Fixed input amounts must then be encoded net of the fee
+    /// Fee settings then apply to the payment tokens: static fee overrides, min trades
+    /// and tier thresholds must be set for the input tokens
+    #[only_owner]
+    #[endpoint(setFeeOnInput)]
+    fn set_fee_on_input(&self, enabled: bool) {
+        self.fee_on_input().set(enabled);
+    }
+
+    /// Enable or disable drain mode for a graceful wind-down
+    /// The referral share of every fee goes to admin and referral balances are frozen
+    /// Aggregation and claims stay available; with no pause on the contract, draining
+    /// is the way to retire referrals while their owners collect what they earned
+    #[only_owner]
+    #[endpoint(setDrainMode)]
+    fn set_drain_mode(&self, enabled: bool) {
+        self.drain_mode().set(enabled);
+    }
+
+    /// Enable or disable per-instruction gas profiling events
+    /// Meant for measuring routes on devnet; costs one storage read per path when off
+    #[only_owner]
+    #[endpoint(setGasProfiling)]
+    fn set_gas_profiling(&self, enabled: bool) {
+        self.gas_profiling().set(enabled);
+    }
+
+    /// Enable or disable transparent EGLD/WEGLD conversion for instruction inputs
+    #[only_owner]
+    #[endpoint(setAutoWrapEnabled)]
+    fn set_auto_wrap_enabled(&self, enabled: bool) {
+        self.auto_wrap_enabled().set(enabled);
+    }
+
+    /// Enable or disable counting distinct callers (one storage entry per new caller)
+    #[only_owner]
+    #[endpoint(setUniqueCallerTracking)]
+    fn set_unique_caller_tracking(&self, enabled: bool) {
+        self.unique_caller_tracking().set(enabled);
+    }
+
+    /// Mark tokens as fee-on-transfer so the vault tracks their real holdings
+    #[only_owner]
+    #[endpoint(addFeeOnTransferTokens)]
+    fn add_fee_on_transfer_tokens(&self, tokens: MultiValueEncoded<TokenId<Self::Api>>) {
+        let mut fot_tokens = self.fee_on_transfer_tokens();
+        for token in tokens {
+            fot_tokens.insert(token);
+        }
+    }
+
+    #[only_owner]
+    #[endpoint(removeFeeOnTransferTokens)]
+    fn remove_fee_on_transfer_tokens(&self, tokens: MultiValueEncoded<TokenId<Self::Api>>) {
+        let mut fot_tokens = self.fee_on_transfer_tokens();
+        for token in tokens {
+            fot_tokens.swap_remove(&token);
+        }
+    }
+
+    /// Cache the decimals of tokens from (token, decimals) pairs
+    #[only_owner]
+    #[endpoint(setTokenDecimals)]
+    fn set_token_decimals(&self, entries: MultiValueEncoded<MultiValue2<TokenId<Self::Api>, u32>>) {
+        let mut token_decimals = self.token_decimals();
+        for entry in entries {
+            let (token, decimals) = entry.into_tuple();
+            require!(decimals <= MAX_TOKEN_DECIMALS, ERR_TOKEN_DECIMALS_TOO_LARGE);
+            token_decimals.insert(token, decimals);
+        }
+    }
+
+    #[only_owner]
+    #[endpoint(removeTokenDecimals)]
+    fn remove_token_decimals(&self, tokens: MultiValueEncoded<TokenId<Self::Api>>) {
+        let mut token_decimals = self.token_decimals();
+        for token in tokens {
+            token_decimals.remove(&token);
+        }
+    }
+
+    /// Cap the gas forwarded to DEX sub-calls for a compact action (0 = no cap)
+    #[only_owner]
+    #[endpoint(setActionGasLimit)]
+    fn set_action_gas_limit(&self, action: u8, gas_limit: u64) {
+        require!(CompactAction::from_u8(action).is_some(), ERR_INVALID_ACTION);
+        self.action_gas_limit(action).set(gas_limit);
+    }
+
+    /// Set the minimum output passed to internal pool calls of a category (0 = built-in default)
+    /// Wrapping and staking take no minimum, so no setting affects them
+    #[only_owner]
+    #[endpoint(setInternalMinOutput)]
+    fn set_internal_min_output(&self, category: types::ActionCategory, min_amount: BigUint) {
+        self.internal_min_output(category).set(min_amount);
+    }
+
+    /// Limit how many instructions may withdraw the same token in one aggregation (0 = no limit)
+    #[only_owner]
+    #[endpoint(setMaxSplitsPerToken)]
+    fn set_max_splits_per_token(&self, max_splits: u32) {
+        self.max_splits_per_token().set(max_splits);
+    }
+
+    /// Cap the payments returned to the recipient, output and refunds together; refunds
+    /// over the cap are kept as admin dust, smallest first (0 = no limit)
+    #[only_owner]
+    #[endpoint(setMaxOutputTokens)]
+    fn set_max_output_tokens(&self, max_output_tokens: u32) {
+        self.max_output_tokens().set(max_output_tokens);
+    }
+
+    /// Reject aggregations whose min_amount_out is below this PPM of the input's
+    /// spot value on the direct xExchange pair (0 = disabled)
+    #[only_owner]
+    #[endpoint(setMinOutputPpm)]
+    fn set_min_output_ppm(&self, ppm: u32) {
+        require!(ppm <= 1_000_000, ERR_PPM_EXCEEDS_100_PERCENT);
+        self.min_output_ppm().set(ppm);
+    }
+
+    /// Check xExchange ZAP reserves against the safe price (TWAP) view at `oracle`
+    /// before pre-balancing; the add reverts when they deviate by more than `tolerance_ppm`
+    #[only_owner]
+    #[endpoint(setZapOracle)]
+    fn set_zap_oracle(&self, oracle: ManagedAddress, tolerance_ppm: u32) {
+        require!(tolerance_ppm <= 1_000_000, ERR_PPM_EXCEEDS_100_PERCENT);
+        self.zap_oracle_address().set(oracle);
+        self.zap_oracle_tolerance_ppm().set(tolerance_ppm);
+    }
+
+    /// Stop checking ZAP reserves against the oracle
+    #[only_owner]
+    #[endpoint(clearZapOracle)]
+    fn clear_zap_oracle(&self) {
+        self.zap_oracle_address().clear();
+        self.zap_oracle_tolerance_ppm().clear();
+    }
+
+    /// Skip ZAP pre-swaps of `token` below `min_amount`; the slightly imbalanced add
+    /// refunds the excess as admin dust (0 = always swap)
+    #[only_owner]
+    #[endpoint(setMinZapSwap)]
+    fn set_min_zap_swap(&self, token: TokenId<Self::Api>, min_amount: BigUint) {
+        self.min_zap_swap(&token).set(min_amount);
+    }
+
+    /// Stake the path's last output with a liquid staking `action` when `token` is the
+    /// requested output and the path ends short of it
+    #[only_owner]
+    #[endpoint(setAutoStake)]
+    fn set_auto_stake(&self, token: TokenId<Self::Api>, action: u8) {
+        require!(
+            matches!(
+                CompactAction::from_u8(action),
+                Some(
+                    CompactAction::XoxnoLiquidStaking
+                        | CompactAction::LXoxnoLiquidStaking
+                        | CompactAction::HatomLiquidStaking
+                )
+            ),
+            ERR_NOT_STAKING_ACTION
+        );
+        self.auto_stake_action(&token).set(action);
+    }
+
+    #[only_owner]
+    #[endpoint(clearAutoStake)]
+    fn clear_auto_stake(&self, token: TokenId<Self::Api>) {
+        self.auto_stake_action(&token).clear();
+    }
+
+    /// Set the global Jex stable swap min multiplier (0 = built-in default)
+    #[only_owner]
+    #[endpoint(setJexStableMinMultiplier)]
+    fn set_jex_stable_min_multiplier(&self, multiplier: u64) {
+        self.jex_stable_min_multiplier().set(multiplier);
+    }
+
+    /// Override the Jex stable swap min multiplier for one pool (0 = use the global one)
+    #[only_owner]
+    #[endpoint(setJexStablePoolMinMultiplier)]
+    fn set_jex_stable_pool_min_multiplier(&self, pool: ManagedAddress, multiplier: u64) {
+        self.jex_stable_pool_min_multiplier(&pool).set(multiplier);
+    }
+
+    /// Cap the Jex stable swap and single-input add minimums at a PPM fraction of the
+    /// input (0 = no cap)
+    #[only_owner]
+    #[endpoint(setJexStableMinInputPpm)]
+    fn set_jex_stable_min_input_ppm(&self, ppm: u32) {
+        require!(ppm <= 1_000_000, ERR_PPM_EXCEEDS_100_PERCENT);
+        self.jex_stable_min_input_ppm().set(ppm);
+    }
+
+    /// Set how many epochs a referral must stay inactive before it can be swept (0 = never)
+    #[only_owner]
+    #[endpoint(setReferralStaleEpochs)]
+    fn set_referral_stale_epochs(&self, epochs: u64) {
+        self.referral_stale_epochs().set(epochs);
+    }
+
+    // --- User Endpoints ---
+
+    /// Set the referral applied to the caller's trades that pass `referral_id` 0
+    /// An id of 0 clears the default
+    #[endpoint(setMyReferral)]
+    fn set_my_referral(&self, id: u64) {
+        let caller = self.blockchain().get_caller();
+        if id == 0 {
+            self.caller_default_referral(&caller).clear();
+            return;
+        }
+        require!(!self.referral_config(id).is_empty(), ERR_REFERRAL_NOT_FOUND);
+        self.caller_default_referral(&caller).set(id);
+    }
+
     // --- Claim Endpoints ---
 
     /// Claim accumulated referral fees for a given referral ID
@@ -76,48 +510,64 @@ pub trait Config: crate::storage::Storage {
         let config = self.referral_config(referral_id).get();
 
         let mut payments = ManagedVec::new();
-        let mut claimed_tokens = ManagedVec::<Self::Api, TokenId<Self::Api>>::new();
 
-        if tokens.len() == 0 {
+        if tokens.is_empty() {
             for (token, amount) in self.referrer_balances(referral_id).iter() {
                 if payments.len() >= 90 {
                     break;
                 }
                 if amount > 0u64 {
-                    payments.push(Payment::new(
-                        token.clone(),
-                        0,
-                        amount.into_non_zero().unwrap(),
-                    ));
-                    claimed_tokens.push(token);
+                    payments.push(Payment::new(token, 0, amount.into_non_zero().unwrap()));
                 }
             }
         } else {
+            // Take each balance out as it is listed, so a repeated token pays once
+            let mut balances = self.referrer_balances(referral_id);
             for token in tokens {
                 if payments.len() >= 90 {
                     break;
                 }
-                if let Some(amount) = self.referrer_balances(referral_id).get(&token) {
+                if let Some(amount) = balances.remove(&token) {
                     if amount > 0u64 {
-                        payments.push(Payment::new(
-                            token.clone(),
-                            0,
-                            amount.into_non_zero().unwrap(),
-                        ));
-                        claimed_tokens.push(token);
+                        payments.push(Payment::new(token, 0, amount.into_non_zero().unwrap()));
                     }
                 }
             }
         }
 
-        // Clear only claimed tokens
-        for token in claimed_tokens.iter() {
-            self.referrer_balances(referral_id).remove(&token);
-        }
+        self.touch_referral_activity(referral_id);
+        self.settle_claim(
+            self.referrer_balances(referral_id),
+            referral_id,
+            &payments,
+            &config.owner,
+        );
+    }
 
-        if !payments.is_empty() {
-            self.tx().to(&config.owner).payment(&payments).transfer();
-        }
+    /// Move the balances of a referral inactive for more than `getReferralStaleEpochs`
+    /// to `treasury`
+    /// Activity is a fee accrual or a claim; a referral with no recorded activity is
+    /// never stale, so balances accrued before activity tracking stay claimable
+    /// Limited to 90 unique tokens per call to prevent out-of-gas
+    #[only_owner]
+    #[endpoint(sweepStaleReferral)]
+    fn sweep_stale_referral(&self, referral_id: u64, treasury: ManagedAddress) {
+        let stale_epochs = self.referral_stale_epochs().get();
+        require!(stale_epochs > 0, ERR_REFERRAL_SWEEP_DISABLED);
+        require!(!self.drain_mode().get(), ERR_REFERRAL_SWEEP_DRAINING);
+
+        let last_activity = self.referral_last_activity(referral_id);
+        require!(!last_activity.is_empty(), ERR_REFERRAL_NOT_STALE);
+        let idle_epochs = self.blockchain().get_block_epoch() - last_activity.get();
+        require!(idle_epochs > stale_epochs, ERR_REFERRAL_NOT_STALE);
+
+        let payments = self.collect_claimable(self.referrer_balances(referral_id));
+        self.settle_claim(
+            self.referrer_balances(referral_id),
+            referral_id,
+            &payments,
+            &treasury,
+        );
     }
 
     /// Claim accumulated admin fees
@@ -126,33 +576,290 @@ pub trait Config: crate::storage::Storage {
     #[only_owner]
     #[endpoint(claimAdminFees)]
     fn claim_admin_fees(&self, recipient: ManagedAddress) {
+        let payments = self.collect_claimable(self.admin_fees());
+        self.settle_claim(self.admin_fees(), 0, &payments, &recipient);
+    }
+
+    /// Claim accumulated admin fees only for the given tokens whose balance exceeds its threshold
+    /// Sub-threshold balances keep accumulating until they are worth moving
+    /// Limited to 90 unique tokens per call to prevent out-of-gas
+    #[only_owner]
+    #[endpoint(claimAdminFeesAboveThreshold)]
+    fn claim_admin_fees_above_threshold(
+        &self,
+        recipient: ManagedAddress,
+        thresholds: MultiValueEncoded<MultiValue2<TokenId<Self::Api>, BigUint<Self::Api>>>,
+    ) {
         let mut payments = ManagedVec::new();
-        let mut claimed_tokens = ManagedVec::<Self::Api, TokenId<Self::Api>>::new();
 
-        for (token, amount) in self.admin_fees().iter() {
+        // Take each balance out as it is claimed, so a repeated token pays once
+        let mut balances = self.admin_fees();
+        for entry in thresholds {
+            if payments.len() >= 90 {
+                break;
+            }
+            let (token, threshold) = entry.into_tuple();
+            if let Some(amount) = balances.get(&token) {
+                if amount > threshold {
+                    balances.remove(&token);
+                    payments.push(Payment::new(token, 0, amount.into_non_zero().unwrap()));
+                }
+            }
+        }
+
+        self.settle_claim(self.admin_fees(), 0, &payments, &recipient);
+    }
+
+    /// Clear every claimed balance, then transfer (checks-effects-interactions)
+    /// All claim paths settle here, so no transfer can happen while a claimed
+    /// balance is still recorded
+    /// `referral_id` owns `balances`, 0 for the admin fees
+    fn settle_claim(
+        &self,
+        balances: MapMapper<TokenId<Self::Api>, BigUint<Self::Api>>,
+        referral_id: u64,
+        payments: &ManagedVec<Payment<Self::Api>>,
+        recipient: &ManagedAddress,
+    ) {
+        self.clear_claimed(balances, referral_id, payments);
+
+        if !payments.is_empty() {
+            self.tx().to(recipient).payment(payments).transfer();
+        }
+    }
+
+    /// Remove only the claimed tokens from a balance map
+    /// Every fee payout clears its balances here, so this is where claimed EGLD stops
+    /// counting as owed
+    fn clear_claimed(
+        &self,
+        mut balances: MapMapper<TokenId<Self::Api>, BigUint<Self::Api>>,
+        referral_id: u64,
+        payments: &ManagedVec<Payment<Self::Api>>,
+    ) {
+        for payment in payments.iter() {
+            balances.remove(&payment.token_identifier);
+            self.release_egld_liability(
+                referral_id,
+                &payment.token_identifier,
+                payment.amount.as_big_uint(),
+            );
+        }
+    }
+
+    /// Every non-zero balance of a map as payments, at most 90 to stay within gas
+    fn collect_claimable(
+        &self,
+        balances: MapMapper<TokenId<Self::Api>, BigUint<Self::Api>>,
+    ) -> ManagedVec<Payment<Self::Api>> {
+        let mut payments = ManagedVec::new();
+        for (token, amount) in balances.iter() {
             if payments.len() >= 90 {
                 break;
             }
             if amount > 0u64 {
-                payments.push(Payment::new(
-                    token.clone(),
-                    0,
-                    amount.into_non_zero().unwrap(),
-                ));
-                claimed_tokens.push(token);
+                payments.push(Payment::new(token, 0, amount.into_non_zero().unwrap()));
             }
         }
+        payments
+    }
 
-        // Clear only claimed tokens
-        for token in claimed_tokens.iter() {
-            self.admin_fees().remove(&token);
+    /// Add the EGLD owed to the balance owners `from` to `from + count - 1` to the fee
+    /// liability, where owner 0 is the admin fees and the others are referral ids
+    ///
+    /// Needed once on a contract upgraded from a version without the liability; pages
+    /// must follow each other from the cursor so no balance is counted twice. Owners
+    /// already covered book their fee credits and payouts live, and `rescueEgld` stays
+    /// blocked until every referral is covered.
+    #[only_owner]
+    #[endpoint(seedEgldFeeLiability)]
+    fn seed_egld_fee_liability(&self, from: u64, count: u64) {
+        require!(
+            !self.egld_liability_seeded().get(),
+            ERR_EGLD_LIABILITY_SEEDED
+        );
+        require!(
+            from == self.egld_liability_seed_cursor().get(),
+            ERR_EGLD_LIABILITY_SEED_GAP
+        );
+
+        let last_id = self.referral_id_counter().get();
+        let end = core::cmp::min(from.saturating_add(count), last_id + 1);
+        let egld = TokenId::from(EGLD_000000_TOKEN_IDENTIFIER.as_bytes());
+        let mut owed = BigUint::zero();
+        for id in from..end {
+            let balances = if id == 0 {
+                self.admin_fees()
+            } else {
+                self.referrer_balances(id)
+            };
+            owed += balances.get(&egld).unwrap_or_default();
         }
 
-        if !payments.is_empty() {
-            self.tx().to(&recipient).payment(&payments).transfer();
+        self.egld_fee_liability()
+            .update(|liability| *liability += owed);
+        self.egld_liability_seed_cursor().set(end);
+        if end > last_id {
+            self.egld_liability_seeded().set(true);
+        }
+    }
+
+    /// Send native EGLD left on the contract (e.g. by wrap/unwrap) to a recipient
+    /// Only EGLD not booked as admin or referral fees can leave, so every owed fee
+    /// stays claimable; sweeps all of it when no amount is given
+    /// Blocked until `seedEgldFeeLiability` has covered every referral
+    #[only_owner]
+    #[endpoint(rescueEgld)]
+    fn rescue_egld(&self, recipient: ManagedAddress, opt_amount: OptionalValue<BigUint>) {
+        require!(
+            self.egld_liability_seeded().get(),
+            ERR_EGLD_LIABILITY_NOT_SEEDED
+        );
+        let balance = self
+            .blockchain()
+            .get_sc_balance(EgldOrEsdtTokenIdentifier::egld(), 0);
+        let owed = self.egld_fee_liability().get();
+        let rescuable = if balance > owed {
+            balance - owed
+        } else {
+            BigUint::zero()
+        };
+        let amount = match opt_amount {
+            OptionalValue::Some(amount) => {
+                require!(amount <= rescuable, ERR_INSUFFICIENT_EGLD_BALANCE);
+                amount
+            }
+            OptionalValue::None => rescuable,
+        };
+
+        if amount > 0u64 {
+            self.tx().to(&recipient).egld(&amount).transfer();
+        }
+    }
+
+    // --- Migration Endpoints ---
+
+    /// Move admin fees and the referrals with ids `from` to `from + count - 1` to
+    /// `new_contract`, a newer aggregator in the same shard whose owner has set this
+    /// contract as its migration source
+    ///
+    /// Each referral's config travels with its balances and is re-created under the same
+    /// id; its tiers, campaign, minimum trade and allowed tokens must be set again. Ids
+    /// without a referral are skipped. Balances are cleared before each transfer, and a
+    /// failing receiver reverts the whole call, so no referrer funds can be lost on the way.
+    /// Every receiver call gets `MIGRATION_CALL_GAS`, so page through the referrals with
+    /// `from`/`count` sized to the transaction gas.
+    /// Limited to 90 unique tokens per balance per call; call again for the rest
+    #[only_owner]
+    #[endpoint(migrateBalances)]
+    fn migrate_balances(&self, new_contract: ManagedAddress, from: u64, count: u64) {
+        let admin_payments = self.collect_claimable(self.admin_fees());
+        if !admin_payments.is_empty() {
+            self.clear_claimed(self.admin_fees(), 0, &admin_payments);
+            self.tx()
+                .to(&new_contract)
+                .raw_call("acceptMigratedAdminFees")
+                .payment(&admin_payments)
+                .gas(MIGRATION_CALL_GAS)
+                .sync_call();
+        }
+
+        let first = core::cmp::max(from, 1);
+        let end = core::cmp::min(
+            from.saturating_add(count),
+            self.referral_id_counter().get() + 1,
+        );
+        for referral_id in first..end {
+            let config_mapper = self.referral_config(referral_id);
+            if config_mapper.is_empty() {
+                continue;
+            }
+            let config = config_mapper.get();
+
+            let payments = self.collect_claimable(self.referrer_balances(referral_id));
+            self.clear_claimed(self.referrer_balances(referral_id), referral_id, &payments);
+            self.tx()
+                .to(&new_contract)
+                .raw_call("acceptMigratedReferral")
+                .argument(&referral_id)
+                .argument(&config.owner)
+                .argument(&config.fee)
+                .argument(&config.active)
+                .payment(&payments)
+                .gas(MIGRATION_CALL_GAS)
+                .sync_call();
+        }
+    }
+
+    /// Accept balances only from `source`, the aggregator being replaced
+    #[only_owner]
+    #[endpoint(setMigrationSource)]
+    fn set_migration_source(&self, source: ManagedAddress) {
+        self.migration_source().set(source);
+    }
+
+    /// Receive admin fees migrated from the migration source
+    #[payable("*")]
+    #[endpoint(acceptMigratedAdminFees)]
+    fn accept_migrated_admin_fees(&self) {
+        self.require_migration_source();
+        for payment in self.call_value().all().iter() {
+            let current = self
+                .admin_fees()
+                .get(&payment.token_identifier)
+                .unwrap_or_default();
+            self.admin_fees().insert(
+                payment.token_identifier.clone(),
+                current + payment.amount.as_big_uint(),
+            );
+            self.book_egld_liability(0, &payment.token_identifier, payment.amount.as_big_uint());
+        }
+    }
+
+    /// Receive a referral migrated from the migration source, re-created under its id
+    /// A later batch for the same referral only adds balances
+    #[payable("*")]
+    #[endpoint(acceptMigratedReferral)]
+    fn accept_migrated_referral(&self, id: u64, owner: ManagedAddress, fee: u32, active: bool) {
+        self.require_migration_source();
+
+        let config_mapper = self.referral_config(id);
+        if config_mapper.is_empty() {
+            config_mapper.set(types::ReferralConfig { owner, fee, active });
+            // Ids created here later must not collide with migrated ones
+            self.referral_id_counter().update(|counter| {
+                if *counter < id {
+                    *counter = id;
+                }
+            });
+        } else {
+            require!(
+                config_mapper.get().owner == owner,
+                ERR_MIGRATION_REFERRAL_CONFLICT
+            );
+        }
+
+        for payment in self.call_value().all().iter() {
+            let current = self
+                .referrer_balances(id)
+                .get(&payment.token_identifier)
+                .unwrap_or_default();
+            self.referrer_balances(id).insert(
+                payment.token_identifier.clone(),
+                current + payment.amount.as_big_uint(),
+            );
+            self.book_egld_liability(id, &payment.token_identifier, payment.amount.as_big_uint());
         }
     }
 
+    fn require_migration_source(&self) {
+        let source = self.migration_source();
+        require!(
+            !source.is_empty() && self.blockchain().get_caller() == source.get(),
+            ERR_NOT_MIGRATION_SOURCE
+        );
+    }
+
     // --- View Functions ---
 
     /// Get all accumulated balances for a referrer
@@ -178,6 +885,14 @@ pub trait Config: crate::storage::Storage {
         result
     }
 
+    /// Get the accumulated balance of a single token for a referrer
+    #[view(getReferrerBalance)]
+    fn get_referrer_balance(&self, referral_id: u64, token: TokenId<Self::Api>) -> BigUint {
+        self.referrer_balances(referral_id)
+            .get(&token)
+            .unwrap_or_default()
+    }
+
     #[view(getReferrerTokens)]
     fn get_referrer_tokens(&self, referral_id: u64) -> ManagedVec<TokenId<Self::Api>> {
         let mut result = ManagedVec::new();
@@ -188,6 +903,25 @@ pub trait Config: crate::storage::Storage {
         result
     }
 
+    /// Get the configs of referral ids `from..=to`, clamped to the ids created so far
+    /// One page per call keeps gas bounded; lifetime earnings are not tracked on-chain
+    #[view(getAllReferrals)]
+    fn get_all_referrals(
+        &self,
+        from: u64,
+        to: u64,
+    ) -> MultiValueEncoded<MultiValue2<u64, types::ReferralConfig<Self::Api>>> {
+        let mut result = MultiValueEncoded::new();
+        let last_id = core::cmp::min(to, self.referral_id_counter().get());
+
+        for id in core::cmp::max(from, 1)..=last_id {
+            if !self.referral_config(id).is_empty() {
+                result.push((id, self.referral_config(id).get()).into());
+            }
+        }
+        result
+    }
+
     /// Get all accumulated admin fees
     #[view(getAdminFees)]
     fn get_admin_fees_view(&self) -> MultiValueEncoded<(TokenId<Self::Api>, BigUint<Self::Api>)> {
@@ -197,4 +931,10 @@ pub trait Config: crate::storage::Storage {
         }
         result
     }
+
+    /// Get the accumulated admin fee of a single token
+    #[view(getAdminFee)]
+    fn get_admin_fee(&self, token: TokenId<Self::Api>) -> BigUint {
+        self.admin_fees().get(&token).unwrap_or_default()
+    }
 }
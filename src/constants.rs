@@ -41,8 +41,35 @@ pub const HATOM_CONTROLLER: [u8; 32] =
 // Magic Numbers
 // ═══════════════════════════════════════════════════════════════════════════════
 
+/// Compact argument format version `xo` and its variants encode
+/// 2: mode codes 96-127 became FixedOrAll, Fixed is limited to amounts[0-93]
+pub const FORMAT_VERSION: u8 = 2;
+
+/// Format versions the decoder accepts as the leading `xo` argument
+pub const SUPPORTED_FORMAT_VERSIONS: [u8; 1] = [FORMAT_VERSION];
+
 /// Minimum output amount for internal hops (prevents zero slippage)
 pub const MIN_INTERNAL_OUTPUT: u64 = 1;
 
+/// Highest number of decimals an ESDT can have
+pub const MAX_TOKEN_DECIMALS: u32 = 18;
+
 /// Basis points divisor (10,000 = 100%)
 pub const TOTAL_FEE: u32 = 10_000;
+
+/// Denominator of xExchange pair fees (`total_fee_percent`, `special_fee_percent`)
+pub const XEXCHANGE_FEE_DENOM: u64 = 100_000;
+
+/// Fixed-point scale for pool price ratios (second token per first token)
+pub const RATIO_PRECISION: u64 = 1_000_000_000_000_000_000;
+
+/// Default multiplier applied to MIN_INTERNAL_OUTPUT for Jex stable swaps
+pub const JEX_STABLE_MIN_MULTIPLIER: u64 = 2;
+
+/// Gas forwarded to each `acceptMigrated*` call of `migrateBalances`, enough to book
+/// 90 tokens on the receiver
+pub const MIGRATION_CALL_GAS: u64 = 20_000_000;
+
+/// Default gas forwarded to an `xoCallback` target, so a callback cannot consume the
+/// gas the rest of the transaction needs
+pub const DEFAULT_CALLBACK_GAS: u64 = 10_000_000;
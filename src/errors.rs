@@ -1,20 +1,79 @@
 // ═══════════════════════════════════════════════════════════════════════════════
 // Static Error Messages
 // ═══════════════════════════════════════════════════════════════════════════════
+//
+// Path failures carry a class prefix so integrators can classify a revert message:
+// - `D: ` decode - the arguments alone are malformed (bad action, index, mode, PPM,
+//   duplicate token, split limit); the same path fails again whatever the chain state
+// - `E: ` execution - the path decoded but failed against on-chain state (balances,
+//   pool responses, slippage, owner settings); it may succeed later
+// Admin and claim errors carry no prefix.
+
+// --- Path: decode ---
+
+pub const ERR_INSTRUCTION_PPM_EXCEEDS_100_PERCENT: &str = "D: PPM value exceeds 1,000,000 (100%)";
+pub const ERR_NO_EXPECTED_OUTPUTS: &str = "D: ExpectOutputs requires at least one token";
+pub const ERR_SAME_COIN_INDEX: &str = "D: Coin indices i and j must differ";
+pub const ERR_SOFT_FAIL_SWAP_ONLY: &str =
+    "D: Soft-fail requires a path of single-input xExchange or Jex swaps";
+pub const ERR_ONEDEX_SINGLE_INPUT: &str = "D: OneDex swap requires exactly one input payment";
+pub const ERR_ZAP_TWO_INPUTS: &str = "D: ZAP add liquidity requires exactly two inputs";
+pub const ERR_ALLOW_FAILURE_SWAP_ONLY: &str = "D: AllowFailure only applies to a swap";
+pub const ERR_LP_TO_CALLER_ZAP_ONLY: &str =
+    "D: LpToCaller only applies to an xExchange, OneDex or Jex add liquidity";
+pub const ERR_MIN_LP_VALUE_XEXCHANGE_ONLY: &str =
+    "D: SetMinLpValue only applies to an xExchange add liquidity";
+pub const ERR_NO_INPUT_PAYMENT: &str = "D: At least one input payment is required";
+pub const ERR_ARB_NOT_ROUND_TRIP: &str = "D: Arbitrage payment must include the output token";
+pub const ERR_PRECISION_EXCEEDS_DECIMALS: &str = "D: Precision exceeds the output token decimals";
+
+// --- Path: execution ---
+
+pub const ERR_PREV_AMOUNT_NOT_AVAILABLE: &str = "E: PrevAmount not available";
+pub const ERR_ZERO_INPUT_AMOUNT: &str = "E: Zero input amount";
+pub const ERR_POOL_ADDRESS_UNRESOLVED: &str = "E: Pool address unresolved for action";
+pub const ERR_NO_REWARDS_TO_RESTAKE: &str = "E: ClaimAndRestake claimed no rewards to restake";
+pub const ERR_LP_TO_CALLER_NOTHING_MINTED: &str = "E: LpToCaller add minted no LP";
+pub const ERR_LP_TO_CALLER_CHARGES_FEES: &str =
+    "E: LP sent straight to the caller is only allowed when no fee is taken from the output";
+pub const ERR_CALLBACK_NOT_WHITELISTED: &str = "E: Callback target not whitelisted";
+pub const ERR_PAIR_FEE_NOT_FOUND: &str = "E: No fee stored for the pool";
+pub const ERR_PAIR_FEE_NO_TIER: &str = "E: Pool fee matches no PairFee tier";
+pub const ERR_ZAP_OUT_NO_OUTPUT: &str = "E: Remove liquidity to a single token produced nothing";
+pub const ERR_WRAPPER_NO_OUTPUT: &str = "E: EGLD wrapper returned no tokens";
+
+// --- Admin and claim ---
 
-pub const ERR_PREV_AMOUNT_NOT_AVAILABLE: &str = "PrevAmount not available";
-pub const ERR_PREV_AMOUNT_TOKEN_MISMATCH: &str = "PrevAmount token mismatch";
-pub const ERR_ZERO_INPUT_AMOUNT: &str = "Zero input amount";
 pub const ERR_FEE_EXCEEDS_100: &str = "Fee exceeds 100%";
 pub const ERR_REFERRAL_FEE_EXCEEDS_50: &str =
     "Referral fee exceeds 50% (total fees would exceed 100%)";
 pub const ERR_REFERRAL_NOT_FOUND: &str = "Referral not found";
+pub const ERR_LAST_ALLOWED_TOKEN: &str =
+    "Cannot remove the last allowed token, clear the restriction instead";
+pub const ERR_REFERRAL_TIERS_NOT_ASCENDING: &str = "Referral fee tier thresholds must be ascending";
+pub const ERR_REFERRAL_CAMPAIGN_WINDOW: &str = "Referral campaign must end after it starts";
+pub const ERR_REFERRAL_SWEEP_DISABLED: &str = "Stale referral sweeping is disabled";
+pub const ERR_REFERRAL_SWEEP_DRAINING: &str = "Stale referral sweeping is disabled while draining";
+pub const ERR_REFERRAL_NOT_STALE: &str = "Referral is not stale";
+pub const ERR_NOT_MIGRATION_SOURCE: &str = "Caller is not the migration source";
+pub const ERR_MIGRATION_REFERRAL_CONFLICT: &str = "Referral id is taken by another owner";
+pub const ERR_INVALID_ACTION: &str = "Invalid action type";
+pub const ERR_INSUFFICIENT_EGLD_BALANCE: &str =
+    "Amount exceeds the contract EGLD balance not owed as fees";
+pub const ERR_EGLD_LIABILITY_NOT_SEEDED: &str =
+    "EGLD fee liability is not seeded yet, call seedEgldFeeLiability";
+pub const ERR_EGLD_LIABILITY_SEEDED: &str = "EGLD fee liability is already seeded";
+pub const ERR_EGLD_LIABILITY_SEED_GAP: &str =
+    "Seeding must continue from getEgldLiabilitySeedCursor";
 pub const ERR_PPM_EXCEEDS_100_PERCENT: &str = "PPM value exceeds 1,000,000 (100%)";
+pub const ERR_NOT_STAKING_ACTION: &str = "Action is not a liquid staking action";
+pub const ERR_TOKEN_DECIMALS_TOO_LARGE: &str = "Token decimals exceed the ESDT maximum of 18";
 
 // ═══════════════════════════════════════════════════════════════════════════════
 // Dynamic Error Prefixes (token info appended at runtime)
 // ═══════════════════════════════════════════════════════════════════════════════
 
-pub const ERR_ONLY_FUNGIBLE_PREFIX: &[u8] = b"Only fungible ESDT tokens are accepted, got ";
-pub const ERR_TOKEN_NOT_FOUND_PREFIX: &[u8] = b"Token not found in vault: ";
-pub const ERR_INSUFFICIENT_BALANCE_PREFIX: &[u8] = b"Insufficient vault balance for token ";
+pub const ERR_ONLY_FUNGIBLE_PREFIX: &[u8] = b"D: Only fungible ESDT tokens are accepted, got ";
+pub const ERR_TOKEN_NOT_FOUND_PREFIX: &[u8] = b"E: Token not found in vault: ";
+pub const ERR_INSUFFICIENT_BALANCE_PREFIX: &[u8] = b"E: Insufficient vault balance for token ";
+pub const ERR_PREV_AMOUNT_TOKEN_MISMATCH_PREFIX: &[u8] = b"E: PrevAmount token mismatch: expected ";
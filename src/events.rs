@@ -0,0 +1,53 @@
+multiversx_sc::imports!();
+
+/// Events emitted by the aggregator for off-chain indexing
+#[multiversx_sc::module]
+pub trait Events {
+    /// Emitted once per aggregation with a route summary
+    /// `token_count` = distinct tokens touched, `protocol_count` = distinct protocols called,
+    /// `peak_vault_tokens` = most distinct tokens held by the vault at once
+    #[event("aggregate")]
+    fn aggregate_event(
+        &self,
+        #[indexed] caller: &ManagedAddress,
+        #[indexed] token_out: &TokenId,
+        #[indexed] amount_out: &BigUint,
+        #[indexed] instruction_count: u32,
+        #[indexed] token_count: u32,
+        #[indexed] protocol_count: u32,
+        #[indexed] peak_vault_tokens: u32,
+    );
+
+    /// Emitted whenever a referral balance grows
+    #[event("referralAccrued")]
+    fn referral_accrued_event(
+        &self,
+        #[indexed] referral_id: u64,
+        #[indexed] token: &TokenId,
+        amount: &BigUint,
+    );
+
+    /// Emitted whenever an admin fee balance grows (fees and kept dust)
+    #[event("adminAccrued")]
+    fn admin_accrued_event(&self, #[indexed] token: &TokenId, amount: &BigUint);
+
+    /// Emitted per instruction while gas profiling is enabled
+    /// `index` = position in the path (the auto-stake step comes last),
+    /// `action` = compact action byte, `gas_used` = gas consumed by its dispatch
+    #[event("gasProfile")]
+    fn gas_profile_event(&self, #[indexed] index: u32, #[indexed] action: u8, gas_used: u64);
+
+    /// Emitted once per ZAP add-liquidity, after the pre-swap
+    /// Amounts are what the pool kept: the balanced inputs minus refunded dust
+    #[event("addLiquidity")]
+    fn add_liquidity_event(
+        &self,
+        #[indexed] pool: &ManagedAddress,
+        #[indexed] token_first: &TokenId,
+        #[indexed] amount_first: &BigUint,
+        #[indexed] token_second: &TokenId,
+        #[indexed] amount_second: &BigUint,
+        #[indexed] lp_token: &TokenId,
+        lp_minted: &BigUint,
+    );
+}
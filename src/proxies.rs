@@ -34,6 +34,21 @@ pub trait DexProxy {
         second_token_amount_min: BigUint,
     );
 
+    #[view(getReservesAndTotalSupply)]
+    fn xexchange_reserves_and_total_supply(&self) -> MultiValue3<BigUint, BigUint, BigUint>;
+
+    /// xExchange's own quote for a fixed-input swap
+    #[view(getAmountOut)]
+    fn xexchange_amount_out(&self, token_in: TokenIdentifier, amount_in: BigUint) -> BigUint;
+
+    /// xExchange safe price view (TWAP over the pair's price observations)
+    #[view(getSafePriceByDefaultOffset)]
+    fn xexchange_safe_price(
+        &self,
+        pair_address: ManagedAddress,
+        input_payment: EsdtTokenPayment,
+    ) -> EsdtTokenPayment;
+
     // ═══════════════════════════════════════════════════════════════════════════
     // OneDex
     // ═══════════════════════════════════════════════════════════════════════════
@@ -56,6 +71,12 @@ pub trait DexProxy {
         unwrap_required: bool,
     );
 
+    #[view(getPairFirstTokenReserve)]
+    fn onedex_first_token_reserve(&self, pair_id: usize) -> BigUint;
+
+    #[view(getPairSecondTokenReserve)]
+    fn onedex_second_token_reserve(&self, pair_id: usize) -> BigUint;
+
     // ═══════════════════════════════════════════════════════════════════════════
     // Jex (CPMM)
     // ═══════════════════════════════════════════════════════════════════════════
@@ -64,6 +85,12 @@ pub trait DexProxy {
     #[endpoint(swapTokensFixedInput)]
     fn jex(&self, min_amount_out: BigUint);
 
+    #[view(getFirstTokenReserve)]
+    fn jex_first_token_reserve(&self) -> BigUint;
+
+    #[view(getSecondTokenReserve)]
+    fn jex_second_token_reserve(&self) -> BigUint;
+
     // ═══════════════════════════════════════════════════════════════════════════
     // Jex (Stable)
     // ═══════════════════════════════════════════════════════════════════════════
@@ -88,6 +115,11 @@ pub trait DexProxy {
     #[endpoint(exchange)]
     fn ash_exchange_stable(&self, token_out: TokenIdentifier, amount_out_min: BigUint);
 
+    /// Swap coin `i` for coin `j`, for pools where a token id does not pick one coin
+    #[payable("*")]
+    #[endpoint(exchangeIndexed)]
+    fn ash_exchange_stable_indexed(&self, i: u32, j: u32, amount_out_min: BigUint);
+
     #[payable("*")]
     #[endpoint(addLiquidity)]
     fn ash_add_liquidity_stable(
@@ -138,6 +170,11 @@ pub trait DexProxy {
     #[endpoint(delegate)]
     fn delegate_hatom(&self);
 
+    /// Xoxno staking reward claim
+    #[payable("*")]
+    #[endpoint(claimRewards)]
+    fn claim_rewards(&self);
+
     // ═══════════════════════════════════════════════════════════════════════════
     // Hatom Lending
     // ═══════════════════════════════════════════════════════════════════════════
@@ -149,4 +186,9 @@ pub trait DexProxy {
     #[payable("*")]
     #[endpoint(redeem)]
     fn hatom_redeem(&self, underlying_amount: OptionalValue<BigUint>);
+
+    /// Controller: deposit the paid hTokens as collateral for `opt_account`
+    #[payable("*")]
+    #[endpoint(enterMarkets)]
+    fn hatom_enter_markets(&self, opt_account: OptionalValue<ManagedAddress>);
 }
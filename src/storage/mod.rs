@@ -1,5 +1,13 @@
-use crate::constants::{HATOM_CONTROLLER, ONE_DEX_ROUTER, TOTAL_FEE, XEXCHANGE_ROUTER};
-use crate::types::{ActionType, PairFee, PairTokens, ReferralConfig};
+use crate::constants::{
+    DEFAULT_CALLBACK_GAS, HATOM_CONTROLLER, JEX_STABLE_MIN_MULTIPLIER, MAX_TOKEN_DECIMALS,
+    MIN_INTERNAL_OUTPUT, ONE_DEX_ROUTER, TOTAL_FEE, WRAPPER_SC, XEXCHANGE_FEE_DENOM,
+    XEXCHANGE_ROUTER,
+};
+use crate::proxies;
+use crate::types::{
+    ActionCategory, ActionType, CallbackTarget, PairFee, PairTokens, ReferralCampaign,
+    ReferralConfig, ReferralFeeTier,
+};
 
 multiversx_sc::imports!();
 
@@ -8,6 +16,9 @@ pub type PairReserves<M> = (BigUint<M>, BigUint<M>);
 
 #[multiversx_sc::module]
 pub trait Storage {
+    #[proxy]
+    fn pool_view_proxy(&self, address: ManagedAddress) -> proxies::Proxy<Self::Api>;
+
     // =========================================================================
     // Unified Reserve & Fee Getters
     // =========================================================================
@@ -51,10 +62,25 @@ pub trait Storage {
     }
 
     /// Get reserves for a pair based on action type (only for add liquidity zap)
+    /// Reads pool storage directly, falling back to the DEX's own reserve views
+    /// when the storage layout yields an empty reserve
     fn get_reserves(
         &self,
         action: &ActionType<Self::Api>,
         pair_address: &ManagedAddress,
+    ) -> PairReserves<Self::Api> {
+        let (first_reserve, second_reserve) = self.get_reserves_from_storage(action, pair_address);
+        if first_reserve == 0u64 || second_reserve == 0u64 {
+            return self.get_reserves_from_view(action, pair_address);
+        }
+        (first_reserve, second_reserve)
+    }
+
+    /// Read reserves straight from the pool's storage
+    fn get_reserves_from_storage(
+        &self,
+        action: &ActionType<Self::Api>,
+        pair_address: &ManagedAddress,
     ) -> PairReserves<Self::Api> {
         match action {
             ActionType::XExchangeAddLiquidity => self.get_xexchange_reserves(pair_address),
@@ -67,6 +93,48 @@ pub trait Storage {
         }
     }
 
+    /// Read reserves through each DEX's public views (readonly sync call)
+    fn get_reserves_from_view(
+        &self,
+        action: &ActionType<Self::Api>,
+        pair_address: &ManagedAddress,
+    ) -> PairReserves<Self::Api> {
+        match action {
+            ActionType::XExchangeAddLiquidity => {
+                let (first_reserve, second_reserve, _) =
+                    self.get_xexchange_reserves_and_supply(pair_address);
+                (first_reserve, second_reserve)
+            }
+            ActionType::OneDexAddLiquidity(pair_id) => {
+                let first_reserve = self
+                    .pool_view_proxy(pair_address.clone())
+                    .onedex_first_token_reserve(*pair_id)
+                    .returns(ReturnsResult)
+                    .sync_call_readonly();
+                let second_reserve = self
+                    .pool_view_proxy(pair_address.clone())
+                    .onedex_second_token_reserve(*pair_id)
+                    .returns(ReturnsResult)
+                    .sync_call_readonly();
+                (first_reserve, second_reserve)
+            }
+            ActionType::JexAddLiquidity => {
+                let first_reserve = self
+                    .pool_view_proxy(pair_address.clone())
+                    .jex_first_token_reserve()
+                    .returns(ReturnsResult)
+                    .sync_call_readonly();
+                let second_reserve = self
+                    .pool_view_proxy(pair_address.clone())
+                    .jex_second_token_reserve()
+                    .returns(ReturnsResult)
+                    .sync_call_readonly();
+                (first_reserve, second_reserve)
+            }
+            _ => (BigUint::zero(), BigUint::zero()),
+        }
+    }
+
     /// Get fee parameters for a pair based on action type (only for add liquidity zap)
     /// Returns (total_fee, special_fee, lp_fee, fee_denom)
     /// - total_fee: used for output calculation
@@ -85,16 +153,16 @@ pub trait Storage {
                 let special_fee = self
                     .xexchange_special_fee_percent(pair_address.clone())
                     .get();
-                (total_fee, special_fee, 0, 100_000)
+                (total_fee, special_fee, 0, XEXCHANGE_FEE_DENOM)
             }
             // OneDex: PairFee enum with base 10,000
             // owner_fee + real_yield_fee leave pool, lp_fee stays
             ActionType::OneDexAddLiquidity(pair_id) => {
                 let router = ManagedAddress::from(ONE_DEX_ROUTER);
                 let pair_fee = self.one_dex_pair_fee(router, *pair_id).get();
-                let total = pair_fee.get_total_fee_percentage();
+                let (total, fee_denom) = pair_fee.as_fee_fraction();
                 let special = pair_fee.get_special_fee_percentage();
-                (total, special, 0, TOTAL_FEE as u64)
+                (total, special, 0, fee_denom)
             }
             // JEX: liq_providers_fees + platform_fees with base 10,000
             // Only LP fees stay in pool, platform fees leave (fee-on-output model)
@@ -150,27 +218,50 @@ pub trait Storage {
         )
     }
 
+    /// Reserves and LP supply through the pair's `getReservesAndTotalSupply` view
+    fn get_xexchange_reserves_and_supply(
+        &self,
+        pair_address: &ManagedAddress,
+    ) -> (BigUint, BigUint, BigUint) {
+        self.pool_view_proxy(pair_address.clone())
+            .xexchange_reserves_and_total_supply()
+            .returns(ReturnsResult)
+            .sync_call_readonly()
+            .into_tuple()
+    }
+
+    /// Local xExchange pair address for a token pair, consulted before the router's pair
+    /// map in either token order (fork bring-up, mainnet patching)
+    #[view(getPairOverride)]
+    #[storage_mapper("pairOverride")]
+    fn pair_override(&self, pair: &PairTokens<Self::Api>) -> SingleValueMapper<ManagedAddress>;
+
     fn get_pair_x(
         &self,
         first_token_id: &TokenIdentifier,
         second_token_id: &TokenIdentifier,
     ) -> ManagedAddress {
+        let pair = PairTokens {
+            first_token_id: first_token_id.clone(),
+            second_token_id: second_token_id.clone(),
+        };
+        let reversed = PairTokens {
+            first_token_id: second_token_id.clone(),
+            second_token_id: first_token_id.clone(),
+        };
+        for key in [&pair, &reversed] {
+            let pair_override = self.pair_override(key);
+            if !pair_override.is_empty() {
+                return pair_override.get();
+            }
+        }
+
         let mapper = self.pair_map(ManagedAddress::from(XEXCHANGE_ROUTER));
 
-        let mut address = mapper
-            .get(&PairTokens {
-                first_token_id: first_token_id.clone(),
-                second_token_id: second_token_id.clone(),
-            })
-            .unwrap_or_else(ManagedAddress::zero);
+        let mut address = mapper.get(&pair).unwrap_or_else(ManagedAddress::zero);
 
         if address.is_zero() {
-            address = mapper
-                .get(&PairTokens {
-                    first_token_id: second_token_id.clone(),
-                    second_token_id: first_token_id.clone(),
-                })
-                .unwrap_or_else(ManagedAddress::zero);
+            address = mapper.get(&reversed).unwrap_or_else(ManagedAddress::zero);
         }
         address
     }
@@ -179,6 +270,13 @@ pub trait Storage {
     // OneDex Storage
     // =========================================================================
 
+    /// Id of the last pair created on the router; pair ids run from 1 to it
+    #[storage_mapper_from_address("last_pair_id")]
+    fn onedex_last_pair_id(
+        &self,
+        address: ManagedAddress,
+    ) -> SingleValueMapper<usize, ManagedAddress>;
+
     #[storage_mapper_from_address("pair_first_token_id")]
     fn onedex_first_token_id(
         &self,
@@ -268,7 +366,16 @@ pub trait Storage {
         token_id: &TokenIdentifier,
     ) -> SingleValueMapper<ManagedAddress, ManagedAddress>;
 
+    /// Local Hatom market for an hToken, consulted before the controller's money markets
+    #[view(getMarketOverride)]
+    #[storage_mapper("marketOverride")]
+    fn market_override(&self, h_token: &TokenIdentifier) -> SingleValueMapper<ManagedAddress>;
+
     fn get_hatom_market(&self, h_token: &TokenIdentifier) -> ManagedAddress {
+        let market_override = self.market_override(h_token);
+        if !market_override.is_empty() {
+            return market_override.get();
+        }
         self.money_markets(ManagedAddress::from(HATOM_CONTROLLER), h_token)
             .get()
     }
@@ -301,6 +408,21 @@ pub trait Storage {
         pair_id: usize,
     ) -> SingleValueMapper<PairFee, ManagedAddress>;
 
+    // =========================================================================
+    // Wrapper Storage
+    // =========================================================================
+
+    #[storage_mapper_from_address("wrappedEgldTokenId")]
+    fn wrapped_egld_token_id(
+        &self,
+        address: ManagedAddress,
+    ) -> SingleValueMapper<TokenIdentifier, ManagedAddress>;
+
+    fn get_wegld_token_id(&self) -> TokenIdentifier {
+        self.wrapped_egld_token_id(ManagedAddress::from(WRAPPER_SC))
+            .get()
+    }
+
     // =========================================================================
     // Fee & Referral Storage (local contract storage)
     // =========================================================================
@@ -312,13 +434,355 @@ pub trait Storage {
     #[storage_mapper("refConfig")]
     fn referral_config(&self, id: u64) -> SingleValueMapper<ReferralConfig<Self::Api>>;
 
+    /// Minimum output amount before the referral share is paid out (0 = always share)
+    /// In fee-on-input mode it is compared to each payment amount instead
+    /// Kept apart from ReferralConfig so already stored configs still decode
+    #[view(getReferralMinTrade)]
+    #[storage_mapper("refMinTrade")]
+    fn referral_min_trade(&self, id: u64) -> SingleValueMapper<BigUint>;
+
+    /// Progressive referral fees, ascending by `min_output` (empty = flat `config.fee`)
+    #[view(getReferralFeeTiers)]
+    #[storage_mapper("refFeeTiers")]
+    fn referral_fee_tiers(
+        &self,
+        id: u64,
+    ) -> SingleValueMapper<ManagedVec<ReferralFeeTier<Self::Api>>>;
+
+    /// Time-limited boosted fee of a referral (empty = no campaign)
+    #[view(getReferralCampaign)]
+    #[storage_mapper("refCampaign")]
+    fn referral_campaign(&self, id: u64) -> SingleValueMapper<ReferralCampaign>;
+
+    /// Referral fee for a trade of `output_amount`: the campaign fee while its window is
+    /// open, otherwise the highest tier reached, else the flat fee
+    fn get_referral_fee(
+        &self,
+        id: u64,
+        config: &ReferralConfig<Self::Api>,
+        output_amount: &BigUint,
+    ) -> u32 {
+        let campaign = self.referral_campaign(id);
+        if !campaign.is_empty() {
+            let campaign = campaign.get();
+            let epoch = self.blockchain().get_block_epoch();
+            if campaign.start_epoch <= epoch && epoch <= campaign.end_epoch {
+                return campaign.fee;
+            }
+        }
+
+        let mut fee = config.fee;
+        for tier in self.referral_fee_tiers(id).get().iter() {
+            if *output_amount < tier.min_output {
+                break;
+            }
+            fee = tier.fee;
+        }
+        fee
+    }
+
+    /// Output tokens a referral earns on (empty = every token)
+    #[view(getReferralAllowedTokens)]
+    #[storage_mapper("refTokens")]
+    fn referral_allowed_tokens(&self, id: u64) -> UnorderedSetMapper<TokenId>;
+
     #[storage_mapper("refBalance")]
     fn referrer_balances(&self, referral_id: u64) -> MapMapper<TokenId, BigUint>;
 
+    /// Epoch of a referral's last fee accrual or claim (empty = none since recording began)
+    #[view(getReferralLastActivity)]
+    #[storage_mapper("refLastActivity")]
+    fn referral_last_activity(&self, id: u64) -> SingleValueMapper<u64>;
+
+    /// Record activity of a referral in the current epoch
+    /// Staleness is counted in epochs, so only the first activity of an epoch is written
+    fn touch_referral_activity(&self, id: u64) {
+        let epoch = self.blockchain().get_block_epoch();
+        let last_activity = self.referral_last_activity(id);
+        if last_activity.is_empty() || last_activity.get() != epoch {
+            last_activity.set(epoch);
+        }
+    }
+
+    /// Epochs without activity after which a referral's balances can be swept (0 = never)
+    #[view(getReferralStaleEpochs)]
+    #[storage_mapper("refStaleEpochs")]
+    fn referral_stale_epochs(&self) -> SingleValueMapper<u64>;
+
+    /// Referral a caller applies to its own trades when passing `referral_id` 0
+    #[view(getCallerDefaultReferral)]
+    #[storage_mapper("callerRef")]
+    fn caller_default_referral(&self, caller: &ManagedAddress) -> SingleValueMapper<u64>;
+
+    /// `referral_id`, or the caller's default referral when it is 0
+    fn resolve_referral_id(&self, referral_id: u64) -> u64 {
+        if referral_id != 0 {
+            return referral_id;
+        }
+        self.caller_default_referral(&self.blockchain().get_caller())
+            .get()
+    }
+
     #[view(getStaticFee)]
     #[storage_mapper("fee")]
     fn static_fee(&self) -> SingleValueMapper<u32>;
 
+    /// Contract endpoints `xoCallback` may call after an aggregation
+    /// Whitelisted per endpoint: the call is made with the aggregator as sender
+    #[view(getCallbackWhitelist)]
+    #[storage_mapper("cbEndpoints")]
+    fn callback_whitelist(&self) -> UnorderedSetMapper<CallbackTarget<Self::Api>>;
+
+    /// Gas forwarded to an `xoCallback` target (0 = DEFAULT_CALLBACK_GAS)
+    #[view(getCallbackGasLimit)]
+    #[storage_mapper("cbGas")]
+    fn callback_gas_limit(&self) -> SingleValueMapper<u64>;
+
+    /// Gas for an `xoCallback` target: the configured limit, capped by the gas left
+    fn get_callback_gas(&self) -> u64 {
+        let gas_limit = match self.callback_gas_limit().get() {
+            0 => DEFAULT_CALLBACK_GAS,
+            gas_limit => gas_limit,
+        };
+        core::cmp::min(gas_limit, self.blockchain().get_gas_left())
+    }
+
+    /// Promo switch: when enabled no fee is taken, whatever the fee settings say
+    #[view(isFreeMode)]
+    #[storage_mapper("freeMode")]
+    fn free_mode(&self) -> SingleValueMapper<bool>;
+
+    /// Fee collection on the payment tokens before the path runs instead of on the output
+    #[view(isFeeOnInput)]
+    #[storage_mapper("feeOnInput")]
+    fn fee_on_input(&self) -> SingleValueMapper<bool>;
+
+    /// Wind-down switch: fees still apply but all go to admin, referral balances stop
+    /// growing and stay claimable for good (stale sweeping is disabled)
+    #[view(isDrainMode)]
+    #[storage_mapper("drainMode")]
+    fn drain_mode(&self) -> SingleValueMapper<bool>;
+
+    /// Profiling switch: when enabled every instruction emits its gas use
+    #[view(isGasProfiling)]
+    #[storage_mapper("gasProfiling")]
+    fn gas_profiling(&self) -> SingleValueMapper<bool>;
+
+    /// Static fee for a specific output token, takes precedence over `static_fee`
+    #[view(getStaticFeeOverride)]
+    #[storage_mapper("feeOverride")]
+    fn static_fee_override(&self, token: &TokenId) -> SingleValueMapper<u32>;
+
+    /// Static fee charged on an output token (override first, then the global fee)
+    fn get_static_fee(&self, token: &TokenId) -> u32 {
+        let fee_override = self.static_fee_override(token);
+        if fee_override.is_empty() {
+            self.static_fee().get()
+        } else {
+            fee_override.get()
+        }
+    }
+
     #[storage_mapper("balances")]
     fn admin_fees(&self) -> MapMapper<TokenId, BigUint>;
+
+    /// EGLD booked as admin fees and referral balances, kept out of `rescueEgld`
+    #[view(getEgldFeeLiability)]
+    #[storage_mapper("egldOwed")]
+    fn egld_fee_liability(&self) -> SingleValueMapper<BigUint>;
+
+    /// Next balance owner `seedEgldFeeLiability` adds to the liability
+    /// (0 = admin fees, then referral ids)
+    #[view(getEgldLiabilitySeedCursor)]
+    #[storage_mapper("egldOwedCursor")]
+    fn egld_liability_seed_cursor(&self) -> SingleValueMapper<u64>;
+
+    /// Set once the liability covers every balance owner, which unlocks `rescueEgld`
+    #[view(isEgldLiabilitySeeded)]
+    #[storage_mapper("egldOwedSeeded")]
+    fn egld_liability_seeded(&self) -> SingleValueMapper<bool>;
+
+    /// Whether the EGLD balance of `referral_id` (0 = admin fees) is in the liability
+    /// Owners not seeded yet are left out until seeding reads their whole balance
+    fn tracks_egld_liability(&self, referral_id: u64) -> bool {
+        self.egld_liability_seeded().get() || referral_id < self.egld_liability_seed_cursor().get()
+    }
+
+    /// Track a fee credit of `token` to `referral_id` (0 = admin fees) in the EGLD liability
+    fn book_egld_liability(&self, referral_id: u64, token: &TokenId, amount: &BigUint) {
+        if token.is_native() && self.tracks_egld_liability(referral_id) {
+            self.egld_fee_liability().update(|owed| *owed += amount);
+        }
+    }
+
+    /// Track a fee payout of `token` by `referral_id` (0 = admin fees) in the EGLD liability
+    fn release_egld_liability(&self, referral_id: u64, token: &TokenId, amount: &BigUint) {
+        if token.is_native() && self.tracks_egld_liability(referral_id) {
+            self.egld_fee_liability().update(|owed| *owed -= amount);
+        }
+    }
+
+    /// Gas ceiling forwarded to DEX sub-calls per compact action (0 = all remaining gas)
+    #[view(getActionGasLimit)]
+    #[storage_mapper("gasLimit")]
+    fn action_gas_limit(&self, action: u8) -> SingleValueMapper<u64>;
+
+    /// Minimum output passed to internal pool calls of a category (0 = MIN_INTERNAL_OUTPUT)
+    #[view(getInternalMinOutput)]
+    #[storage_mapper("internalMin")]
+    fn internal_min_output(&self, category: ActionCategory) -> SingleValueMapper<BigUint>;
+
+    /// Minimum output for internal pool calls of `category`
+    fn get_internal_min_output(&self, category: ActionCategory) -> BigUint {
+        let min = self.internal_min_output(category).get();
+        if min == 0u64 {
+            BigUint::from(MIN_INTERNAL_OUTPUT)
+        } else {
+            min
+        }
+    }
+
+    /// Maximum number of instructions allowed to withdraw the same token (0 = unlimited)
+    #[view(getMaxSplitsPerToken)]
+    #[storage_mapper("maxSplits")]
+    fn max_splits_per_token(&self) -> SingleValueMapper<u32>;
+
+    /// Maximum number of payments returned to the recipient, output and refunds
+    /// together (0 = unlimited)
+    #[view(getMaxOutputTokens)]
+    #[storage_mapper("maxOutTokens")]
+    fn max_output_tokens(&self) -> SingleValueMapper<u32>;
+
+    /// Lowest accepted min_amount_out, in PPM of the input's spot value on the
+    /// direct xExchange pair (0 = disabled)
+    #[view(getMinOutputPpm)]
+    #[storage_mapper("minOutPpm")]
+    fn min_output_ppm(&self) -> SingleValueMapper<u32>;
+
+    /// xExchange safe price view used to sanity check ZAP reserves (empty = disabled)
+    #[view(getZapOracleAddress)]
+    #[storage_mapper("zapOracle")]
+    fn zap_oracle_address(&self) -> SingleValueMapper<ManagedAddress>;
+
+    /// Allowed deviation of pool reserves from the oracle TWAP, in PPM
+    #[view(getZapOracleTolerance)]
+    #[storage_mapper("zapOracleTol")]
+    fn zap_oracle_tolerance_ppm(&self) -> SingleValueMapper<u32>;
+
+    /// Smallest ZAP pre-swap worth a DEX call, per swapped token (0 = always swap)
+    #[view(getMinZapSwap)]
+    #[storage_mapper("minZapSwap")]
+    fn min_zap_swap(&self, token: &TokenId) -> SingleValueMapper<BigUint>;
+
+    /// Compact staking action run on the path's last output when `token_out` is this
+    /// liquid staking token and the path did not reach it (empty = no auto-stake)
+    #[view(getAutoStakeAction)]
+    #[storage_mapper("autoStake")]
+    fn auto_stake_action(&self, token: &TokenId) -> SingleValueMapper<u8>;
+
+    /// Global Jex stable swap min multiplier (0 = JEX_STABLE_MIN_MULTIPLIER)
+    #[view(getJexStableMinMultiplier)]
+    #[storage_mapper("jexMinMul")]
+    fn jex_stable_min_multiplier(&self) -> SingleValueMapper<u64>;
+
+    /// Per-pool Jex stable swap min multiplier, takes precedence over the global one
+    #[view(getJexStablePoolMinMultiplier)]
+    #[storage_mapper("jexPoolMinMul")]
+    fn jex_stable_pool_min_multiplier(&self, pool: &ManagedAddress) -> SingleValueMapper<u64>;
+
+    /// Multiplier for a Jex stable pool's swap min (pool override, then global, then default)
+    fn get_jex_stable_min_multiplier(&self, pool: &ManagedAddress) -> u64 {
+        let pool_multiplier = self.jex_stable_pool_min_multiplier(pool).get();
+        if pool_multiplier > 0 {
+            return pool_multiplier;
+        }
+        let multiplier = self.jex_stable_min_multiplier().get();
+        if multiplier > 0 {
+            multiplier
+        } else {
+            JEX_STABLE_MIN_MULTIPLIER
+        }
+    }
+
+    /// Cap of the Jex stable minimums as a PPM fraction of a single input (0 = no cap)
+    /// Stable pools return about their input, so this keeps the absolute minimum from
+    /// blocking tiny trades
+    #[view(getJexStableMinInputPpm)]
+    #[storage_mapper("jexMinInputPpm")]
+    fn jex_stable_min_input_ppm(&self) -> SingleValueMapper<u32>;
+
+    /// When enabled, EGLD/WEGLD inputs are wrapped/unwrapped on the fly
+    #[view(isAutoWrapEnabled)]
+    #[storage_mapper("autoWrap")]
+    fn auto_wrap_enabled(&self) -> SingleValueMapper<bool>;
+
+    /// Tokens whose transfers may deliver less than sent; their vault balances are
+    /// reconciled against the contract balance after every instruction
+    #[view(getFeeOnTransferTokens)]
+    #[storage_mapper("fotTokens")]
+    fn fee_on_transfer_tokens(&self) -> UnorderedSetMapper<TokenId>;
+
+    /// Decimals of known ESDTs, filled by the owner
+    /// ESDT properties live in the metachain system SC, which a contract cannot read
+    /// synchronously, so tokens are cached ahead of the features scaling by decimals
+    #[view(getTokenDecimals)]
+    #[storage_mapper("tokenDecimals")]
+    fn token_decimals(&self) -> MapMapper<TokenId, u32>;
+
+    /// Decimals of `token`: 18 for EGLD, otherwise the cached value
+    fn get_decimals(&self, token: &TokenId) -> u32 {
+        if token.is_native() {
+            return MAX_TOKEN_DECIMALS;
+        }
+        match self.token_decimals().get(token) {
+            Some(decimals) => decimals,
+            None => sc_panic!("E: Decimals unknown for token {}", token),
+        }
+    }
+
+    /// Aggregator allowed to migrate its balances into this one (empty = none)
+    #[view(getMigrationSource)]
+    #[storage_mapper("migrationSource")]
+    fn migration_source(&self) -> SingleValueMapper<ManagedAddress>;
+
+    // =========================================================================
+    // Usage Metrics
+    // =========================================================================
+
+    /// Number of successful aggregations
+    #[view(getTotalAggregations)]
+    #[storage_mapper("totalAggs")]
+    fn total_aggregations(&self) -> SingleValueMapper<u64>;
+
+    /// Count distinct callers in `getUniqueCallers` (off by default)
+    /// Tracking keeps one `knownCaller` entry per distinct caller, never removed, so
+    /// storage grows with the user base
+    #[view(isUniqueCallerTrackingEnabled)]
+    #[storage_mapper("trackCallers")]
+    fn unique_caller_tracking(&self) -> SingleValueMapper<bool>;
+
+    /// Number of distinct addresses that completed at least one aggregation while
+    /// unique caller tracking was enabled
+    #[view(getUniqueCallers)]
+    #[storage_mapper("uniqueCallers")]
+    fn unique_callers(&self) -> SingleValueMapper<u64>;
+
+    #[view(isKnownCaller)]
+    #[storage_mapper("knownCaller")]
+    fn known_caller(&self, caller: &ManagedAddress) -> SingleValueMapper<bool>;
+
+    /// Count a successful aggregation: one counter write, plus a first-time caller's
+    /// entry when unique caller tracking is enabled
+    fn record_aggregation(&self, caller: &ManagedAddress) {
+        self.total_aggregations().update(|count| *count += 1);
+
+        if !self.unique_caller_tracking().get() {
+            return;
+        }
+        let known = self.known_caller(caller);
+        if known.is_empty() {
+            known.set(true);
+            self.unique_callers().update(|count| *count += 1);
+        }
+    }
 }
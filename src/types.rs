@@ -1,6 +1,8 @@
 use multiversx_sc::derive_imports::*;
 multiversx_sc::imports!();
 
+use crate::constants::TOTAL_FEE;
+
 /// Supported DEX types - the contract knows how to call each one
 #[type_abi]
 #[derive(
@@ -49,6 +51,138 @@ pub enum ActionType<M: ManagedTypeApi> {
     // Hatom operations
     HatomRedeem,
     HatomSupply(TokenIdentifier<M>), // hToken identifier output token
+
+    // Control
+    ClearPrev, // Reset prev_result between independent path segments
+
+    // xExchange composite operations
+    XExchangeRemoveLiquiditySingle(TokenIdentifier<M>), // Target output token (zap-out)
+
+    // AshSwap V1 Stable, LP minted straight to the caller
+    AshSwapPoolAddLiquidityToCaller,
+
+    // Liquid staking rewards
+    ClaimRewards, // Xoxno staking claim, claimed tokens are deposited into the vault
+
+    // AshSwap V2 swap with an explicit minimum
+    AshSwapV2SwapMin(BigUint<M>), // min_dy forwarded to the pool
+
+    // Control
+    SetMinLpOut(BigUint<M>), // Minimum LP for the next add-liquidity instruction
+    SetZapRatio(ZapRatio<M>), // Pool ratio bound for the next ZAP add
+    ExpectOutputs(ManagedVec<M, TokenIdentifier<M>>), // Output tokens of the next multi-output call
+
+    // Hatom composite
+    HatomCompound,    // Redeem hTokens and supply the underlying again in one dispatch
+    HatomEnterMarket, // Deposit hTokens as collateral for the caller
+
+    // Control
+    UnwrapOutputs, // Deposit the next call's WEGLD outputs as EGLD
+
+    // AshSwap V1 swap by coin index
+    AshSwapPoolSwapIndexed(CoinIndices), // Input and output coin indices
+
+    // Control
+    AllowFailure, // Refund the next swap's input instead of reverting when its pool call fails
+    RequireFullInput, // Revert if any payment token is left in the vault after the path
+    LpToCaller,   // Send the LP of the next ZAP add straight to the caller
+    SetMinLpValue(LpValueMin<M>), // Minimum reserve value of the LP minted by the next xExchange add
+
+    // Liquid staking rewards composite
+    ClaimAndRestake, // Xoxno staking claim, claimed rewards delegated again in one dispatch
+}
+
+impl<M: ManagedTypeApi> ActionType<M> {
+    /// Compact action this variant is encoded as
+    pub fn compact(&self) -> CompactAction {
+        match self {
+            ActionType::XExchangeSwap(_) => CompactAction::XExchangeSwap,
+            ActionType::XExchangeAddLiquidity => CompactAction::XExchangeAddLiquidity,
+            ActionType::XExchangeRemoveLiquidity => CompactAction::XExchangeRemoveLiquidity,
+            ActionType::AshSwapPoolSwap(_) => CompactAction::AshSwapPoolSwap,
+            ActionType::AshSwapPoolAddLiquidity => CompactAction::AshSwapPoolAddLiquidity,
+            ActionType::AshSwapPoolRemoveLiquidity(_) => CompactAction::AshSwapPoolRemoveLiquidity,
+            ActionType::AshSwapV2Swap => CompactAction::AshSwapV2Swap,
+            ActionType::AshSwapV2AddLiquidity => CompactAction::AshSwapV2AddLiquidity,
+            ActionType::AshSwapV2RemoveLiquidity(_) => CompactAction::AshSwapV2RemoveLiquidity,
+            ActionType::OneDexSwap(_) => CompactAction::OneDexSwap,
+            ActionType::OneDexAddLiquidity(_) => CompactAction::OneDexAddLiquidity,
+            ActionType::OneDexRemoveLiquidity => CompactAction::OneDexRemoveLiquidity,
+            ActionType::JexSwap => CompactAction::JexSwap,
+            ActionType::JexAddLiquidity => CompactAction::JexAddLiquidity,
+            ActionType::JexRemoveLiquidity => CompactAction::JexRemoveLiquidity,
+            ActionType::JexStableSwap(_) => CompactAction::JexStableSwap,
+            ActionType::JexStableAddLiquidity => CompactAction::JexStableAddLiquidity,
+            ActionType::JexStableRemoveLiquidity(_) => CompactAction::JexStableRemoveLiquidity,
+            ActionType::Wrapping => CompactAction::Wrapping,
+            ActionType::UnWrapping => CompactAction::UnWrapping,
+            ActionType::XoxnoLiquidStaking => CompactAction::XoxnoLiquidStaking,
+            ActionType::LXoxnoLiquidStaking => CompactAction::LXoxnoLiquidStaking,
+            ActionType::HatomLiquidStaking => CompactAction::HatomLiquidStaking,
+            ActionType::HatomRedeem => CompactAction::HatomRedeem,
+            ActionType::HatomSupply(_) => CompactAction::HatomSupply,
+            ActionType::ClearPrev => CompactAction::ClearPrev,
+            ActionType::XExchangeRemoveLiquiditySingle(_) => {
+                CompactAction::XExchangeRemoveLiquiditySingle
+            }
+            ActionType::AshSwapPoolAddLiquidityToCaller => {
+                CompactAction::AshSwapPoolAddLiquidityToCaller
+            }
+            ActionType::ClaimRewards => CompactAction::ClaimRewards,
+            ActionType::AshSwapV2SwapMin(_) => CompactAction::AshSwapV2SwapMin,
+            ActionType::SetMinLpOut(_) => CompactAction::SetMinLpOut,
+            ActionType::SetZapRatio(_) => CompactAction::SetZapRatio,
+            ActionType::ExpectOutputs(_) => CompactAction::ExpectOutputs,
+            ActionType::HatomCompound => CompactAction::HatomCompound,
+            ActionType::HatomEnterMarket => CompactAction::HatomEnterMarket,
+            ActionType::UnwrapOutputs => CompactAction::UnwrapOutputs,
+            ActionType::AshSwapPoolSwapIndexed(_) => CompactAction::AshSwapPoolSwapIndexed,
+            ActionType::AllowFailure => CompactAction::AllowFailure,
+            ActionType::RequireFullInput => CompactAction::RequireFullInput,
+            ActionType::LpToCaller => CompactAction::LpToCaller,
+            ActionType::SetMinLpValue(_) => CompactAction::SetMinLpValue,
+            ActionType::ClaimAndRestake => CompactAction::ClaimAndRestake,
+        }
+    }
+}
+
+/// Expected second-per-first reserve ratio (scaled by RATIO_PRECISION) and allowed deviation
+#[type_abi]
+#[derive(
+    TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, PartialEq, Debug, ManagedVecItem,
+)]
+pub struct ZapRatio<M: ManagedTypeApi> {
+    pub ratio: BigUint<M>,
+    pub tolerance_ppm: u32,
+}
+
+/// Minimum value of minted LP, in one of the pool's tokens at post-add reserves
+#[type_abi]
+#[derive(
+    TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, PartialEq, Debug, ManagedVecItem,
+)]
+pub struct LpValueMin<M: ManagedTypeApi> {
+    pub token: TokenIdentifier<M>,
+    pub amount: BigUint<M>,
+}
+
+/// AshSwap stable pool coin indices: swap coin `i` for coin `j`
+#[type_abi]
+#[derive(
+    TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, PartialEq, Debug, ManagedVecItem,
+)]
+pub struct CoinIndices {
+    pub i: u32,
+    pub j: u32,
+}
+
+/// Actions sharing one configurable internal minimum output
+/// Wrapping, staking and lending calls take no minimum and have no category
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, Copy, PartialEq, Debug)]
+pub enum ActionCategory {
+    Swap,
+    Liquidity,
 }
 
 /// How to determine the input amount for an instruction
@@ -59,6 +193,9 @@ pub enum ActionType<M: ManagedTypeApi> {
 pub enum AmountMode<M: ManagedTypeApi> {
     /// Fixed amount specified (first hop when input amount is known).
     Fixed(BigUint<M>),
+    /// Fixed amount, or the entire vault balance when it is short of it.
+    /// Tolerates off-chain amounts a few wei above the balance after rounding.
+    FixedOrAll(BigUint<M>),
     /// Parts per million of vault balance.
     /// 1_000_000 = 100%, 600_000 = 60%.
     Ppm(u32),
@@ -92,6 +229,44 @@ pub struct Instruction<M: ManagedTypeApi> {
     pub address: Option<ManagedAddress<M>>,
 }
 
+/// Statically known output of a decoded instruction (see `pathOutputTokens`)
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, PartialEq, ManagedVecItem)]
+pub enum PathOutput<M: ManagedTypeApi> {
+    /// Output token fixed by the instruction itself
+    Token(TokenIdentifier<M>),
+    /// Output depends on the pool (LP mint/burn, staking, redeem), tagged with the compact action
+    Unknown(u8),
+}
+
+/// ZAP math of one zappable add-liquidity venue (see `getZapMath`)
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, ManagedVecItem)]
+pub struct ZapVenueMath {
+    /// Compact add-liquidity action byte
+    pub action: u8,
+    /// Fee mode: false = fee on input (`FeeMode::OnInput`), true = on output (`FeeMode::OnOutput`)
+    pub fee_on_output: bool,
+    /// Denominator the venue's fee numerators are expressed in
+    pub fee_denom: u64,
+}
+
+/// Constants an off-chain ZAP replica must match (see `getZapMath`)
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, Clone)]
+pub struct ZapMath<M: ManagedTypeApi> {
+    pub max_binary_search_iterations: u32,
+    pub venues: ManagedVec<M, ZapVenueMath>,
+}
+
+/// Route summary collected while executing a path (see the `aggregate` event)
+pub struct PathStats {
+    pub instructions: u32,
+    pub tokens: u32,
+    pub protocols: u32,
+    pub peak_vault_tokens: u32,
+}
+
 // External
 
 #[type_abi]
@@ -118,6 +293,22 @@ impl PairFee {
         }
     }
 
+    /// Total fee as the (fee_num, fee_denom) pair the ZAP math takes, using the
+    /// OneDex pool's own base of 10,000 (e.g. 0.4% = 40 / 10,000)
+    pub fn as_fee_fraction(&self) -> (u64, u64) {
+        (self.get_total_fee_percentage(), TOTAL_FEE as u64)
+    }
+
+    /// Tier charging exactly `fee_num / fee_denom`, if any
+    pub fn from_fee_fraction(fee_num: u64, fee_denom: u64) -> Option<PairFee> {
+        [PairFee::Percent04, PairFee::Percent06, PairFee::Percent10]
+            .into_iter()
+            .find(|tier| {
+                let (tier_num, tier_denom) = tier.as_fee_fraction();
+                fee_num * tier_denom == tier_num * fee_denom
+            })
+    }
+
     /// Returns owner_fee + real_yield_fee (the portion that leaves the pool)
     pub fn get_special_fee_percentage(&self) -> u64 {
         match self {
@@ -128,6 +319,14 @@ impl PairFee {
     }
 }
 
+/// Contract endpoint `xoCallback` may call once an aggregation is done
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, PartialEq)]
+pub struct CallbackTarget<M: ManagedTypeApi> {
+    pub address: ManagedAddress<M>,
+    pub endpoint: ManagedBuffer<M>,
+}
+
 /// Referral configuration stored per referral ID
 #[type_abi]
 #[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, Clone)]
@@ -137,6 +336,23 @@ pub struct ReferralConfig<M: ManagedTypeApi> {
     pub active: bool,
 }
 
+/// Referral fee applied once a trade's output reaches `min_output`
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, ManagedVecItem, Clone)]
+pub struct ReferralFeeTier<M: ManagedTypeApi> {
+    pub min_output: BigUint<M>,
+    pub fee: u32, // basis points (10,000 = 100%)
+}
+
+/// Boosted referral fee applied from `start_epoch` to `end_epoch`, both inclusive
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, Clone)]
+pub struct ReferralCampaign {
+    pub fee: u32, // basis points (10,000 = 100%)
+    pub start_epoch: u64,
+    pub end_epoch: u64,
+}
+
 // =============================================================================
 // Compact Encoding Types (for efficient transaction payloads)
 // =============================================================================
@@ -180,37 +396,135 @@ pub enum CompactAction {
     // Hatom (23-24)
     HatomRedeem = 23,
     HatomSupply = 24,
+    // Control (25)
+    ClearPrev = 25,
+    // xExchange composite (26)
+    XExchangeRemoveLiquiditySingle = 26,
+    // AshSwap V1 direct to caller (27)
+    AshSwapPoolAddLiquidityToCaller = 27,
+    // Liquid staking rewards (28)
+    ClaimRewards = 28,
+    // AshSwap V2 with explicit min (29)
+    AshSwapV2SwapMin = 29,
+    // Control (30-32)
+    SetMinLpOut = 30,
+    SetZapRatio = 31,
+    ExpectOutputs = 32,
+    // Hatom composite (33-34)
+    HatomCompound = 33,
+    HatomEnterMarket = 34,
+    // Control (35)
+    UnwrapOutputs = 35,
+    // AshSwap V1 by coin index (36)
+    AshSwapPoolSwapIndexed = 36,
+    // Control (37-40)
+    AllowFailure = 37,
+    RequireFullInput = 38,
+    LpToCaller = 39,
+    SetMinLpValue = 40,
+    // Liquid staking rewards composite (41)
+    ClaimAndRestake = 41,
 }
 
 impl CompactAction {
+    /// Every action in discriminant order (`ALL[n] as u8 == n`)
+    pub const ALL: [CompactAction; 42] = [
+        Self::XExchangeSwap,
+        Self::XExchangeAddLiquidity,
+        Self::XExchangeRemoveLiquidity,
+        Self::AshSwapPoolSwap,
+        Self::AshSwapPoolAddLiquidity,
+        Self::AshSwapPoolRemoveLiquidity,
+        Self::AshSwapV2Swap,
+        Self::AshSwapV2AddLiquidity,
+        Self::AshSwapV2RemoveLiquidity,
+        Self::OneDexSwap,
+        Self::OneDexAddLiquidity,
+        Self::OneDexRemoveLiquidity,
+        Self::JexSwap,
+        Self::JexAddLiquidity,
+        Self::JexRemoveLiquidity,
+        Self::JexStableSwap,
+        Self::JexStableAddLiquidity,
+        Self::JexStableRemoveLiquidity,
+        Self::Wrapping,
+        Self::UnWrapping,
+        Self::XoxnoLiquidStaking,
+        Self::LXoxnoLiquidStaking,
+        Self::HatomLiquidStaking,
+        Self::HatomRedeem,
+        Self::HatomSupply,
+        Self::ClearPrev,
+        Self::XExchangeRemoveLiquiditySingle,
+        Self::AshSwapPoolAddLiquidityToCaller,
+        Self::ClaimRewards,
+        Self::AshSwapV2SwapMin,
+        Self::SetMinLpOut,
+        Self::SetZapRatio,
+        Self::ExpectOutputs,
+        Self::HatomCompound,
+        Self::HatomEnterMarket,
+        Self::UnwrapOutputs,
+        Self::AshSwapPoolSwapIndexed,
+        Self::AllowFailure,
+        Self::RequireFullInput,
+        Self::LpToCaller,
+        Self::SetMinLpValue,
+        Self::ClaimAndRestake,
+    ];
+
     pub fn from_u8(value: u8) -> Option<Self> {
-        match value {
-            0 => Some(Self::XExchangeSwap),
-            1 => Some(Self::XExchangeAddLiquidity),
-            2 => Some(Self::XExchangeRemoveLiquidity),
-            3 => Some(Self::AshSwapPoolSwap),
-            4 => Some(Self::AshSwapPoolAddLiquidity),
-            5 => Some(Self::AshSwapPoolRemoveLiquidity),
-            6 => Some(Self::AshSwapV2Swap),
-            7 => Some(Self::AshSwapV2AddLiquidity),
-            8 => Some(Self::AshSwapV2RemoveLiquidity),
-            9 => Some(Self::OneDexSwap),
-            10 => Some(Self::OneDexAddLiquidity),
-            11 => Some(Self::OneDexRemoveLiquidity),
-            12 => Some(Self::JexSwap),
-            13 => Some(Self::JexAddLiquidity),
-            14 => Some(Self::JexRemoveLiquidity),
-            15 => Some(Self::JexStableSwap),
-            16 => Some(Self::JexStableAddLiquidity),
-            17 => Some(Self::JexStableRemoveLiquidity),
-            18 => Some(Self::Wrapping),
-            19 => Some(Self::UnWrapping),
-            20 => Some(Self::XoxnoLiquidStaking),
-            21 => Some(Self::LXoxnoLiquidStaking),
-            22 => Some(Self::HatomLiquidStaking),
-            23 => Some(Self::HatomRedeem),
-            24 => Some(Self::HatomSupply),
-            _ => None,
+        Self::ALL.get(value as usize).copied()
+    }
+
+    /// Exact inverse of `from_u8`
+    pub const fn to_u8(&self) -> u8 {
+        *self as u8
+    }
+
+    /// Bit identifying the protocol this action calls (0 for control actions)
+    /// OR-ing the bits of a path and counting them gives the number of protocols used
+    pub fn protocol_bit(&self) -> u32 {
+        match self {
+            Self::XExchangeSwap
+            | Self::XExchangeAddLiquidity
+            | Self::XExchangeRemoveLiquidity
+            | Self::XExchangeRemoveLiquiditySingle => 1 << 0,
+            Self::AshSwapPoolSwap
+            | Self::AshSwapPoolAddLiquidity
+            | Self::AshSwapPoolRemoveLiquidity
+            | Self::AshSwapV2Swap
+            | Self::AshSwapV2AddLiquidity
+            | Self::AshSwapV2RemoveLiquidity
+            | Self::AshSwapPoolAddLiquidityToCaller
+            | Self::AshSwapV2SwapMin
+            | Self::AshSwapPoolSwapIndexed => 1 << 1,
+            Self::OneDexSwap | Self::OneDexAddLiquidity | Self::OneDexRemoveLiquidity => 1 << 2,
+            Self::JexSwap
+            | Self::JexAddLiquidity
+            | Self::JexRemoveLiquidity
+            | Self::JexStableSwap
+            | Self::JexStableAddLiquidity
+            | Self::JexStableRemoveLiquidity => 1 << 3,
+            Self::Wrapping | Self::UnWrapping => 1 << 4,
+            Self::XoxnoLiquidStaking
+            | Self::LXoxnoLiquidStaking
+            | Self::ClaimRewards
+            | Self::ClaimAndRestake => 1 << 5,
+            Self::HatomLiquidStaking
+            | Self::HatomRedeem
+            | Self::HatomSupply
+            | Self::HatomCompound
+            | Self::HatomEnterMarket => 1 << 6,
+            Self::ClearPrev
+            | Self::SetMinLpOut
+            | Self::SetZapRatio
+            | Self::ExpectOutputs
+            | Self::UnwrapOutputs
+            | Self::AllowFailure
+            | Self::RequireFullInput
+            | Self::LpToCaller
+            | Self::SetMinLpValue => 0,
         }
     }
 
@@ -223,6 +537,7 @@ impl CompactAction {
                 | Self::OneDexSwap
                 | Self::JexStableSwap
                 | Self::HatomSupply
+                | Self::XExchangeRemoveLiquiditySingle
         )
     }
 
@@ -242,6 +557,7 @@ impl CompactAction {
             Self::AshSwapPoolAddLiquidity
                 | Self::AshSwapV2AddLiquidity
                 | Self::JexStableAddLiquidity
+                | Self::AshSwapPoolAddLiquidityToCaller
         )
     }
 
@@ -256,23 +572,133 @@ impl CompactAction {
         )
     }
 
+    /// Check if this is a control action (no inputs, nothing dispatched)
+    /// Format: [action, arg1, 0, arg2, 0, IDX_AUTO]
+    /// ExpectOutputs: [action, tok1, tok2, tok3, tok4, IDX_AUTO] (IDX_NONE = unused)
+    pub fn is_control(&self) -> bool {
+        matches!(
+            self,
+            Self::ClearPrev
+                | Self::SetMinLpOut
+                | Self::SetZapRatio
+                | Self::ExpectOutputs
+                | Self::UnwrapOutputs
+                | Self::AllowFailure
+                | Self::RequireFullInput
+                | Self::LpToCaller
+                | Self::SetMinLpValue
+        )
+    }
+
+    /// Category whose internal minimum output this action's pool call uses
+    pub fn category(&self) -> Option<ActionCategory> {
+        if self.is_swap() {
+            return Some(ActionCategory::Swap);
+        }
+        match self {
+            Self::XExchangeAddLiquidity
+            | Self::XExchangeRemoveLiquidity
+            | Self::XExchangeRemoveLiquiditySingle
+            | Self::AshSwapPoolAddLiquidity
+            | Self::AshSwapPoolRemoveLiquidity
+            | Self::AshSwapPoolAddLiquidityToCaller
+            | Self::AshSwapV2AddLiquidity
+            | Self::AshSwapV2RemoveLiquidity
+            | Self::OneDexAddLiquidity
+            | Self::OneDexRemoveLiquidity
+            | Self::JexAddLiquidity
+            | Self::JexRemoveLiquidity
+            | Self::JexStableAddLiquidity
+            | Self::JexStableRemoveLiquidity => Some(ActionCategory::Liquidity),
+            _ => None,
+        }
+    }
+
+    /// Check if this is a single-pool swap (one input token in, one output token back)
+    pub fn is_swap(&self) -> bool {
+        matches!(
+            self,
+            Self::XExchangeSwap
+                | Self::AshSwapPoolSwap
+                | Self::AshSwapPoolSwapIndexed
+                | Self::AshSwapV2Swap
+                | Self::AshSwapV2SwapMin
+                | Self::OneDexSwap
+                | Self::JexSwap
+                | Self::JexStableSwap
+        )
+    }
+
+    /// Check if this action carries its own minimum output in the amounts registry
+    /// Format: [action, in_tok, in_mode, min_idx, 0, addr]
+    pub fn needs_explicit_min(&self) -> bool {
+        matches!(self, Self::AshSwapV2SwapMin)
+    }
+
+    /// Check if this action addresses pool coins by index instead of token
+    /// Format: [action, in_tok, in_mode, i, j, addr]
+    pub fn needs_coin_indices(&self) -> bool {
+        matches!(self, Self::AshSwapPoolSwapIndexed)
+    }
+
     /// Check if this action needs pair_id (OneDex add liquidity)
     /// Format: [action, pair_id, tok1, mode1, tok2, mode2]
     pub fn needs_pair_id(&self) -> bool {
         matches!(self, Self::OneDexAddLiquidity)
     }
+
+    /// Check if this action calls a pool given in the address registry
+    /// Every other action resolves its target itself (router, pair map or fixed contract)
+    pub fn needs_address(&self) -> bool {
+        matches!(
+            self,
+            Self::XExchangeRemoveLiquidity
+                | Self::XExchangeRemoveLiquiditySingle
+                | Self::AshSwapPoolSwap
+                | Self::AshSwapPoolSwapIndexed
+                | Self::AshSwapPoolAddLiquidity
+                | Self::AshSwapPoolRemoveLiquidity
+                | Self::AshSwapPoolAddLiquidityToCaller
+                | Self::AshSwapV2Swap
+                | Self::AshSwapV2SwapMin
+                | Self::AshSwapV2AddLiquidity
+                | Self::AshSwapV2RemoveLiquidity
+                | Self::JexSwap
+                | Self::JexAddLiquidity
+                | Self::JexRemoveLiquidity
+                | Self::JexStableSwap
+                | Self::JexStableAddLiquidity
+                | Self::JexStableRemoveLiquidity
+        )
+    }
 }
 
+// Compile-time check that `ALL` is indexed by discriminant, keeping `from_u8`
+// and `to_u8` in lockstep with the enum (`build_action_type` is an exhaustive
+// match, so the compiler already forces it to handle every variant)
+const _: () = {
+    let mut i = 0;
+    while i < CompactAction::ALL.len() {
+        assert!(CompactAction::ALL[i].to_u8() as usize == i);
+        i += 1;
+    }
+};
+
 /// Compact amount mode as u8
-/// 0 = All, 1 = Prev, 2-127 = Fixed amount index, 128-255 = PPM index
+/// 0 = All, 1 = Prev, 2-95 = Fixed amount index, 96-127 = FixedOrAll amount index,
+/// 128-255 = PPM index
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub enum CompactMode {
     All,
     Prev,
-    Fixed(u8), // Index into amounts registry (amounts[idx] is the exact amount)
-    Ppm(u8),   // Index into amounts registry (amounts[idx] is the PPM value)
+    Fixed(u8),      // Index into amounts registry (amounts[idx] is the exact amount)
+    FixedOrAll(u8), // Index into amounts registry (amounts[idx] is the amount, capped to balance)
+    Ppm(u8),        // Index into amounts registry (amounts[idx] is the PPM value)
 }
 
+/// Threshold for FixedOrAll mode (values >= this and below MODE_PPM_THRESHOLD)
+pub const MODE_FIXED_OR_ALL_THRESHOLD: u8 = 96;
+
 /// Threshold for PPM mode (values >= this are PPM indices)
 pub const MODE_PPM_THRESHOLD: u8 = 128;
 
@@ -282,7 +708,11 @@ impl CompactMode {
             0 => Self::All,
             1 => Self::Prev,
             v if v >= MODE_PPM_THRESHOLD => Self::Ppm(v - MODE_PPM_THRESHOLD),
-            v => Self::Fixed(v - 2), // 2-127 → amounts[0-125]
+            // 96-127 → amounts[0-31]
+            v if v >= MODE_FIXED_OR_ALL_THRESHOLD => {
+                Self::FixedOrAll(v - MODE_FIXED_OR_ALL_THRESHOLD)
+            }
+            v => Self::Fixed(v - 2), // 2-95 → amounts[0-93]
         }
     }
 }
@@ -1,17 +1,23 @@
 multiversx_sc::imports!();
 
 use crate::constants::{
-    HATOM_STAKING, LXOXNO_STAKING, MIN_INTERNAL_OUTPUT, ONE_DEX_ROUTER, TOTAL_FEE, WRAPPER_SC,
-    XEGLD_STAKING,
+    FORMAT_VERSION, HATOM_CONTROLLER, HATOM_STAKING, LXOXNO_STAKING, MIN_INTERNAL_OUTPUT,
+    ONE_DEX_ROUTER, RATIO_PRECISION, SUPPORTED_FORMAT_VERSIONS, TOTAL_FEE, WRAPPER_SC,
+    XEGLD_STAKING, XEXCHANGE_FEE_DENOM,
 };
 use crate::errors::{
-    ERR_PPM_EXCEEDS_100_PERCENT, ERR_PREV_AMOUNT_NOT_AVAILABLE, ERR_PREV_AMOUNT_TOKEN_MISMATCH,
-    ERR_ZERO_INPUT_AMOUNT,
+    ERR_ALLOW_FAILURE_SWAP_ONLY, ERR_INSTRUCTION_PPM_EXCEEDS_100_PERCENT,
+    ERR_LP_TO_CALLER_NOTHING_MINTED, ERR_LP_TO_CALLER_ZAP_ONLY, ERR_MIN_LP_VALUE_XEXCHANGE_ONLY,
+    ERR_NO_EXPECTED_OUTPUTS, ERR_NO_INPUT_PAYMENT, ERR_NO_REWARDS_TO_RESTAKE,
+    ERR_ONEDEX_SINGLE_INPUT, ERR_POOL_ADDRESS_UNRESOLVED, ERR_PREV_AMOUNT_NOT_AVAILABLE,
+    ERR_PREV_AMOUNT_TOKEN_MISMATCH_PREFIX, ERR_SAME_COIN_INDEX, ERR_SOFT_FAIL_SWAP_ONLY,
+    ERR_WRAPPER_NO_OUTPUT, ERR_ZAP_OUT_NO_OUTPUT, ERR_ZAP_TWO_INPUTS, ERR_ZERO_INPUT_AMOUNT,
 };
 use crate::types::{
-    AmountMode, CompactAction, CompactMode, InputArg, Instruction, IDX_AUTO, IDX_EGLD, IDX_NONE,
+    ActionCategory, AmountMode, CompactAction, CompactMode, InputArg, Instruction, IDX_AUTO,
+    IDX_EGLD, IDX_NONE,
 };
-use crate::vault::Vault;
+use crate::vault::{FeeOnTransferSnapshot, Vault};
 use crate::zap;
 use crate::{proxies, types};
 use multiversx_sc::chain_core::EGLD_000000_TOKEN_IDENTIFIER;
@@ -23,22 +29,77 @@ pub type AmountRegistry<M> = ManagedVec<M, BigUint<M>>;
 
 /// Utility functions module for aggregator operations
 #[multiversx_sc::module]
-pub trait Utils: crate::storage::Storage {
+pub trait Utils: crate::storage::Storage + crate::events::Events {
     #[proxy]
     fn proxy_call(&self, address: ManagedAddress) -> proxies::Proxy<Self::Api>;
 
     /// Return only the output token to the caller, keep dust as protocol revenue
     fn return_vault_to_caller(&self, vault: Vault<Self::Api>, token_out: &TokenId<Self::Api>) {
         let caller = self.blockchain().get_caller();
+        self.return_vault_to(vault, token_out, &caller);
+    }
 
-        for payment in vault.get_all_payments().iter() {
-            if payment.token_identifier == *token_out {
-                self.tx().to(&caller).payment(payment.clone()).transfer();
-            } else {
-                // Keep all other tokens (dust) as protocol revenue
-                self.accumulate_admin_fee(&payment.token_identifier, payment.amount.as_big_uint());
+    /// Send only the output token to `recipient`, keep dust as protocol revenue
+    ///
+    /// The transferred amount is exactly the vault's post-fee balance, withdrawn in full,
+    /// so fee rounding can neither strand a wei nor over-transfer.
+    /// Inputs of skipped `AllowFailure` swaps are refunded in a second transfer, one
+    /// payment per skipped swap. With `max_output_tokens` set, the output and refund
+    /// payments together are capped: the smallest refunds over the cap are kept as dust.
+    fn return_vault_to(
+        &self,
+        mut vault: Vault<Self::Api>,
+        token_out: &TokenId<Self::Api>,
+        recipient: &ManagedAddress<Self::Api>,
+    ) {
+        let mut sent = 0usize;
+        if vault.contains(token_out) {
+            let amount_out = vault.withdraw_all(token_out);
+            if let Some(amount_out) = amount_out.into_non_zero() {
+                self.tx()
+                    .to(recipient)
+                    .payment(Payment::new(token_out.clone(), 0u64, amount_out))
+                    .transfer();
+                sent = 1;
+            }
+        }
+
+        let mut refunds = vault.take_refunds();
+        let max_outputs = self.max_output_tokens().get() as usize;
+        if max_outputs > 0 {
+            while !refunds.is_empty() && sent + refunds.len() > max_outputs {
+                let smallest = self.smallest_payment_index(&refunds);
+                let refund = refunds.get(smallest).clone();
+                refunds.remove(smallest);
+                vault.deposit(&refund.token_identifier, &refund.amount);
+            }
+        }
+        if !refunds.is_empty() {
+            self.tx().to(recipient).payment(refunds).transfer();
+        }
+
+        // Keep all other tokens (dust) as protocol revenue, accrued in token order so
+        // the fee events do not depend on the path's vault history
+        for payment in vault.get_all_payments_sorted().iter() {
+            self.debit_fee(
+                &mut vault,
+                &payment.token_identifier,
+                payment.amount.as_big_uint(),
+                0,
+                &BigUint::zero(),
+            );
+        }
+    }
+
+    /// Index of the payment with the smallest amount, the first one on ties
+    fn smallest_payment_index(&self, payments: &ManagedVec<Payment<Self::Api>>) -> usize {
+        let mut smallest = 0;
+        for (index, payment) in payments.iter().enumerate() {
+            if payment.amount < payments.get(smallest).amount {
+                smallest = index;
             }
         }
+        smallest
     }
 
     /// Resolve token index to TokenId (vault format)
@@ -49,7 +110,80 @@ pub trait Utils: crate::storage::Storage {
     ) -> TokenId<Self::Api> {
         match idx {
             IDX_EGLD => TokenId::from(EGLD_000000_TOKEN_IDENTIFIER.as_bytes()),
-            _ => TokenId::from(tokens.get(idx as usize).as_managed_buffer().clone()),
+            _ => self.registry_token(tokens, idx),
+        }
+    }
+
+    // --- Registry Access ---
+    //
+    // A miscounted MultiValueEncodedCounted shifts every following argument, which
+    // usually surfaces as an out-of-range index; report it with the registry sizes
+    // instead of an opaque ManagedVec panic.
+
+    /// Registry entries naming EGLD (`EGLD-000000`, the legacy `EGLD` or an empty id)
+    /// resolve to `EGLD-000000`, the same vault key as IDX_EGLD, so one asset never
+    /// splits across two balances.
+    fn registry_token(&self, tokens: &TokenRegistry<Self::Api>, idx: u8) -> TokenId<Self::Api> {
+        self.require_registry_index(b"tokens", idx as usize, tokens.len());
+        TokenId::from(tokens.get(idx as usize).as_managed_buffer().clone())
+    }
+
+    fn registry_address(
+        &self,
+        addresses: &AddressRegistry<Self::Api>,
+        idx: usize,
+    ) -> ManagedAddress<Self::Api> {
+        self.require_registry_index(b"addresses", idx, addresses.len());
+        addresses.get(idx).clone()
+    }
+
+    fn registry_amount(&self, amounts: &AmountRegistry<Self::Api>, idx: u8) -> BigUint<Self::Api> {
+        self.require_registry_index(b"amounts", idx as usize, amounts.len());
+        amounts.get(idx as usize).clone()
+    }
+
+    fn require_registry_index(&self, registry: &[u8], idx: usize, len: usize) {
+        if idx >= len {
+            let name = ManagedBuffer::new_from_bytes(registry);
+            let idx = idx as u32;
+            let len = len as u32;
+            sc_panic!(
+                "D: Registry length mismatch: {} index {} but only {} entries",
+                name,
+                idx,
+                len
+            );
+        }
+    }
+
+    /// Reject a path encoded in a compact format this contract does not decode
+    fn require_format_version(&self, version: u8) {
+        if !SUPPORTED_FORMAT_VERSIONS.contains(&version) {
+            let version = version as u32;
+            let expected = FORMAT_VERSION as u32;
+            sc_panic!(
+                "D: Unsupported format version {}, expected {}",
+                version,
+                expected
+            );
+        }
+    }
+
+    /// Reject a tokens registry listing the same token at two indices
+    /// O(N²), fine for the handful of tokens a route references
+    fn require_unique_tokens(&self, tokens: &TokenRegistry<Self::Api>) {
+        for i in 0..tokens.len() {
+            let token = tokens.get(i);
+            for j in (i + 1)..tokens.len() {
+                if *tokens.get(j) == *token {
+                    sc_panic!(
+                        "D: Duplicate token in registry: {} at {} and {}",
+                        *token,
+                        i,
+                        j
+                    );
+                }
+            }
         }
     }
 
@@ -75,10 +209,19 @@ pub trait Utils: crate::storage::Storage {
         amounts: &AmountRegistry<Self::Api>,
     ) -> Instruction<Self::Api> {
         let compact_action = CompactAction::from_u8(action_byte)
-            .unwrap_or_else(|| sc_panic!("Invalid action type: {}", action_byte));
+            .unwrap_or_else(|| sc_panic!("D: Invalid action type: {}", action_byte));
 
         // Build ActionType from compact action
-        let action = self.build_action_type(&compact_action, byte1, pair_id_or_addr, tokens);
+        let action = self.build_action_type(
+            &compact_action,
+            byte1,
+            byte2,
+            byte3,
+            byte4,
+            pair_id_or_addr,
+            tokens,
+            amounts,
+        );
 
         // Build inputs based on action type
         let inputs = self.build_inputs(
@@ -92,12 +235,21 @@ pub trait Utils: crate::storage::Storage {
             amounts,
         );
 
+        if let Some(inputs) = &inputs {
+            for input in inputs.iter() {
+                self.require_not_self_swap(&action, &input.token);
+            }
+        }
+
         // Resolve address (for OneDex add liquidity, addr is auto-resolved)
         let address = if compact_action.needs_pair_id() || pair_id_or_addr as u8 == IDX_AUTO {
             None // Auto-resolved in dispatch
         } else {
-            Some(addresses.get(pair_id_or_addr as usize).clone())
+            Some(self.registry_address(addresses, pair_id_or_addr as usize))
         };
+        if address.is_none() && compact_action.needs_address() {
+            sc_panic!("D: Action {} requires an explicit address", action_byte);
+        }
 
         Instruction {
             action,
@@ -106,16 +258,38 @@ pub trait Utils: crate::storage::Storage {
         }
     }
 
+    /// Reject an instruction whose known output token is also its input
+    ///
+    /// DEX proxies disagree on a same-token swap (some revert, some echo the input), so
+    /// it is rejected up front rather than skipped: it always points at a broken route.
+    /// Chained `PrevAmount` inputs are only known at execution and checked there.
+    fn require_not_self_swap(
+        &self,
+        action: &types::ActionType<Self::Api>,
+        input_token: &ManagedBuffer<Self::Api>,
+    ) {
+        if let Some(types::PathOutput::Token(token)) = self.static_output(action) {
+            if token.as_managed_buffer() == input_token {
+                sc_panic!("D: Instruction swaps token {} to itself", input_token);
+            }
+        }
+    }
+
     /// Build ActionType from CompactAction, resolving output token where needed
     ///
     /// For most actions, byte1 is tok1_idx.
     /// For OneDex add liquidity, pair_id is passed directly as u16.
+    /// For actions with an explicit minimum, byte3 indexes the amounts registry.
     fn build_action_type(
         &self,
         compact: &CompactAction,
         byte1: u8,
+        byte2: u8,
+        byte3: u8,
+        byte4: u8,
         pair_id_or_addr: u16,
         tokens: &TokenRegistry<Self::Api>,
+        amounts: &AmountRegistry<Self::Api>,
     ) -> types::ActionType<Self::Api> {
         match compact {
             CompactAction::XExchangeSwap => {
@@ -143,8 +317,18 @@ pub trait Utils: crate::storage::Storage {
                 types::ActionType::OneDexSwap(out_token)
             }
             CompactAction::OneDexAddLiquidity => {
-                // pair_id is directly passed as u16
-                types::ActionType::OneDexAddLiquidity(pair_id_or_addr as usize)
+                // pair_id is directly passed as u16; an id the router never created would
+                // only fail there with an opaque error
+                let pair_id = pair_id_or_addr as usize;
+                let pair_count = self
+                    .onedex_last_pair_id(ManagedAddress::from(ONE_DEX_ROUTER))
+                    .get();
+                require!(
+                    pair_id > 0 && pair_id <= pair_count,
+                    "D: OneDex pair_id {} out of range",
+                    pair_id_or_addr
+                );
+                types::ActionType::OneDexAddLiquidity(pair_id)
             }
             CompactAction::OneDexRemoveLiquidity => types::ActionType::OneDexRemoveLiquidity,
             CompactAction::JexSwap => types::ActionType::JexSwap,
@@ -164,10 +348,64 @@ pub trait Utils: crate::storage::Storage {
             CompactAction::LXoxnoLiquidStaking => types::ActionType::LXoxnoLiquidStaking,
             CompactAction::HatomLiquidStaking => types::ActionType::HatomLiquidStaking,
             CompactAction::HatomRedeem => types::ActionType::HatomRedeem,
+            CompactAction::HatomCompound => types::ActionType::HatomCompound,
+            CompactAction::HatomEnterMarket => types::ActionType::HatomEnterMarket,
             CompactAction::HatomSupply => {
                 let out_token = self.resolve_token(byte1, tokens);
                 types::ActionType::HatomSupply(out_token)
             }
+            CompactAction::ClearPrev => types::ActionType::ClearPrev,
+            CompactAction::XExchangeRemoveLiquiditySingle => {
+                let out_token = self.resolve_token(byte1, tokens);
+                types::ActionType::XExchangeRemoveLiquiditySingle(out_token)
+            }
+            CompactAction::AshSwapPoolAddLiquidityToCaller => {
+                types::ActionType::AshSwapPoolAddLiquidityToCaller
+            }
+            CompactAction::ClaimRewards => types::ActionType::ClaimRewards,
+            CompactAction::ClaimAndRestake => types::ActionType::ClaimAndRestake,
+            CompactAction::AshSwapV2SwapMin => {
+                types::ActionType::AshSwapV2SwapMin(self.registry_amount(amounts, byte3))
+            }
+            CompactAction::SetMinLpOut => {
+                types::ActionType::SetMinLpOut(self.registry_amount(amounts, byte1))
+            }
+            CompactAction::SetZapRatio => {
+                let tolerance_ppm = self.registry_amount(amounts, byte3);
+                require!(
+                    tolerance_ppm <= 1_000_000u64,
+                    ERR_INSTRUCTION_PPM_EXCEEDS_100_PERCENT
+                );
+                types::ActionType::SetZapRatio(types::ZapRatio {
+                    ratio: self.registry_amount(amounts, byte1),
+                    tolerance_ppm: tolerance_ppm.to_u64().unwrap() as u32,
+                })
+            }
+            CompactAction::ExpectOutputs => {
+                let mut expected = ManagedVec::new();
+                for idx in [byte1, byte2, byte3, byte4] {
+                    if idx != IDX_NONE {
+                        expected.push(self.resolve_token(idx, tokens));
+                    }
+                }
+                require!(!expected.is_empty(), ERR_NO_EXPECTED_OUTPUTS);
+                types::ActionType::ExpectOutputs(expected)
+            }
+            CompactAction::UnwrapOutputs => types::ActionType::UnwrapOutputs,
+            CompactAction::AllowFailure => types::ActionType::AllowFailure,
+            CompactAction::RequireFullInput => types::ActionType::RequireFullInput,
+            CompactAction::LpToCaller => types::ActionType::LpToCaller,
+            CompactAction::SetMinLpValue => types::ActionType::SetMinLpValue(types::LpValueMin {
+                token: self.resolve_token(byte1, tokens),
+                amount: self.registry_amount(amounts, byte3),
+            }),
+            CompactAction::AshSwapPoolSwapIndexed => {
+                require!(byte3 != byte4, ERR_SAME_COIN_INDEX);
+                types::ActionType::AshSwapPoolSwapIndexed(types::CoinIndices {
+                    i: byte3 as u32,
+                    j: byte4 as u32,
+                })
+            }
         }
     }
 
@@ -179,7 +417,7 @@ pub trait Utils: crate::storage::Storage {
     ) -> TokenIdentifier<Self::Api> {
         match idx {
             IDX_EGLD => TokenIdentifier::from(EGLD_000000_TOKEN_IDENTIFIER),
-            _ => tokens.get(idx as usize).clone(),
+            _ => TokenIdentifier::from(self.registry_token(tokens, idx)),
         }
     }
 
@@ -205,6 +443,9 @@ pub trait Utils: crate::storage::Storage {
     ///
     /// For OneDex add_liquidity:
     ///   - byte1 = tok1, byte2 = tok2, byte3 = shared_mode, byte4 = 0, u16 = pair_id
+    ///
+    /// For single-input swaps with an explicit minimum:
+    ///   - byte1 = input token, byte2 = input mode, byte3 = min index (used in ActionType)
     fn build_inputs(
         &self,
         compact_action: &CompactAction,
@@ -216,6 +457,11 @@ pub trait Utils: crate::storage::Storage {
         tokens: &TokenRegistry<Self::Api>,
         amounts: &AmountRegistry<Self::Api>,
     ) -> Option<ManagedVec<Self::Api, InputArg<Self::Api>>> {
+        // Control actions carry no inputs
+        if compact_action.is_control() {
+            return None;
+        }
+
         // For swap-like actions, byte layout is different:
         // byte1 = output token (handled elsewhere), byte2 = input token, byte3 = input mode
         if compact_action.needs_output_token() {
@@ -288,6 +534,23 @@ pub trait Utils: crate::storage::Storage {
             return Some(inputs);
         }
 
+        // For swaps with an explicit min or coin indices: byte1 = input token, byte2 = input mode
+        // Layout: [action, in_tok, in_mode, min_idx, 0, addr] or [action, in_tok, in_mode, i, j, addr]
+        if compact_action.needs_explicit_min() || compact_action.needs_coin_indices() {
+            let input_mode = CompactMode::from_u8(byte2);
+
+            if matches!(input_mode, CompactMode::Prev) && byte1 == IDX_NONE {
+                return None;
+            }
+
+            let mut inputs = ManagedVec::new();
+            inputs.push(InputArg {
+                token: self.token_idx_to_buffer(byte1, tokens),
+                mode: self.compact_mode_to_amount_mode(&input_mode, amounts),
+            });
+            return Some(inputs);
+        }
+
         // For OneDex add liquidity with u16 pair_id and shared mode
         // Layout: [action, tok1, tok2, shared_mode, 0, pair_id(u16)]
         if compact_action.needs_pair_id() {
@@ -352,7 +615,7 @@ pub trait Utils: crate::storage::Storage {
         match idx {
             IDX_EGLD => ManagedBuffer::from(EGLD_000000_TOKEN_IDENTIFIER.as_bytes()),
             IDX_NONE => ManagedBuffer::new(),
-            _ => tokens.get(idx as usize).as_managed_buffer().clone(),
+            _ => self.registry_token(tokens, idx).into_managed_buffer(),
         }
     }
 
@@ -366,14 +629,99 @@ pub trait Utils: crate::storage::Storage {
         match mode {
             CompactMode::All => AmountMode::All,
             CompactMode::Prev => AmountMode::PrevAmount,
-            CompactMode::Fixed(idx) => AmountMode::Fixed(amounts.get(*idx as usize).clone()),
+            CompactMode::Fixed(idx) => AmountMode::Fixed(self.registry_amount(amounts, *idx)),
+            CompactMode::FixedOrAll(idx) => {
+                AmountMode::FixedOrAll(self.registry_amount(amounts, *idx))
+            }
             CompactMode::Ppm(idx) => {
                 // Read PPM value from amounts registry (stored as BigUint, convert to u32)
-                let ppm_value = amounts.get(*idx as usize);
-                let ppm_u64 = ppm_value.to_u64().unwrap_or(0);
-                // Validate PPM is within valid range (0 to 1,000,000)
-                require!(ppm_u64 <= 1_000_000, ERR_PPM_EXCEEDS_100_PERCENT);
-                AmountMode::Ppm(ppm_u64 as u32)
+                let ppm_value = self.registry_amount(amounts, *idx);
+                // Validate on the BigUint before narrowing: values above u64::MAX would
+                // otherwise decode to 0, and anything <= 1,000,000 always fits u32
+                require!(
+                    ppm_value <= 1_000_000u64,
+                    ERR_INSTRUCTION_PPM_EXCEEDS_100_PERCENT
+                );
+                AmountMode::Ppm(ppm_value.to_u64().unwrap() as u32)
+            }
+        }
+    }
+
+    /// Output of an instruction as far as it is known without executing it
+    /// Returns None for actions that produce nothing (control actions, collateral deposits)
+    fn static_output(
+        &self,
+        action: &types::ActionType<Self::Api>,
+    ) -> Option<types::PathOutput<Self::Api>> {
+        match action {
+            types::ActionType::XExchangeSwap(token)
+            | types::ActionType::AshSwapPoolSwap(token)
+            | types::ActionType::OneDexSwap(token)
+            | types::ActionType::JexStableSwap(token)
+            | types::ActionType::HatomSupply(token)
+            | types::ActionType::XExchangeRemoveLiquiditySingle(token) => {
+                Some(types::PathOutput::Token(token.clone()))
+            }
+            types::ActionType::Wrapping => {
+                Some(types::PathOutput::Token(self.get_wegld_token_id()))
+            }
+            types::ActionType::UnWrapping => Some(types::PathOutput::Token(TokenIdentifier::from(
+                EGLD_000000_TOKEN_IDENTIFIER,
+            ))),
+            types::ActionType::ClearPrev
+            | types::ActionType::SetMinLpOut(_)
+            | types::ActionType::SetZapRatio(_)
+            | types::ActionType::ExpectOutputs(_)
+            | types::ActionType::UnwrapOutputs
+            | types::ActionType::AllowFailure
+            | types::ActionType::RequireFullInput
+            | types::ActionType::LpToCaller
+            | types::ActionType::SetMinLpValue(_)
+            | types::ActionType::HatomEnterMarket => None,
+            _ => Some(types::PathOutput::Unknown(action.compact().to_u8())),
+        }
+    }
+
+    /// Count withdrawals per input token and reject fan-outs above `max_splits`
+    fn track_splits(
+        &self,
+        split_counts: &mut ManagedMapEncoded<Self::Api, ManagedBuffer<Self::Api>, u32>,
+        instr: &Instruction<Self::Api>,
+        max_splits: u32,
+    ) {
+        if let Some(inputs) = &instr.inputs {
+            for input in inputs.iter() {
+                let count = split_counts.get(&input.token) + 1;
+                if count > max_splits {
+                    sc_panic!(
+                        "D: Split limit exceeded for token {}: {} withdrawals, max {}",
+                        input.token,
+                        count,
+                        max_splits
+                    );
+                }
+                split_counts.put(&input.token, &count);
+            }
+        }
+    }
+
+    /// Record the input tokens and known output token of an instruction, once each
+    fn track_tokens(
+        &self,
+        touched_tokens: &mut ManagedVec<Self::Api, ManagedBuffer<Self::Api>>,
+        instr: &Instruction<Self::Api>,
+    ) {
+        if let Some(inputs) = &instr.inputs {
+            for input in inputs.iter() {
+                if !touched_tokens.contains(&input.token) {
+                    touched_tokens.push(input.token.clone());
+                }
+            }
+        }
+        if let Some(types::PathOutput::Token(token)) = self.static_output(&instr.action) {
+            let token = token.into_managed_buffer();
+            if !touched_tokens.contains(&token) {
+                touched_tokens.push(token);
             }
         }
     }
@@ -386,27 +734,105 @@ pub trait Utils: crate::storage::Storage {
         vault: &mut Vault<Self::Api>,
         instr: &Instruction<Self::Api>,
         token_out: &TokenId<Self::Api>,
+        min_amount_out: &BigUint<Self::Api>,
     ) {
+        // Control actions: nothing to withdraw or dispatch
+        match &instr.action {
+            types::ActionType::ClearPrev => {
+                vault.clear_prev_result();
+                return;
+            }
+            types::ActionType::SetMinLpOut(min_lp_out) => {
+                vault.set_min_lp_out(min_lp_out);
+                return;
+            }
+            types::ActionType::SetZapRatio(zap_ratio) => {
+                vault.set_zap_ratio(zap_ratio);
+                return;
+            }
+            types::ActionType::ExpectOutputs(expected) => {
+                vault.set_expected_outputs(expected);
+                return;
+            }
+            types::ActionType::UnwrapOutputs => {
+                vault.set_unwrap_outputs();
+                return;
+            }
+            types::ActionType::AllowFailure => {
+                vault.set_allow_failure();
+                return;
+            }
+            types::ActionType::RequireFullInput => {
+                vault.set_require_full_input();
+                return;
+            }
+            types::ActionType::LpToCaller => {
+                vault.set_lp_to_caller();
+                return;
+            }
+            types::ActionType::SetMinLpValue(min_lp_value) => {
+                vault.set_min_lp_value(min_lp_value);
+                return;
+            }
+            _ => {}
+        }
+
+        // Valuing shares needs the LP supply, which only the xExchange pair exposes
+        if vault.has_min_lp_value() {
+            require!(
+                instr.action == types::ActionType::XExchangeAddLiquidity,
+                ERR_MIN_LP_VALUE_XEXCHANGE_ONLY
+            );
+        }
+
+        if vault.is_lp_to_caller() {
+            require!(
+                self.is_zappable_add_liquidity(&instr.action),
+                ERR_LP_TO_CALLER_ZAP_ONLY
+            );
+        }
+
+        // Only a swap is an independent branch: anything else may leave state a later
+        // instruction relies on
+        if vault.is_failure_allowed() {
+            require!(
+                instr.action.compact().is_swap(),
+                ERR_ALLOW_FAILURE_SWAP_ONLY
+            );
+        }
+
+        let fot_snapshot = self.snapshot_fee_on_transfer(vault);
+
         let mut input_payments = ManagedVec::new();
 
         if let Some(inputs) = &instr.inputs {
+            let auto_wrap = self.auto_wrap_enabled().get();
+            let mut consumed_leg = false;
+
             // 1. Withdraw all required inputs from vault
             for input in inputs.iter() {
                 let token = TokenId::from(input.token.clone());
 
+                if auto_wrap {
+                    self.auto_wrap_input(vault, &token, &input.mode);
+                }
+
                 let actual_amount = match &input.mode {
                     AmountMode::Fixed(amount) => vault.withdraw(&token, amount),
+                    AmountMode::FixedOrAll(amount) => vault.withdraw_up_to(&token, amount),
                     AmountMode::Ppm(ppm) => vault.withdraw_ppm(&token, ppm),
                     AmountMode::All => vault.withdraw_all(&token),
+                    AmountMode::PrevAmount if vault.has_prev_result_for(&token) => {
+                        consumed_leg = true;
+                        let prev_amount = vault.take_prev_result_for(&token);
+                        vault.withdraw(&token, &prev_amount)
+                    }
                     AmountMode::PrevAmount => {
-                        let prev_result = vault.get_prev_result();
+                        let prev_result = vault.take_prev_result();
                         require!(prev_result.is_some(), ERR_PREV_AMOUNT_NOT_AVAILABLE);
-                        let prev_value = prev_result.as_ref().unwrap();
-                        require!(
-                            token == prev_value.token_identifier,
-                            ERR_PREV_AMOUNT_TOKEN_MISMATCH
-                        );
-                        vault.withdraw(&token, prev_value.amount.clone().as_big_uint())
+                        let prev_value = prev_result.unwrap();
+                        self.require_prev_token(&token, &prev_value.token_identifier);
+                        vault.withdraw(&token, prev_value.amount.as_big_uint())
                     }
                 };
 
@@ -418,15 +844,253 @@ pub trait Utils: crate::storage::Storage {
                     actual_amount.into_non_zero().unwrap(),
                 ));
             }
+
+            // Declared legs serve only the first instruction drawing on them, so a leg it
+            // left untouched cannot stand in for a later instruction's previous result
+            if consumed_leg {
+                vault.clear_prev_results_by_token();
+            }
         } else {
-            let prev = vault.get_prev_result().clone().unwrap();
+            let prev = vault.take_prev_result();
+            require!(prev.is_some(), ERR_PREV_AMOUNT_NOT_AVAILABLE);
+            let prev = prev.unwrap();
+            self.require_not_self_swap(&instr.action, prev.token_identifier.as_managed_buffer());
             // Withdraw from vault to keep it in sync with actual contract holdings
             vault.withdraw(&prev.token_identifier, prev.amount.as_big_uint());
             input_payments.push(prev);
         }
 
-        // 2. Dispatch to appropriate proxy
-        self.dispatch_to_proxy(vault, instr, &input_payments, token_out);
+        // 2. Only ZAP adds keep the reserve cache in sync; any other DEX call may move reserves
+        if !self.is_zappable_add_liquidity(&instr.action) {
+            vault.invalidate_reserves();
+        }
+
+        // 3. Dispatch to appropriate proxy
+        self.dispatch_to_proxy(vault, instr, &input_payments, token_out, min_amount_out);
+
+        // 4. Fee-on-transfer tokens: trust the contract balance, not the reported amounts
+        if let Some(snapshot) = fot_snapshot {
+            self.reconcile_fee_on_transfer(vault, &snapshot);
+        }
+    }
+
+    /// Fail unless a `PrevAmount` input names the token the previous instruction produced
+    fn require_prev_token(&self, token: &TokenId<Self::Api>, prev_token: &TokenId<Self::Api>) {
+        if token == prev_token {
+            return;
+        }
+        // Build detailed error: "PrevAmount token mismatch: expected X, got Y"
+        let mut buffer: ManagedBufferBuilder<Self::Api> =
+            ManagedBufferBuilder::new_from_slice(ERR_PREV_AMOUNT_TOKEN_MISMATCH_PREFIX);
+        buffer.append_managed_buffer(token.as_managed_buffer());
+        buffer.append_managed_buffer(&ManagedBuffer::from(b", got "));
+        buffer.append_managed_buffer(prev_token.as_managed_buffer());
+        sc_panic!(buffer.into_managed_buffer());
+    }
+
+    // --- Fee-on-Transfer ---
+
+    /// Record vault and contract balances of every fee-on-transfer token
+    /// None when no such token is configured, so the common path pays nothing
+    fn snapshot_fee_on_transfer(
+        &self,
+        vault: &Vault<Self::Api>,
+    ) -> Option<FeeOnTransferSnapshot<Self::Api>> {
+        let fot_tokens = self.fee_on_transfer_tokens();
+        if fot_tokens.is_empty() {
+            return None;
+        }
+
+        let mut snapshot = FeeOnTransferSnapshot {
+            tokens: ManagedVec::new(),
+            vault_balances: ManagedVec::new(),
+            sc_balances: ManagedVec::new(),
+        };
+        for token in fot_tokens.iter() {
+            let vault_balance = if vault.contains(&token) {
+                vault.balance_of(&token)
+            } else {
+                BigUint::zero()
+            };
+            snapshot
+                .sc_balances
+                .push(self.blockchain().get_sc_balance(&token, 0));
+            snapshot.vault_balances.push(vault_balance);
+            snapshot.tokens.push(token);
+        }
+        Some(snapshot)
+    }
+
+    /// Lower the vault's tracked balance of each fee-on-transfer token to what the
+    /// contract actually gained or lost since the snapshot, so later All/Ppm/PrevAmount
+    /// withdrawals only spend real holdings
+    fn reconcile_fee_on_transfer(
+        &self,
+        vault: &mut Vault<Self::Api>,
+        snapshot: &FeeOnTransferSnapshot<Self::Api>,
+    ) {
+        for i in 0..snapshot.tokens.len() {
+            let token = snapshot.tokens.get(i);
+            if !vault.contains(&token) {
+                continue;
+            }
+
+            let sc_before = snapshot.sc_balances.get(i);
+            let sc_after = self.blockchain().get_sc_balance(&*token, 0);
+            let vault_before = snapshot.vault_balances.get(i);
+            let received = &*vault_before + &sc_after;
+            let expected = if received > *sc_before {
+                received - &*sc_before
+            } else {
+                BigUint::zero()
+            };
+
+            let tracked = vault.balance_of(&token);
+            if tracked <= expected {
+                continue;
+            }
+
+            let shortfall = tracked - &expected;
+            vault.withdraw(&token, &shortfall);
+
+            if let Some(prev) = vault.peek_prev_result().clone() {
+                if prev.token_identifier == *token {
+                    let prev_amount = prev.amount.into_big_uint();
+                    if prev_amount > shortfall {
+                        let adjusted = prev_amount - &shortfall;
+                        vault.set_prev_result(&Payment::new(
+                            prev.token_identifier,
+                            0u64,
+                            adjusted.into_non_zero().unwrap(),
+                        ));
+                    } else {
+                        vault.clear_prev_result();
+                    }
+                }
+            }
+        }
+    }
+
+    // --- Auto Wrap/Unwrap ---
+
+    /// Make an EGLD or WEGLD input available by converting the counterpart held in the vault
+    ///
+    /// - Fixed/FixedOrAll: converts only the shortfall between the vault balance and the
+    ///   fixed amount
+    /// - All/Ppm: converts the whole counterpart balance when the input token is absent
+    /// - PrevAmount: converts the previous output when it is the counterpart token
+    fn auto_wrap_input(
+        &self,
+        vault: &mut Vault<Self::Api>,
+        token: &TokenId<Self::Api>,
+        mode: &AmountMode<Self::Api>,
+    ) {
+        let egld = TokenId::from(EGLD_000000_TOKEN_IDENTIFIER.as_bytes());
+        let wegld = TokenId::from(self.get_wegld_token_id().into_managed_buffer());
+
+        let counterpart = if *token == egld {
+            wegld
+        } else if *token == wegld {
+            egld
+        } else {
+            return;
+        };
+
+        let amount = match mode {
+            AmountMode::Fixed(amount) | AmountMode::FixedOrAll(amount) => {
+                let current = if vault.contains(token) {
+                    vault.balance_of(token)
+                } else {
+                    BigUint::zero()
+                };
+                if &current >= amount {
+                    return;
+                }
+                amount - &current
+            }
+            AmountMode::Ppm(_) | AmountMode::All => {
+                if vault.contains(token) {
+                    return;
+                }
+                if !vault.contains(&counterpart) {
+                    return;
+                }
+                vault.balance_of(&counterpart)
+            }
+            AmountMode::PrevAmount => match vault.peek_prev_result() {
+                Some(prev) if prev.token_identifier == counterpart => {
+                    prev.amount.as_big_uint().clone()
+                }
+                _ => return,
+            },
+        };
+
+        if amount == 0u64 || !vault.contains(&counterpart) {
+            return;
+        }
+        if vault.balance_of(&counterpart) < amount {
+            return;
+        }
+
+        let converted = self.convert_egld_wegld(vault, &counterpart, &amount);
+        if matches!(mode, AmountMode::PrevAmount) {
+            vault.set_prev_result(&converted);
+        }
+    }
+
+    /// Wrap EGLD or unwrap WEGLD held in the vault, depositing the result back
+    fn convert_egld_wegld(
+        &self,
+        vault: &mut Vault<Self::Api>,
+        from: &TokenId<Self::Api>,
+        amount: &BigUint<Self::Api>,
+    ) -> Payment<Self::Api> {
+        vault.withdraw(from, amount);
+        let result = self.wrap_or_unwrap(from, amount);
+        vault.deposit(&result.token_identifier, &result.amount);
+        result
+    }
+
+    /// Wrap EGLD or unwrap WEGLD through the wrapper contract, returning the counterpart
+    /// The call gets the gas limit of the matching `Wrapping` / `UnWrapping` action
+    fn wrap_or_unwrap(
+        &self,
+        from: &TokenId<Self::Api>,
+        amount: &BigUint<Self::Api>,
+    ) -> Payment<Self::Api> {
+        let mut call = self.proxy_call(ManagedAddress::from(WRAPPER_SC));
+        let back_transfers = if from.is_native() {
+            call.wrap_egld()
+                .egld(amount)
+                .gas(self.get_sub_call_gas(&types::ActionType::Wrapping))
+                .returns(ReturnsBackTransfersReset)
+                .sync_call()
+        } else {
+            call.unwrap_egld()
+                .payment(Payment::new(
+                    from.clone(),
+                    0u64,
+                    amount.clone().into_non_zero().unwrap(),
+                ))
+                .gas(self.get_sub_call_gas(&types::ActionType::UnWrapping))
+                .returns(ReturnsBackTransfersReset)
+                .sync_call()
+        };
+
+        let received = back_transfers.into_payment_vec();
+        require!(!received.is_empty(), ERR_WRAPPER_NO_OUTPUT);
+        let first = received.get(0).clone();
+        first
+    }
+
+    /// Native EGLD amount for EGLD-only endpoints, unwrapping a WEGLD input first
+    fn native_egld_amount(&self, payment: &Payment<Self::Api>) -> BigUint<Self::Api> {
+        if payment.token_identifier.is_native() {
+            return payment.amount.as_big_uint().clone();
+        }
+        let unwrapped =
+            self.wrap_or_unwrap(&payment.token_identifier, payment.amount.as_big_uint());
+        unwrapped.amount.as_big_uint().clone()
     }
 
     // --- Dispatch Logic ---
@@ -438,13 +1102,35 @@ pub trait Utils: crate::storage::Storage {
         instr: &Instruction<Self::Api>,
         payments: &ManagedVec<Payment<Self::Api>>,
         token_out: &TokenId<Self::Api>,
+        min_amount_out: &BigUint<Self::Api>,
     ) {
         // For zappable add_liquidity actions, use pre-balance optimization
         if self.is_zappable_add_liquidity(&instr.action) {
             return self.pre_balance_and_add_liquidity(vault, instr, payments, token_out);
         }
 
-        let min = BigUint::from(MIN_INTERNAL_OUTPUT);
+        // Zap-out: remove liquidity and swap the other leg into the target token
+        if let types::ActionType::XExchangeRemoveLiquiditySingle(target) = &instr.action {
+            return self.remove_liquidity_single(vault, instr, payments, target);
+        }
+
+        // xExchange pairs only trade WEGLD: wrap a native EGLD input inline
+        let input_payments = payments;
+        let wrapped_inline = matches!(instr.action, types::ActionType::XExchangeSwap(_))
+            && payments.get(0).token_identifier.is_native();
+        let wrapped_payments;
+        let payments = if wrapped_inline {
+            let input = payments.get(0);
+            wrapped_payments = ManagedVec::from_single_item(
+                self.wrap_or_unwrap(&input.token_identifier, input.amount.as_big_uint()),
+            );
+            &wrapped_payments
+        } else {
+            payments
+        };
+
+        let min = self.internal_min_output_for(&instr.action);
+        let gas = self.get_sub_call_gas(&instr.action);
 
         let mut call = self.get_proxy_call(instr, payments);
 
@@ -454,30 +1140,60 @@ pub trait Utils: crate::storage::Storage {
             types::ActionType::XExchangeSwap(token_out) => call
                 .xexchange(token_out, min)
                 .payment(payments)
-                .returns(ReturnsBackTransfersReset)
-                .sync_call(),
+                .gas(gas)
+                .returns(ReturnsHandledOrError::new().returns(ReturnsBackTransfersReset))
+                .sync_call_fallible(),
             types::ActionType::XExchangeAddLiquidity => call
                 .xdex_add_liquidity(min.clone(), min)
                 .payment(payments)
-                .returns(ReturnsBackTransfersReset)
-                .sync_call(),
+                .gas(gas)
+                .returns(ReturnsHandledOrError::new().returns(ReturnsBackTransfersReset))
+                .sync_call_fallible(),
             types::ActionType::XExchangeRemoveLiquidity => call
                 .xdex_remove_liquidity(min.clone(), min)
                 .payment(payments)
-                .returns(ReturnsBackTransfersReset)
-                .sync_call(),
+                .gas(gas)
+                .returns(ReturnsHandledOrError::new().returns(ReturnsBackTransfersReset))
+                .sync_call_fallible(),
 
             // --- AshSwap V1 (Stable) ---
             types::ActionType::AshSwapPoolSwap(token_out) => call
                 .ash_exchange_stable(token_out, min)
                 .payment(payments)
-                .returns(ReturnsBackTransfersReset)
-                .sync_call(),
+                .gas(gas)
+                .returns(ReturnsHandledOrError::new().returns(ReturnsBackTransfersReset))
+                .sync_call_fallible(),
+            types::ActionType::AshSwapPoolSwapIndexed(indices) => call
+                .ash_exchange_stable_indexed(indices.i, indices.j, min)
+                .payment(payments)
+                .gas(gas)
+                .returns(ReturnsHandledOrError::new().returns(ReturnsBackTransfersReset))
+                .sync_call_fallible(),
             types::ActionType::AshSwapPoolAddLiquidity => call
-                .ash_add_liquidity_stable(min, self.blockchain().get_sc_address())
+                .ash_add_liquidity_stable(
+                    vault.take_min_lp_out().unwrap_or(min),
+                    self.blockchain().get_sc_address(),
+                )
                 .payment(payments)
-                .returns(ReturnsBackTransfersReset)
-                .sync_call(),
+                .gas(gas)
+                .returns(ReturnsHandledOrError::new().returns(ReturnsBackTransfersReset))
+                .sync_call_fallible(),
+            // LP skips the vault, so the pool enforces `min_amount_out` (or a higher
+            // `SetMinLpOut`) on its own mint; `execute_path` only allows this on fee-free
+            // trades. The mint is not observable here, so each such add must reach the
+            // minimum alone.
+            types::ActionType::AshSwapPoolAddLiquidityToCaller => {
+                vault.mark_output_delivered();
+                let min_lp_out = match vault.take_min_lp_out() {
+                    Some(min_lp_out) if min_lp_out > *min_amount_out => min_lp_out,
+                    _ => min_amount_out.clone(),
+                };
+                call.ash_add_liquidity_stable(min_lp_out, self.blockchain().get_caller())
+                    .payment(payments)
+                    .gas(gas)
+                    .returns(ReturnsHandledOrError::new().returns(ReturnsBackTransfersReset))
+                    .sync_call_fallible()
+            }
             types::ActionType::AshSwapPoolRemoveLiquidity(out_tokens) => call
                 .ash_remove_liquidity_stable({
                     let mut mv = MultiValueEncoded::new();
@@ -487,23 +1203,32 @@ pub trait Utils: crate::storage::Storage {
                     mv
                 })
                 .payment(payments)
-                .returns(ReturnsBackTransfersReset)
-                .sync_call(),
+                .gas(gas)
+                .returns(ReturnsHandledOrError::new().returns(ReturnsBackTransfersReset))
+                .sync_call_fallible(),
 
             // --- AshSwap V2 (Crypto) ---
             types::ActionType::AshSwapV2Swap => call
                 .ash_exchange_crypto(min)
                 .payment(payments)
-                .returns(ReturnsBackTransfersReset)
-                .sync_call(),
+                .gas(gas)
+                .returns(ReturnsHandledOrError::new().returns(ReturnsBackTransfersReset))
+                .sync_call_fallible(),
+            types::ActionType::AshSwapV2SwapMin(min_dy) => call
+                .ash_exchange_crypto(min_dy)
+                .payment(payments)
+                .gas(gas)
+                .returns(ReturnsHandledOrError::new().returns(ReturnsBackTransfersReset))
+                .sync_call_fallible(),
             types::ActionType::AshSwapV2AddLiquidity => call
                 .ash_add_liquidity_crypto(
-                    min,
+                    vault.take_min_lp_out().unwrap_or(min),
                     OptionalValue::<multiversx_sc::types::ManagedAddress<Self::Api>>::None,
                 )
                 .payment(payments)
-                .returns(ReturnsBackTransfersReset)
-                .sync_call(),
+                .gas(gas)
+                .returns(ReturnsHandledOrError::new().returns(ReturnsBackTransfersReset))
+                .sync_call_fallible(),
             types::ActionType::AshSwapV2RemoveLiquidity(out_tokens) => call
                 .ash_remove_liquidity_crypto(
                     {
@@ -516,8 +1241,9 @@ pub trait Utils: crate::storage::Storage {
                     OptionalValue::<multiversx_sc::types::ManagedAddress<Self::Api>>::None,
                 )
                 .payment(payments)
-                .returns(ReturnsBackTransfersReset)
-                .sync_call(),
+                .gas(gas)
+                .returns(ReturnsHandledOrError::new().returns(ReturnsBackTransfersReset))
+                .sync_call_fallible(),
             types::ActionType::JexStableRemoveLiquidity(out_tokens) => call
                 .jex_remove_liquidity_stable({
                     let mut mv = MultiValueEncoded::new();
@@ -527,199 +1253,933 @@ pub trait Utils: crate::storage::Storage {
                     mv
                 })
                 .payment(payments)
-                .returns(ReturnsBackTransfersReset)
-                .sync_call(),
+                .gas(gas)
+                .returns(ReturnsHandledOrError::new().returns(ReturnsBackTransfersReset))
+                .sync_call_fallible(),
 
             // --- OneDex ---
+            // Single-hop only: the path is [input, output], so exactly one input may feed it
             types::ActionType::OneDexSwap(token_out) => {
+                require!(payments.len() == 1, ERR_ONEDEX_SINGLE_INPUT);
                 let mut path = MultiValueEncoded::new();
-                for input in payments.iter() {
-                    unsafe {
-                        path.push(input.token_identifier.clone().into_esdt_unchecked());
-                    }
+                unsafe {
+                    path.push(
+                        payments
+                            .get(0)
+                            .token_identifier
+                            .clone()
+                            .into_esdt_unchecked(),
+                    );
                 }
                 path.push(token_out.clone());
                 call.onedex(min, false, path)
                     .payment(payments)
-                    .returns(ReturnsBackTransfersReset)
-                    .sync_call()
+                    .gas(gas)
+                    .returns(ReturnsHandledOrError::new().returns(ReturnsBackTransfersReset))
+                    .sync_call_fallible()
             }
             types::ActionType::OneDexAddLiquidity(_) => call
                 .xdex_add_liquidity(min.clone(), min)
                 .payment(payments)
-                .returns(ReturnsBackTransfersReset)
-                .sync_call(),
+                .gas(gas)
+                .returns(ReturnsHandledOrError::new().returns(ReturnsBackTransfersReset))
+                .sync_call_fallible(),
             types::ActionType::OneDexRemoveLiquidity => call
                 .onedex_remove_liquidity(min.clone(), min.clone(), false)
                 .payment(payments)
-                .returns(ReturnsBackTransfersReset)
-                .sync_call(),
+                .gas(gas)
+                .returns(ReturnsHandledOrError::new().returns(ReturnsBackTransfersReset))
+                .sync_call_fallible(),
 
             // --- Jex (CPMM) ---
             types::ActionType::JexSwap => call
                 .jex(min)
                 .payment(payments)
-                .returns(ReturnsBackTransfersReset)
-                .sync_call(),
+                .gas(gas)
+                .returns(ReturnsHandledOrError::new().returns(ReturnsBackTransfersReset))
+                .sync_call_fallible(),
             types::ActionType::JexAddLiquidity => call
                 .xdex_add_liquidity(min.clone(), min)
                 .payment(payments)
-                .returns(ReturnsBackTransfersReset)
-                .sync_call(),
+                .gas(gas)
+                .returns(ReturnsHandledOrError::new().returns(ReturnsBackTransfersReset))
+                .sync_call_fallible(),
             types::ActionType::JexRemoveLiquidity => call
                 .xdex_remove_liquidity(min.clone(), min)
                 .payment(payments)
-                .returns(ReturnsBackTransfersReset)
-                .sync_call(),
+                .gas(gas)
+                .returns(ReturnsHandledOrError::new().returns(ReturnsBackTransfersReset))
+                .sync_call_fallible(),
 
             // --- Jex (Stable) ---
             types::ActionType::JexStableSwap(token_out) => call
-                .jex_swap_stable(token_out, min * 2u64)
+                .jex_swap_stable(
+                    token_out,
+                    self.jex_stable_min(
+                        &instr.action,
+                        min * self.get_jex_stable_min_multiplier(
+                            &instr.address.clone().unwrap_or_else(ManagedAddress::zero),
+                        ),
+                        payments,
+                    ),
+                )
                 .payment(payments)
-                .returns(ReturnsBackTransfersReset)
-                .sync_call(),
+                .gas(gas)
+                .returns(ReturnsHandledOrError::new().returns(ReturnsBackTransfersReset))
+                .sync_call_fallible(),
             types::ActionType::JexStableAddLiquidity => call
-                .jex_add_liquidity_stable(min * 2u64)
+                .jex_add_liquidity_stable(
+                    vault.take_min_lp_out().unwrap_or_else(|| {
+                        self.jex_stable_min(&instr.action, min * 2u64, payments)
+                    }),
+                )
                 .payment(payments)
-                .returns(ReturnsBackTransfersReset)
-                .sync_call(),
+                .gas(gas)
+                .returns(ReturnsHandledOrError::new().returns(ReturnsBackTransfersReset))
+                .sync_call_fallible(),
             // --- EGLD Wrapping ---
             types::ActionType::Wrapping => call
                 .wrap_egld()
                 .egld(payments.get(0).amount.as_big_uint())
-                .returns(ReturnsBackTransfersReset)
-                .sync_call(),
+                .gas(gas)
+                .returns(ReturnsHandledOrError::new().returns(ReturnsBackTransfersReset))
+                .sync_call_fallible(),
             types::ActionType::UnWrapping => call
                 .unwrap_egld()
                 .payment(payments)
-                .returns(ReturnsBackTransfersReset)
-                .sync_call(),
+                .gas(gas)
+                .returns(ReturnsHandledOrError::new().returns(ReturnsBackTransfersReset))
+                .sync_call_fallible(),
 
             // --- Liquid Staking ---
             types::ActionType::XoxnoLiquidStaking | types::ActionType::LXoxnoLiquidStaking => call
                 .delegate(OptionalValue::<multiversx_sc::types::ManagedAddress<Self::Api>>::None)
                 .payment(payments)
-                .returns(ReturnsBackTransfersReset)
-                .sync_call(),
-            types::ActionType::HatomLiquidStaking => call
-                .delegate_hatom()
-                .egld(payments.get(0).amount.as_big_uint())
-                .returns(ReturnsBackTransfersReset)
-                .sync_call(),
+                .gas(gas)
+                .returns(ReturnsHandledOrError::new().returns(ReturnsBackTransfersReset))
+                .sync_call_fallible(),
+            // The unwrap spends gas, so the stake's budget is taken after it
+            types::ActionType::HatomLiquidStaking => {
+                let egld_amount = self.native_egld_amount(&payments.get(0));
+                call.delegate_hatom()
+                    .egld(egld_amount)
+                    .gas(self.get_sub_call_gas(&instr.action))
+                    .returns(ReturnsHandledOrError::new().returns(ReturnsBackTransfersReset))
+                    .sync_call_fallible()
+            }
+            types::ActionType::ClaimRewards => call
+                .claim_rewards()
+                .payment(payments)
+                .gas(gas)
+                .returns(ReturnsHandledOrError::new().returns(ReturnsBackTransfersReset))
+                .sync_call_fallible(),
+            // The claimed rewards go straight back to the staking contract, so only the
+            // staked tokens they mint reach the vault
+            types::ActionType::ClaimAndRestake => {
+                let claimed = call
+                    .claim_rewards()
+                    .payment(payments)
+                    .gas(gas)
+                    .returns(ReturnsHandledOrError::new().returns(ReturnsBackTransfersReset))
+                    .sync_call_fallible();
+                let rewards = self
+                    .expect_sub_call(&instr.action, claimed)
+                    .into_payment_vec();
+                require!(!rewards.is_empty(), ERR_NO_REWARDS_TO_RESTAKE);
+                self.get_proxy_call(instr, payments)
+                    .delegate(OptionalValue::<ManagedAddress<Self::Api>>::None)
+                    .payment(&rewards)
+                    .gas(self.get_sub_call_gas(&instr.action))
+                    .returns(ReturnsHandledOrError::new().returns(ReturnsBackTransfersReset))
+                    .sync_call_fallible()
+            }
 
             // --- Hatom Lending ---
             types::ActionType::HatomRedeem => call
                 .hatom_redeem(OptionalValue::<BigUint<Self::Api>>::None)
                 .payment(payments)
-                .returns(ReturnsBackTransfersReset)
-                .sync_call(),
+                .gas(gas)
+                .returns(ReturnsHandledOrError::new().returns(ReturnsBackTransfersReset))
+                .sync_call_fallible(),
             types::ActionType::HatomSupply(_) => call
                 .hatom_mint()
                 .payment(payments)
-                .returns(ReturnsBackTransfersReset)
-                .sync_call(),
+                .gas(gas)
+                .returns(ReturnsHandledOrError::new().returns(ReturnsBackTransfersReset))
+                .sync_call_fallible(),
+            // Collateral is credited to the caller and nothing comes back to the vault; the
+            // deposited hTokens are checked against `min_amount_out` like LP sent to the
+            // caller
+            types::ActionType::HatomEnterMarket => {
+                let deposited = payments.get(0).amount.as_big_uint().clone();
+                let result = call
+                    .hatom_enter_markets(OptionalValue::Some(self.blockchain().get_caller()))
+                    .payment(payments)
+                    .gas(gas)
+                    .returns(ReturnsHandledOrError::new().returns(ReturnsBackTransfersReset))
+                    .sync_call_fallible();
+                if result.is_ok() {
+                    vault.deliver_lp(&deposited);
+                }
+                result
+            }
+            // The redeemed underlying goes straight back into the market, so only the
+            // re-minted hTokens reach the vault and nothing is counted twice
+            types::ActionType::HatomCompound => {
+                let redeemed = call
+                    .hatom_redeem(OptionalValue::<BigUint<Self::Api>>::None)
+                    .payment(payments)
+                    .gas(gas)
+                    .returns(ReturnsHandledOrError::new().returns(ReturnsBackTransfersReset))
+                    .sync_call_fallible();
+                let underlying = self
+                    .expect_sub_call(&instr.action, redeemed)
+                    .into_payment_vec();
+                self.get_proxy_call(instr, payments)
+                    .hatom_mint()
+                    .payment(&underlying)
+                    .gas(self.get_sub_call_gas(&instr.action))
+                    .returns(ReturnsHandledOrError::new().returns(ReturnsBackTransfersReset))
+                    .sync_call_fallible()
+            }
+
+            // --- Handled before dispatch ---
+            types::ActionType::ClearPrev
+            | types::ActionType::SetMinLpOut(_)
+            | types::ActionType::SetZapRatio(_)
+            | types::ActionType::ExpectOutputs(_)
+            | types::ActionType::UnwrapOutputs
+            | types::ActionType::AllowFailure
+            | types::ActionType::RequireFullInput
+            | types::ActionType::LpToCaller
+            | types::ActionType::SetMinLpValue(_)
+            | types::ActionType::XExchangeRemoveLiquiditySingle(_) => return,
+        };
+
+        // Consumed whatever the outcome, so only the one swap it precedes is best-effort
+        let allow_failure = vault.take_allow_failure();
+        let back_transfers = match back_transfers {
+            Err(_) if allow_failure => {
+                // Undo an inline wrap, so the EGLD that was paid in is what gets refunded
+                if wrapped_inline {
+                    let wrapped = payments.get(0);
+                    self.wrap_or_unwrap(&wrapped.token_identifier, wrapped.amount.as_big_uint());
+                }
+                self.skip_failed_call(vault, input_payments);
+                return;
+            }
+            result => self.expect_sub_call(&instr.action, result),
         };
 
         // Standard result handling for non-add-liquidity operations
         // (add_liquidity is handled at the start of this function via pre_balance_and_add_liquidity)
         let result = back_transfers.into_payment_vec();
         let result_len = result.len();
+        self.require_result_count(&instr.action, result_len);
+        self.require_swap_output(instr, payments, &result);
+        let expected_outputs = vault.take_expected_outputs();
+        if expected_outputs.is_some() {
+            // A multi-output call starts a fresh set of legs
+            vault.clear_prev_results_by_token();
+        }
+        // Only read the WEGLD id when an unwrap was requested
+        let unwrap_token = if vault.take_unwrap_outputs() {
+            Some(TokenId::from(
+                self.get_wegld_token_id().into_managed_buffer(),
+            ))
+        } else {
+            None
+        };
         for funds in result.iter() {
+            let mut funds = self.normalize_refund(&instr.action, payments, &funds);
+            if unwrap_token.as_ref() == Some(&funds.token_identifier) {
+                funds = self.wrap_or_unwrap(&funds.token_identifier, funds.amount.as_big_uint());
+            }
             if result_len == 1 {
                 // For single-output operations, set prev_result for PrevAmount mode
                 vault.set_prev_result(&funds);
             }
+            if let Some(expected) = &expected_outputs {
+                // Declared outputs: PrevAmount on each leg reads its own amount
+                if !expected.contains(&funds.token_identifier) {
+                    sc_panic!("E: Unexpected output token: {}", funds.token_identifier);
+                }
+                vault.add_prev_result_for(&funds.token_identifier, funds.amount.as_big_uint());
+            }
             vault.deposit(&funds.token_identifier, &funds.amount);
         }
+
+        if let Some(expected) = &expected_outputs {
+            for token in expected.iter() {
+                if !vault.has_prev_result_for(&token) {
+                    sc_panic!("E: Expected output token not received: {}", *token);
+                }
+            }
+        }
+    }
+
+    /// Final staking instruction for a liquid staking `token_out` opted in with
+    /// `setAutoStake`; without inputs it stakes the previous result
+    fn auto_stake_instruction(
+        &self,
+        token_out: &TokenId<Self::Api>,
+    ) -> Option<Instruction<Self::Api>> {
+        let auto_stake = self.auto_stake_action(token_out);
+        if auto_stake.is_empty() {
+            return None;
+        }
+        let action = match CompactAction::from_u8(auto_stake.get()) {
+            Some(CompactAction::XoxnoLiquidStaking) => types::ActionType::XoxnoLiquidStaking,
+            Some(CompactAction::LXoxnoLiquidStaking) => types::ActionType::LXoxnoLiquidStaking,
+            Some(CompactAction::HatomLiquidStaking) => types::ActionType::HatomLiquidStaking,
+            _ => return None,
+        };
+        Some(Instruction {
+            action,
+            inputs: None,
+            address: None,
+        })
+    }
+
+    /// Set aside the inputs of a failed best-effort swap for the recipient
+    ///
+    /// Nothing was produced, so pending modifiers of the call are dropped and a later
+    /// `PrevAmount` chained onto it finds no previous result and reverts.
+    fn skip_failed_call(
+        &self,
+        vault: &mut Vault<Self::Api>,
+        payments: &ManagedVec<Payment<Self::Api>>,
+    ) {
+        vault.clear_prev_result();
+        vault.take_expected_outputs();
+        vault.take_unwrap_outputs();
+        for payment in payments.iter() {
+            vault.add_refund(&payment);
+        }
+    }
+
+    /// Reject a sub-call that succeeded but sent back fewer payments than its action
+    /// always produces, which would otherwise silently drop the input
+    ///
+    /// Remove liquidity must return every leg, anything else at least one payment.
+    /// Actions delivering straight to the caller legitimately return nothing.
+    fn require_result_count(&self, action: &types::ActionType<Self::Api>, result_len: usize) {
+        let expected = match action {
+            types::ActionType::AshSwapPoolAddLiquidityToCaller
+            | types::ActionType::HatomEnterMarket => return,
+            types::ActionType::XExchangeRemoveLiquidity
+            | types::ActionType::XExchangeRemoveLiquiditySingle(_)
+            | types::ActionType::OneDexRemoveLiquidity
+            | types::ActionType::JexRemoveLiquidity => 2,
+            types::ActionType::AshSwapPoolRemoveLiquidity(out_tokens)
+            | types::ActionType::AshSwapV2RemoveLiquidity(out_tokens)
+            | types::ActionType::JexStableRemoveLiquidity(out_tokens) => *out_tokens,
+            _ => 1,
+        };
+
+        let returned = result_len as u32;
+        if returned < expected {
+            let action_byte = action.compact().to_u8();
+            sc_panic!(
+                "E: Action {} returned {} payments, expected {}",
+                action_byte,
+                returned,
+                expected
+            );
+        }
+    }
+
+    /// Reject a swap that handed back only its input tokens
+    ///
+    /// Some router configurations return the input untouched when no liquidity is
+    /// found instead of reverting; without this check the failure would only surface
+    /// later as a confusing `PrevAmount` token mismatch.
+    fn require_swap_output(
+        &self,
+        instr: &Instruction<Self::Api>,
+        payments: &ManagedVec<Payment<Self::Api>>,
+        result: &ManagedVec<Payment<Self::Api>>,
+    ) {
+        if !instr.action.compact().is_swap() {
+            return;
+        }
+
+        let only_input_returned = result.iter().all(|output| {
+            payments
+                .iter()
+                .any(|input| input.token_identifier == output.token_identifier)
+        });
+        if only_input_returned {
+            // Resolved again only on this failure path to name the pool
+            let pool = self.resolve_proxy_address(instr, payments);
+            sc_panic!(
+                "E: Swap produced no output (no liquidity?) at pool {:x}",
+                pool.as_managed_buffer()
+            );
+        }
+    }
+
+    /// Bring a WEGLD/EGLD refund back to the form its input was sent in
+    ///
+    /// A pool refunding part of an EGLD input as WEGLD (or the reverse) would otherwise
+    /// leave it under a vault key no later instruction withdraws from. Intended outputs
+    /// (wrap/unwrap results and known output tokens) are left untouched.
+    fn normalize_refund(
+        &self,
+        action: &types::ActionType<Self::Api>,
+        payments: &ManagedVec<Payment<Self::Api>>,
+        funds: &Payment<Self::Api>,
+    ) -> Payment<Self::Api> {
+        if matches!(
+            action,
+            types::ActionType::Wrapping
+                | types::ActionType::UnWrapping
+                | types::ActionType::HatomLiquidStaking
+        ) {
+            return funds.clone();
+        }
+        if let Some(types::PathOutput::Token(token)) = self.static_output(action) {
+            if funds.token_identifier == TokenId::from(token.into_managed_buffer()) {
+                return funds.clone();
+            }
+        }
+
+        let egld = TokenId::from(EGLD_000000_TOKEN_IDENTIFIER.as_bytes());
+        let wegld = TokenId::from(self.get_wegld_token_id().into_managed_buffer());
+        let counterpart = if funds.token_identifier == egld {
+            wegld
+        } else if funds.token_identifier == wegld {
+            egld
+        } else {
+            return funds.clone();
+        };
+
+        if !payments
+            .iter()
+            .any(|input| input.token_identifier == counterpart)
+        {
+            return funds.clone();
+        }
+        self.wrap_or_unwrap(&funds.token_identifier, funds.amount.as_big_uint())
     }
 
-    /// Resolve the proxy address for a given instruction
+    /// Minimum output for an action's pool call, by its category
+    fn internal_min_output_for(&self, action: &types::ActionType<Self::Api>) -> BigUint<Self::Api> {
+        match action.compact().category() {
+            Some(category) => self.get_internal_min_output(category),
+            None => BigUint::from(MIN_INTERNAL_OUTPUT),
+        }
+    }
+
+    /// Jex stable minimum, capped at `getJexStableMinInputPpm` of the input when set
+    ///
+    /// Only single-input calls are capped: amounts of different tokens (and decimals)
+    /// cannot be summed into one input. The cap never goes below the action's internal
+    /// minimum output and never raises the minimum.
+    fn jex_stable_min(
+        &self,
+        action: &types::ActionType<Self::Api>,
+        min: BigUint<Self::Api>,
+        payments: &ManagedVec<Payment<Self::Api>>,
+    ) -> BigUint<Self::Api> {
+        let input_ppm = self.jex_stable_min_input_ppm().get();
+        if input_ppm == 0 || payments.len() != 1 {
+            return min;
+        }
+        let input = payments.get(0).amount.as_big_uint() * input_ppm / 1_000_000u32;
+        let cap = core::cmp::max(input, self.internal_min_output_for(action));
+        core::cmp::min(min, cap)
+    }
+
+    /// Gas to forward to a DEX sub-call: the configured ceiling for the action,
+    /// or all remaining gas when no ceiling is set
+    fn get_sub_call_gas(&self, action: &types::ActionType<Self::Api>) -> u64 {
+        let gas_left = self.blockchain().get_gas_left();
+        let gas_limit = self.action_gas_limit(action.compact().to_u8()).get();
+        if gas_limit == 0 || gas_limit > gas_left {
+            gas_left
+        } else {
+            gas_limit
+        }
+    }
+
+    /// Unwrap a fallible DEX sub-call, failing with the action that reverted
+    fn expect_sub_call(
+        &self,
+        action: &types::ActionType<Self::Api>,
+        result: Result<BackTransfers<Self::Api>, u32>,
+    ) -> BackTransfers<Self::Api> {
+        result.unwrap_or_else(|error_code| {
+            let action_byte = action.compact().to_u8();
+            sc_panic!(
+                "E: Sub-call failed for action {}: error code {}",
+                action_byte,
+                error_code
+            )
+        })
+    }
+
+    /// Proxy to the contract a given instruction calls
     fn get_proxy_call(
         &self,
         instr: &Instruction<Self::Api>,
         payments: &ManagedVec<Payment<Self::Api>>,
     ) -> proxies::ProxyTo<Self::Api> {
+        let address = self.resolve_proxy_address(instr, payments);
+        self.proxy_call(address)
+    }
+
+    /// Resolve the address of the contract a given instruction calls
+    fn resolve_proxy_address(
+        &self,
+        instr: &Instruction<Self::Api>,
+        payments: &ManagedVec<Payment<Self::Api>>,
+    ) -> ManagedAddress<Self::Api> {
+        require!(!payments.is_empty(), ERR_NO_INPUT_PAYMENT);
         let first_payment = payments.get(0).clone();
 
-        match &instr.action {
-            types::ActionType::XExchangeSwap(token_out) => {
-                self.proxy_call(self.get_pair_x(token_out, unsafe {
-                    first_payment.token_identifier.as_esdt_unchecked()
-                }))
-            }
+        let address = match &instr.action {
+            types::ActionType::XExchangeSwap(token_out) => self.get_pair_x(token_out, unsafe {
+                first_payment.token_identifier.as_esdt_unchecked()
+            }),
             types::ActionType::XExchangeAddLiquidity => unsafe {
                 let second_token = payments
                     .get(1)
                     .clone()
                     .token_identifier
                     .into_esdt_unchecked();
-                self.proxy_call(self.get_pair_x(
+                self.get_pair_x(
                     first_payment.token_identifier.as_esdt_unchecked(),
                     &second_token,
-                ))
+                )
             },
             types::ActionType::OneDexSwap(_)
             | types::ActionType::OneDexAddLiquidity(_)
-            | types::ActionType::OneDexRemoveLiquidity => {
-                self.proxy_call(ManagedAddress::from(ONE_DEX_ROUTER))
-            }
+            | types::ActionType::OneDexRemoveLiquidity => ManagedAddress::from(ONE_DEX_ROUTER),
             types::ActionType::Wrapping | types::ActionType::UnWrapping => {
-                self.proxy_call(ManagedAddress::from(WRAPPER_SC))
+                ManagedAddress::from(WRAPPER_SC)
             }
-            types::ActionType::XoxnoLiquidStaking => {
-                self.proxy_call(ManagedAddress::from(XEGLD_STAKING))
+            types::ActionType::XoxnoLiquidStaking => ManagedAddress::from(XEGLD_STAKING),
+            types::ActionType::LXoxnoLiquidStaking
+            | types::ActionType::ClaimRewards
+            | types::ActionType::ClaimAndRestake => ManagedAddress::from(LXOXNO_STAKING),
+            types::ActionType::HatomLiquidStaking => ManagedAddress::from(HATOM_STAKING),
+            types::ActionType::HatomRedeem | types::ActionType::HatomCompound => unsafe {
+                self.get_hatom_market(first_payment.token_identifier.clone().as_esdt_unchecked())
+            },
+            types::ActionType::HatomSupply(token) => self.get_hatom_market(token),
+            types::ActionType::HatomEnterMarket => ManagedAddress::from(HATOM_CONTROLLER),
+            _ => instr.address.clone().unwrap_or_else(ManagedAddress::zero),
+        };
+        require!(!address.is_zero(), ERR_POOL_ADDRESS_UNRESOLVED);
+        address
+    }
+
+    // --- Soft-Fail Quote ---
+
+    /// Quote the output of a swap-only path from pool reserves, without any call
+    ///
+    /// Runs the instructions against an in-memory vault, so amount modes behave as in
+    /// execution. The first xExchange hop on a pair is quoted by the pair's `getAmountOut`
+    /// view; other hops use the CPMM math, on reserves moved by earlier hops on the same
+    /// pool. Only xExchange and Jex CPMM swaps (and ClearPrev) can be quoted.
+    fn quote_swap_path(
+        &self,
+        payment: &PaymentVec<Self::Api>,
+        token_out: &TokenId<Self::Api>,
+        tokens: &TokenRegistry<Self::Api>,
+        addresses: &AddressRegistry<Self::Api>,
+        amounts: &AmountRegistry<Self::Api>,
+        instructions: MultiValueEncoded<MultiValue6<u8, u8, u8, u8, u8, u16>>,
+    ) -> BigUint<Self::Api> {
+        let mut vault = Vault::from_payment(payment);
+        let mut reserves = ManagedMapEncoded::<Self::Api, ManagedBuffer, BigUint>::new();
+
+        for compact_instr in instructions {
+            let (action_byte, byte1, byte2, byte3, byte4, pair_id_or_addr) =
+                compact_instr.into_tuple();
+            let instruction = self.decode_compact_instruction(
+                action_byte,
+                byte1,
+                byte2,
+                byte3,
+                byte4,
+                pair_id_or_addr,
+                tokens,
+                addresses,
+                amounts,
+            );
+
+            if matches!(instruction.action, types::ActionType::ClearPrev) {
+                vault.clear_prev_result();
+                continue;
             }
-            types::ActionType::LXoxnoLiquidStaking => {
-                self.proxy_call(ManagedAddress::from(LXOXNO_STAKING))
+            // The quote assumes every swap succeeds and checks no leftovers
+            if matches!(
+                instruction.action,
+                types::ActionType::AllowFailure
+                    | types::ActionType::RequireFullInput
+                    | types::ActionType::LpToCaller
+            ) {
+                continue;
             }
-            types::ActionType::HatomLiquidStaking => {
-                self.proxy_call(ManagedAddress::from(HATOM_STAKING))
+            require!(
+                matches!(
+                    instruction.action,
+                    types::ActionType::XExchangeSwap(_) | types::ActionType::JexSwap
+                ),
+                ERR_SOFT_FAIL_SWAP_ONLY
+            );
+
+            // Same withdrawal rules as execute_instruction
+            let (token_in, amount_in) = match &instruction.inputs {
+                Some(inputs) => {
+                    require!(inputs.len() == 1, ERR_SOFT_FAIL_SWAP_ONLY);
+                    let input = inputs.get(0);
+                    let token = TokenId::from(input.token.clone());
+                    let amount = match &input.mode {
+                        AmountMode::Fixed(amount) => vault.withdraw(&token, amount),
+                        AmountMode::FixedOrAll(amount) => vault.withdraw_up_to(&token, amount),
+                        AmountMode::Ppm(ppm) => vault.withdraw_ppm(&token, ppm),
+                        AmountMode::All => vault.withdraw_all(&token),
+                        AmountMode::PrevAmount => {
+                            let prev_result = vault.take_prev_result();
+                            require!(prev_result.is_some(), ERR_PREV_AMOUNT_NOT_AVAILABLE);
+                            let prev_value = prev_result.unwrap();
+                            self.require_prev_token(&token, &prev_value.token_identifier);
+                            vault.withdraw(&token, prev_value.amount.as_big_uint())
+                        }
+                    };
+                    (token, amount)
+                }
+                None => {
+                    let prev = vault.take_prev_result();
+                    require!(prev.is_some(), ERR_PREV_AMOUNT_NOT_AVAILABLE);
+                    let prev = prev.unwrap();
+                    let amount = vault.withdraw(&prev.token_identifier, prev.amount.as_big_uint());
+                    (prev.token_identifier, amount)
+                }
+            };
+
+            let pool_in = self.to_pool_token(&token_in);
+            let (pool, pool_out, fee_num, fee_denom, fee_mode) = match &instruction.action {
+                types::ActionType::XExchangeSwap(out) => {
+                    let pool = self.get_pair_x(out, &pool_in);
+                    require!(!pool.is_zero(), ERR_POOL_ADDRESS_UNRESOLVED);
+                    let total_fee = self.xexchange_total_fee_percent(pool.clone()).get();
+                    let special_fee = self.xexchange_special_fee_percent(pool.clone()).get();
+                    let fee_mode = zap::FeeMode::OnInput {
+                        special_fee_num: special_fee,
+                    };
+                    (pool, out.clone(), total_fee, XEXCHANGE_FEE_DENOM, fee_mode)
+                }
+                _ => {
+                    let pool = instruction
+                        .address
+                        .clone()
+                        .unwrap_or_else(ManagedAddress::zero);
+                    require!(!pool.is_zero(), ERR_POOL_ADDRESS_UNRESOLVED);
+                    let first_token = self.jex_first_token_id(pool.clone()).get();
+                    let pool_out = if first_token == pool_in {
+                        self.jex_second_token_id(pool.clone()).get()
+                    } else {
+                        first_token
+                    };
+                    let lp_fees = self.jex_liq_providers_fees(pool.clone()).get() as u64;
+                    let platform_fees = self.jex_platform_fees(pool.clone()).get() as u64;
+                    let fee_mode = zap::FeeMode::OnOutput {
+                        lp_fee_num: lp_fees,
+                    };
+                    (
+                        pool,
+                        pool_out,
+                        lp_fees + platform_fees,
+                        TOTAL_FEE as u64,
+                        fee_mode,
+                    )
+                }
+            };
+
+            let key_in = self.quote_reserve_key(&pool, &pool_in);
+            let key_out = self.quote_reserve_key(&pool, &pool_out);
+            let first_hop_on_pool = !reserves.contains(&key_in);
+            let reserve_in =
+                self.quote_reserve(&mut reserves, &key_in, &instruction.action, &pool, &pool_in);
+            let reserve_out = self.quote_reserve(
+                &mut reserves,
+                &key_out,
+                &instruction.action,
+                &pool,
+                &pool_out,
+            );
+
+            let (mut output, mut amount_out_leaving, amount_in_to_reserves) =
+                zap::simulate_swap_output(
+                    &amount_in,
+                    &reserve_in,
+                    &reserve_out,
+                    fee_num,
+                    fee_denom,
+                    fee_mode,
+                );
+
+            // An xExchange pair not yet moved by this quote can answer for itself, which
+            // keeps the quote exact; later hops on it fall back to the tracked reserves
+            if first_hop_on_pool
+                && matches!(instruction.action, types::ActionType::XExchangeSwap(_))
+            {
+                output = self
+                    .pool_view_proxy(pool.clone())
+                    .xexchange_amount_out(&pool_in, &amount_in)
+                    .returns(ReturnsResult)
+                    .sync_call_readonly();
+                amount_out_leaving = output.clone();
             }
-            types::ActionType::HatomRedeem => self.proxy_call(unsafe {
-                self.get_hatom_market(first_payment.token_identifier.clone().as_esdt_unchecked())
-            }),
-            types::ActionType::HatomSupply(token) => self.proxy_call(self.get_hatom_market(token)),
-            _ => self.proxy_call(instr.address.clone().unwrap()),
+
+            let output = match output.into_non_zero() {
+                Some(output) => output,
+                None => return BigUint::zero(),
+            };
+
+            reserves.put(&key_in, &(reserve_in + amount_in_to_reserves));
+            reserves.put(&key_out, &(reserve_out - amount_out_leaving));
+
+            let token_out_hop = TokenId::from(pool_out.into_managed_buffer());
+            let result = Payment::new(token_out_hop, 0u64, output);
+            vault.set_prev_result(&result);
+            vault.deposit(&result.token_identifier, &result.amount);
+        }
+
+        if vault.contains(token_out) {
+            vault.balance_of(token_out)
+        } else {
+            BigUint::zero()
+        }
+    }
+
+    fn quote_reserve_key(
+        &self,
+        pool: &ManagedAddress<Self::Api>,
+        token: &TokenIdentifier<Self::Api>,
+    ) -> ManagedBuffer<Self::Api> {
+        let mut key = pool.as_managed_buffer().clone();
+        key.append(token.as_managed_buffer());
+        key
+    }
+
+    /// Reserve of a token in a pool, as left behind by earlier quoted hops
+    fn quote_reserve(
+        &self,
+        reserves: &mut ManagedMapEncoded<Self::Api, ManagedBuffer<Self::Api>, BigUint<Self::Api>>,
+        key: &ManagedBuffer<Self::Api>,
+        action: &types::ActionType<Self::Api>,
+        pool: &ManagedAddress<Self::Api>,
+        token: &TokenIdentifier<Self::Api>,
+    ) -> BigUint<Self::Api> {
+        if reserves.contains(key) {
+            return reserves.get(key);
+        }
+        let reserve = match action {
+            types::ActionType::XExchangeSwap(_) => {
+                self.xexchange_pair_reserve(pool.clone(), token).get()
+            }
+            _ => {
+                if self.jex_first_token_id(pool.clone()).get() == *token {
+                    self.jex_first_token_reserve(pool.clone()).get()
+                } else {
+                    self.jex_second_token_reserve(pool.clone()).get()
+                }
+            }
+        };
+        reserves.put(key, &reserve);
+        reserve
+    }
+
+    // --- Slippage Floor ---
+
+    /// Reject a min_amount_out that is implausibly low for the input
+    ///
+    /// The reference value is the spot quote on the direct xExchange pair between the
+    /// (first) input and the output token. Paths without such a pair are not checked.
+    fn require_slippage_floor(
+        &self,
+        payments: &PaymentVec<Self::Api>,
+        token_out: &TokenId<Self::Api>,
+        min_amount_out: &BigUint<Self::Api>,
+        min_output_ppm: u32,
+    ) {
+        require!(!payments.is_empty(), ERR_NO_INPUT_PAYMENT);
+        let payment = payments.get(0);
+        let token_in = self.to_pool_token(&payment.token_identifier);
+        let token_out = self.to_pool_token(token_out);
+        if token_in == token_out {
+            return;
+        }
+
+        let pair = self.get_pair_x(&token_in, &token_out);
+        if pair.is_zero() {
+            return;
+        }
+        let reserve_in = self.xexchange_pair_reserve(pair.clone(), &token_in).get();
+        let reserve_out = self.xexchange_pair_reserve(pair, &token_out).get();
+        if reserve_in == 0u64 {
+            return;
+        }
+
+        let spot_out = payment.amount.as_big_uint() * &reserve_out / &reserve_in;
+        let floor = spot_out * min_output_ppm / 1_000_000u64;
+        require!(
+            *min_amount_out >= floor,
+            "E: Slippage floor not met: min {}, floor {}",
+            min_amount_out,
+            floor
+        );
+    }
+
+    /// Token as it is listed in pools (EGLD trades as WEGLD)
+    fn to_pool_token(&self, token: &TokenId<Self::Api>) -> TokenIdentifier<Self::Api> {
+        if token.is_native() {
+            self.get_wegld_token_id()
+        } else {
+            unsafe { token.clone().into_esdt_unchecked() }
         }
     }
 
     // --- Fee Logic ---
 
     /// Apply fees to the output token before returning to caller
-    /// referral_id = 0 means no referral
+    /// referral_id = 0 means no referral; in fee-on-input mode the fees were already
+    /// taken from the payments
     fn apply_fees(
         &self,
         vault: &mut Vault<Self::Api>,
         token_out: &TokenId<Self::Api>,
         referral_id: u64,
     ) {
-        // No fees if referral_id is 0
-        if referral_id == 0 {
+        if self.fee_on_input().get() || !self.charges_fees(referral_id) {
             return;
         }
 
-        // Check for valid referral
-        if !self.referral_config(referral_id).is_empty() {
-            let config = self.referral_config(referral_id).get();
-            if config.active {
-                let output_balance = vault.balance_of(token_out);
-                let static_fee_bps = self.static_fee().get();
-
-                // Calculate static fee (goes to admin) + referral fee (goes to referrer)
-                let static_fee = &output_balance * static_fee_bps / TOTAL_FEE;
-                let referral_fee = &output_balance * config.fee / TOTAL_FEE;
-                let total = &static_fee + &referral_fee;
+        let output_balance = vault.balance_of(token_out);
+        let (admin_fee, referral_fee) =
+            self.compute_fees(token_out, token_out, referral_id, &output_balance);
+        self.debit_fee(vault, token_out, &admin_fee, referral_id, &referral_fee);
+    }
 
-                // Withdraw total fees from vault
-                vault.withdraw(token_out, &total);
+    /// Fee-on-input mode: take the fees from every payment token before the path runs
+    /// Each payment is charged under the fee settings keyed by its own token and amount
+    /// (see `compute_fees`), e.g. the min trade is compared to the payment amount
+    fn apply_input_fees(
+        &self,
+        vault: &mut Vault<Self::Api>,
+        token_out: &TokenId<Self::Api>,
+        referral_id: u64,
+    ) {
+        if !self.charges_fees(referral_id) {
+            return;
+        }
+        for payment in vault.get_all_payments_sorted().iter() {
+            let token = &payment.token_identifier;
+            let (admin_fee, referral_fee) =
+                self.compute_fees(token, token_out, referral_id, payment.amount.as_big_uint());
+            self.debit_fee(vault, token, &admin_fee, referral_id, &referral_fee);
+        }
+    }
 
-                // Accumulate fees separately
-                self.accumulate_admin_fee(token_out, &static_fee);
-                if config.fee > 0 {
-                    self.accumulate_referrer_fee(referral_id, token_out, &referral_fee);
-                }
+    /// Payments left after fee-on-input fees, computed without booking them
+    fn net_of_input_fees(
+        &self,
+        payment: &PaymentVec<Self::Api>,
+        token_out: &TokenId<Self::Api>,
+        referral_id: u64,
+    ) -> PaymentVec<Self::Api> {
+        let mut vault = Vault::from_payment(payment);
+        if self.charges_fees(referral_id) {
+            for payment in vault.get_all_payments().iter() {
+                let token = &payment.token_identifier;
+                let (admin_fee, referral_fee) =
+                    self.compute_fees(token, token_out, referral_id, payment.amount.as_big_uint());
+                vault.withdraw(token, &(admin_fee + referral_fee));
             }
         }
+        vault.get_all_payments()
+    }
+
+    /// Fees apply only with an active referral, and never during a free promo
+    fn charges_fees(&self, referral_id: u64) -> bool {
+        if referral_id == 0 || self.free_mode().get() {
+            return false;
+        }
+        let config = self.referral_config(referral_id);
+        !config.is_empty() && config.get().active
+    }
+
+    /// Split the fee on a charged amount into (admin, referral) shares
+    /// Only meaningful when `charges_fees(referral_id)` holds
+    /// In drain mode the whole fee goes to admin, so referral balances stop growing
+    ///
+    /// `token` and `amount` are what the fee is taken from: the output, or each payment
+    /// in fee-on-input mode. The static fee override, min trade and tier thresholds are
+    /// keyed by that token and amount; the referral's allowed tokens always gate on the
+    /// aggregation's `token_out`.
+    fn compute_fees(
+        &self,
+        token: &TokenId<Self::Api>,
+        token_out: &TokenId<Self::Api>,
+        referral_id: u64,
+        amount: &BigUint<Self::Api>,
+    ) -> (BigUint<Self::Api>, BigUint<Self::Api>) {
+        let config = self.referral_config(referral_id).get();
+        let static_fee_bps = self.get_static_fee(token);
+
+        // Calculate static fee (goes to admin) + referral fee (goes to referrer)
+        let static_fee = self.fee_amount(amount, static_fee_bps);
+        let referral_fee_bps = self.get_referral_fee(referral_id, &config, amount);
+        let referral_fee = self.fee_amount(amount, referral_fee_bps);
+
+        // Trades below the referral's minimum or outside its allowed markets
+        // send the referral share to admin
+        let allowed_tokens = self.referral_allowed_tokens(referral_id);
+        let token_allowed = allowed_tokens.is_empty() || allowed_tokens.contains(token_out);
+        if *amount < self.referral_min_trade(referral_id).get()
+            || !token_allowed
+            || self.drain_mode().get()
+        {
+            (static_fee + referral_fee, BigUint::zero())
+        } else {
+            (static_fee, referral_fee)
+        }
+    }
+
+    /// Fee of `bps` basis points on `base`, rounded down
+    ///
+    /// Invariant: fees never round up, so the remainder of the division always stays
+    /// with the user (e.g. base 1 at 1 bps is a zero fee). Every fee share is computed
+    /// here so the rounding direction cannot drift between call sites.
+    fn fee_amount(&self, base: &BigUint<Self::Api>, bps: u32) -> BigUint<Self::Api> {
+        // BigUint division truncates, which is rounding down for unsigned values
+        base * bps / TOTAL_FEE
+    }
+
+    /// Move fees out of the vault and into the admin and referrer balances in one step,
+    /// so the vault always decreases by exactly what the fee balances gain
+    ///
+    /// The only path into `admin_fees` and `referrer_balances`: path fees, leftover dust
+    /// and ZAP refunds are all debited here.
+    fn debit_fee(
+        &self,
+        vault: &mut Vault<Self::Api>,
+        token: &TokenId<Self::Api>,
+        admin_amount: &BigUint<Self::Api>,
+        referral_id: u64,
+        referral_amount: &BigUint<Self::Api>,
+    ) {
+        vault.withdraw(token, &(admin_amount + referral_amount));
+
+        self.accumulate_admin_fee(token, admin_amount);
+        if *referral_amount > 0u64 {
+            self.accumulate_referrer_fee(referral_id, token, referral_amount);
+        }
     }
 
     fn accumulate_referrer_fee(
@@ -731,11 +2191,20 @@ pub trait Utils: crate::storage::Storage {
         let current = self.referrer_balances(id).get(token).unwrap_or_default();
         self.referrer_balances(id)
             .insert(token.clone(), &current + amount);
+        self.book_egld_liability(id, token, amount);
+        if *amount > 0u64 {
+            self.touch_referral_activity(id);
+            self.referral_accrued_event(id, token, amount);
+        }
     }
 
     fn accumulate_admin_fee(&self, token: &TokenId<Self::Api>, amount: &BigUint<Self::Api>) {
         let current = self.admin_fees().get(token).unwrap_or_default();
         self.admin_fees().insert(token.clone(), &current + amount);
+        self.book_egld_liability(0, token, amount);
+        if *amount > 0u64 {
+            self.admin_accrued_event(token, amount);
+        }
     }
 
     // --- Pre-Balance Add Liquidity (Optimized ZAP) ---
@@ -756,6 +2225,11 @@ pub trait Utils: crate::storage::Storage {
     /// This does: compute optimal swap → swap → add_liquidity (once)
     ///
     /// Saves ~400k gas by avoiding the second add_liquidity call
+    ///
+    /// After `LpToCaller` the minted LP is sent straight to the caller instead of the
+    /// vault. The pairs mint only to the sender, so this is a post-transfer; the LP of
+    /// every such add is summed in the vault and checked against `min_amount_out` once
+    /// the path has run.
     fn pre_balance_and_add_liquidity(
         &self,
         vault: &mut Vault<Self::Api>,
@@ -763,13 +2237,34 @@ pub trait Utils: crate::storage::Storage {
         payments: &ManagedVec<Payment<Self::Api>>,
         token_out: &TokenId<Self::Api>,
     ) {
-        let min = BigUint::from(MIN_INTERNAL_OUTPUT);
+        // Pool resolution reads both inputs, so their count is checked before anything
+        require!(payments.len() == 2, ERR_ZAP_TWO_INPUTS);
+
+        let min = self.get_internal_min_output(ActionCategory::Liquidity);
+        let swap_min = self.get_internal_min_output(ActionCategory::Swap);
+        let gas = self.get_sub_call_gas(&instr.action);
 
         // 1. Get pool info
         let pool_address = self.resolve_pool_address(&instr.action, instr, payments);
-        let (reserve_first, reserve_second) = self.get_reserves(&instr.action, &pool_address);
+        require!(!pool_address.is_zero(), ERR_POOL_ADDRESS_UNRESOLVED);
+        let cache_key = self.reserve_cache_key(&instr.action, &pool_address);
+        let (reserve_first, reserve_second) = match vault.cached_reserves(&cache_key) {
+            Some(reserves) => reserves,
+            None => self.get_reserves(&instr.action, &pool_address),
+        };
         let pool_first_token = self.get_pool_first_token(&instr.action, &pool_address);
         let pool_second_token = self.get_pool_second_token(&instr.action, &pool_address);
+        if let Some(zap_ratio) = vault.take_zap_ratio() {
+            self.require_pool_ratio(&reserve_first, &reserve_second, &zap_ratio);
+        }
+        if instr.action == types::ActionType::XExchangeAddLiquidity {
+            self.require_reserves_match_oracle(
+                &pool_address,
+                &pool_first_token,
+                &reserve_first,
+                &reserve_second,
+            );
+        }
         let (fee_num, special_fee_num, lp_fee_num, fee_denom) =
             self.get_fee(&instr.action, &pool_address);
         let fee_mode = match &instr.action {
@@ -779,7 +2274,29 @@ pub trait Utils: crate::storage::Storage {
             _ => zap::FeeMode::OnInput { special_fee_num },
         };
 
-        // 2. Get current balances (payments are always in first, second order)
+        // 2. Line the inputs up with the pool's (first, second) order, so the ZAP never
+        // balances against the wrong reserves
+        let reordered_payments;
+        let payments = {
+            let input_first = self.to_pool_token(&payments.get(0).token_identifier);
+            let input_second = self.to_pool_token(&payments.get(1).token_identifier);
+            if input_first == pool_first_token && input_second == pool_second_token {
+                payments
+            } else if input_first == pool_second_token && input_second == pool_first_token {
+                reordered_payments =
+                    ManagedVec::from_iter([payments.get(1).clone(), payments.get(0).clone()]);
+                &reordered_payments
+            } else {
+                sc_panic!(
+                    "E: ZAP inputs {} and {} do not match pool tokens {} and {}",
+                    input_first,
+                    input_second,
+                    pool_first_token,
+                    pool_second_token
+                );
+            }
+        };
+
         let balance_first = payments.get(0).amount.as_big_uint().clone();
         let balance_second = payments.get(1).amount.as_big_uint().clone();
         let token_first = payments.get(0).token_identifier.clone();
@@ -797,7 +2314,21 @@ pub trait Utils: crate::storage::Storage {
         );
 
         // 4. Execute swap if needed and compute final balances
-        let (final_first, final_second) = if swap_amount > 0u64 {
+        // Track the pool reserves our own operations leave behind for later ZAPs
+        let mut cached_first = reserve_first.clone();
+        let mut cached_second = reserve_second.clone();
+        let mut reserves_exact = true;
+
+        // A swap below the configured minimum costs a call for negligible balance gain
+        let swap_token = if swap_from_first {
+            &token_first
+        } else {
+            &token_second
+        };
+        let worth_swapping =
+            swap_amount > 0u64 && swap_amount >= self.min_zap_swap(swap_token).get();
+
+        let (final_first, final_second) = if worth_swapping {
             if swap_from_first {
                 // Swap some first token for second
                 let swap_payment = ManagedVec::from_single_item(Payment::new(
@@ -809,30 +2340,47 @@ pub trait Utils: crate::storage::Storage {
                 let swap_result = match &instr.action {
                     types::ActionType::XExchangeAddLiquidity => self
                         .proxy_call(pool_address.clone())
-                        .xexchange(&pool_second_token, min.clone())
+                        .xexchange(&pool_second_token, swap_min.clone())
                         .payment(&swap_payment)
-                        .returns(ReturnsBackTransfersReset)
-                        .sync_call(),
+                        .gas(gas)
+                        .returns(ReturnsHandledOrError::new().returns(ReturnsBackTransfersReset))
+                        .sync_call_fallible(),
                     types::ActionType::OneDexAddLiquidity(_) => {
                         let mut path = MultiValueEncoded::new();
                         path.push(pool_first_token.clone());
                         path.push(pool_second_token.clone());
                         self.proxy_call(ManagedAddress::from(ONE_DEX_ROUTER))
-                            .onedex(min.clone(), false, path)
+                            .onedex(swap_min.clone(), false, path)
                             .payment(&swap_payment)
-                            .returns(ReturnsBackTransfersReset)
-                            .sync_call()
+                            .gas(gas)
+                            .returns(
+                                ReturnsHandledOrError::new().returns(ReturnsBackTransfersReset),
+                            )
+                            .sync_call_fallible()
                     }
                     types::ActionType::JexAddLiquidity => self
                         .proxy_call(pool_address.clone())
-                        .jex(min.clone())
+                        .jex(swap_min.clone())
                         .payment(&swap_payment)
-                        .returns(ReturnsBackTransfersReset)
-                        .sync_call(),
+                        .gas(gas)
+                        .returns(ReturnsHandledOrError::new().returns(ReturnsBackTransfersReset))
+                        .sync_call_fallible(),
                     _ => return,
                 };
 
-                let received = swap_result.to_single_esdt().amount;
+                let received = self
+                    .expect_sub_call(&instr.action, swap_result)
+                    .to_single_esdt()
+                    .amount;
+                reserves_exact = zap::apply_swap_to_reserves(
+                    &swap_amount,
+                    &received,
+                    &mut cached_first,
+                    &mut cached_second,
+                    fee_num,
+                    fee_denom,
+                    fee_mode,
+                );
                 (&balance_first - &swap_amount, &balance_second + &received)
             } else {
                 // Swap some second token for first
@@ -845,38 +2393,58 @@ pub trait Utils: crate::storage::Storage {
                 let swap_result = match &instr.action {
                     types::ActionType::XExchangeAddLiquidity => self
                         .proxy_call(pool_address.clone())
-                        .xexchange(&pool_first_token, min.clone())
+                        .xexchange(&pool_first_token, swap_min.clone())
                         .payment(&swap_payment)
-                        .returns(ReturnsBackTransfersReset)
-                        .sync_call(),
+                        .gas(gas)
+                        .returns(ReturnsHandledOrError::new().returns(ReturnsBackTransfersReset))
+                        .sync_call_fallible(),
                     types::ActionType::OneDexAddLiquidity(_) => {
                         let mut path = MultiValueEncoded::new();
                         path.push(pool_second_token.clone());
                         path.push(pool_first_token.clone());
                         self.proxy_call(ManagedAddress::from(ONE_DEX_ROUTER))
-                            .onedex(min.clone(), false, path)
+                            .onedex(swap_min.clone(), false, path)
                             .payment(&swap_payment)
-                            .returns(ReturnsBackTransfersReset)
-                            .sync_call()
+                            .gas(gas)
+                            .returns(
+                                ReturnsHandledOrError::new().returns(ReturnsBackTransfersReset),
+                            )
+                            .sync_call_fallible()
                     }
                     types::ActionType::JexAddLiquidity => self
                         .proxy_call(pool_address.clone())
-                        .jex(min.clone())
+                        .jex(swap_min.clone())
                         .payment(&swap_payment)
-                        .returns(ReturnsBackTransfersReset)
-                        .sync_call(),
+                        .gas(gas)
+                        .returns(ReturnsHandledOrError::new().returns(ReturnsBackTransfersReset))
+                        .sync_call_fallible(),
                     _ => return,
                 };
 
-                let received = swap_result.to_single_esdt().amount;
+                let received = self
+                    .expect_sub_call(&instr.action, swap_result)
+                    .to_single_esdt()
+                    .amount;
+                reserves_exact = zap::apply_swap_to_reserves(
+                    &swap_amount,
+                    &received,
+                    &mut cached_second,
+                    &mut cached_first,
+                    fee_num,
+                    fee_denom,
+                    fee_mode,
+                );
                 (&balance_first + &received, &balance_second - &swap_amount)
             }
         } else {
-            // Already balanced, no swap needed
+            // Already balanced (or close enough), no swap needed
             (balance_first, balance_second)
         };
 
         // 5. Create balanced payments for add_liquidity (always in first, second order)
+        self.require_zap_sides(&token_first, &final_first, &token_second, &final_second);
+        let mut added_first = final_first.clone();
+        let mut added_second = final_second.clone();
         let mut lp_payments = ManagedVec::new();
         lp_payments.push(Payment::new(
             token_first.clone(),
@@ -891,22 +2459,315 @@ pub trait Utils: crate::storage::Storage {
 
         // 6. Execute SINGLE add_liquidity
         let lp_result = self
-            .proxy_call(pool_address)
+            .proxy_call(pool_address.clone())
             .xdex_add_liquidity(min.clone(), min)
             .payment(&lp_payments)
-            .returns(ReturnsBackTransfersReset)
-            .sync_call();
+            .gas(gas)
+            .returns(ReturnsHandledOrError::new().returns(ReturnsBackTransfersReset))
+            .sync_call_fallible();
 
         // 7. Deposit LP tokens to vault, accumulate dust to admin fees
         // LP token is always token_out since add_liquidity is always the last instruction
+        let lp_result = self.expect_sub_call(&instr.action, lp_result);
+        let lp_to_caller = vault.take_lp_to_caller();
+        let mut lp_minted = BigUint::zero();
         for payment in lp_result.into_payment_vec().iter() {
             if payment.token_identifier == *token_out {
-                vault.deposit(&payment.token_identifier, &payment.amount);
+                lp_minted += payment.amount.as_big_uint();
+                if !lp_to_caller {
+                    vault.deposit(&payment.token_identifier, &payment.amount);
+                }
             } else {
-                // Dust from LP creation goes to admin fees
-                self.accumulate_admin_fee(&payment.token_identifier, payment.amount.as_big_uint());
+                // Refunded dust never entered the pool reserves
+                if payment.token_identifier == token_first {
+                    added_first -= payment.amount.as_big_uint();
+                } else if payment.token_identifier == token_second {
+                    added_second -= payment.amount.as_big_uint();
+                }
+                // Dust from LP creation goes to admin fees, through the vault like any fee
+                vault.deposit(&payment.token_identifier, &payment.amount);
+                self.debit_fee(
+                    vault,
+                    &payment.token_identifier,
+                    payment.amount.as_big_uint(),
+                    0,
+                    &BigUint::zero(),
+                );
+            }
+        }
+
+        if lp_minted == 0u64 {
+            let action_byte = instr.action.compact().to_u8();
+            sc_panic!(
+                "E: Action {} returned no LP token {}",
+                action_byte,
+                token_out
+            );
+        }
+
+        // CPMM pools only take per-token minimums, so the LP minimum is checked here
+        if let Some(min_lp_out) = vault.take_min_lp_out() {
+            require!(
+                lp_minted >= min_lp_out,
+                "E: LP output below minimum: have {}, need {}",
+                lp_minted,
+                min_lp_out
+            );
+        }
+
+        if let Some(min_lp_value) = vault.take_min_lp_value() {
+            self.require_lp_value(
+                &pool_address,
+                &pool_first_token,
+                &pool_second_token,
+                &lp_minted,
+                &min_lp_value,
+            );
+        }
+
+        // LP skips the vault; `execute_path` only allows this on fee-free trades
+        if lp_to_caller {
+            let lp_amount = lp_minted
+                .clone()
+                .into_non_zero()
+                .unwrap_or_else(|| sc_panic!(ERR_LP_TO_CALLER_NOTHING_MINTED));
+            vault.deliver_lp(&lp_minted);
+            self.tx()
+                .to(self.blockchain().get_caller())
+                .payment(Payment::new(token_out.clone(), 0u64, lp_amount))
+                .transfer();
+        }
+
+        self.add_liquidity_event(
+            &pool_address,
+            &token_first,
+            &added_first,
+            &token_second,
+            &added_second,
+            token_out,
+            &lp_minted,
+        );
+
+        // 8. Remember the reserves for a later ZAP into the same pool in this transaction
+        if reserves_exact {
+            vault.cache_reserves(
+                &cache_key,
+                &(cached_first + added_first, cached_second + added_second),
+            );
+        } else {
+            vault.invalidate_reserves_of(&cache_key);
+        }
+    }
+
+    /// Reject LP shares worth less than the minimum in the reference token
+    /// A share holds both reserves and its two halves are worth the same at the pool
+    /// price, so its value is `2 * lp * reserve_ref / supply` at the post-add reserves
+    fn require_lp_value(
+        &self,
+        pool_address: &ManagedAddress<Self::Api>,
+        pool_first_token: &TokenIdentifier<Self::Api>,
+        pool_second_token: &TokenIdentifier<Self::Api>,
+        lp_minted: &BigUint<Self::Api>,
+        min_lp_value: &types::LpValueMin<Self::Api>,
+    ) {
+        let (reserve_first, reserve_second, supply) =
+            self.get_xexchange_reserves_and_supply(pool_address);
+        let token = &min_lp_value.token;
+        let min_value = &min_lp_value.amount;
+        let reference = self.to_pool_token(&TokenId::from(token.as_managed_buffer().clone()));
+        let reserve_ref = if reference == *pool_first_token {
+            reserve_first
+        } else if reference == *pool_second_token {
+            reserve_second
+        } else {
+            sc_panic!(
+                "E: LP value token {} is not in pool tokens {} and {}",
+                token,
+                pool_first_token,
+                pool_second_token
+            );
+        };
+
+        let value = if supply == 0u64 {
+            BigUint::zero()
+        } else {
+            lp_minted * &reserve_ref * 2u64 / supply
+        };
+        require!(
+            value >= *min_value,
+            "E: LP value below minimum: have {} {}, need {}",
+            value,
+            token,
+            min_value
+        );
+    }
+
+    /// Reject a ZAP add whose balanced amounts left either side empty
+    /// CPMM pools take no single-sided adds, so an emptied leg cannot be added
+    fn require_zap_sides(
+        &self,
+        token_first: &TokenId<Self::Api>,
+        final_first: &BigUint<Self::Api>,
+        token_second: &TokenId<Self::Api>,
+        final_second: &BigUint<Self::Api>,
+    ) {
+        require!(
+            *final_first > 0u64 && *final_second > 0u64,
+            "E: ZAP left an empty side: {} {}, {} {}",
+            token_first,
+            final_first,
+            token_second,
+            final_second
+        );
+    }
+
+    /// Reject a ZAP into a pool whose price ratio is off the caller's expectation
+    /// Ratio = second reserve per first reserve, scaled by RATIO_PRECISION
+    fn require_pool_ratio(
+        &self,
+        reserve_first: &BigUint<Self::Api>,
+        reserve_second: &BigUint<Self::Api>,
+        zap_ratio: &types::ZapRatio<Self::Api>,
+    ) {
+        require!(
+            *reserve_first > 0u64,
+            "E: Pool ratio unavailable: empty reserves"
+        );
+        let expected_ratio = &zap_ratio.ratio;
+        let ratio = reserve_second * RATIO_PRECISION / reserve_first;
+        let deviation = if ratio > *expected_ratio {
+            &ratio - expected_ratio
+        } else {
+            expected_ratio - &ratio
+        };
+        let tolerance = expected_ratio * zap_ratio.tolerance_ppm / 1_000_000u64;
+        require!(
+            deviation <= tolerance,
+            "E: Pool ratio off expected: have {}, expected {}",
+            ratio,
+            expected_ratio
+        );
+    }
+
+    /// Reject a ZAP whose pool reserves disagree with the xExchange TWAP beyond tolerance
+    ///
+    /// Quotes the whole first reserve through the oracle: an unmanipulated pool prices it
+    /// close to its own second reserve. Skipped when no oracle is configured.
+    fn require_reserves_match_oracle(
+        &self,
+        pool_address: &ManagedAddress,
+        first_token: &TokenIdentifier<Self::Api>,
+        reserve_first: &BigUint<Self::Api>,
+        reserve_second: &BigUint<Self::Api>,
+    ) {
+        if self.zap_oracle_address().is_empty() {
+            return;
+        }
+        let oracle = self.zap_oracle_address().get();
+
+        let twap_second = self
+            .proxy_call(oracle)
+            .xexchange_safe_price(
+                pool_address,
+                EsdtTokenPayment::new(first_token.clone(), 0, reserve_first.clone()),
+            )
+            .returns(ReturnsResult)
+            .sync_call_readonly()
+            .amount;
+
+        let deviation = if twap_second > *reserve_second {
+            &twap_second - reserve_second
+        } else {
+            reserve_second - &twap_second
+        };
+        let tolerance = reserve_second * self.zap_oracle_tolerance_ppm().get() / 1_000_000u64;
+        require!(
+            deviation <= tolerance,
+            "E: ZAP reserves deviate from oracle: spot {}, twap {}",
+            reserve_second,
+            twap_second
+        );
+    }
+
+    /// Reserve cache key: the pool address, plus the pair id for the shared OneDex router
+    fn reserve_cache_key(
+        &self,
+        action: &types::ActionType<Self::Api>,
+        pool_address: &ManagedAddress,
+    ) -> ManagedBuffer {
+        let mut key = pool_address.as_managed_buffer().clone();
+        if let types::ActionType::OneDexAddLiquidity(pair_id) = action {
+            key.append_bytes(&(*pair_id as u64).to_be_bytes());
+        }
+        key
+    }
+
+    // --- Remove Liquidity to Single Token (ZAP-out) ---
+
+    /// Remove xExchange liquidity and swap the non-target leg back through the same pool,
+    /// so only the target token lands in the vault
+    fn remove_liquidity_single(
+        &self,
+        vault: &mut Vault<Self::Api>,
+        instr: &Instruction<Self::Api>,
+        payments: &ManagedVec<Payment<Self::Api>>,
+        target: &TokenIdentifier<Self::Api>,
+    ) {
+        // Modifiers of a multi-output call do not apply to the single target output, but
+        // are consumed so they cannot leak into a later instruction
+        vault.take_expected_outputs();
+        vault.take_unwrap_outputs();
+        vault.take_allow_failure();
+
+        let min = self.get_internal_min_output(ActionCategory::Liquidity);
+        let swap_min = self.get_internal_min_output(ActionCategory::Swap);
+        let pool_address = instr.address.clone().unwrap_or_else(ManagedAddress::zero);
+        require!(!pool_address.is_zero(), ERR_POOL_ADDRESS_UNRESOLVED);
+        let target_id = TokenId::from(target.as_managed_buffer().clone());
+
+        // 1. Burn LP into both legs
+        let gas = self.get_sub_call_gas(&instr.action);
+        let removed = self
+            .proxy_call(pool_address.clone())
+            .xdex_remove_liquidity(min.clone(), min.clone())
+            .payment(payments)
+            .gas(gas)
+            .returns(ReturnsHandledOrError::new().returns(ReturnsBackTransfersReset))
+            .sync_call_fallible();
+        let removed = self
+            .expect_sub_call(&instr.action, removed)
+            .into_payment_vec();
+        self.require_result_count(&instr.action, removed.len());
+
+        // 2. Keep the target leg, swap the other one into the target
+        let mut total = BigUint::zero();
+        for leg in removed.iter() {
+            if leg.token_identifier == target_id {
+                total += leg.amount.as_big_uint();
+                continue;
             }
+
+            let gas = self.get_sub_call_gas(&instr.action);
+            let swapped = self
+                .proxy_call(pool_address.clone())
+                .xexchange(target, swap_min.clone())
+                .payment(leg.clone())
+                .gas(gas)
+                .returns(ReturnsHandledOrError::new().returns(ReturnsBackTransfersReset))
+                .sync_call_fallible();
+            total += self
+                .expect_sub_call(&instr.action, swapped)
+                .to_single_esdt()
+                .amount;
         }
+
+        // 3. Single output, so it can be chained with PrevAmount
+        let total = total
+            .into_non_zero()
+            .unwrap_or_else(|| sc_panic!(ERR_ZAP_OUT_NO_OUTPUT));
+        let output = Payment::new(target_id, 0u64, total);
+        vault.set_prev_result(&output);
+        vault.deposit(&output.token_identifier, &output.amount);
     }
 
     /// Resolve pool address for ZAP operations based on action type.
@@ -944,7 +2805,7 @@ pub trait Utils: crate::storage::Storage {
             }
             types::ActionType::JexAddLiquidity => {
                 // Jex requires explicit address from instruction
-                instr.address.clone().unwrap()
+                instr.address.clone().unwrap_or_else(ManagedAddress::zero)
             }
             _ => instr.address.clone().unwrap_or_else(ManagedAddress::zero),
         }
@@ -3,6 +3,7 @@ multiversx_sc::imports!();
 use crate::errors::{
     ERR_INSUFFICIENT_BALANCE_PREFIX, ERR_ONLY_FUNGIBLE_PREFIX, ERR_TOKEN_NOT_FOUND_PREFIX,
 };
+use crate::types::{LpValueMin, ZapRatio};
 use multiversx_sc::api::VMApi;
 
 /// In-memory vault for tracking intermediate token balances during aggregation
@@ -11,6 +12,36 @@ pub struct Vault<M: VMApi> {
     balances: ManagedMapEncoded<M, TokenId<M>, BigUint<M>>,
     tokens: ManagedVec<M, TokenId<M>>,
     prev_result: Option<Payment<M>>,
+    output_delivered: bool,
+    min_lp_out: Option<BigUint<M>>,
+    zap_ratio: Option<ZapRatio<M>>,
+    /// Minimum reserve value of the LP the next xExchange add must mint
+    min_lp_value: Option<LpValueMin<M>>,
+    /// Deposit the WEGLD outputs of the next call as EGLD
+    unwrap_outputs: bool,
+    /// Refund the next swap's input instead of reverting when its pool call fails
+    allow_failure: bool,
+    /// Revert if any payment token is left in the vault once the path has run
+    require_full_input: bool,
+    /// Send the LP of the next ZAP add straight to the caller
+    lp_to_caller: bool,
+    /// LP already sent to the caller by `LpToCaller` adds, checked against the minimum
+    delivered_lp: BigUint<M>,
+    /// Inputs of skipped swaps, owed to the recipient and kept out of the balances
+    refunds: ManagedVec<M, Payment<M>>,
+    /// Output tokens declared for the next multi-output call
+    expected_outputs: Option<ManagedVec<M, TokenId<M>>>,
+    /// Per-token results of the last call with declared outputs, consumed by PrevAmount
+    prev_results_by_token: ManagedMapEncoded<M, TokenId<M>, BigUint<M>>,
+    /// Pool reserves as left behind by our own ZAP operations, keyed by pool
+    reserve_cache: ManagedMapEncoded<M, ManagedBuffer<M>, (BigUint<M>, BigUint<M>)>,
+}
+
+/// Vault and contract balances of the fee-on-transfer tokens before an instruction
+pub struct FeeOnTransferSnapshot<M: VMApi> {
+    pub tokens: ManagedVec<M, TokenId<M>>,
+    pub vault_balances: ManagedVec<M, BigUint<M>>,
+    pub sc_balances: ManagedVec<M, BigUint<M>>,
 }
 
 impl<M: VMApi> Vault<M> {
@@ -20,23 +51,243 @@ impl<M: VMApi> Vault<M> {
             balances: ManagedMapEncoded::new(),
             tokens: ManagedVec::new(),
             prev_result: None,
+            output_delivered: false,
+            min_lp_out: None,
+            zap_ratio: None,
+            min_lp_value: None,
+            unwrap_outputs: false,
+            allow_failure: false,
+            require_full_input: false,
+            lp_to_caller: false,
+            delivered_lp: BigUint::zero(),
+            refunds: ManagedVec::new(),
+            expected_outputs: None,
+            prev_results_by_token: ManagedMapEncoded::new(),
+            reserve_cache: ManagedMapEncoded::new(),
         }
     }
 
-    pub fn get_prev_result(&self) -> &Option<Payment<M>> {
+    /// Read the previous result without consuming it
+    pub fn peek_prev_result(&self) -> &Option<Payment<M>> {
         &self.prev_result
     }
 
+    /// Consume the previous result
+    ///
+    /// A previous result is spent by exactly one `PrevAmount` input: a second one
+    /// without a new producing instruction in between finds nothing and reverts
+    /// instead of reusing a stale amount.
+    pub fn take_prev_result(&mut self) -> Option<Payment<M>> {
+        self.prev_result.take()
+    }
+
     pub fn set_prev_result(&mut self, payment: &Payment<M>) {
         self.prev_result = Some(payment.clone());
     }
 
-    /// Initialize vault from a single ESDT payment
+    /// Drop the previous result so a later PrevAmount cannot consume it
+    pub fn clear_prev_result(&mut self) {
+        self.prev_result = None;
+        self.prev_results_by_token = ManagedMapEncoded::new();
+    }
+
+    /// Declare the output tokens of the next multi-output call; replaces any
+    /// per-token results still pending from an earlier declaration
+    pub fn set_expected_outputs(&mut self, tokens: &ManagedVec<M, TokenIdentifier<M>>) {
+        let mut expected = ManagedVec::new();
+        for token in tokens.iter() {
+            expected.push(TokenId::from(token.as_managed_buffer().clone()));
+        }
+        self.expected_outputs = Some(expected);
+        self.prev_results_by_token = ManagedMapEncoded::new();
+    }
+
+    /// Consume the declared output tokens, if any
+    pub fn take_expected_outputs(&mut self) -> Option<ManagedVec<M, TokenId<M>>> {
+        self.expected_outputs.take()
+    }
+
+    /// Unwrap the WEGLD outputs of the next call before they are deposited
+    pub fn set_unwrap_outputs(&mut self) {
+        self.unwrap_outputs = true;
+    }
+
+    /// Consume the pending unwrap request
+    pub fn take_unwrap_outputs(&mut self) -> bool {
+        core::mem::take(&mut self.unwrap_outputs)
+    }
+
+    /// Let the next swap fail without reverting the aggregation
+    pub fn set_allow_failure(&mut self) {
+        self.allow_failure = true;
+    }
+
+    pub fn is_failure_allowed(&self) -> bool {
+        self.allow_failure
+    }
+
+    /// Consume the pending failure allowance
+    pub fn take_allow_failure(&mut self) -> bool {
+        core::mem::take(&mut self.allow_failure)
+    }
+
+    /// Require the path to route every payment token in full
+    pub fn set_require_full_input(&mut self) {
+        self.require_full_input = true;
+    }
+
+    pub fn is_full_input_required(&self) -> bool {
+        self.require_full_input
+    }
+
+    /// Deliver the LP of the next ZAP add to the caller instead of the vault
+    pub fn set_lp_to_caller(&mut self) {
+        self.lp_to_caller = true;
+    }
+
+    pub fn is_lp_to_caller(&self) -> bool {
+        self.lp_to_caller
+    }
+
+    /// Consume the pending LP delivery request
+    pub fn take_lp_to_caller(&mut self) -> bool {
+        core::mem::take(&mut self.lp_to_caller)
+    }
+
+    /// Set aside an input of a skipped swap for the recipient
+    pub fn add_refund(&mut self, payment: &Payment<M>) {
+        self.refunds.push(payment.clone());
+    }
+
+    /// Total of `token` set aside as refunds so far
+    pub fn refunded(&self, token: &TokenId<M>) -> BigUint<M> {
+        let mut total = BigUint::zero();
+        for refund in self.refunds.iter() {
+            if refund.token_identifier == *token {
+                total += refund.amount.as_big_uint();
+            }
+        }
+        total
+    }
+
+    /// Consume the refunds owed to the recipient
+    pub fn take_refunds(&mut self) -> ManagedVec<M, Payment<M>> {
+        core::mem::take(&mut self.refunds)
+    }
+
+    pub fn add_prev_result_for(&mut self, token: &TokenId<M>, amount: &BigUint<M>) {
+        let current = self.prev_results_by_token.get(token);
+        self.prev_results_by_token.put(token, &(current + amount));
+    }
+
+    pub fn has_prev_result_for(&self, token: &TokenId<M>) -> bool {
+        self.prev_results_by_token.contains(token)
+    }
+
+    /// Drop every pending per-token result, keeping the single previous result
+    pub fn clear_prev_results_by_token(&mut self) {
+        self.prev_results_by_token = ManagedMapEncoded::new();
+    }
+
+    /// Consume the per-token result of a token (zero if none is pending)
+    pub fn take_prev_result_for(&mut self, token: &TokenId<M>) -> BigUint<M> {
+        let amount = self.prev_results_by_token.get(token);
+        self.prev_results_by_token.remove(token);
+        amount
+    }
+
+    /// Record that the output was sent straight to the caller, bypassing the vault
+    pub fn mark_output_delivered(&mut self) {
+        self.output_delivered = true;
+    }
+
+    pub fn is_output_delivered(&self) -> bool {
+        self.output_delivered
+    }
+
+    /// Record LP sent straight to the caller, summed over the adds of a split path
+    pub fn deliver_lp(&mut self, amount: &BigUint<M>) {
+        self.output_delivered = true;
+        self.delivered_lp += amount;
+    }
+
+    pub fn delivered_lp(&self) -> &BigUint<M> {
+        &self.delivered_lp
+    }
+
+    /// Set the minimum LP amount the next add-liquidity must mint
+    pub fn set_min_lp_out(&mut self, min_lp_out: &BigUint<M>) {
+        self.min_lp_out = Some(min_lp_out.clone());
+    }
+
+    /// Consume the pending LP minimum, if any
+    pub fn take_min_lp_out(&mut self) -> Option<BigUint<M>> {
+        self.min_lp_out.take()
+    }
+
+    /// Set the pool ratio bound the next ZAP add must satisfy
+    pub fn set_zap_ratio(&mut self, zap_ratio: &ZapRatio<M>) {
+        self.zap_ratio = Some(zap_ratio.clone());
+    }
+
+    /// Consume the pending ZAP ratio bound, if any
+    pub fn take_zap_ratio(&mut self) -> Option<ZapRatio<M>> {
+        self.zap_ratio.take()
+    }
+
+    /// Set the minimum value the LP of the next xExchange add must be worth
+    pub fn set_min_lp_value(&mut self, min_lp_value: &LpValueMin<M>) {
+        self.min_lp_value = Some(min_lp_value.clone());
+    }
+
+    pub fn has_min_lp_value(&self) -> bool {
+        self.min_lp_value.is_some()
+    }
+
+    /// Consume the pending LP value minimum, if any
+    pub fn take_min_lp_value(&mut self) -> Option<LpValueMin<M>> {
+        self.min_lp_value.take()
+    }
+
+    /// Reserves cached for a pool, if our own operations are the last to have touched it
+    pub fn cached_reserves(&self, pool_key: &ManagedBuffer<M>) -> Option<(BigUint<M>, BigUint<M>)> {
+        if self.reserve_cache.contains(pool_key) {
+            Some(self.reserve_cache.get(pool_key))
+        } else {
+            None
+        }
+    }
+
+    pub fn cache_reserves(
+        &mut self,
+        pool_key: &ManagedBuffer<M>,
+        reserves: &(BigUint<M>, BigUint<M>),
+    ) {
+        self.reserve_cache.put(pool_key, reserves);
+    }
+
+    /// Forget the cached reserves of one pool
+    pub fn invalidate_reserves_of(&mut self, pool_key: &ManagedBuffer<M>) {
+        self.reserve_cache.remove(pool_key);
+    }
+
+    /// Forget all cached reserves (any call outside a ZAP may have moved them)
+    pub fn invalidate_reserves(&mut self) {
+        self.reserve_cache = ManagedMapEncoded::new();
+    }
+
+    /// Initialize vault from every incoming payment
+    ///
+    /// `call_value().all()` already lists native EGLD next to the ESDTs, whether it came
+    /// as the call value or as an `EGLD-000000` entry of a multi-transfer, so EGLD is
+    /// keyed like every other EGLD balance in the vault. Repeated tokens are merged by
+    /// `deposit`.
     pub fn from_payment(payments: &PaymentVec<M>) -> Self {
         let mut vault = Self::new();
         for payment in payments.iter() {
             if payment.token_nonce != 0 {
-                let mut buffer = ManagedBufferBuilder::<M>::new_from_slice(ERR_ONLY_FUNGIBLE_PREFIX);
+                let mut buffer =
+                    ManagedBufferBuilder::<M>::new_from_slice(ERR_ONLY_FUNGIBLE_PREFIX);
                 buffer.append_managed_buffer(payment.token_identifier.as_managed_buffer());
                 let msg = buffer.into_managed_buffer();
                 M::error_api_impl().signal_error_from_buffer(msg.get_handle());
@@ -57,6 +308,20 @@ impl<M: VMApi> Vault<M> {
         self.balances.get(token)
     }
 
+    /// Number of distinct tokens currently held
+    pub fn len(&self) -> usize {
+        self.tokens.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    /// Check if the vault currently holds a token
+    pub fn contains(&self, token: &TokenId<M>) -> bool {
+        self.balances.contains(token)
+    }
+
     /// Add amount to vault (creates entry if token not present)
     pub fn deposit(&mut self, token: &TokenId<M>, amount: &NonZeroBigUint<M>) {
         if !self.balances.contains(token) {
@@ -96,6 +361,15 @@ impl<M: VMApi> Vault<M> {
         amount.clone()
     }
 
+    /// Withdraw the fixed amount, or the entire balance when it is smaller
+    pub fn withdraw_up_to(&mut self, token: &TokenId<M>, amount: &BigUint<M>) -> BigUint<M> {
+        if self.contains(token) && &self.balance_of(token) < amount {
+            self.withdraw_all(token)
+        } else {
+            self.withdraw(token, amount)
+        }
+    }
+
     /// Withdraw entire balance of a token (panics if not found)
     pub fn withdraw_all(&mut self, token: &TokenId<M>) -> BigUint<M> {
         let amount = self.balance_of(token);
@@ -143,7 +417,7 @@ impl<M: VMApi> Vault<M> {
     pub fn ppm_of(&self, token: &TokenId<M>, ppm: &u32) -> BigUint<M> {
         // Validate PPM range (should be caught earlier, but defense in depth)
         if *ppm > 1_000_000 {
-            M::error_api_impl().signal_error(b"PPM exceeds 1,000,000 (100%)");
+            M::error_api_impl().signal_error(b"E: PPM exceeds 1,000,000 (100%)");
         }
         let balance = self.balance_of(token);
         (&balance * *ppm) / 1_000_000u64
@@ -164,12 +438,45 @@ impl<M: VMApi> Vault<M> {
         payments
     }
 
+    /// Same as `get_all_payments`, ordered by token identifier bytes so the
+    /// result does not depend on the order tokens entered or left the vault
+    pub fn get_all_payments_sorted(&self) -> ManagedVec<M, Payment<M>> {
+        let mut payments = self.get_all_payments();
+
+        // Insertion sort - vaults only ever hold a handful of tokens
+        for i in 1..payments.len() {
+            let mut j = i;
+            while j > 0
+                && token_id_lt(
+                    &payments.get(j).token_identifier,
+                    &payments.get(j - 1).token_identifier,
+                )
+            {
+                let lower = payments.get(j).clone();
+                let upper = payments.get(j - 1).clone();
+                let _ = payments.set(j - 1, lower);
+                let _ = payments.set(j, upper);
+                j -= 1;
+            }
+        }
+        payments
+    }
+
     /// Check if vault has at least the minimum amount of a token
     pub fn has_minimum(&self, token: &TokenId<M>, min_amount: &BigUint<M>) -> bool {
         self.balance_of(token) >= *min_amount
     }
 }
 
+/// Byte-wise ordering of token identifiers (identifiers are at most 17 bytes)
+fn token_id_lt<M: VMApi>(a: &TokenId<M>, b: &TokenId<M>) -> bool {
+    let mut a_bytes = [0u8; 32];
+    let mut b_bytes = [0u8; 32];
+    let a_slice = a.as_managed_buffer().load_to_byte_array(&mut a_bytes);
+    let b_slice = b.as_managed_buffer().load_to_byte_array(&mut b_bytes);
+    a_slice < b_slice
+}
+
 impl<M: VMApi> Default for Vault<M> {
     fn default() -> Self {
         Self::new()
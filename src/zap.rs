@@ -59,8 +59,12 @@ pub fn simulate_swap_output<M: ManagedTypeApi>(
         return (BigUint::zero(), BigUint::zero(), BigUint::zero());
     }
 
-    // Safety check: fee_num should not exceed fee_denom
-    if fee_num > fee_denom {
+    // Safety check: no fee part may exceed fee_denom, or the fee factors below underflow
+    let split_fee_num = match fee_mode {
+        FeeMode::OnInput { special_fee_num } => special_fee_num,
+        FeeMode::OnOutput { lp_fee_num } => lp_fee_num,
+    };
+    if fee_denom == 0 || fee_num > fee_denom || split_fee_num > fee_denom {
         return (BigUint::zero(), BigUint::zero(), BigUint::zero());
     }
 
@@ -108,6 +112,36 @@ pub fn simulate_swap_output<M: ManagedTypeApi>(
     }
 }
 
+/// Apply one of our own swaps to a copy of the pool reserves
+///
+/// The deltas come from `simulate_swap_output`. They are only trusted when the simulated
+/// output matches what the pool actually paid out; otherwise returns false and the
+/// reserves must be re-read from the pool.
+pub fn apply_swap_to_reserves<M: ManagedTypeApi>(
+    amount_in: &BigUint<M>,
+    received: &BigUint<M>,
+    reserve_in: &mut BigUint<M>,
+    reserve_out: &mut BigUint<M>,
+    fee_num: u64,
+    fee_denom: u64,
+    fee_mode: FeeMode,
+) -> bool {
+    let (output, amount_out_leaving, amount_in_to_reserves) = simulate_swap_output(
+        amount_in,
+        reserve_in,
+        reserve_out,
+        fee_num,
+        fee_denom,
+        fee_mode,
+    );
+    if output != *received || amount_out_leaving > *reserve_out {
+        return false;
+    }
+    *reserve_in += amount_in_to_reserves;
+    *reserve_out -= amount_out_leaving;
+    true
+}
+
 /// Given two token balances and pool state, compute optimal swap to balance them
 /// for add_liquidity. This is called BEFORE add_liquidity to pre-balance tokens.
 ///
@@ -125,7 +159,6 @@ pub fn simulate_swap_output<M: ManagedTypeApi>(
 /// - If swap_from_first is true: swap `swap_amount` of first token for second
 /// - If swap_from_first is false: swap `swap_amount` of second token for first
 /// - If swap_amount is 0: tokens are already perfectly balanced
-
 pub fn compute_optimal_pre_swap<M: ManagedTypeApi>(
     balance_first: &BigUint<M>,
     balance_second: &BigUint<M>,
@@ -187,6 +220,79 @@ pub fn compute_optimal_pre_swap<M: ManagedTypeApi>(
     }
 }
 
+/// Estimate the dust left by a ZAP: pre-swap with `compute_optimal_pre_swap`, then
+/// apply the pool's `quote()` on the post-swap reserves
+///
+/// # Returns
+/// (dust_first, dust_second): amounts add_liquidity would refund (one is always zero)
+pub fn estimate_dust<M: ManagedTypeApi>(
+    balance_first: &BigUint<M>,
+    balance_second: &BigUint<M>,
+    reserve_first: &BigUint<M>,
+    reserve_second: &BigUint<M>,
+    fee_num: u64,
+    fee_denom: u64,
+    fee_mode: FeeMode,
+) -> (BigUint<M>, BigUint<M>) {
+    if reserve_first == &BigUint::zero() || reserve_second == &BigUint::zero() {
+        return (BigUint::zero(), BigUint::zero());
+    }
+
+    let (swap_from_first, swap_amount) = compute_optimal_pre_swap(
+        balance_first,
+        balance_second,
+        reserve_first,
+        reserve_second,
+        fee_num,
+        fee_denom,
+        fee_mode,
+    );
+
+    let mut final_first = balance_first.clone();
+    let mut final_second = balance_second.clone();
+    let mut new_reserve_first = reserve_first.clone();
+    let mut new_reserve_second = reserve_second.clone();
+
+    if swap_amount > 0u64 {
+        if swap_from_first {
+            let (received, amount_out_leaving, amount_in_to_reserves) = simulate_swap_output(
+                &swap_amount,
+                reserve_first,
+                reserve_second,
+                fee_num,
+                fee_denom,
+                fee_mode,
+            );
+            final_first -= &swap_amount;
+            final_second += &received;
+            new_reserve_first += &amount_in_to_reserves;
+            new_reserve_second -= &amount_out_leaving;
+        } else {
+            let (received, amount_out_leaving, amount_in_to_reserves) = simulate_swap_output(
+                &swap_amount,
+                reserve_second,
+                reserve_first,
+                fee_num,
+                fee_denom,
+                fee_mode,
+            );
+            final_second -= &swap_amount;
+            final_first += &received;
+            new_reserve_second += &amount_in_to_reserves;
+            new_reserve_first -= &amount_out_leaving;
+        }
+    }
+
+    // Same set_optimal_amounts logic as the pool (see binary_search_pre_swap)
+    let quote_second = &final_first * &new_reserve_second / &new_reserve_first;
+    if quote_second <= final_second {
+        (BigUint::zero(), &final_second - &quote_second)
+    } else {
+        let quote_first = &final_second * &new_reserve_first / &new_reserve_second;
+        (&final_first - &quote_first, BigUint::zero())
+    }
+}
+
 /// Binary search to find optimal swap amount for pre-balancing two token balances.
 ///
 /// The goal is to minimize dust returned by the SC's add_liquidity function.
@@ -252,7 +358,7 @@ fn binary_search_pre_swap<M: ManagedTypeApi>(
 
         let quote_other_from_swap = &final_swap_balance * &new_reserve_out / &new_reserve_in;
 
-        let dust = if &quote_other_from_swap <= &final_other_balance {
+        let dust = if quote_other_from_swap <= final_other_balance {
             // SC will use all of swap_balance, return excess other_balance
             &final_other_balance - &quote_other_from_swap
         } else {
@@ -0,0 +1,83 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+
+use aggregator::config::Config;
+use aggregator::types::ActionType;
+use aggregator::utils::Utils;
+use common::*;
+
+/// A -> B swap of amounts[0]
+const SWAP_FIXED_0: Row = (0, 1, 0, 2, 0, 0);
+
+#[test]
+fn action_gas_limit_caps_the_sub_call_gas() {
+    let mut world = setup();
+
+    for gas_limit in [0u64, 1_000_000, u64::MAX] {
+        world
+            .tx()
+            .from(OWNER)
+            .to(AGG)
+            .whitebox(aggregator::contract_obj, |sc| {
+                sc.set_action_gas_limit(0, gas_limit);
+                let gas_left = sc.blockchain().get_gas_left();
+                let expected = if gas_limit == 0 {
+                    gas_left
+                } else {
+                    gas_limit.min(gas_left)
+                };
+                let action = ActionType::XExchangeSwap(esdt(TOKEN_B));
+                assert_eq!(sc.get_sub_call_gas(&action), expected);
+            });
+    }
+}
+
+#[test]
+fn action_gas_limit_of_an_unknown_action_is_rejected() {
+    let mut world = setup();
+
+    let result = world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .returns(ReturnsHandledOrError::new())
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.set_action_gas_limit(u8::MAX, 1_000_000);
+        });
+
+    assert_eq!(
+        result.unwrap_err().message,
+        aggregator::errors::ERR_INVALID_ACTION
+    );
+}
+
+#[test]
+fn a_failed_capped_sub_call_names_its_action() {
+    let mut world = setup_pair();
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.set_action_gas_limit(0, 1_000_000);
+        });
+
+    // 1 A is too small for the pool to produce any output
+    let result = xo_call(
+        &mut world,
+        (TOKEN_A, 1),
+        0,
+        1,
+        0,
+        &[TOKEN_A, TOKEN_B],
+        &[PAIR],
+        &[1],
+        &[SWAP_FIXED_0],
+    );
+
+    assert_eq!(
+        result.unwrap_err().message,
+        "E: Sub-call failed for action 0: error code 1"
+    );
+}
@@ -0,0 +1,123 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::Log;
+
+use aggregator::config::Config;
+use aggregator::Aggregator;
+use common::*;
+
+const SWAP_FIXED_0: Row = (0, 1, 0, 2, 0, 0);
+const ZAP_ADD_ALL: Row = (1, 0, 0, 1, 0, 0);
+
+/// (pool, token_first, amount_first, token_second, amount_second, lp_token, lp_minted)
+type AddLiquidity = (Vec<u8>, Vec<u8>, u64, Vec<u8>, u64, Vec<u8>, u64);
+
+fn add_liquidity_events(logs: &[Log]) -> Vec<AddLiquidity> {
+    let amount = |bytes: &Vec<u8>| u64::top_decode(bytes.as_slice()).unwrap();
+    logs.iter()
+        .filter(|log| log.topics[0] == b"addLiquidity")
+        .map(|log| {
+            (
+                log.topics[1].clone(),
+                log.topics[2].clone(),
+                amount(&log.topics[3]),
+                log.topics[4].clone(),
+                amount(&log.topics[5]),
+                log.topics[6].clone(),
+                amount(&log.data[0]),
+            )
+        })
+        .collect()
+}
+
+fn expected(amount_first: u64, amount_second: u64, lp_minted: u64) -> AddLiquidity {
+    (
+        PAIR.to_address().as_bytes().to_vec(),
+        TOKEN_A.as_bytes().to_vec(),
+        amount_first,
+        TOKEN_B.as_bytes().to_vec(),
+        amount_second,
+        LP_TOKEN.as_bytes().to_vec(),
+        lp_minted,
+    )
+}
+
+#[test]
+fn a_balanced_add_reports_its_inputs_and_shares() {
+    let mut world = setup_pair();
+
+    let logs = world
+        .tx()
+        .from(USER)
+        .to(AGG)
+        .multi_esdt(ManagedVec::<StaticApi, _>::from_iter(
+            [TOKEN_A, TOKEN_B].map(|token| {
+                EsdtTokenPayment::new(token.to_token_identifier(), 0, BigUint::from(1_000u64))
+            }),
+        ))
+        .returns(ReturnsLogs)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.aggregate(
+                aggregator::constants::FORMAT_VERSION,
+                BigUint::zero(),
+                2,
+                0,
+                registry_tokens(&[TOKEN_A, TOKEN_B, LP_TOKEN]),
+                registry_addresses(&[PAIR]),
+                registry_amounts(&[]),
+                instruction_rows(&[ZAP_ADD_ALL]),
+            );
+        });
+
+    assert_eq!(
+        add_liquidity_events(&logs),
+        vec![expected(1_000, 1_000, 1_000)]
+    );
+    world.check_account(USER).esdt_balance(LP_TOKEN, 1_000u64);
+}
+
+#[test]
+fn refunded_dust_is_left_out_of_the_reported_amounts() {
+    let mut world = setup_pair();
+    // Skip the pre-swap, so the pool refunds the A its ratio cannot take
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.set_min_zap_swap(token_id(TOKEN_A), BigUint::from(100u64));
+        });
+
+    let logs = world
+        .tx()
+        .from(USER)
+        .to(AGG)
+        .esdt(TestEsdtTransfer(TOKEN_A, 0, 2_000))
+        .returns(ReturnsLogs)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.aggregate(
+                aggregator::constants::FORMAT_VERSION,
+                BigUint::zero(),
+                2,
+                0,
+                registry_tokens(&[TOKEN_A, TOKEN_B, LP_TOKEN]),
+                registry_addresses(&[PAIR]),
+                registry_amounts(&[1_000]),
+                instruction_rows(&[SWAP_FIXED_0, ZAP_ADD_ALL]),
+            );
+        });
+
+    // The pair took 997 of the 1,000 A left after the swap, and all 996 B
+    let events = add_liquidity_events(&logs);
+    assert_eq!(events.len(), 1);
+    let lp_minted = events[0].6;
+    assert_eq!(events, vec![expected(997, 996, lp_minted)]);
+    world
+        .check_account(PAIR)
+        .esdt_balance(TOKEN_A, 1_001_997u64);
+    world
+        .check_account(PAIR)
+        .esdt_balance(TOKEN_B, 1_000_000u64);
+    world.check_account(USER).esdt_balance(LP_TOKEN, lp_minted);
+}
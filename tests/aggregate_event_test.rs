@@ -0,0 +1,74 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::Log;
+
+use aggregator::Aggregator;
+use common::*;
+
+const SWAP_A_TO_B: Row = (0, 1, 0, 0, 0, 0);
+const SWAP_FIXED_0: Row = (0, 1, 0, 2, 0, 0);
+const SWAP_PREV_B_TO_A: Row = (0, 0, 1, 1, 0, 0);
+/// AshSwap V1 stable swap of the previous B output back to A, on addresses[1]
+const ASH_SWAP_PREV_B_TO_A: Row = (3, 0, 1, 1, 0, 1);
+
+/// Run `rows` from 1,000 A back to A and return the `aggregate` event counts as
+/// (instructions, tokens, protocols, peak vault tokens)
+fn route_counts(world: &mut ScenarioWorld, amounts: &[u64], rows: &[Row]) -> (u32, u32, u32, u32) {
+    let amounts = amounts.to_vec();
+    let rows = rows.to_vec();
+    let logs: Vec<Log> = world
+        .tx()
+        .from(USER)
+        .to(AGG)
+        .esdt(TestEsdtTransfer(TOKEN_A, 0, 1_000))
+        .returns(ReturnsLogs)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.aggregate(
+                aggregator::constants::FORMAT_VERSION,
+                BigUint::from(1u64),
+                0,
+                0,
+                registry_tokens(&[TOKEN_A, TOKEN_B]),
+                registry_addresses(&[PAIR, STABLE_POOL]),
+                registry_amounts(&amounts),
+                instruction_rows(&rows),
+            );
+        });
+    let event = logs
+        .iter()
+        .find(|log| log.topics[0] == b"aggregate")
+        .expect("aggregate event");
+    let count = |topic: usize| u32::top_decode(event.topics[topic].as_slice()).unwrap();
+    (count(4), count(5), count(6), count(7))
+}
+
+#[test]
+fn a_multi_dex_route_counts_each_protocol_once() {
+    let mut world = setup_pair();
+    deploy_stable_pool(&mut world, 1_000_000);
+
+    let counts = route_counts(&mut world, &[], &[SWAP_A_TO_B, ASH_SWAP_PREV_B_TO_A]);
+
+    assert_eq!(counts, (2, 2, 2, 1));
+}
+
+#[test]
+fn a_single_dex_route_counts_one_protocol() {
+    let mut world = setup_pair();
+    deploy_stable_pool(&mut world, 1_000_000);
+
+    let counts = route_counts(&mut world, &[], &[SWAP_A_TO_B, SWAP_PREV_B_TO_A]);
+
+    assert_eq!(counts, (2, 2, 1, 1));
+}
+
+#[test]
+fn a_partial_swap_holds_both_tokens_at_the_peak() {
+    let mut world = setup_pair();
+
+    // Swapping 500 of the 1,000 A leaves A and B in the vault until B is swapped back
+    let counts = route_counts(&mut world, &[500], &[SWAP_FIXED_0, SWAP_PREV_B_TO_A]);
+
+    assert_eq!(counts.3, 2);
+}
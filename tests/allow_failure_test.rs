@@ -0,0 +1,82 @@
+mod common;
+
+use common::*;
+
+const ALLOW_FAILURE: Row = (37, 0, 0, 0, 0, 255);
+/// A -> B swap of amounts[0]
+const SWAP_FIXED_0: Row = (0, 1, 0, 2, 0, 0);
+/// A -> B swap of amounts[1], too small for the pool to produce any output
+const SWAP_FIXED_1: Row = (0, 1, 0, 3, 0, 0);
+
+#[test]
+fn allow_failure_refunds_a_failed_swap() {
+    let mut world = setup_pair();
+
+    xo_call(
+        &mut world,
+        (TOKEN_A, 2_000),
+        0,
+        1,
+        0,
+        &[TOKEN_A, TOKEN_B],
+        &[PAIR],
+        &[1_000, 1],
+        &[SWAP_FIXED_0, ALLOW_FAILURE, SWAP_FIXED_1],
+    )
+    .unwrap();
+
+    // Only the skipped 1 A comes back, the unrouted 999 A stays as dust
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_A, 1_000_000u64 - 2_000 + 1);
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_B, 1_000_996u64);
+}
+
+#[test]
+fn allow_failure_only_covers_the_next_swap() {
+    let mut world = setup_pair();
+
+    // The flag is spent by the first, successful swap: the failing one reverts the path
+    let result = xo_call(
+        &mut world,
+        (TOKEN_A, 2_000),
+        0,
+        1,
+        0,
+        &[TOKEN_A, TOKEN_B],
+        &[PAIR],
+        &[1_000, 1],
+        &[ALLOW_FAILURE, SWAP_FIXED_0, SWAP_FIXED_1],
+    );
+
+    assert!(result.is_err());
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_A, 1_000_000u64);
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_B, 1_000_000u64);
+}
+
+#[test]
+fn allow_failure_does_not_leak_into_a_later_non_swap() {
+    let mut world = setup_pair();
+
+    // ZAP add of the remaining A and the swapped B after a best-effort swap
+    xo_call(
+        &mut world,
+        (TOKEN_A, 2_000),
+        0,
+        2,
+        0,
+        &[TOKEN_A, TOKEN_B, LP_TOKEN],
+        &[PAIR],
+        &[1_000],
+        &[ALLOW_FAILURE, SWAP_FIXED_0, (1, 0, 0, 1, 0, 0)],
+    )
+    .unwrap();
+
+    world.check_account(USER).esdt_balance(LP_TOKEN, 997u64);
+}
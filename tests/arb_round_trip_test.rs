@@ -0,0 +1,112 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::TxResponseStatus;
+
+use aggregator::errors::ERR_ARB_NOT_ROUND_TRIP;
+use aggregator::Aggregator;
+use common::*;
+
+const PAIR_BC: TestSCAddress = TestSCAddress::new("pair-bc");
+const PAIR_CA: TestSCAddress = TestSCAddress::new("pair-ca");
+const LP_BC: TestTokenIdentifier = TestTokenIdentifier::new("LPBC-000009");
+const LP_CA: TestTokenIdentifier = TestTokenIdentifier::new("LPCA-00000a");
+
+/// A -> B -> C -> A, each hop swapping everything in the vault
+const ROUND_TRIP: &[Row] = &[(0, 1, 0, 0, 0, 0), (0, 2, 1, 0, 0, 1), (0, 0, 2, 0, 0, 2)];
+
+/// Triangle of 0.3% pairs where the C/A pair prices A at `a_per_c` C
+fn setup_triangle(a_per_c: u64) -> ScenarioWorld {
+    let mut world = setup_pair();
+    deploy_pair_at(
+        &mut world,
+        PAIR_BC,
+        (TOKEN_B, TOKEN_C),
+        LP_BC,
+        (1_000_000, 1_000_000),
+        1_000_000,
+        300,
+    );
+    deploy_pair_at(
+        &mut world,
+        PAIR_CA,
+        (TOKEN_C, TOKEN_A),
+        LP_CA,
+        (1_000_000, 1_000_000 * a_per_c),
+        1_000_000,
+        300,
+    );
+    world
+}
+
+/// Round trip 1,000 `payment` through `xoArb` asking for `min_profit` of A
+fn arb(
+    world: &mut ScenarioWorld,
+    payment: TestTokenIdentifier,
+    min_profit: u64,
+) -> Result<(), TxResponseStatus> {
+    world
+        .tx()
+        .from(USER)
+        .to(AGG)
+        .esdt(TestEsdtTransfer(payment, 0, 1_000))
+        .returns(ReturnsHandledOrError::new())
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.aggregate_arb(
+                aggregator::constants::FORMAT_VERSION,
+                BigUint::from(min_profit),
+                0,
+                0,
+                registry_tokens(&[TOKEN_A, TOKEN_B, TOKEN_C]),
+                registry_addresses(&[PAIR, PAIR_BC, PAIR_CA]),
+                registry_amounts(&[]),
+                instruction_rows(ROUND_TRIP),
+            );
+        })
+}
+
+#[test]
+fn a_profitable_round_trip_pays_out_the_gain() {
+    let mut world = setup_triangle(2);
+
+    arb(&mut world, TOKEN_A, 900).unwrap();
+
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_A, 1_000_976u64);
+}
+
+#[test]
+fn an_unprofitable_round_trip_reverts() {
+    let mut world = setup_triangle(1);
+
+    let result = arb(&mut world, TOKEN_A, 0);
+
+    assert_eq!(
+        result.unwrap_err().message,
+        "E: Slippage limit exceeded: have 988, need 1000"
+    );
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_A, 1_000_000u64);
+}
+
+#[test]
+fn a_round_trip_short_of_the_profit_floor_reverts() {
+    let mut world = setup_triangle(2);
+
+    assert!(arb(&mut world, TOKEN_A, 1_000).is_err());
+
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_A, 1_000_000u64);
+}
+
+#[test]
+fn a_payment_without_the_output_token_is_rejected() {
+    let mut world = setup_triangle(2);
+
+    let result = arb(&mut world, TOKEN_B, 0);
+
+    assert_eq!(result.unwrap_err().message, ERR_ARB_NOT_ROUND_TRIP);
+}
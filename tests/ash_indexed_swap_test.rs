@@ -0,0 +1,95 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::TxResponseStatus;
+
+use aggregator::errors::ERR_SAME_COIN_INDEX;
+use aggregator::types::{ActionType, CoinIndices};
+use aggregator::utils::Utils;
+use common::*;
+
+/// Stable pool at STABLE_POOL listing A as coin 0 and B as coin 1
+fn setup_coins() -> ScenarioWorld {
+    let mut world = setup();
+    deploy_stable_pool(&mut world, 1_000_000);
+    world
+        .tx()
+        .from(OWNER)
+        .to(STABLE_POOL)
+        .whitebox(mock_stable::contract_obj, |sc| {
+            use mock_stable::MockStable;
+            sc.coin(0).set(esdt(TOKEN_A));
+            sc.coin(1).set(esdt(TOKEN_B));
+        });
+    world
+}
+
+/// Swap all A in the vault from coin `i` to coin `j`
+fn swap_indexed(world: &mut ScenarioWorld, i: u8, j: u8) -> Result<(), TxResponseStatus> {
+    xo_call(
+        world,
+        (TOKEN_A, 1_000),
+        1_000,
+        1,
+        0,
+        &[TOKEN_A, TOKEN_B],
+        &[STABLE_POOL],
+        &[],
+        &[(36, 0, 0, i, j, 0)],
+    )
+}
+
+#[test]
+fn indexed_swap_decodes_its_coin_indices() {
+    let mut world = setup_coins();
+
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            let instr = sc.decode_compact_instruction(
+                36,
+                0,
+                0,
+                0,
+                1,
+                0,
+                &registry_tokens(&[TOKEN_A, TOKEN_B]).to_vec(),
+                &registry_addresses(&[STABLE_POOL]).to_vec(),
+                &registry_amounts(&[]).to_vec(),
+            );
+            assert_eq!(
+                instr.action,
+                ActionType::AshSwapPoolSwapIndexed(CoinIndices { i: 0, j: 1 })
+            );
+            assert_eq!(instr.address, Some(STABLE_POOL.to_managed_address()));
+        });
+}
+
+#[test]
+fn indexed_swap_calls_the_pool_with_its_coin_indices() {
+    let mut world = setup_coins();
+
+    swap_indexed(&mut world, 0, 1).unwrap();
+
+    world.check_account(USER).esdt_balance(TOKEN_A, 999_000u64);
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_B, 1_001_000u64);
+    world
+        .query()
+        .to(STABLE_POOL)
+        .whitebox(mock_stable::contract_obj, |sc| {
+            use mock_stable::MockStable;
+            assert_eq!(sc.last_coin_indices().get(), (0, 1));
+        });
+}
+
+#[test]
+fn indexed_swap_rejects_equal_coin_indices() {
+    let mut world = setup_coins();
+
+    let result = swap_indexed(&mut world, 1, 1);
+
+    assert_eq!(result.unwrap_err().message, ERR_SAME_COIN_INDEX);
+}
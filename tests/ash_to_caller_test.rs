@@ -0,0 +1,93 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::TxResponseStatus;
+
+use aggregator::config::Config;
+use common::*;
+
+/// Stable add of all A, LP minted straight to the caller
+const ADD_TO_CALLER: Row = (27, 0, 255, 255, 0, 0);
+/// LP minimum from amounts[0]
+const SET_MIN_LP_OUT: Row = (30, 0, 0, 0, 0, 255);
+
+fn add_to_caller(
+    world: &mut ScenarioWorld,
+    min_amount_out: u64,
+    referral_id: u64,
+    amounts: &[u64],
+    rows: &[Row],
+) -> Result<(), TxResponseStatus> {
+    let tokens = [TOKEN_A, TOKEN_B, STABLE_LP];
+    xo_call(
+        world,
+        (TOKEN_A, 1_000),
+        min_amount_out,
+        2,
+        referral_id,
+        &tokens,
+        &[STABLE_POOL],
+        amounts,
+        rows,
+    )
+}
+
+fn last_add_min(world: &mut ScenarioWorld) -> u64 {
+    let mut min = 0;
+    world
+        .query()
+        .to(STABLE_POOL)
+        .whitebox(mock_stable::contract_obj, |sc| {
+            use mock_stable::MockStable;
+            min = sc.last_add_min().get().to_u64().unwrap();
+        });
+    min
+}
+
+fn setup_pool() -> ScenarioWorld {
+    let mut world = setup();
+    deploy_stable_pool(&mut world, 1_000_000);
+    world
+}
+
+#[test]
+fn ash_add_to_caller_mints_to_the_caller_with_the_aggregate_minimum() {
+    let mut world = setup_pool();
+
+    add_to_caller(&mut world, 900, 0, &[], &[ADD_TO_CALLER]).unwrap();
+
+    world.check_account(USER).esdt_balance(STABLE_LP, 1_000u64);
+    world.check_account(AGG).esdt_balance(STABLE_LP, 0u64);
+    assert_eq!(last_add_min(&mut world), 900);
+}
+
+#[test]
+fn ash_add_to_caller_never_goes_below_the_aggregate_minimum() {
+    let mut world = setup_pool();
+
+    add_to_caller(&mut world, 900, 0, &[500], &[SET_MIN_LP_OUT, ADD_TO_CALLER]).unwrap();
+    assert_eq!(last_add_min(&mut world), 900);
+
+    // A higher SetMinLpOut still tightens it
+    add_to_caller(&mut world, 900, 0, &[950], &[SET_MIN_LP_OUT, ADD_TO_CALLER]).unwrap();
+    assert_eq!(last_add_min(&mut world), 950);
+}
+
+#[test]
+fn ash_add_to_caller_is_rejected_when_output_fees_apply() {
+    let mut world = setup_pool();
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.add_referral(REFERRER.to_managed_address(), 100);
+        });
+
+    let message = add_to_caller(&mut world, 900, 1, &[], &[ADD_TO_CALLER])
+        .unwrap_err()
+        .message;
+
+    assert_eq!(message, aggregator::errors::ERR_LP_TO_CALLER_CHARGES_FEES);
+    world.check_account(USER).esdt_balance(STABLE_LP, 0u64);
+}
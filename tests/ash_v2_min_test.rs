@@ -0,0 +1,53 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::TxResponseStatus;
+
+use common::*;
+
+/// AshSwap V2 swap of all A on PAIR, with amounts[0] as min_dy
+const SWAP_MIN_0: Row = (29, 0, 0, 0, 0, 0);
+
+fn swap_with_min(world: &mut ScenarioWorld, min_dy: u64) -> Result<(), TxResponseStatus> {
+    xo_call(
+        world,
+        (TOKEN_A, 1_000),
+        0,
+        1,
+        0,
+        &[TOKEN_A, TOKEN_B],
+        &[PAIR],
+        &[min_dy],
+        &[SWAP_MIN_0],
+    )
+}
+
+#[test]
+fn ash_v2_swap_passes_a_met_min_dy() {
+    let mut world = setup_pair();
+
+    swap_with_min(&mut world, 996).unwrap();
+
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_B, 1_000_996u64);
+}
+
+#[test]
+fn ash_v2_swap_reverts_below_a_strict_min_dy() {
+    let mut world = setup_pair();
+
+    // The pool pays 996 B for 1,000 A
+    let result = swap_with_min(&mut world, 997);
+
+    assert_eq!(
+        result.unwrap_err().message,
+        "E: Sub-call failed for action 29: error code 1"
+    );
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_A, 1_000_000u64);
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_B, 1_000_000u64);
+}
@@ -0,0 +1,114 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::TxResponseStatus;
+
+use aggregator::config::Config;
+use aggregator::errors::ERR_NOT_STAKING_ACTION;
+use common::*;
+
+/// B -> WEGLD swap of everything in the vault
+const SWAP_B_TO_WEGLD: Row = (0, 0, 1, 0, 0, 0);
+
+/// WEGLD/B pair, funded wrapper and Hatom staking minting D 1:1 for EGLD
+fn setup_staking() -> ScenarioWorld {
+    let mut world = setup();
+    deploy_pair_at(
+        &mut world,
+        WEGLD_PAIR,
+        (WEGLD, TOKEN_B),
+        WEGLD_LP,
+        (1_000_000, 1_000_000),
+        1_000_000,
+        300,
+    );
+    fund_wrapper(&mut world, 1_000_000);
+    deploy_hatom_staking(&mut world);
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.set_auto_wrap_enabled(true);
+        });
+    world
+}
+
+fn set_auto_stake(world: &mut ScenarioWorld, action: u8) -> Result<(), TxResponseStatus> {
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .returns(ReturnsHandledOrError::new())
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.set_auto_stake(token_id(TOKEN_D), action);
+        })
+}
+
+/// Swap 1,000 B to WEGLD asking for D, with no staking instruction
+fn buy_staked(world: &mut ScenarioWorld) -> Result<(), TxResponseStatus> {
+    xo_call(
+        world,
+        (TOKEN_B, 1_000),
+        996,
+        2,
+        0,
+        &[WEGLD, TOKEN_B, TOKEN_D],
+        &[WEGLD_PAIR],
+        &[],
+        &[SWAP_B_TO_WEGLD],
+    )
+}
+
+#[test]
+fn a_path_ending_in_wegld_is_staked_to_an_opted_in_token_out() {
+    let mut world = setup_staking();
+    set_auto_stake(&mut world, 22).unwrap();
+
+    buy_staked(&mut world).unwrap();
+
+    world.check_account(USER).esdt_balance(TOKEN_D, 996u64);
+    world.check_account(USER).esdt_balance(TOKEN_B, 999_000u64);
+    world.check_account(AGG).balance(0u64);
+}
+
+#[test]
+fn without_the_opt_in_the_path_never_reaches_token_out() {
+    let mut world = setup_staking();
+
+    let result = buy_staked(&mut world);
+
+    assert_eq!(
+        result.unwrap_err().message,
+        "E: Token not found in vault: TOKD-000004"
+    );
+}
+
+#[test]
+fn only_liquid_staking_actions_can_auto_stake() {
+    let mut world = setup_staking();
+
+    let result = set_auto_stake(&mut world, 0);
+
+    assert_eq!(result.unwrap_err().message, ERR_NOT_STAKING_ACTION);
+}
+
+#[test]
+fn clearing_the_opt_in_stops_auto_staking() {
+    let mut world = setup_staking();
+    set_auto_stake(&mut world, 22).unwrap();
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.clear_auto_stake(token_id(TOKEN_D));
+        });
+
+    let result = buy_staked(&mut world);
+
+    assert_eq!(
+        result.unwrap_err().message,
+        "E: Token not found in vault: TOKD-000004"
+    );
+}
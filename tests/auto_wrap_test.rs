@@ -0,0 +1,106 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::TxResponseStatus;
+
+use aggregator::config::Config;
+use common::*;
+
+/// B -> WEGLD swap of everything in the vault
+const SWAP_B_TO_WEGLD: Row = (0, 0, 1, 0, 0, 0);
+/// WEGLD -> B swap of everything in the vault
+const SWAP_WEGLD_TO_B: Row = (0, 1, 0, 0, 0, 0);
+/// Hatom staking of all native EGLD in the vault
+const STAKE_EGLD: Row = (22, 254, 0, 255, 0, 255);
+
+fn setup_wegld(auto_wrap: bool) -> ScenarioWorld {
+    let mut world = setup();
+    deploy_pair_at(
+        &mut world,
+        WEGLD_PAIR,
+        (WEGLD, TOKEN_B),
+        WEGLD_LP,
+        (1_000_000, 1_000_000),
+        1_000_000,
+        300,
+    );
+    fund_wrapper(&mut world, 1_000_000);
+    deploy_hatom_staking(&mut world);
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.set_auto_wrap_enabled(auto_wrap);
+        });
+    world
+}
+
+fn stake_swapped_wegld(world: &mut ScenarioWorld) -> Result<(), TxResponseStatus> {
+    xo_call(
+        world,
+        (TOKEN_B, 1_000),
+        0,
+        2,
+        0,
+        &[WEGLD, TOKEN_B, TOKEN_D],
+        &[WEGLD_PAIR],
+        &[],
+        &[SWAP_B_TO_WEGLD, STAKE_EGLD],
+    )
+}
+
+fn swap_paid_egld(world: &mut ScenarioWorld) -> Result<(), TxResponseStatus> {
+    xo_call_egld(
+        world,
+        1_000,
+        0,
+        1,
+        0,
+        &[WEGLD, TOKEN_B],
+        &[WEGLD_PAIR],
+        &[],
+        &[SWAP_WEGLD_TO_B],
+    )
+}
+
+#[test]
+fn auto_unwrap_feeds_an_egld_input_from_wegld_mid_path() {
+    let mut world = setup_wegld(true);
+
+    stake_swapped_wegld(&mut world).unwrap();
+
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_B, 1_000_000u64 - 1_000);
+    world.check_account(USER).esdt_balance(TOKEN_D, 996u64);
+}
+
+#[test]
+fn auto_wrap_feeds_a_wegld_input_from_egld() {
+    let mut world = setup_wegld(true);
+
+    swap_paid_egld(&mut world).unwrap();
+
+    world
+        .check_account(USER)
+        .balance(1_000_000u64 - 1_000)
+        .esdt_balance(TOKEN_B, 1_000_996u64);
+}
+
+#[test]
+fn without_the_flag_the_counterpart_is_not_converted() {
+    let mut world = setup_wegld(false);
+
+    let unwrap_err = stake_swapped_wegld(&mut world).unwrap_err();
+    let wrap_err = swap_paid_egld(&mut world).unwrap_err();
+
+    assert_eq!(
+        unwrap_err.message,
+        "E: Token not found in vault: EGLD-000000"
+    );
+    assert_eq!(
+        wrap_err.message,
+        "E: Token not found in vault: WEGLD-000007"
+    );
+}
@@ -0,0 +1,121 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+
+use aggregator::constants::ONE_DEX_ROUTER;
+use aggregator::Aggregator;
+use common::*;
+
+type Venue = (u8, Address, u64, u64);
+
+fn big(value: u64) -> Vec<u8> {
+    num_bigint::BigUint::from(value).to_bytes_be()
+}
+
+/// OneDex router pair `pair_id` trading `tokens` with `reserves`
+fn set_onedex_pair(
+    world: &mut ScenarioWorld,
+    pair_id: u32,
+    tokens: (TestTokenIdentifier, TestTokenIdentifier),
+    reserves: (u64, u64),
+) {
+    let entries = [
+        (&b"pair_first_token_id"[..], tokens.0.as_bytes().to_vec()),
+        (&b"pair_second_token_id"[..], tokens.1.as_bytes().to_vec()),
+        (&b"pair_first_token_reserve"[..], big(reserves.0)),
+        (&b"pair_second_token_reserve"[..], big(reserves.1)),
+    ];
+    for (key, value) in entries {
+        let mut key = key.to_vec();
+        key.extend_from_slice(&pair_id.to_be_bytes());
+        set_protocol_storage(world, &ONE_DEX_ROUTER, &key, &value);
+    }
+}
+
+/// xExchange A/B pair, OneDex pairs 1 (A/B) and 2 (A/C), and a Jex B/A pool
+fn setup_venues() -> ScenarioWorld {
+    let mut world = setup();
+    deploy_pair(&mut world, 1_000, 2_000, 1_000, 300);
+    set_onedex_pair(&mut world, 1, (TOKEN_A, TOKEN_B), (3_000, 4_000));
+    set_onedex_pair(&mut world, 2, (TOKEN_A, TOKEN_C), (7_000, 8_000));
+    deploy_mock_pool(&mut world, JEX_POOL);
+    for (key, value) in [
+        (&b"first_token_id"[..], TOKEN_B.as_bytes().to_vec()),
+        (&b"second_token_id"[..], TOKEN_A.as_bytes().to_vec()),
+        (&b"first_token_reserve"[..], big(6_000)),
+        (&b"second_token_reserve"[..], big(5_000)),
+    ] {
+        set_pool_storage(&mut world, JEX_POOL, key, &value);
+    }
+    world
+}
+
+fn best_cpmm_pools(
+    world: &mut ScenarioWorld,
+    token_in: TestTokenIdentifier,
+    token_out: TestTokenIdentifier,
+) -> Vec<Venue> {
+    let mut venues = Vec::new();
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            let mut onedex_pair_ids = MultiValueEncodedCounted::new();
+            onedex_pair_ids.push(1usize);
+            onedex_pair_ids.push(2usize);
+            let mut jex_pools = MultiValueEncoded::new();
+            jex_pools.push(JEX_POOL.to_managed_address());
+            for venue in sc
+                .best_cpmm_pool_for(esdt(token_in), esdt(token_out), onedex_pair_ids, jex_pools)
+                .into_iter()
+            {
+                let (action, pool, reserve_in, reserve_out) = venue.into_tuple();
+                venues.push((
+                    action,
+                    pool.to_address(),
+                    reserve_in.to_u64().unwrap(),
+                    reserve_out.to_u64().unwrap(),
+                ));
+            }
+        });
+    venues
+}
+
+#[test]
+fn every_venue_pairing_the_tokens_is_listed_with_oriented_reserves() {
+    let mut world = setup_venues();
+
+    assert_eq!(
+        best_cpmm_pools(&mut world, TOKEN_A, TOKEN_B),
+        vec![
+            (0, PAIR.to_address(), 1_000, 2_000),
+            (9, protocol_address(&ONE_DEX_ROUTER), 3_000, 4_000),
+            (12, JEX_POOL.to_address(), 5_000, 6_000),
+        ]
+    );
+}
+
+#[test]
+fn reserves_follow_the_requested_direction() {
+    let mut world = setup_venues();
+
+    assert_eq!(
+        best_cpmm_pools(&mut world, TOKEN_B, TOKEN_A),
+        vec![
+            (0, PAIR.to_address(), 2_000, 1_000),
+            (9, protocol_address(&ONE_DEX_ROUTER), 4_000, 3_000),
+            (12, JEX_POOL.to_address(), 6_000, 5_000),
+        ]
+    );
+}
+
+#[test]
+fn only_venues_with_the_pair_are_listed() {
+    let mut world = setup_venues();
+
+    assert_eq!(
+        best_cpmm_pools(&mut world, TOKEN_C, TOKEN_A),
+        vec![(9, protocol_address(&ONE_DEX_ROUTER), 8_000, 7_000)]
+    );
+    assert!(best_cpmm_pools(&mut world, TOKEN_B, TOKEN_C).is_empty());
+}
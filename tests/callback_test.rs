@@ -0,0 +1,116 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::TxResponseStatus;
+
+use aggregator::config::Config;
+use aggregator::constants::DEFAULT_CALLBACK_GAS;
+use aggregator::storage::Storage;
+use aggregator::Aggregator;
+use common::*;
+
+const RECEIVER: TestSCAddress = TestSCAddress::new("callback-receiver");
+
+fn setup_receiver() -> ScenarioWorld {
+    let mut world = setup_pair();
+    world
+        .account(RECEIVER)
+        .nonce(1)
+        .code(MOCK_PAIR_CODE)
+        .owner(OWNER);
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            let mut targets = MultiValueEncoded::new();
+            targets.push(
+                (
+                    RECEIVER.to_managed_address(),
+                    ManagedBuffer::from(b"notify"),
+                )
+                    .into(),
+            );
+            sc.add_callback_targets(targets);
+        });
+    world
+}
+
+/// Swap 1000 A to B, then call `endpoint` on RECEIVER
+fn swap_with_callback(
+    world: &mut ScenarioWorld,
+    endpoint: &'static [u8],
+) -> Result<(), TxResponseStatus> {
+    world
+        .tx()
+        .from(USER)
+        .to(AGG)
+        .esdt(TestEsdtTransfer(TOKEN_A, 0, 1_000u64))
+        .returns(ReturnsHandledOrError::new())
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.aggregate_with_callback(
+                aggregator::constants::FORMAT_VERSION,
+                RECEIVER.to_managed_address(),
+                ManagedBuffer::from(endpoint),
+                BigUint::zero(),
+                1,
+                0,
+                registry_tokens(&[TOKEN_A, TOKEN_B]),
+                registry_addresses(&[PAIR]),
+                registry_amounts(&[]),
+                instruction_rows(&[(0, 1, 0, 0, 0, 0)]),
+            );
+        })
+}
+
+#[test]
+fn callback_calls_a_whitelisted_endpoint() {
+    let mut world = setup_receiver();
+
+    swap_with_callback(&mut world, b"notify").unwrap();
+
+    world
+        .query()
+        .to(RECEIVER)
+        .whitebox(mock_pair::contract_obj, |sc| {
+            use mock_pair::MockPair;
+            let (token, amount) = sc.last_notified().get();
+            assert_eq!(token, token_id(TOKEN_B));
+            assert_eq!(amount, BigUint::from(996u64));
+        });
+}
+
+#[test]
+fn callback_rejects_other_endpoints_of_a_whitelisted_contract() {
+    let mut world = setup_receiver();
+
+    let result = swap_with_callback(&mut world, b"setRaw");
+
+    assert_eq!(
+        result.unwrap_err().message,
+        aggregator::errors::ERR_CALLBACK_NOT_WHITELISTED
+    );
+}
+
+#[test]
+fn callback_gas_is_capped_by_the_limit_and_the_gas_left() {
+    let mut world = setup_receiver();
+
+    for gas_limit in [0u64, 1_000_000, u64::MAX] {
+        world
+            .tx()
+            .from(OWNER)
+            .to(AGG)
+            .whitebox(aggregator::contract_obj, |sc| {
+                sc.set_callback_gas_limit(gas_limit);
+                let limit = if gas_limit == 0 {
+                    DEFAULT_CALLBACK_GAS
+                } else {
+                    gas_limit
+                };
+                let gas_left = sc.blockchain().get_gas_left();
+                assert_eq!(sc.get_callback_gas(), limit.min(gas_left));
+            });
+        swap_with_callback(&mut world, b"notify").unwrap();
+    }
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use common::*;
+
+/// Claim with all of the A input, staking contract resolved automatically
+const CLAIM_REWARDS: Row = (28, 0, 0, 255, 0, 255);
+const CLAIM_AND_RESTAKE: Row = (41, 0, 0, 255, 0, 255);
+
+#[test]
+fn claim_and_restake_delivers_staked_rewards() {
+    let mut world = setup();
+    deploy_staking(&mut world, 500);
+
+    xo_call(
+        &mut world,
+        (TOKEN_A, 1_000),
+        500,
+        3,
+        0,
+        &[TOKEN_A, TOKEN_B, TOKEN_C, TOKEN_D],
+        &[],
+        &[],
+        &[CLAIM_AND_RESTAKE],
+    )
+    .unwrap();
+
+    // The 500 C of rewards were delegated again, only the staked D reaches the caller
+    world.check_account(USER).esdt_balance(TOKEN_D, 500u64);
+    world.check_account(USER).esdt_balance(TOKEN_C, 0u64);
+    world.check_account(AGG).esdt_balance(TOKEN_C, 0u64);
+    world.check_account(AGG).esdt_balance(TOKEN_D, 0u64);
+}
+
+#[test]
+fn claim_rewards_without_restake_delivers_the_rewards() {
+    let mut world = setup();
+    deploy_staking(&mut world, 500);
+
+    xo_call(
+        &mut world,
+        (TOKEN_A, 1_000),
+        500,
+        2,
+        0,
+        &[TOKEN_A, TOKEN_B, TOKEN_C, TOKEN_D],
+        &[],
+        &[],
+        &[CLAIM_REWARDS],
+    )
+    .unwrap();
+
+    world.check_account(USER).esdt_balance(TOKEN_C, 500u64);
+    world.check_account(USER).esdt_balance(TOKEN_D, 0u64);
+}
+
+#[test]
+fn claim_and_restake_rejects_an_empty_claim() {
+    let mut world = setup();
+    deploy_staking(&mut world, 0);
+
+    let result = xo_call(
+        &mut world,
+        (TOKEN_A, 1_000),
+        0,
+        3,
+        0,
+        &[TOKEN_A, TOKEN_B, TOKEN_C, TOKEN_D],
+        &[],
+        &[],
+        &[CLAIM_AND_RESTAKE],
+    );
+
+    assert_eq!(
+        result.unwrap_err().message,
+        aggregator::errors::ERR_NO_REWARDS_TO_RESTAKE
+    );
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_A, 1_000_000u64);
+}
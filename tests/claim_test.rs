@@ -0,0 +1,364 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::TxResponseStatus;
+
+use aggregator::config::Config;
+use aggregator::errors::{
+    ERR_EGLD_LIABILITY_NOT_SEEDED, ERR_EGLD_LIABILITY_SEEDED, ERR_EGLD_LIABILITY_SEED_GAP,
+};
+use aggregator::storage::Storage;
+use aggregator::utils::Utils;
+use aggregator::Aggregator;
+use common::*;
+use multiversx_sc::chain_core::EGLD_000000_TOKEN_IDENTIFIER;
+
+/// Referral 1 owned by REFERRER with `referral` of TOKEN_A booked to it and
+/// `admin` of TOKEN_A booked as admin fees, all held by the aggregator
+fn setup_balances(referral: u64, admin: u64) -> ScenarioWorld {
+    let mut world = setup();
+    world.set_esdt_balance(AGG, TOKEN_A.as_bytes(), referral + admin);
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            let id = sc.add_referral(REFERRER.to_managed_address(), 100);
+            assert_eq!(id, 1);
+            sc.referrer_balances(id)
+                .insert(token_id(TOKEN_A), BigUint::from(referral));
+            sc.admin_fees()
+                .insert(token_id(TOKEN_A), BigUint::from(admin));
+        });
+    world
+}
+
+#[test]
+fn claim_referral_fees_pays_a_repeated_token_once() {
+    let mut world = setup_balances(100, 500);
+
+    world
+        .tx()
+        .from(USER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            let mut tokens = MultiValueEncoded::new();
+            for _ in 0..3 {
+                tokens.push(token_id(TOKEN_A));
+            }
+            sc.claim_referral_fees(1, tokens);
+        });
+
+    world.check_account(REFERRER).esdt_balance(TOKEN_A, 100u64);
+    world.check_account(AGG).esdt_balance(TOKEN_A, 500u64);
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            assert!(sc.referrer_balances(1).get(&token_id(TOKEN_A)).is_none());
+            assert_eq!(
+                sc.admin_fees().get(&token_id(TOKEN_A)),
+                Some(BigUint::from(500u64))
+            );
+        });
+}
+
+fn claim_above(world: &mut ScenarioWorld, entries: &[(TestTokenIdentifier, u64)]) {
+    let entries = entries.to_vec();
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            let mut thresholds = MultiValueEncoded::new();
+            for (token, threshold) in &entries {
+                thresholds.push((token_id(*token), BigUint::from(*threshold)).into());
+            }
+            sc.claim_admin_fees_above_threshold(OWNER.to_managed_address(), thresholds);
+        });
+}
+
+#[test]
+fn claim_admin_fees_above_threshold_pays_a_repeated_token_once() {
+    let mut world = setup_balances(100, 500);
+
+    claim_above(&mut world, &[(TOKEN_A, 10), (TOKEN_A, 10), (TOKEN_A, 0)]);
+
+    world.check_account(OWNER).esdt_balance(TOKEN_A, 500u64);
+    world.check_account(AGG).esdt_balance(TOKEN_A, 100u64);
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            assert!(sc.admin_fees().get(&token_id(TOKEN_A)).is_none());
+            assert_eq!(
+                sc.referrer_balances(1).get(&token_id(TOKEN_A)),
+                Some(BigUint::from(100u64))
+            );
+        });
+}
+
+#[test]
+fn claim_admin_fees_above_threshold_keeps_sub_threshold_balances() {
+    let mut world = setup_balances(100, 500);
+
+    claim_above(&mut world, &[(TOKEN_A, 500)]);
+
+    world.check_account(AGG).esdt_balance(TOKEN_A, 600u64);
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            assert_eq!(
+                sc.admin_fees().get(&token_id(TOKEN_A)),
+                Some(BigUint::from(500u64))
+            );
+        });
+}
+
+/// Referral 1 and admin fees each owed 100 EGLD, plus `stray` EGLD nobody is owed
+fn setup_egld(stray: u64) -> ScenarioWorld {
+    let mut world = setup();
+    world.set_egld_balance(AGG, 200 + stray);
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            let egld = TokenId::from(EGLD_000000_TOKEN_IDENTIFIER.as_bytes());
+            let id = sc.add_referral(REFERRER.to_managed_address(), 100);
+            sc.accumulate_referrer_fee(id, &egld, &BigUint::from(100u64));
+            sc.accumulate_admin_fee(&egld, &BigUint::from(100u64));
+        });
+    world
+}
+
+#[test]
+fn rescue_egld_sweeps_only_egld_not_owed_as_fees() {
+    let mut world = setup_egld(50);
+
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.rescue_egld(USER.to_managed_address(), OptionalValue::None);
+        });
+
+    world.check_account(USER).balance(1_000_050u64);
+    world.check_account(AGG).balance(200u64);
+}
+
+#[test]
+fn rescue_egld_rejects_an_amount_owed_as_fees() {
+    let mut world = setup_egld(50);
+
+    let result = world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .returns(ReturnsHandledOrError::new())
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.rescue_egld(
+                USER.to_managed_address(),
+                OptionalValue::Some(BigUint::from(51u64)),
+            );
+        });
+
+    assert_eq!(
+        result.unwrap_err().message,
+        aggregator::errors::ERR_INSUFFICIENT_EGLD_BALANCE
+    );
+    world.check_account(AGG).balance(250u64);
+}
+
+fn egld_fee_liability(world: &mut ScenarioWorld) -> u64 {
+    let mut owed = 0u64;
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            owed = sc.egld_fee_liability().get().to_u64().unwrap();
+        });
+    owed
+}
+
+#[test]
+fn egld_fee_liability_follows_credits_and_claims() {
+    let mut world = setup_egld(50);
+    assert_eq!(egld_fee_liability(&mut world), 200);
+
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.claim_admin_fees(OWNER.to_managed_address());
+        });
+    assert_eq!(egld_fee_liability(&mut world), 100);
+
+    world
+        .tx()
+        .from(REFERRER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.claim_referral_fees(1, MultiValueEncoded::new());
+        });
+    assert_eq!(egld_fee_liability(&mut world), 0);
+
+    // Only the stray EGLD was ever rescuable
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.rescue_egld(USER.to_managed_address(), OptionalValue::None);
+        });
+    world.check_account(USER).balance(1_000_050u64);
+    world.check_account(AGG).balance(0u64);
+}
+
+/// Admin fees owed 30 EGLD and referrals 1 and 2 owed 70 and 50 EGLD, booked by a
+/// version without the liability and then upgraded, plus 10 stray EGLD
+fn setup_unseeded() -> ScenarioWorld {
+    let mut world = setup();
+    world.set_egld_balance(AGG, 160u64);
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            let egld = TokenId::from(EGLD_000000_TOKEN_IDENTIFIER.as_bytes());
+            for amount in [70u64, 50] {
+                let id = sc.add_referral(REFERRER.to_managed_address(), 100);
+                sc.referrer_balances(id)
+                    .insert(egld.clone(), BigUint::from(amount));
+            }
+            sc.admin_fees().insert(egld, BigUint::from(30u64));
+            sc.egld_liability_seeded().clear();
+            sc.upgrade();
+        });
+    world
+}
+
+fn seed(world: &mut ScenarioWorld, from: u64, count: u64) -> Result<(), TxResponseStatus> {
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .returns(ReturnsHandledOrError::new())
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.seed_egld_fee_liability(from, count);
+        })
+}
+
+fn rescue_all(world: &mut ScenarioWorld) -> Result<(), TxResponseStatus> {
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .returns(ReturnsHandledOrError::new())
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.rescue_egld(USER.to_managed_address(), OptionalValue::None);
+        })
+}
+
+#[test]
+fn upgrade_leaves_the_egld_fee_liability_to_paginated_seeding() {
+    let mut world = setup_unseeded();
+
+    assert_eq!(egld_fee_liability(&mut world), 0);
+    assert_eq!(
+        rescue_all(&mut world).unwrap_err().message,
+        ERR_EGLD_LIABILITY_NOT_SEEDED
+    );
+
+    // Admin fees and referral 1, then referral 2
+    seed(&mut world, 0, 2).unwrap();
+    assert_eq!(egld_fee_liability(&mut world), 100);
+    assert_eq!(
+        rescue_all(&mut world).unwrap_err().message,
+        ERR_EGLD_LIABILITY_NOT_SEEDED
+    );
+    seed(&mut world, 2, 10).unwrap();
+    assert_eq!(egld_fee_liability(&mut world), 150);
+
+    rescue_all(&mut world).unwrap();
+    world.check_account(USER).balance(1_000_010u64);
+    world.check_account(AGG).balance(150u64);
+}
+
+#[test]
+fn seeding_pages_must_follow_each_other() {
+    let mut world = setup_unseeded();
+    seed(&mut world, 0, 1).unwrap();
+
+    assert_eq!(
+        seed(&mut world, 2, 1).unwrap_err().message,
+        ERR_EGLD_LIABILITY_SEED_GAP
+    );
+    assert_eq!(
+        seed(&mut world, 0, 1).unwrap_err().message,
+        ERR_EGLD_LIABILITY_SEED_GAP
+    );
+
+    seed(&mut world, 1, 2).unwrap();
+    assert_eq!(
+        seed(&mut world, 3, 1).unwrap_err().message,
+        ERR_EGLD_LIABILITY_SEEDED
+    );
+}
+
+#[test]
+fn fees_moving_during_seeding_are_counted_once() {
+    let mut world = setup_unseeded();
+    seed(&mut world, 0, 2).unwrap();
+
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            let egld = TokenId::from(EGLD_000000_TOKEN_IDENTIFIER.as_bytes());
+            // Seeded owners book live, referral 2 is read whole when seeded
+            sc.accumulate_admin_fee(&egld, &BigUint::from(5u64));
+            sc.accumulate_referrer_fee(2, &egld, &BigUint::from(5u64));
+        });
+    assert_eq!(egld_fee_liability(&mut world), 105);
+
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.claim_admin_fees(OWNER.to_managed_address());
+        });
+    assert_eq!(egld_fee_liability(&mut world), 70);
+
+    seed(&mut world, 2, 1).unwrap();
+    assert_eq!(egld_fee_liability(&mut world), 125);
+}
+
+#[test]
+fn single_token_fee_views_match_the_bulk_views() {
+    let mut world = setup_balances(100, 500);
+
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            for (token, amount) in sc.get_admin_fees_view().into_iter() {
+                assert_eq!(sc.get_admin_fee(token), amount);
+            }
+            for (token, amount) in sc
+                .get_referrer_balances(1, MultiValueEncoded::new())
+                .into_iter()
+            {
+                assert_eq!(sc.get_referrer_balance(1, token), amount);
+            }
+            assert_eq!(sc.get_admin_fee(token_id(TOKEN_A)), 500u64);
+            assert_eq!(sc.get_referrer_balance(1, token_id(TOKEN_A)), 100u64);
+            assert_eq!(sc.get_admin_fee(token_id(TOKEN_B)), 0u64);
+            assert_eq!(sc.get_referrer_balance(1, token_id(TOKEN_B)), 0u64);
+        });
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use common::*;
+
+/// A -> B swap of amounts[0]
+const SWAP_FIXED_0: Row = (0, 1, 0, 2, 0, 0);
+/// B -> A swap of the previous instruction's output
+const SWAP_PREV_BACK: Row = (0, 0, 255, 1, 0, 0);
+const CLEAR_PREV: Row = (25, 0, 0, 0, 0, 255);
+
+#[test]
+fn prev_result_carries_over_without_clear_prev() {
+    let mut world = setup_pair();
+
+    xo_call(
+        &mut world,
+        (TOKEN_A, 1_000),
+        0,
+        0,
+        0,
+        &[TOKEN_A, TOKEN_B],
+        &[PAIR],
+        &[1_000],
+        &[SWAP_FIXED_0, SWAP_PREV_BACK],
+    )
+    .unwrap();
+
+    // The 996 B bought back 994 A
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_A, 1_000_000u64 - 1_000 + 994);
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_B, 1_000_000u64);
+}
+
+#[test]
+fn clear_prev_keeps_a_segment_from_consuming_the_last_output() {
+    let mut world = setup_pair();
+
+    let result = xo_call(
+        &mut world,
+        (TOKEN_A, 1_000),
+        0,
+        0,
+        0,
+        &[TOKEN_A, TOKEN_B],
+        &[PAIR],
+        &[1_000],
+        &[SWAP_FIXED_0, CLEAR_PREV, SWAP_PREV_BACK],
+    );
+
+    assert_eq!(
+        result.unwrap_err().message,
+        aggregator::errors::ERR_PREV_AMOUNT_NOT_AVAILABLE
+    );
+}
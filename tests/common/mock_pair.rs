@@ -0,0 +1,381 @@
+//! xExchange-style CPMM pair with the storage layout the aggregator reads
+
+multiversx_sc::imports!();
+
+#[multiversx_sc::contract]
+pub trait MockPair {
+    #[init]
+    fn init(&self) {}
+
+    #[payable("*")]
+    #[endpoint(swapTokensFixedInput)]
+    fn swap_tokens_fixed_input(&self, token_out: TokenIdentifier, amount_out_min: BigUint) {
+        let payment = self.call_value().single().clone();
+        let token_in = TokenIdentifier::from(payment.token_identifier.as_managed_buffer().clone());
+        // Knob for a dry router: hand the input back untouched instead of reverting
+        if self.return_input().get() {
+            self.tx()
+                .to(self.blockchain().get_caller())
+                .payment(payment)
+                .transfer();
+            return;
+        }
+        // Knob for a partial fill: this much of the input comes back as native EGLD
+        let egld_refund = self.egld_refund().get();
+        let amount_in = payment.amount.as_big_uint() - &egld_refund;
+
+        let amount_out = self.get_amount_out(token_in.clone(), amount_in.clone());
+        require!(amount_out >= amount_out_min, "Slippage exceeded");
+        require!(amount_out > 0u64, "Zero output");
+
+        self.reserve(&token_in).update(|r| *r += &amount_in);
+        if self.withhold_outputs().get() {
+            return;
+        }
+        self.reserve(&token_out).update(|r| *r -= &amount_out);
+        self.tx()
+            .to(self.blockchain().get_caller())
+            .single_esdt(&token_out, 0, &amount_out)
+            .transfer();
+        if egld_refund > 0u64 {
+            self.tx()
+                .to(self.blockchain().get_caller())
+                .egld(&egld_refund)
+                .transfer();
+        }
+    }
+
+    /// AshSwap V2 crypto pool swap: the pair's quote, paid in the other token
+    #[payable("*")]
+    #[endpoint(exchange)]
+    fn exchange(&self, min_dy: BigUint) {
+        let payment = self.call_value().single().clone();
+        let token_in = TokenIdentifier::from(payment.token_identifier.as_managed_buffer().clone());
+        let token_out = self.other_token(&token_in);
+        self.swap_tokens_fixed_input(token_out, min_dy);
+    }
+
+    /// OneDex router swap along `path`; only the single-hop [input, output] shape is priced
+    #[payable("*")]
+    #[endpoint(swapMultiTokensFixedInput)]
+    fn swap_multi_tokens_fixed_input(
+        &self,
+        amount_out_min: BigUint,
+        _unwrap_required: bool,
+        path: MultiValueEncoded<TokenIdentifier>,
+    ) {
+        let path = path.to_vec();
+        let payment = self.call_value().single().clone();
+        let token_in = TokenIdentifier::from(payment.token_identifier.as_managed_buffer().clone());
+        require!(path.len() == 2, "Invalid path length");
+        require!(*path.get(0) == token_in, "Path does not start at the input");
+        self.swap_tokens_fixed_input(path.get(1).clone(), amount_out_min);
+    }
+
+    #[view(getAmountOut)]
+    fn get_amount_out(&self, token_in: TokenIdentifier, amount_in: BigUint) -> BigUint {
+        let reserve_in = self.reserve(&token_in).get();
+        let reserve_out = self.reserve(&self.other_token(&token_in)).get();
+        let amount_with_fee = &amount_in * (100_000 - self.total_fee_percent().get());
+        &amount_with_fee * &reserve_out / (&reserve_in * 100_000u64 + &amount_with_fee)
+    }
+
+    fn other_token(&self, token: &TokenIdentifier) -> TokenIdentifier {
+        let first = self.first_token_id().get();
+        if *token == first {
+            self.second_token_id().get()
+        } else {
+            first
+        }
+    }
+
+    #[payable("*")]
+    #[endpoint(addLiquidity)]
+    fn add_liquidity(&self, first_token_amount_min: BigUint, second_token_amount_min: BigUint) {
+        let payments = self.call_value().all().clone();
+        let amount_first = payments.get(0).amount.as_big_uint().clone();
+        let amount_second = payments.get(1).amount.as_big_uint().clone();
+        let first = self.first_token_id().get();
+        let second = self.second_token_id().get();
+
+        let reserve_first = self.reserve(&first).get();
+        let reserve_second = self.reserve(&second).get();
+        let supply = self.lp_token_supply().get();
+
+        let (used_first, used_second, lp) = if supply == 0u64 {
+            let lp = core::cmp::min(amount_first.clone(), amount_second.clone());
+            (amount_first.clone(), amount_second.clone(), lp)
+        } else {
+            let second_optimal = &amount_first * &reserve_second / &reserve_first;
+            let (used_first, used_second) = if second_optimal <= amount_second {
+                (amount_first.clone(), second_optimal)
+            } else {
+                (
+                    &amount_second * &reserve_first / &reserve_second,
+                    amount_second.clone(),
+                )
+            };
+            let lp = &used_first * &supply / &reserve_first;
+            (used_first, used_second, lp)
+        };
+        require!(used_first >= first_token_amount_min, "First token slippage");
+        require!(
+            used_second >= second_token_amount_min,
+            "Second token slippage"
+        );
+
+        // Knob for an off-value add: mint only a share of the fair LP amount
+        let mint_ppm = self.lp_mint_ppm().get();
+        let lp = if mint_ppm == 0 {
+            lp
+        } else {
+            lp * mint_ppm / 1_000_000u32
+        };
+
+        self.reserve(&first).update(|r| *r += &used_first);
+        self.reserve(&second).update(|r| *r += &used_second);
+        self.lp_token_supply().update(|s| *s += &lp);
+
+        let mut out = ManagedVec::<Self::Api, EsdtTokenPayment>::new();
+        if !self.withhold_outputs().get() {
+            out.push(EsdtTokenPayment::new(
+                self.lp_token_identifier().get(),
+                0,
+                lp,
+            ));
+        }
+        if amount_first > used_first {
+            out.push(EsdtTokenPayment::new(first, 0, amount_first - used_first));
+        }
+        if amount_second > used_second {
+            out.push(EsdtTokenPayment::new(
+                second,
+                0,
+                amount_second - used_second,
+            ));
+        }
+        if out.is_empty() {
+            return;
+        }
+        self.tx()
+            .to(self.blockchain().get_caller())
+            .multi_esdt(out)
+            .transfer();
+    }
+
+    #[payable("*")]
+    #[endpoint(removeLiquidity)]
+    fn remove_liquidity(&self, first_token_amount_min: BigUint, second_token_amount_min: BigUint) {
+        let payment = self.call_value().single().clone();
+        let lp = payment.amount.as_big_uint().clone();
+        let first = self.first_token_id().get();
+        let second = self.second_token_id().get();
+        let supply = self.lp_token_supply().get();
+
+        let amount_first = &lp * &self.reserve(&first).get() / &supply;
+        let amount_second = &lp * &self.reserve(&second).get() / &supply;
+        require!(
+            amount_first >= first_token_amount_min,
+            "First token slippage"
+        );
+        require!(
+            amount_second >= second_token_amount_min,
+            "Second token slippage"
+        );
+
+        self.reserve(&first).update(|r| *r -= &amount_first);
+        self.reserve(&second).update(|r| *r -= &amount_second);
+        self.lp_token_supply().update(|s| *s -= &lp);
+
+        let mut out = ManagedVec::<Self::Api, EsdtTokenPayment>::new();
+        out.push(EsdtTokenPayment::new(first, 0, amount_first));
+        if !self.withhold_outputs().get() {
+            out.push(EsdtTokenPayment::new(second, 0, amount_second));
+        }
+        self.tx()
+            .to(self.blockchain().get_caller())
+            .multi_esdt(out)
+            .transfer();
+    }
+
+    #[view(getReservesAndTotalSupply)]
+    fn get_reserves_and_total_supply(&self) -> MultiValue3<BigUint, BigUint, BigUint> {
+        let (first_reserve, second_reserve) = self.view_reserves();
+        (first_reserve, second_reserve, self.lp_token_supply().get()).into()
+    }
+
+    /// OneDex router view, answering for any pair id
+    #[view(getPairFirstTokenReserve)]
+    fn get_pair_first_token_reserve(&self, _pair_id: usize) -> BigUint {
+        self.view_reserves().0
+    }
+
+    #[view(getPairSecondTokenReserve)]
+    fn get_pair_second_token_reserve(&self, _pair_id: usize) -> BigUint {
+        self.view_reserves().1
+    }
+
+    /// Jex pair view
+    #[view(getFirstTokenReserve)]
+    fn get_first_token_reserve(&self) -> BigUint {
+        self.view_reserves().0
+    }
+
+    #[view(getSecondTokenReserve)]
+    fn get_second_token_reserve(&self) -> BigUint {
+        self.view_reserves().1
+    }
+
+    /// Reserves the views report: `reportedReserves` when set, else the swap reserves
+    fn view_reserves(&self) -> (BigUint, BigUint) {
+        if !self.reported_reserves().is_empty() {
+            return self.reported_reserves().get();
+        }
+        let first = self.first_token_id().get();
+        let second = self.second_token_id().get();
+        (self.reserve(&first).get(), self.reserve(&second).get())
+    }
+
+    /// xExchange safe price stand-in: quotes `input_payment` at `safePricePpm` of its amount
+    #[view(getSafePriceByDefaultOffset)]
+    fn get_safe_price_by_default_offset(
+        &self,
+        _pair_address: ManagedAddress,
+        input_payment: EsdtTokenPayment,
+    ) -> EsdtTokenPayment {
+        let amount = input_payment.amount * self.safe_price_ppm().get() / 1_000_000u32;
+        EsdtTokenPayment::new(self.other_token(&input_payment.token_identifier), 0, amount)
+    }
+
+    /// EGLD wrapper stand-in: pay out `wrappedEgldTokenId` 1:1 for the EGLD
+    #[payable("EGLD")]
+    #[endpoint(wrapEgld)]
+    fn wrap_egld(&self) {
+        let amount = self.call_value().egld().clone();
+        self.tx()
+            .to(self.blockchain().get_caller())
+            .single_esdt(&self.wrapped_egld_token_id().get(), 0, &amount)
+            .transfer();
+    }
+
+    /// EGLD wrapper stand-in: pay out EGLD 1:1 for the wrapped token
+    #[payable("*")]
+    #[endpoint(unwrapEgld)]
+    fn unwrap_egld(&self) {
+        let payment = self.call_value().single().clone();
+        self.tx()
+            .to(self.blockchain().get_caller())
+            .egld(payment.amount.as_big_uint())
+            .transfer();
+    }
+
+    /// Hatom market stand-in: mint the LP token 1:1 for the paid underlying
+    #[payable("*")]
+    #[endpoint(mint)]
+    fn mint(&self) {
+        let payment = self.call_value().single().clone();
+        self.tx()
+            .to(self.blockchain().get_caller())
+            .single_esdt(
+                &self.lp_token_identifier().get(),
+                0,
+                payment.amount.as_big_uint(),
+            )
+            .transfer();
+    }
+
+    /// Hatom market stand-in: pay the underlying (`first_token_id`) 1:1 for the hTokens
+    #[payable("*")]
+    #[endpoint(redeem)]
+    fn redeem(&self, _underlying_amount: OptionalValue<BigUint>) {
+        let payment = self.call_value().single().clone();
+        if self.withhold_outputs().get() {
+            return;
+        }
+        self.tx()
+            .to(self.blockchain().get_caller())
+            .single_esdt(
+                &self.first_token_id().get(),
+                0,
+                payment.amount.as_big_uint(),
+            )
+            .transfer();
+    }
+
+    /// Hatom controller stand-in: hold the paid hTokens as collateral of the account
+    #[payable("*")]
+    #[endpoint(enterMarkets)]
+    fn enter_markets(&self, opt_account: OptionalValue<ManagedAddress>) {
+        let payment = self.call_value().single().clone();
+        let account = opt_account
+            .into_option()
+            .unwrap_or_else(|| self.blockchain().get_caller());
+        self.collateral(&account)
+            .update(|c| *c += payment.amount.as_big_uint());
+    }
+
+    /// Callback target recording the last `xoCallback` notification
+    #[endpoint(notify)]
+    fn notify(&self, token_out: TokenId, amount_out: BigUint) {
+        self.last_notified().set((token_out, amount_out));
+    }
+
+    /// Write any storage entry, for protocol accounts holding foreign storage layouts
+    #[endpoint(setRaw)]
+    fn set_raw(&self, key: ManagedBuffer, value: ManagedBuffer) {
+        SingleValueMapper::<Self::Api, ManagedBuffer>::new(
+            multiversx_sc::storage::StorageKey::from(key),
+        )
+        .set(value);
+    }
+
+    #[storage_mapper("first_token_id")]
+    fn first_token_id(&self) -> SingleValueMapper<TokenIdentifier>;
+
+    #[storage_mapper("second_token_id")]
+    fn second_token_id(&self) -> SingleValueMapper<TokenIdentifier>;
+
+    #[storage_mapper("lpTokenIdentifier")]
+    fn lp_token_identifier(&self) -> SingleValueMapper<TokenIdentifier>;
+
+    #[storage_mapper("reserve")]
+    fn reserve(&self, token_id: &TokenIdentifier) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("reportedReserves")]
+    fn reported_reserves(&self) -> SingleValueMapper<(BigUint, BigUint)>;
+
+    #[storage_mapper("lp_token_supply")]
+    fn lp_token_supply(&self) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("total_fee_percent")]
+    fn total_fee_percent(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("special_fee_percent")]
+    fn special_fee_percent(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("wrappedEgldTokenId")]
+    fn wrapped_egld_token_id(&self) -> SingleValueMapper<TokenIdentifier>;
+
+    #[storage_mapper("lastNotified")]
+    fn last_notified(&self) -> SingleValueMapper<(TokenId, BigUint)>;
+
+    #[storage_mapper("lpMintPpm")]
+    fn lp_mint_ppm(&self) -> SingleValueMapper<u32>;
+
+    #[storage_mapper("egldRefund")]
+    fn egld_refund(&self) -> SingleValueMapper<BigUint>;
+
+    /// Knob for a call that succeeds but keeps its output: swaps and redeems send
+    /// nothing back, adds withhold the LP and removes send only the first leg
+    #[storage_mapper("withholdOutputs")]
+    fn withhold_outputs(&self) -> SingleValueMapper<bool>;
+
+    #[storage_mapper("returnInput")]
+    fn return_input(&self) -> SingleValueMapper<bool>;
+
+    #[storage_mapper("safePricePpm")]
+    fn safe_price_ppm(&self) -> SingleValueMapper<u32>;
+
+    #[storage_mapper("collateral")]
+    fn collateral(&self, account: &ManagedAddress) -> SingleValueMapper<BigUint>;
+}
@@ -0,0 +1,82 @@
+//! Stable pool pricing every token 1:1, with the AshSwap and Jex stable endpoints
+
+multiversx_sc::imports!();
+
+#[multiversx_sc::contract]
+pub trait MockStable {
+    #[init]
+    fn init(&self) {}
+
+    /// AshSwap V1 swap
+    #[payable("*")]
+    #[endpoint(exchange)]
+    fn exchange(&self, token_out: TokenIdentifier, amount_out_min: BigUint) {
+        self.swap_one_to_one(token_out, amount_out_min);
+    }
+
+    /// AshSwap V1 swap by coin index, paying out coin `j`
+    #[payable("*")]
+    #[endpoint(exchangeIndexed)]
+    fn exchange_indexed(&self, i: u32, j: u32, amount_out_min: BigUint) {
+        self.last_coin_indices().set((i, j));
+        self.swap_one_to_one(self.coin(j).get(), amount_out_min);
+    }
+
+    /// Jex stable swap
+    #[payable("*")]
+    #[endpoint(swap)]
+    fn swap(&self, token_out: TokenIdentifier, amount_out_min: BigUint) {
+        self.swap_one_to_one(token_out, amount_out_min);
+    }
+
+    /// AshSwap V1 add (receiver required) and Jex stable add (no receiver)
+    /// Mints one LP per input unit, whatever the token
+    #[payable("*")]
+    #[endpoint(addLiquidity)]
+    fn add_liquidity(&self, min_mint: BigUint, opt_receiver: OptionalValue<ManagedAddress>) {
+        let mut minted = BigUint::zero();
+        for payment in self.call_value().all().iter() {
+            minted += payment.amount.as_big_uint();
+        }
+        self.last_add_min().set(&min_mint);
+        require!(minted >= min_mint, "Slippage exceeded");
+
+        let receiver = match opt_receiver {
+            OptionalValue::Some(receiver) => receiver,
+            OptionalValue::None => self.blockchain().get_caller(),
+        };
+        self.tx()
+            .to(receiver)
+            .single_esdt(&self.lp_token_identifier().get(), 0, &minted)
+            .transfer();
+    }
+
+    fn swap_one_to_one(&self, token_out: TokenIdentifier, amount_out_min: BigUint) {
+        let payment = self.call_value().single().clone();
+        let amount_out = payment.amount.as_big_uint().clone();
+        self.last_swap_min().set(&amount_out_min);
+        require!(amount_out >= amount_out_min, "Slippage exceeded");
+        self.tx()
+            .to(self.blockchain().get_caller())
+            .single_esdt(&token_out, 0, &amount_out)
+            .transfer();
+    }
+
+    #[storage_mapper("lpTokenIdentifier")]
+    fn lp_token_identifier(&self) -> SingleValueMapper<TokenIdentifier>;
+
+    #[storage_mapper("coin")]
+    fn coin(&self, index: u32) -> SingleValueMapper<TokenIdentifier>;
+
+    /// Coin indices (i, j) the last indexed swap was called with
+    #[storage_mapper("lastCoinIndices")]
+    fn last_coin_indices(&self) -> SingleValueMapper<(u32, u32)>;
+
+    /// Minimum the last add was called with
+    #[storage_mapper("lastAddMin")]
+    fn last_add_min(&self) -> SingleValueMapper<BigUint>;
+
+    /// Minimum the last swap was called with
+    #[storage_mapper("lastSwapMin")]
+    fn last_swap_min(&self) -> SingleValueMapper<BigUint>;
+}
@@ -0,0 +1,51 @@
+//! Xoxno-style staking contract: claims pay a fixed reward, delegation mints 1:1
+
+multiversx_sc::imports!();
+
+#[multiversx_sc::contract]
+pub trait MockStaking {
+    #[init]
+    fn init(&self) {}
+
+    /// Keeps the payment and pays `rewardAmount` of the reward token, if any
+    #[payable("*")]
+    #[endpoint(claimRewards)]
+    fn claim_rewards(&self) {
+        if self.reward_amount().get() == 0u32 {
+            return;
+        }
+        self.tx()
+            .to(self.blockchain().get_caller())
+            .single_esdt(&self.reward_token().get(), 0, &self.reward_amount().get())
+            .transfer();
+    }
+
+    /// Mints one staked token per reward token (or native EGLD, as Hatom) paid in
+    #[payable("*")]
+    #[endpoint(delegate)]
+    fn delegate(&self, to: OptionalValue<ManagedAddress>) {
+        let payment = self.call_value().egld_or_single_esdt();
+        require!(
+            payment.token_identifier.is_egld()
+                || payment.token_identifier == self.reward_token().get(),
+            "Only the reward token can be delegated"
+        );
+        let receiver = match to {
+            OptionalValue::Some(receiver) => receiver,
+            OptionalValue::None => self.blockchain().get_caller(),
+        };
+        self.tx()
+            .to(receiver)
+            .single_esdt(&self.staked_token().get(), 0, &payment.amount)
+            .transfer();
+    }
+
+    #[storage_mapper("rewardToken")]
+    fn reward_token(&self) -> SingleValueMapper<TokenIdentifier>;
+
+    #[storage_mapper("rewardAmount")]
+    fn reward_amount(&self) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("stakedToken")]
+    fn staked_token(&self) -> SingleValueMapper<TokenIdentifier>;
+}
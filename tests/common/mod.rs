@@ -0,0 +1,430 @@
+#![allow(dead_code)]
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::TxResponseStatus;
+
+use aggregator::Aggregator;
+
+pub const OWNER: TestAddress = TestAddress::new("owner");
+pub const USER: TestAddress = TestAddress::new("user");
+pub const REFERRER: TestAddress = TestAddress::new("referrer");
+pub const AGG: TestSCAddress = TestSCAddress::new("aggregator");
+pub const CODE: MxscPath = MxscPath::new("output/aggregator.mxsc.json");
+
+pub const TOKEN_A: TestTokenIdentifier = TestTokenIdentifier::new("TOKA-000001");
+pub const TOKEN_B: TestTokenIdentifier = TestTokenIdentifier::new("TOKB-000002");
+pub const TOKEN_C: TestTokenIdentifier = TestTokenIdentifier::new("TOKC-000003");
+pub const TOKEN_D: TestTokenIdentifier = TestTokenIdentifier::new("TOKD-000004");
+pub const WEGLD: TestTokenIdentifier = TestTokenIdentifier::new("WEGLD-000007");
+
+/// World with the aggregator deployed by OWNER and funded user accounts
+pub fn setup() -> ScenarioWorld {
+    let mut world = ScenarioWorld::new();
+    world.register_contract(CODE, aggregator::ContractBuilder);
+    world.account(OWNER).nonce(1).balance(1_000_000u64);
+    world
+        .account(USER)
+        .nonce(1)
+        .balance(1_000_000u64)
+        .esdt_balance(TOKEN_A, 1_000_000u64)
+        .esdt_balance(TOKEN_B, 1_000_000u64);
+    world.account(REFERRER).nonce(1);
+    world.register_contract(MOCK_PAIR_CODE, mock_pair::ContractBuilder);
+    world.register_contract(MOCK_STABLE_CODE, mock_stable::ContractBuilder);
+    world.register_contract(MOCK_STAKING_CODE, mock_staking::ContractBuilder);
+    for protocol in [
+        &aggregator::constants::XEXCHANGE_ROUTER,
+        &aggregator::constants::ONE_DEX_ROUTER,
+        &aggregator::constants::WRAPPER_SC,
+        &aggregator::constants::HATOM_CONTROLLER,
+    ] {
+        protocol_account(&mut world, protocol);
+    }
+    set_protocol_storage(
+        &mut world,
+        &aggregator::constants::WRAPPER_SC,
+        b"wrappedEgldTokenId",
+        WEGLD.as_bytes(),
+    );
+    world
+        .tx()
+        .from(OWNER)
+        .raw_deploy()
+        .code(CODE)
+        .new_address(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.init();
+        });
+    world
+}
+
+/// Address of a hardcoded protocol contract, as a scenario account key
+pub fn protocol_address(bytes: &[u8; 32]) -> Address {
+    Address::from_slice(bytes)
+}
+
+/// Put mock code on a hardcoded protocol address so it can hold storage
+pub fn protocol_account(world: &mut ScenarioWorld, bytes: &[u8; 32]) {
+    world
+        .account(protocol_address(bytes))
+        .nonce(1)
+        .code(MOCK_PAIR_CODE)
+        .owner(OWNER);
+}
+
+/// Write a raw storage entry on a protocol account created by `protocol_account`
+pub fn set_protocol_storage(world: &mut ScenarioWorld, bytes: &[u8; 32], key: &[u8], value: &[u8]) {
+    let key = key.to_vec();
+    let value = value.to_vec();
+    world
+        .tx()
+        .from(OWNER)
+        .to(protocol_address(bytes))
+        .whitebox(mock_pair::contract_obj, |sc| {
+            use mock_pair::MockPair;
+            sc.set_raw(ManagedBuffer::from(key), ManagedBuffer::from(value));
+        });
+}
+
+/// Managed token id for use inside whitebox closures
+pub fn token_id(token: TestTokenIdentifier) -> TokenId<DebugApi> {
+    TokenId::from(token.as_bytes())
+}
+
+pub fn esdt(token: TestTokenIdentifier) -> TokenIdentifier<DebugApi> {
+    TokenIdentifier::from(token.as_bytes())
+}
+
+pub mod mock_pair;
+pub mod mock_stable;
+pub mod mock_staking;
+
+pub const PAIR: TestSCAddress = TestSCAddress::new("pair-ab");
+pub const MOCK_PAIR_CODE: MxscPath = MxscPath::new("output/mock-pair.mxsc.json");
+pub const LP_TOKEN: TestTokenIdentifier = TestTokenIdentifier::new("LPAB-000005");
+
+pub const STABLE_POOL: TestSCAddress = TestSCAddress::new("stable-pool");
+pub const MOCK_STABLE_CODE: MxscPath = MxscPath::new("output/mock-stable.mxsc.json");
+pub const STABLE_LP: TestTokenIdentifier = TestTokenIdentifier::new("STLP-000006");
+
+/// 1:1 stable pool at STABLE_POOL holding `liquidity` of A and B and LP to mint
+pub fn deploy_stable_pool(world: &mut ScenarioWorld, liquidity: u64) {
+    world
+        .account(STABLE_POOL)
+        .nonce(1)
+        .code(MOCK_STABLE_CODE)
+        .owner(OWNER)
+        .esdt_balance(TOKEN_A, liquidity)
+        .esdt_balance(TOKEN_B, liquidity)
+        .esdt_balance(STABLE_LP, 1_000_000_000_000u64);
+    world
+        .tx()
+        .from(OWNER)
+        .to(STABLE_POOL)
+        .whitebox(mock_stable::contract_obj, |sc| {
+            use mock_stable::MockStable;
+            sc.lp_token_identifier().set(esdt(STABLE_LP));
+        });
+}
+
+pub const MOCK_STAKING_CODE: MxscPath = MxscPath::new("output/mock-staking.mxsc.json");
+
+/// Xoxno staking mock at LXOXNO_STAKING: each claim pays `reward` of TOKEN_C and
+/// delegating TOKEN_C mints TOKEN_D 1:1
+pub fn deploy_staking(world: &mut ScenarioWorld, reward: u64) {
+    world
+        .account(protocol_address(&aggregator::constants::LXOXNO_STAKING))
+        .nonce(1)
+        .code(MOCK_STAKING_CODE)
+        .owner(OWNER)
+        .esdt_balance(TOKEN_C, 1_000_000_000u64)
+        .esdt_balance(TOKEN_D, 1_000_000_000u64);
+    world
+        .tx()
+        .from(OWNER)
+        .to(protocol_address(&aggregator::constants::LXOXNO_STAKING))
+        .whitebox(mock_staking::contract_obj, |sc| {
+            use mock_staking::MockStaking;
+            sc.reward_token().set(esdt(TOKEN_C));
+            sc.reward_amount().set(BigUint::from(reward));
+            sc.staked_token().set(esdt(TOKEN_D));
+        });
+}
+
+/// Hatom staking mock at HATOM_STAKING: delegating native EGLD mints TOKEN_D 1:1
+pub fn deploy_hatom_staking(world: &mut ScenarioWorld) {
+    let staking = protocol_address(&aggregator::constants::HATOM_STAKING);
+    world
+        .account(staking.clone())
+        .nonce(1)
+        .code(MOCK_STAKING_CODE)
+        .owner(OWNER)
+        .esdt_balance(TOKEN_D, 1_000_000_000u64);
+    world
+        .tx()
+        .from(OWNER)
+        .to(staking)
+        .whitebox(mock_staking::contract_obj, |sc| {
+            use mock_staking::MockStaking;
+            sc.staked_token().set(esdt(TOKEN_D));
+        });
+}
+
+/// xExchange-style A/B pair at PAIR with the given reserves, LP supply and fee
+/// (out of 100,000), funded with enough LP to mint from
+pub fn deploy_pair(
+    world: &mut ScenarioWorld,
+    reserve_a: u64,
+    reserve_b: u64,
+    lp_supply: u64,
+    total_fee_percent: u64,
+) {
+    deploy_pair_at(
+        world,
+        PAIR,
+        (TOKEN_A, TOKEN_B),
+        LP_TOKEN,
+        (reserve_a, reserve_b),
+        lp_supply,
+        total_fee_percent,
+    );
+}
+
+/// xExchange-style pair at `pair` trading `tokens` (first, second) with `reserves`,
+/// registered as the pair of those tokens and funded with enough `lp_token` to mint
+pub fn deploy_pair_at(
+    world: &mut ScenarioWorld,
+    pair: TestSCAddress,
+    tokens: (TestTokenIdentifier, TestTokenIdentifier),
+    lp_token: TestTokenIdentifier,
+    reserves: (u64, u64),
+    lp_supply: u64,
+    total_fee_percent: u64,
+) {
+    let (first, second) = tokens;
+    world
+        .account(pair)
+        .nonce(1)
+        .code(MOCK_PAIR_CODE)
+        .owner(OWNER)
+        .esdt_balance(first, reserves.0)
+        .esdt_balance(second, reserves.1)
+        .esdt_balance(lp_token, 1_000_000_000_000u64);
+    world
+        .tx()
+        .from(OWNER)
+        .to(pair)
+        .whitebox(mock_pair::contract_obj, |sc| {
+            use mock_pair::MockPair;
+            sc.first_token_id().set(esdt(first));
+            sc.second_token_id().set(esdt(second));
+            sc.lp_token_identifier().set(esdt(lp_token));
+            sc.reserve(&esdt(first)).set(BigUint::from(reserves.0));
+            sc.reserve(&esdt(second)).set(BigUint::from(reserves.1));
+            sc.lp_token_supply().set(BigUint::from(lp_supply));
+            sc.total_fee_percent().set(total_fee_percent);
+        });
+    // The aggregator resolves xExchange pairs by token pair, not by the address byte
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            use aggregator::storage::Storage;
+            sc.pair_override(&aggregator::types::PairTokens {
+                first_token_id: esdt(first),
+                second_token_id: esdt(second),
+            })
+            .set(pair.to_managed_address());
+        });
+}
+
+/// World from `setup` with the standard 1:1 A/B pair at PAIR (1,000,000 each, 0.3% fee)
+pub fn setup_pair() -> ScenarioWorld {
+    let mut world = setup();
+    deploy_pair(&mut world, 1_000_000, 1_000_000, 1_000_000, 300);
+    world
+}
+
+pub const JEX_POOL: TestSCAddress = TestSCAddress::new("jex-pool");
+
+/// Bare mock pool account at `pool`, for tests that fill its storage themselves
+pub fn deploy_mock_pool(world: &mut ScenarioWorld, pool: TestSCAddress) {
+    world
+        .account(pool)
+        .nonce(1)
+        .code(MOCK_PAIR_CODE)
+        .owner(OWNER);
+}
+
+/// Write a raw storage entry of the mock pool at `pool`, as a DEX would lay it out
+pub fn set_pool_storage(world: &mut ScenarioWorld, pool: TestSCAddress, key: &[u8], value: &[u8]) {
+    let key = key.to_vec();
+    let value = value.to_vec();
+    world
+        .tx()
+        .from(OWNER)
+        .to(pool)
+        .whitebox(mock_pair::contract_obj, |sc| {
+            use mock_pair::MockPair;
+            sc.set_raw(
+                ManagedBuffer::from(key.as_slice()),
+                ManagedBuffer::from(value.as_slice()),
+            );
+        });
+}
+
+pub const WEGLD_PAIR: TestSCAddress = TestSCAddress::new("pair-wegld-b");
+pub const WEGLD_LP: TestTokenIdentifier = TestTokenIdentifier::new("LPWB-000008");
+
+/// Fund the wrapper with `liquidity` of both EGLD and WEGLD, so it can wrap and unwrap
+pub fn fund_wrapper(world: &mut ScenarioWorld, liquidity: u64) {
+    let wrapper = protocol_address(&aggregator::constants::WRAPPER_SC);
+    world.set_egld_balance(wrapper.clone(), liquidity);
+    world.set_esdt_balance(wrapper, WEGLD.as_bytes(), liquidity);
+}
+
+/// Compact instruction row
+pub type Row = (u8, u8, u8, u8, u8, u16);
+
+/// Run `xo` as USER with one payment, through a whitebox call
+#[allow(clippy::too_many_arguments)]
+pub fn xo_call(
+    world: &mut ScenarioWorld,
+    payment: (TestTokenIdentifier, u64),
+    min_amount_out: u64,
+    token_out: u8,
+    referral_id: u64,
+    tokens: &[TestTokenIdentifier],
+    addresses: &[TestSCAddress],
+    amounts: &[u64],
+    rows: &[Row],
+) -> Result<(), TxResponseStatus> {
+    let tokens = tokens.to_vec();
+    let addresses = addresses.to_vec();
+    let amounts = amounts.to_vec();
+    let rows = rows.to_vec();
+    world
+        .tx()
+        .from(USER)
+        .to(AGG)
+        .esdt(TestEsdtTransfer(payment.0, 0, payment.1))
+        .returns(ReturnsHandledOrError::new())
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.aggregate(
+                aggregator::constants::FORMAT_VERSION,
+                BigUint::from(min_amount_out),
+                token_out,
+                referral_id,
+                registry_tokens(&tokens),
+                registry_addresses(&addresses),
+                registry_amounts(&amounts),
+                instruction_rows(&rows),
+            );
+        })
+}
+
+/// Run `xo` as USER paying native EGLD, through a whitebox call
+#[allow(clippy::too_many_arguments)]
+pub fn xo_call_egld(
+    world: &mut ScenarioWorld,
+    egld_amount: u64,
+    min_amount_out: u64,
+    token_out: u8,
+    referral_id: u64,
+    tokens: &[TestTokenIdentifier],
+    addresses: &[TestSCAddress],
+    amounts: &[u64],
+    rows: &[Row],
+) -> Result<(), TxResponseStatus> {
+    let tokens = tokens.to_vec();
+    let addresses = addresses.to_vec();
+    let amounts = amounts.to_vec();
+    let rows = rows.to_vec();
+    world
+        .tx()
+        .from(USER)
+        .to(AGG)
+        .egld(egld_amount)
+        .returns(ReturnsHandledOrError::new())
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.aggregate(
+                aggregator::constants::FORMAT_VERSION,
+                BigUint::from(min_amount_out),
+                token_out,
+                referral_id,
+                registry_tokens(&tokens),
+                registry_addresses(&addresses),
+                registry_amounts(&amounts),
+                instruction_rows(&rows),
+            );
+        })
+}
+
+pub fn registry_tokens(
+    tokens: &[TestTokenIdentifier],
+) -> MultiValueEncodedCounted<DebugApi, TokenIdentifier<DebugApi>> {
+    let mut out = MultiValueEncodedCounted::new();
+    for token in tokens {
+        out.push(esdt(*token));
+    }
+    out
+}
+
+pub fn registry_addresses(
+    addresses: &[TestSCAddress],
+) -> MultiValueEncodedCounted<DebugApi, ManagedAddress<DebugApi>> {
+    let mut out = MultiValueEncodedCounted::new();
+    for address in addresses {
+        out.push(address.to_managed_address());
+    }
+    out
+}
+
+pub fn registry_amounts(amounts: &[u64]) -> MultiValueEncodedCounted<DebugApi, BigUint<DebugApi>> {
+    let mut out = MultiValueEncodedCounted::new();
+    for amount in amounts {
+        out.push(BigUint::from(*amount));
+    }
+    out
+}
+
+pub fn instruction_rows(
+    rows: &[Row],
+) -> MultiValueEncoded<DebugApi, MultiValue6<u8, u8, u8, u8, u8, u16>> {
+    let mut out = MultiValueEncoded::new();
+    for row in rows {
+        out.push((*row).into());
+    }
+    out
+}
+
+pub const HATOM_MARKET: TestSCAddress = TestSCAddress::new("hatom-market");
+
+/// Hatom market at HATOM_MARKET minting LP_TOKEN as its hToken, 1:1 for any underlying,
+/// registered as the LP_TOKEN market override
+pub fn deploy_hatom_market(world: &mut ScenarioWorld) {
+    world
+        .account(HATOM_MARKET)
+        .nonce(1)
+        .code(MOCK_PAIR_CODE)
+        .owner(OWNER)
+        .esdt_balance(LP_TOKEN, 1_000_000_000_000u64);
+    world
+        .tx()
+        .from(OWNER)
+        .to(HATOM_MARKET)
+        .whitebox(mock_pair::contract_obj, |sc| {
+            use mock_pair::MockPair;
+            sc.lp_token_identifier().set(esdt(LP_TOKEN));
+        });
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            use aggregator::storage::Storage;
+            sc.market_override(&esdt(LP_TOKEN))
+                .set(HATOM_MARKET.to_managed_address());
+        });
+}
@@ -0,0 +1,117 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+
+use aggregator::types::CompactAction;
+use aggregator::utils::Utils;
+use common::*;
+
+/// Wire byte of every action
+/// Exhaustive on purpose: a new variant fails to compile here until it is listed,
+/// and listed with a byte `ALL` does not map back to it, the tests below fail
+fn wire_byte(action: CompactAction) -> u8 {
+    match action {
+        CompactAction::XExchangeSwap => 0,
+        CompactAction::XExchangeAddLiquidity => 1,
+        CompactAction::XExchangeRemoveLiquidity => 2,
+        CompactAction::AshSwapPoolSwap => 3,
+        CompactAction::AshSwapPoolAddLiquidity => 4,
+        CompactAction::AshSwapPoolRemoveLiquidity => 5,
+        CompactAction::AshSwapV2Swap => 6,
+        CompactAction::AshSwapV2AddLiquidity => 7,
+        CompactAction::AshSwapV2RemoveLiquidity => 8,
+        CompactAction::OneDexSwap => 9,
+        CompactAction::OneDexAddLiquidity => 10,
+        CompactAction::OneDexRemoveLiquidity => 11,
+        CompactAction::JexSwap => 12,
+        CompactAction::JexAddLiquidity => 13,
+        CompactAction::JexRemoveLiquidity => 14,
+        CompactAction::JexStableSwap => 15,
+        CompactAction::JexStableAddLiquidity => 16,
+        CompactAction::JexStableRemoveLiquidity => 17,
+        CompactAction::Wrapping => 18,
+        CompactAction::UnWrapping => 19,
+        CompactAction::XoxnoLiquidStaking => 20,
+        CompactAction::LXoxnoLiquidStaking => 21,
+        CompactAction::HatomLiquidStaking => 22,
+        CompactAction::HatomRedeem => 23,
+        CompactAction::HatomSupply => 24,
+        CompactAction::ClearPrev => 25,
+        CompactAction::XExchangeRemoveLiquiditySingle => 26,
+        CompactAction::AshSwapPoolAddLiquidityToCaller => 27,
+        CompactAction::ClaimRewards => 28,
+        CompactAction::AshSwapV2SwapMin => 29,
+        CompactAction::SetMinLpOut => 30,
+        CompactAction::SetZapRatio => 31,
+        CompactAction::ExpectOutputs => 32,
+        CompactAction::HatomCompound => 33,
+        CompactAction::HatomEnterMarket => 34,
+        CompactAction::UnwrapOutputs => 35,
+        CompactAction::AshSwapPoolSwapIndexed => 36,
+        CompactAction::AllowFailure => 37,
+        CompactAction::RequireFullInput => 38,
+        CompactAction::LpToCaller => 39,
+        CompactAction::SetMinLpValue => 40,
+        CompactAction::ClaimAndRestake => 41,
+    }
+}
+
+/// Highest wire byte listed in `wire_byte`, update together with it
+const LAST_WIRE_BYTE: u8 = 41;
+
+#[test]
+fn from_u8_matches_wire_bytes() {
+    assert_eq!(CompactAction::ALL.len(), LAST_WIRE_BYTE as usize + 1);
+    for byte in 0..=u8::MAX {
+        match CompactAction::from_u8(byte) {
+            Some(action) => {
+                assert_eq!(wire_byte(action), byte);
+                assert_eq!(action.to_u8(), byte);
+            }
+            None => assert!(byte > LAST_WIRE_BYTE, "byte {byte} does not decode"),
+        }
+    }
+}
+
+#[test]
+fn to_u8_round_trips_through_from_u8() {
+    for action in CompactAction::ALL {
+        assert_eq!(CompactAction::from_u8(action.to_u8()), Some(action));
+    }
+}
+
+#[test]
+fn every_action_builds_its_action_type() {
+    let mut world = setup();
+
+    // OneDex adds check the pair exists at the router
+    set_protocol_storage(
+        &mut world,
+        &aggregator::constants::ONE_DEX_ROUTER,
+        b"last_pair_id",
+        &[1],
+    );
+
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            let mut tokens = ManagedVec::new();
+            for token in [TOKEN_A, TOKEN_B, TOKEN_C, TOKEN_D] {
+                tokens.push(esdt(token));
+            }
+            let mut amounts = ManagedVec::new();
+            for amount in 1u64..=4 {
+                amounts.push(BigUint::from(amount));
+            }
+
+            for byte in 0..=u8::MAX {
+                let Some(action) = CompactAction::from_u8(byte) else {
+                    continue;
+                };
+                // Canonical bytes: every token/amount index in range, distinct coin indices
+                let built = sc.build_action_type(&action, 0, 1, 2, 3, 1, &tokens, &amounts);
+                assert_eq!(built.compact(), action, "action byte {byte}");
+            }
+        });
+}
@@ -0,0 +1,119 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::TxResponseStatus;
+
+use aggregator::config::Config;
+use aggregator::errors::ERR_REFERRAL_NOT_FOUND;
+use aggregator::storage::Storage;
+use common::*;
+
+const SWAP_A_TO_B: Row = (0, 1, 0, 0, 0, 0);
+
+/// Pair deployed, with referral 1 at 1% and referral 2 at 2%
+fn setup_referrals() -> ScenarioWorld {
+    let mut world = setup_pair();
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.add_referral(REFERRER.to_managed_address(), 100);
+            sc.add_referral(REFERRER.to_managed_address(), 200);
+        });
+    world
+}
+
+fn set_my_referral(world: &mut ScenarioWorld, id: u64) -> Result<(), TxResponseStatus> {
+    world
+        .tx()
+        .from(USER)
+        .to(AGG)
+        .returns(ReturnsHandledOrError::new())
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.set_my_referral(id);
+        })
+}
+
+fn swap(world: &mut ScenarioWorld, referral_id: u64) {
+    xo_call(
+        world,
+        (TOKEN_A, 1_000),
+        0,
+        1,
+        referral_id,
+        &[TOKEN_A, TOKEN_B],
+        &[PAIR],
+        &[],
+        &[SWAP_A_TO_B],
+    )
+    .unwrap();
+}
+
+/// B booked for (referral 1, referral 2)
+fn referral_balances(world: &mut ScenarioWorld) -> (u64, u64) {
+    let mut out = (0, 0);
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            let booked = |id| {
+                sc.referrer_balances(id)
+                    .get(&token_id(TOKEN_B))
+                    .unwrap_or_default()
+                    .to_u64()
+                    .unwrap()
+            };
+            out = (booked(1), booked(2));
+        });
+    out
+}
+
+#[test]
+fn the_default_referral_applies_when_none_is_passed() {
+    let mut world = setup_referrals();
+    set_my_referral(&mut world, 1).unwrap();
+
+    swap(&mut world, 0);
+
+    assert_eq!(referral_balances(&mut world), (9, 0));
+}
+
+#[test]
+fn an_explicit_referral_overrides_the_default() {
+    let mut world = setup_referrals();
+    set_my_referral(&mut world, 1).unwrap();
+
+    swap(&mut world, 2);
+
+    assert_eq!(referral_balances(&mut world), (0, 19));
+}
+
+#[test]
+fn without_a_default_no_referral_is_applied() {
+    let mut world = setup_referrals();
+
+    swap(&mut world, 0);
+
+    assert_eq!(referral_balances(&mut world), (0, 0));
+}
+
+#[test]
+fn setting_zero_clears_the_default() {
+    let mut world = setup_referrals();
+    set_my_referral(&mut world, 1).unwrap();
+    set_my_referral(&mut world, 0).unwrap();
+
+    swap(&mut world, 0);
+
+    assert_eq!(referral_balances(&mut world), (0, 0));
+}
+
+#[test]
+fn an_unknown_default_referral_is_rejected() {
+    let mut world = setup_referrals();
+
+    let result = set_my_referral(&mut world, 3);
+
+    assert_eq!(result.unwrap_err().message, ERR_REFERRAL_NOT_FOUND);
+}
@@ -0,0 +1,117 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+
+use aggregator::config::Config;
+use aggregator::storage::Storage;
+use common::*;
+
+const SWAP_A_TO_B: Row = (0, 1, 0, 0, 0, 0);
+
+/// Referral 1 at 1%, with a pool to trade through
+fn setup_referral() -> ScenarioWorld {
+    let mut world = setup_pair();
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.add_referral(REFERRER.to_managed_address(), 100);
+        });
+    world
+}
+
+fn set_drain_mode(world: &mut ScenarioWorld, enabled: bool) {
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.set_drain_mode(enabled);
+        });
+}
+
+fn swap_with_referral(world: &mut ScenarioWorld) {
+    xo_call(
+        world,
+        (TOKEN_A, 1_000),
+        0,
+        1,
+        1,
+        &[TOKEN_A, TOKEN_B],
+        &[PAIR],
+        &[],
+        &[SWAP_A_TO_B],
+    )
+    .unwrap();
+}
+
+/// (admin, referral) fee balances in TOKEN_B
+fn fee_balances(world: &mut ScenarioWorld) -> (u64, u64) {
+    let mut balances = (0, 0);
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            let token = token_id(TOKEN_B);
+            let admin = sc.admin_fees().get(&token).unwrap_or_default();
+            let referral = sc.referrer_balances(1).get(&token).unwrap_or_default();
+            balances = (admin.to_u64().unwrap(), referral.to_u64().unwrap());
+        });
+    balances
+}
+
+#[test]
+fn draining_routes_the_referral_share_to_admin() {
+    let mut normal = setup_referral();
+    swap_with_referral(&mut normal);
+    let (admin, referral) = fee_balances(&mut normal);
+    assert!(referral > 0);
+
+    let mut draining = setup_referral();
+    set_drain_mode(&mut draining, true);
+    swap_with_referral(&mut draining);
+
+    assert_eq!(fee_balances(&mut draining), (admin + referral, 0));
+    let user_out = 1_000_000u64 + 996 - admin - referral;
+    draining.check_account(USER).esdt_balance(TOKEN_B, user_out);
+}
+
+#[test]
+fn draining_freezes_but_keeps_claimable_referral_balances() {
+    let mut world = setup_referral();
+    swap_with_referral(&mut world);
+    let (_, referral) = fee_balances(&mut world);
+
+    set_drain_mode(&mut world, true);
+    swap_with_referral(&mut world);
+    assert_eq!(fee_balances(&mut world).1, referral);
+
+    world
+        .tx()
+        .from(REFERRER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            let mut tokens = MultiValueEncoded::new();
+            tokens.push(token_id(TOKEN_B));
+            sc.claim_referral_fees(1, tokens);
+        });
+
+    world
+        .check_account(REFERRER)
+        .esdt_balance(TOKEN_B, referral);
+    assert_eq!(fee_balances(&mut world).1, 0);
+}
+
+#[test]
+fn leaving_drain_mode_resumes_referral_accrual() {
+    let mut world = setup_referral();
+    set_drain_mode(&mut world, true);
+    swap_with_referral(&mut world);
+    assert_eq!(fee_balances(&mut world).1, 0);
+
+    set_drain_mode(&mut world, false);
+    swap_with_referral(&mut world);
+
+    assert!(fee_balances(&mut world).1 > 0);
+}
@@ -0,0 +1,49 @@
+mod common;
+
+use common::*;
+
+/// xExchange swap of all A into B
+const SWAP_A_TO_B: Row = (0, 1, 0, 0, 0, 0);
+
+#[test]
+fn a_registry_listing_a_token_twice_is_rejected() {
+    let mut world = setup_pair();
+
+    let result = xo_call(
+        &mut world,
+        (TOKEN_A, 1_000),
+        0,
+        1,
+        0,
+        &[TOKEN_A, TOKEN_B, TOKEN_A],
+        &[PAIR],
+        &[],
+        &[SWAP_A_TO_B],
+    );
+
+    assert_eq!(
+        result.unwrap_err().message,
+        "D: Duplicate token in registry: TOKA-000001 at 0 and 2"
+    );
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_A, 1_000_000u64);
+}
+
+#[test]
+fn a_registry_of_distinct_tokens_decodes() {
+    let mut world = setup_pair();
+
+    xo_call(
+        &mut world,
+        (TOKEN_A, 1_000),
+        0,
+        1,
+        0,
+        &[TOKEN_A, TOKEN_B, TOKEN_C],
+        &[PAIR],
+        &[],
+        &[SWAP_A_TO_B],
+    )
+    .unwrap();
+}
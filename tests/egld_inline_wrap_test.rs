@@ -0,0 +1,77 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+
+use common::*;
+
+const ALLOW_FAILURE: Row = (37, 0, 0, 0, 0, 255);
+/// EGLD -> B swap of amounts[0] on the WEGLD/B pair, with no wrap instruction
+const SWAP_EGLD_FIXED_0: Row = (0, 1, 254, 2, 0, 0);
+/// EGLD -> B swap of amounts[1], too small for the pool to produce any output
+const SWAP_EGLD_FIXED_1: Row = (0, 1, 254, 3, 0, 0);
+
+fn setup_wegld_pair() -> ScenarioWorld {
+    let mut world = setup();
+    deploy_pair_at(
+        &mut world,
+        WEGLD_PAIR,
+        (WEGLD, TOKEN_B),
+        WEGLD_LP,
+        (1_000_000, 1_000_000),
+        1_000_000,
+        300,
+    );
+    fund_wrapper(&mut world, 1_000_000);
+    world
+}
+
+#[test]
+fn native_egld_is_wrapped_inline_for_an_xexchange_swap() {
+    let mut world = setup_wegld_pair();
+
+    xo_call_egld(
+        &mut world,
+        1_000,
+        996,
+        1,
+        0,
+        &[WEGLD, TOKEN_B],
+        &[WEGLD_PAIR],
+        &[1_000],
+        &[SWAP_EGLD_FIXED_0],
+    )
+    .unwrap();
+
+    world
+        .check_account(USER)
+        .balance(1_000_000u64 - 1_000)
+        .esdt_balance(TOKEN_B, 1_000_996u64);
+    world
+        .check_account(WEGLD_PAIR)
+        .esdt_balance(WEGLD, 1_001_000u64);
+}
+
+#[test]
+fn a_failed_inline_wrapped_swap_refunds_native_egld() {
+    let mut world = setup_wegld_pair();
+
+    xo_call_egld(
+        &mut world,
+        2_000,
+        0,
+        1,
+        0,
+        &[WEGLD, TOKEN_B],
+        &[WEGLD_PAIR],
+        &[1_000, 1],
+        &[SWAP_EGLD_FIXED_0, ALLOW_FAILURE, SWAP_EGLD_FIXED_1],
+    )
+    .unwrap();
+
+    // The skipped 1 EGLD comes back as EGLD, not as the WEGLD it was wrapped into
+    world.check_account(USER).balance(1_000_000u64 - 2_000 + 1);
+    world.check_account(USER).esdt_balance(WEGLD, 0u64);
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_B, 1_000_996u64);
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+
+use common::*;
+
+/// Wrap all the paid EGLD, then swap all WEGLD to B
+const WRAP: Row = (18, 254, 0, 255, 0, 255);
+const SWAP_WEGLD_TO_B: Row = (0, 1, 0, 0, 0, 0);
+
+/// WEGLD/B pair that fills only part of each swap and refunds the rest as native EGLD
+fn setup_egld_refund(egld_refund: u64) -> ScenarioWorld {
+    let mut world = setup();
+    deploy_pair_at(
+        &mut world,
+        WEGLD_PAIR,
+        (WEGLD, TOKEN_B),
+        WEGLD_LP,
+        (1_000_000, 1_000_000),
+        1_000_000,
+        300,
+    );
+    world.set_egld_balance(WEGLD_PAIR, 1_000_000u64);
+    fund_wrapper(&mut world, 1_000_000);
+    world
+        .tx()
+        .from(OWNER)
+        .to(WEGLD_PAIR)
+        .whitebox(mock_pair::contract_obj, |sc| {
+            use mock_pair::MockPair;
+            sc.egld_refund().set(BigUint::from(egld_refund));
+        });
+    world
+}
+
+#[test]
+fn an_egld_refund_of_a_wegld_input_is_kept_as_wegld() {
+    let mut world = setup_egld_refund(100);
+
+    xo_call_egld(
+        &mut world,
+        1_000,
+        0,
+        1,
+        0,
+        &[WEGLD, TOKEN_B],
+        &[WEGLD_PAIR],
+        &[],
+        &[WRAP, SWAP_WEGLD_TO_B],
+    )
+    .unwrap();
+
+    // 900 WEGLD swapped; the 100 EGLD refunded is wrapped back and booked as WEGLD dust
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_B, 1_000_000u64 + 896);
+    world
+        .check_account(AGG)
+        .balance(0u64)
+        .esdt_balance(WEGLD, 100u64);
+}
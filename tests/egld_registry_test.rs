@@ -0,0 +1,75 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+
+use aggregator::types::IDX_EGLD;
+use aggregator::utils::{TokenRegistry, Utils};
+use aggregator::vault::Vault;
+use aggregator::Aggregator;
+use common::*;
+
+/// Typed argument decoding rejects EGLD in the registry, build the raw entry to get past it
+fn raw_registry(entry: &str) -> TokenRegistry<DebugApi> {
+    let raw = unsafe { TokenId::new_unchecked(ManagedBuffer::from(entry)) };
+    let mut tokens = ManagedVec::new();
+    tokens.push(unsafe { TokenIdentifier::esdt_unchecked(raw.into()) });
+    tokens
+}
+
+#[test]
+fn registry_egld_entries_share_the_idx_egld_balance() {
+    let mut world = setup();
+
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            for entry in ["EGLD-000000", "EGLD", ""] {
+                let tokens = raw_registry(entry);
+                let native = sc.resolve_token_to_id(IDX_EGLD, &tokens);
+                let registry = sc.resolve_token_to_id(0, &tokens);
+                assert_eq!(registry, native);
+
+                let mut vault = Vault::<DebugApi>::new();
+                let amount = BigUint::from(1_000u64).into_non_zero().unwrap();
+                vault.deposit(&native, &amount);
+                vault.deposit(&registry, &amount);
+                assert_eq!(vault.len(), 1);
+                assert_eq!(vault.balance_of(&native), 2_000u64);
+            }
+        });
+}
+
+#[test]
+fn aggregate_rejects_an_egld_registry_entry() {
+    let mut world = setup();
+
+    let result = world
+        .tx()
+        .from(USER)
+        .to(AGG)
+        .egld(1_000u64)
+        .returns(ReturnsHandledOrError::new())
+        .whitebox(aggregator::contract_obj, |sc| {
+            let mut tokens = MultiValueEncodedCounted::new();
+            for token in raw_registry("EGLD-000000").iter() {
+                tokens.push(token.clone());
+            }
+            sc.aggregate(
+                aggregator::constants::FORMAT_VERSION,
+                BigUint::from(1u64),
+                0,
+                0,
+                tokens,
+                registry_addresses(&[]),
+                registry_amounts(&[]),
+                instruction_rows(&[]),
+            );
+        });
+
+    assert_eq!(
+        result.unwrap_err().message,
+        "serializer decode error: ESDT expected"
+    );
+    world.check_account(USER).balance(1_000_000u64);
+}
@@ -0,0 +1,151 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+
+use aggregator::Aggregator;
+use common::*;
+
+const SWAP_A_TO_B: Row = (0, 1, 0, 0, 0, 0);
+const ZAP_ADD_ALL: Row = (1, 0, 0, 1, 0, 0);
+const REMOVE_LIQUIDITY: Row = (2, 2, 0, 255, 0, 0);
+/// Redeem all LP_TOKEN hTokens for the underlying A
+const REDEEM: Row = (23, 1, 0, 255, 0, 255);
+
+/// Make the mock at `pool` succeed while keeping its output
+fn withhold_outputs(world: &mut ScenarioWorld, pool: TestSCAddress) {
+    world
+        .tx()
+        .from(OWNER)
+        .to(pool)
+        .whitebox(mock_pair::contract_obj, |sc| {
+            use mock_pair::MockPair;
+            sc.withhold_outputs().set(true);
+        });
+}
+
+#[test]
+fn a_swap_returning_nothing_reverts() {
+    let mut world = setup_pair();
+    withhold_outputs(&mut world, PAIR);
+
+    let result = xo_call(
+        &mut world,
+        (TOKEN_A, 1_000),
+        0,
+        1,
+        0,
+        &[TOKEN_A, TOKEN_B],
+        &[PAIR],
+        &[],
+        &[SWAP_A_TO_B],
+    );
+
+    assert_eq!(
+        result.unwrap_err().message,
+        "E: Action 0 returned 0 payments, expected 1"
+    );
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_A, 1_000_000u64);
+}
+
+#[test]
+fn a_remove_liquidity_returning_one_leg_reverts() {
+    let mut world = setup_pair();
+    world.set_esdt_balance(USER, LP_TOKEN.as_bytes(), 1_000u64);
+    withhold_outputs(&mut world, PAIR);
+
+    let result = xo_call(
+        &mut world,
+        (LP_TOKEN, 1_000),
+        0,
+        0,
+        0,
+        &[TOKEN_A, TOKEN_B, LP_TOKEN],
+        &[PAIR],
+        &[],
+        &[REMOVE_LIQUIDITY],
+    );
+
+    assert_eq!(
+        result.unwrap_err().message,
+        "E: Action 2 returned 1 payments, expected 2"
+    );
+    world.check_account(USER).esdt_balance(LP_TOKEN, 1_000u64);
+}
+
+#[test]
+fn an_add_liquidity_returning_no_lp_reverts() {
+    let mut world = setup_pair();
+    withhold_outputs(&mut world, PAIR);
+
+    // Inputs already at the pool ratio, so the add is the only call to the pair
+    let result = world
+        .tx()
+        .from(USER)
+        .to(AGG)
+        .multi_esdt(ManagedVec::<StaticApi, _>::from_iter(
+            [TOKEN_A, TOKEN_B].map(|token| {
+                EsdtTokenPayment::new(token.to_token_identifier(), 0, BigUint::from(1_000u64))
+            }),
+        ))
+        .returns(ReturnsHandledOrError::new())
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.aggregate(
+                aggregator::constants::FORMAT_VERSION,
+                BigUint::zero(),
+                2,
+                0,
+                registry_tokens(&[TOKEN_A, TOKEN_B, LP_TOKEN]),
+                registry_addresses(&[PAIR]),
+                registry_amounts(&[]),
+                instruction_rows(&[ZAP_ADD_ALL]),
+            );
+        });
+
+    assert_eq!(
+        result.unwrap_err().message,
+        "E: Action 1 returned no LP token LPAB-000005"
+    );
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_A, 1_000_000u64);
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_B, 1_000_000u64);
+}
+
+#[test]
+fn a_redeem_returning_nothing_reverts() {
+    let mut world = setup();
+    deploy_hatom_market(&mut world);
+    world.set_esdt_balance(HATOM_MARKET, TOKEN_A.as_bytes(), 1_000_000u64);
+    world.set_esdt_balance(USER, LP_TOKEN.as_bytes(), 1_000u64);
+    world
+        .tx()
+        .from(OWNER)
+        .to(HATOM_MARKET)
+        .whitebox(mock_pair::contract_obj, |sc| {
+            use mock_pair::MockPair;
+            sc.first_token_id().set(esdt(TOKEN_A));
+        });
+    withhold_outputs(&mut world, HATOM_MARKET);
+
+    let result = xo_call(
+        &mut world,
+        (LP_TOKEN, 1_000),
+        0,
+        0,
+        0,
+        &[TOKEN_A, LP_TOKEN],
+        &[],
+        &[],
+        &[REDEEM],
+    );
+
+    assert_eq!(
+        result.unwrap_err().message,
+        "E: Action 23 returned 0 payments, expected 1"
+    );
+    world.check_account(USER).esdt_balance(LP_TOKEN, 1_000u64);
+}
@@ -0,0 +1,66 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::TxResponseStatus;
+
+use aggregator::config::Config;
+use common::*;
+
+const ALLOW_FAILURE: Row = (37, 0, 0, 0, 0, 255);
+const ZAP_ADD_ALL: Row = (1, 0, 0, 1, 0, 0);
+/// A -> B swap of amounts[0]
+const SWAP_FIXED_0: Row = (0, 1, 0, 2, 0, 0);
+
+fn run(world: &mut ScenarioWorld, amounts: &[u64], rows: &[Row]) -> Result<(), TxResponseStatus> {
+    xo_call(
+        world,
+        (TOKEN_A, 1_000),
+        0,
+        1,
+        0,
+        &[TOKEN_A, TOKEN_B],
+        &[PAIR],
+        amounts,
+        rows,
+    )
+}
+
+#[test]
+fn decode_failures_carry_the_d_prefix() {
+    let mut world = setup_pair();
+
+    let err = run(&mut world, &[], &[(200, 0, 0, 0, 0, 0)]).unwrap_err();
+    assert_eq!(err.message, "D: Invalid action type: 200");
+
+    let err = run(&mut world, &[], &[ALLOW_FAILURE, ZAP_ADD_ALL]).unwrap_err();
+    assert_eq!(err.message, aggregator::errors::ERR_ALLOW_FAILURE_SWAP_ONLY);
+    assert!(err.message.starts_with("D: "));
+}
+
+#[test]
+fn execution_failures_carry_the_e_prefix() {
+    let mut world = setup_pair();
+
+    let err = run(&mut world, &[2_000], &[SWAP_FIXED_0]).unwrap_err();
+    assert_eq!(
+        err.message,
+        "E: Insufficient vault balance for token TOKA-000001: have 1000, need 2000"
+    );
+}
+
+#[test]
+fn admin_failures_carry_no_prefix() {
+    let mut world = setup();
+
+    let err = world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .returns(ReturnsHandledOrError::new())
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.set_action_gas_limit(200, 1_000_000);
+        })
+        .unwrap_err();
+    assert_eq!(err.message, aggregator::errors::ERR_INVALID_ACTION);
+    assert!(!err.message.starts_with("D: ") && !err.message.starts_with("E: "));
+}
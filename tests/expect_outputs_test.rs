@@ -0,0 +1,84 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+
+use aggregator::storage::Storage;
+use common::*;
+
+const EXPECT_A_AND_B: Row = (32, 0, 1, 255, 255, 255);
+const REMOVE_ALL_LP: Row = (2, 2, 0, 255, 0, 0);
+/// A -> B swap of the previous result (the A leg)
+const SWAP_PREV_A_TO_B: Row = (0, 1, 0, 1, 0, 0);
+/// B -> A swap of the previous result
+const SWAP_PREV_B_TO_A: Row = (0, 0, 1, 1, 0, 0);
+const ZAP_ADD_PREV: Row = (1, 0, 1, 1, 1, 0);
+
+fn setup_lp_holder() -> ScenarioWorld {
+    let mut world = setup_pair();
+    world.set_esdt_balance(USER, LP_TOKEN.as_bytes(), 1_000u64);
+    world
+}
+
+fn admin_fee(world: &mut ScenarioWorld, token: TestTokenIdentifier) -> u64 {
+    let mut fee = 0;
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            fee = sc
+                .admin_fees()
+                .get(&token_id(token))
+                .unwrap_or_default()
+                .to_u64()
+                .unwrap();
+        });
+    fee
+}
+
+#[test]
+fn unused_leg_does_not_outlive_its_first_consumer() {
+    let mut world = setup_lp_holder();
+
+    // Removing 1000 LP returns 1000 A and 1000 B; the A leg is swapped to B, then the
+    // next PrevAmount of B must take that swap's output, not the untouched B leg
+    xo_call(
+        &mut world,
+        (LP_TOKEN, 1_000),
+        0,
+        0,
+        0,
+        &[TOKEN_A, TOKEN_B, LP_TOKEN],
+        &[PAIR],
+        &[],
+        &[
+            EXPECT_A_AND_B,
+            REMOVE_ALL_LP,
+            SWAP_PREV_A_TO_B,
+            SWAP_PREV_B_TO_A,
+        ],
+    )
+    .unwrap();
+
+    // The B leg was never routed, so it is exactly what stays behind as dust
+    assert_eq!(admin_fee(&mut world, TOKEN_B), 1_000);
+}
+
+#[test]
+fn one_instruction_can_consume_every_leg() {
+    let mut world = setup_lp_holder();
+
+    xo_call(
+        &mut world,
+        (LP_TOKEN, 1_000),
+        0,
+        2,
+        0,
+        &[TOKEN_A, TOKEN_B, LP_TOKEN],
+        &[PAIR],
+        &[],
+        &[EXPECT_A_AND_B, REMOVE_ALL_LP, ZAP_ADD_PREV],
+    )
+    .unwrap();
+
+    world.check_account(USER).esdt_balance(LP_TOKEN, 1_000u64);
+}
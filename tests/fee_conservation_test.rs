@@ -0,0 +1,149 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+
+use aggregator::config::Config;
+use aggregator::storage::Storage;
+use common::*;
+
+const SWAP_A_TO_B: Row = (0, 1, 0, 0, 0, 0);
+/// A -> B swap of amounts[0]
+const SWAP_FIXED_0: Row = (0, 1, 0, 2, 0, 0);
+const ZAP_ADD_ALL: Row = (1, 0, 0, 1, 0, 0);
+
+/// Pair deployed, 10% static fee on B and referral 1 at 1%
+fn setup_fees() -> ScenarioWorld {
+    let mut world = setup_pair();
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.add_referral(REFERRER.to_managed_address(), 100);
+            sc.set_static_fee_override(token_id(TOKEN_B), 1_000);
+        });
+    world
+}
+
+/// Admin and referral 1 balances of `token`, and what the contract actually holds of it
+fn fees_and_holdings(world: &mut ScenarioWorld, token: TestTokenIdentifier) -> (u64, u64, u64) {
+    let mut out = (0, 0, 0);
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            let id = token_id(token);
+            let admin = sc.admin_fees().get(&id).unwrap_or_default();
+            let referral = sc.referrer_balances(1).get(&id).unwrap_or_default();
+            let held = sc
+                .blockchain()
+                .get_sc_balance(EgldOrEsdtTokenIdentifier::esdt(esdt(token)), 0);
+            out = (
+                admin.to_u64().unwrap(),
+                referral.to_u64().unwrap(),
+                held.to_u64().unwrap(),
+            );
+        });
+    out
+}
+
+#[test]
+fn output_fees_leave_the_vault_as_fee_balances() {
+    let mut world = setup_fees();
+
+    xo_call(
+        &mut world,
+        (TOKEN_A, 1_000),
+        0,
+        1,
+        1,
+        &[TOKEN_A, TOKEN_B],
+        &[PAIR],
+        &[],
+        &[SWAP_A_TO_B],
+    )
+    .unwrap();
+
+    let (admin, referral, held) = fees_and_holdings(&mut world, TOKEN_B);
+    assert!(admin > 0 && referral > 0);
+    assert_eq!(admin + referral, held);
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_B, 1_000_000u64 + 996 - held);
+}
+
+#[test]
+fn leftover_dust_is_booked_as_admin_fees() {
+    let mut world = setup_fees();
+
+    xo_call(
+        &mut world,
+        (TOKEN_A, 2_000),
+        0,
+        1,
+        0,
+        &[TOKEN_A, TOKEN_B],
+        &[PAIR],
+        &[1_000],
+        &[SWAP_FIXED_0],
+    )
+    .unwrap();
+
+    assert_eq!(fees_and_holdings(&mut world, TOKEN_A), (1_000, 0, 1_000));
+}
+
+#[test]
+fn zap_dust_is_booked_as_admin_fees() {
+    let mut world = setup_fees();
+
+    xo_call(
+        &mut world,
+        (TOKEN_A, 2_000),
+        0,
+        2,
+        0,
+        &[TOKEN_A, TOKEN_B, LP_TOKEN],
+        &[PAIR],
+        &[1_000],
+        &[SWAP_FIXED_0, ZAP_ADD_ALL],
+    )
+    .unwrap();
+
+    for token in [TOKEN_A, TOKEN_B, LP_TOKEN] {
+        let (admin, referral, held) = fees_and_holdings(&mut world, token);
+        assert_eq!(referral, 0);
+        assert_eq!(admin, held, "{token:?}");
+    }
+}
+
+#[test]
+fn the_caller_gets_exactly_the_post_fee_balance() {
+    let mut world = setup_pair();
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.add_referral(REFERRER.to_managed_address(), 17);
+            sc.set_static_fee_override(token_id(TOKEN_B), 33);
+        });
+
+    xo_call(
+        &mut world,
+        (TOKEN_A, 1_000),
+        992,
+        1,
+        1,
+        &[TOKEN_A, TOKEN_B],
+        &[PAIR],
+        &[],
+        &[SWAP_A_TO_B],
+    )
+    .unwrap();
+
+    // Both fees round down on 996 B (3.29 and 1.69), the caller gets the rest to the wei
+    assert_eq!(fees_and_holdings(&mut world, TOKEN_B), (3, 1, 4));
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_B, 1_000_000u64 + 992);
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::Log;
+
+use aggregator::config::Config;
+use aggregator::Aggregator;
+use common::*;
+
+const SWAP_A_TO_B: Row = (0, 1, 0, 0, 0, 0);
+
+/// Pair deployed, 10% static fee on B and referral 1 at 1%
+fn setup_fees() -> ScenarioWorld {
+    let mut world = setup_pair();
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.add_referral(REFERRER.to_managed_address(), 100);
+            sc.set_static_fee_override(token_id(TOKEN_B), 1_000);
+        });
+    world
+}
+
+/// Swap 1,000 A to B with `referral_id` and return the emitted logs
+fn swap_logs(world: &mut ScenarioWorld, referral_id: u64) -> Vec<Log> {
+    world
+        .tx()
+        .from(USER)
+        .to(AGG)
+        .esdt(TestEsdtTransfer(TOKEN_A, 0, 1_000))
+        .returns(ReturnsLogs)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.aggregate(
+                aggregator::constants::FORMAT_VERSION,
+                BigUint::from(1u64),
+                1,
+                referral_id,
+                registry_tokens(&[TOKEN_A, TOKEN_B]),
+                registry_addresses(&[PAIR]),
+                registry_amounts(&[]),
+                instruction_rows(&[SWAP_A_TO_B]),
+            );
+        })
+}
+
+/// Topics after the event name and the decoded amount of every `event` log
+fn accruals(logs: &[Log], event: &[u8]) -> Vec<(Vec<Vec<u8>>, u64)> {
+    logs.iter()
+        .filter(|log| log.topics[0] == event)
+        .map(|log| {
+            (
+                log.topics[1..].to_vec(),
+                u64::top_decode(log.data[0].as_slice()).unwrap(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn a_fee_bearing_swap_emits_one_accrual_per_balance() {
+    let mut world = setup_fees();
+
+    let logs = swap_logs(&mut world, 1);
+
+    // 1% and 10% of the 996 B output
+    assert_eq!(
+        accruals(&logs, b"referralAccrued"),
+        vec![(vec![vec![1], TOKEN_B.as_bytes().to_vec()], 9)]
+    );
+    assert_eq!(
+        accruals(&logs, b"adminAccrued"),
+        vec![(vec![TOKEN_B.as_bytes().to_vec()], 99)]
+    );
+}
+
+#[test]
+fn a_fee_free_swap_emits_no_accrual() {
+    let mut world = setup_fees();
+
+    let logs = swap_logs(&mut world, 0);
+
+    assert!(accruals(&logs, b"referralAccrued").is_empty());
+    assert!(accruals(&logs, b"adminAccrued").is_empty());
+}
@@ -0,0 +1,102 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::TxResponseStatus;
+
+use aggregator::config::Config;
+use aggregator::storage::Storage;
+use common::*;
+
+const SWAP_A_TO_B: Row = (0, 1, 0, 0, 0, 0);
+
+/// Fee-on-input with a 10% static fee on A and referral 1 at 1%
+fn setup_fee_on_input() -> ScenarioWorld {
+    let mut world = setup_pair();
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.add_referral(REFERRER.to_managed_address(), 100);
+            sc.set_static_fee_override(token_id(TOKEN_A), 1_000);
+            sc.set_fee_on_input(true);
+        });
+    world
+}
+
+fn swap(world: &mut ScenarioWorld, min_amount_out: u64) -> Result<(), TxResponseStatus> {
+    xo_call(
+        world,
+        (TOKEN_A, 1_000),
+        min_amount_out,
+        1,
+        1,
+        &[TOKEN_A, TOKEN_B],
+        &[PAIR],
+        &[],
+        &[SWAP_A_TO_B],
+    )
+}
+
+fn fee_balances(world: &mut ScenarioWorld) -> (u64, u64) {
+    let mut balances = (0, 0);
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            let token = token_id(TOKEN_A);
+            let admin = sc.admin_fees().get(&token).unwrap_or_default();
+            let referral = sc.referrer_balances(1).get(&token).unwrap_or_default();
+            balances = (admin.to_u64().unwrap(), referral.to_u64().unwrap());
+        });
+    balances
+}
+
+#[test]
+fn fee_on_input_charges_the_payment_token() {
+    let mut world = setup_fee_on_input();
+
+    swap(&mut world, 0).unwrap();
+
+    assert_eq!(fee_balances(&mut world), (100, 10));
+}
+
+#[test]
+fn fee_on_input_compares_the_min_trade_to_the_payment() {
+    let mut world = setup_fee_on_input();
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.set_referral_min_trade(1, BigUint::from(1_001u64));
+        });
+
+    swap(&mut world, 0).unwrap();
+
+    // 1000 A is below the referral's min trade, so its share goes to admin
+    assert_eq!(fee_balances(&mut world), (110, 0));
+}
+
+fn setup_slippage_floor() -> ScenarioWorld {
+    let mut world = setup_fee_on_input();
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.set_min_output_ppm(990_000);
+        });
+    world
+}
+
+#[test]
+fn fee_on_input_quotes_the_slippage_floor_net_of_fees() {
+    // 890 A is routed: floor 881, where the gross payment would have floored at 990
+    let mut world = setup_slippage_floor();
+    swap(&mut world, 885).unwrap();
+
+    let mut world = setup_slippage_floor();
+    let message = swap(&mut world, 880).unwrap_err().message;
+    assert_eq!(message, "E: Slippage floor not met: min 880, floor 881");
+}
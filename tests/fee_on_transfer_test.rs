@@ -0,0 +1,103 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+
+use aggregator::config::Config;
+use aggregator::utils::Utils;
+use aggregator::vault::{FeeOnTransferSnapshot, Vault};
+use common::*;
+
+fn nz(amount: u64) -> NonZeroBigUint<DebugApi> {
+    BigUint::from(amount).into_non_zero().unwrap()
+}
+
+fn mark_fee_on_transfer(world: &mut ScenarioWorld, token: TestTokenIdentifier) {
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            let mut tokens = MultiValueEncoded::new();
+            tokens.push(token_id(token));
+            sc.add_fee_on_transfer_tokens(tokens);
+        });
+}
+
+#[test]
+fn a_short_delivery_lowers_the_tracked_balance_and_prev_amount() {
+    let mut world = setup();
+    mark_fee_on_transfer(&mut world, TOKEN_B);
+    // A pool reported 1,000 B sent, but the transfer only delivered 990
+    world.set_esdt_balance(AGG, TOKEN_B.as_bytes(), 990u64);
+
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            // Taken before the instruction: nothing held yet
+            let mut snapshot = FeeOnTransferSnapshot {
+                tokens: ManagedVec::new(),
+                vault_balances: ManagedVec::new(),
+                sc_balances: ManagedVec::new(),
+            };
+            snapshot.tokens.push(token_id(TOKEN_B));
+            snapshot.vault_balances.push(BigUint::zero());
+            snapshot.sc_balances.push(BigUint::zero());
+
+            let mut vault = Vault::new();
+            vault.deposit(&token_id(TOKEN_B), &nz(1_000));
+            vault.set_prev_result(&Payment::new(token_id(TOKEN_B), 0, nz(1_000)));
+
+            sc.reconcile_fee_on_transfer(&mut vault, &snapshot);
+
+            assert_eq!(vault.balance_of(&token_id(TOKEN_B)), 990u64);
+            let prev = vault.peek_prev_result().clone().unwrap();
+            assert_eq!(prev.amount.into_big_uint(), 990u64);
+        });
+}
+
+#[test]
+fn only_listed_tokens_are_snapshotted() {
+    let mut world = setup();
+
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            assert!(sc.snapshot_fee_on_transfer(&Vault::new()).is_none());
+        });
+
+    mark_fee_on_transfer(&mut world, TOKEN_B);
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            let snapshot = sc.snapshot_fee_on_transfer(&Vault::new()).unwrap();
+            assert_eq!(snapshot.tokens.len(), 1);
+            assert!(*snapshot.tokens.get(0) == token_id(TOKEN_B));
+        });
+}
+
+#[test]
+fn a_full_delivery_of_a_listed_token_is_left_as_reported() {
+    let mut world = setup_pair();
+    mark_fee_on_transfer(&mut world, TOKEN_B);
+
+    xo_call(
+        &mut world,
+        (TOKEN_A, 1_000),
+        996,
+        1,
+        0,
+        &[TOKEN_A, TOKEN_B],
+        &[PAIR],
+        &[],
+        &[(0, 1, 0, 0, 0, 0)],
+    )
+    .unwrap();
+
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_B, 1_000_996u64);
+}
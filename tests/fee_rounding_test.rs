@@ -0,0 +1,36 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+
+use aggregator::utils::Utils;
+use common::*;
+
+fn fee_amount(world: &mut ScenarioWorld, base: u64, bps: u32) -> u64 {
+    let mut fee = 0;
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            fee = sc.fee_amount(&BigUint::from(base), bps).to_u64().unwrap();
+        });
+    fee
+}
+
+#[test]
+fn fees_below_one_unit_round_down_to_zero() {
+    let mut world = setup();
+
+    assert_eq!(fee_amount(&mut world, 1, 1), 0);
+    assert_eq!(fee_amount(&mut world, 9_999, 1), 0);
+    assert_eq!(fee_amount(&mut world, 3, 3_333), 0);
+}
+
+#[test]
+fn fees_round_down_at_each_unit_boundary() {
+    let mut world = setup();
+
+    assert_eq!(fee_amount(&mut world, 10_000, 1), 1);
+    assert_eq!(fee_amount(&mut world, 19_999, 1), 1);
+    assert_eq!(fee_amount(&mut world, 20_000, 1), 2);
+    assert_eq!(fee_amount(&mut world, 1, 10_000), 1);
+}
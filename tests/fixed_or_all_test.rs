@@ -0,0 +1,75 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::TxResponseStatus;
+
+use common::*;
+
+/// Swap A to B using FixedOrAll on amounts[0]
+const SWAP_FIXED_OR_ALL: Row = (0, 1, 0, 96, 0, 0);
+
+/// Swap A to B using Fixed on amounts[0]
+const SWAP_FIXED: Row = (0, 1, 0, 2, 0, 0);
+
+fn swap_a_to_b(world: &mut ScenarioWorld, amount: u64, row: Row) -> Result<(), TxResponseStatus> {
+    xo_call(
+        world,
+        (TOKEN_A, 1_000),
+        1,
+        1,
+        0,
+        &[TOKEN_A, TOKEN_B],
+        &[PAIR],
+        &[amount],
+        &[row],
+    )
+}
+
+#[test]
+fn fixed_or_all_withdraws_an_exact_balance() {
+    let mut world = setup_pair();
+
+    swap_a_to_b(&mut world, 1_000, SWAP_FIXED_OR_ALL).unwrap();
+
+    world.check_account(USER).esdt_balance(TOKEN_A, 999_000u64);
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_B, 1_000_996u64);
+}
+
+#[test]
+fn fixed_or_all_takes_the_whole_balance_when_short_by_one() {
+    let mut world = setup_pair();
+
+    swap_a_to_b(&mut world, 1_001, SWAP_FIXED_OR_ALL).unwrap();
+
+    world.check_account(USER).esdt_balance(TOKEN_A, 999_000u64);
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_B, 1_000_996u64);
+}
+
+#[test]
+fn fixed_reverts_when_short_by_one() {
+    let mut world = setup_pair();
+
+    assert!(swap_a_to_b(&mut world, 1_001, SWAP_FIXED).is_err());
+
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_A, 1_000_000u64);
+}
+
+#[test]
+fn fixed_or_all_withdraws_only_the_fixed_amount_from_an_abundant_balance() {
+    let mut world = setup_pair();
+
+    swap_a_to_b(&mut world, 500, SWAP_FIXED_OR_ALL).unwrap();
+
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_B, 1_000_498u64);
+    world
+        .check_account(PAIR)
+        .esdt_balance(TOKEN_A, 1_000_500u64);
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::TxResponseStatus;
+
+use aggregator::constants::FORMAT_VERSION;
+use aggregator::Aggregator;
+use common::*;
+
+const SWAP_A_TO_B: Row = (0, 1, 0, 0, 0, 0);
+
+/// Swap 1,000 A to B through `xo` encoded as `format_version`
+fn xo_with_version(world: &mut ScenarioWorld, format_version: u8) -> Result<(), TxResponseStatus> {
+    world
+        .tx()
+        .from(USER)
+        .to(AGG)
+        .esdt(TestEsdtTransfer(TOKEN_A, 0, 1_000))
+        .returns(ReturnsHandledOrError::new())
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.aggregate(
+                format_version,
+                BigUint::from(996u64),
+                1,
+                0,
+                registry_tokens(&[TOKEN_A, TOKEN_B]),
+                registry_addresses(&[PAIR]),
+                registry_amounts(&[]),
+                instruction_rows(&[SWAP_A_TO_B]),
+            );
+        })
+}
+
+#[test]
+fn xo_accepts_the_current_format_version() {
+    let mut world = setup_pair();
+
+    xo_with_version(&mut world, FORMAT_VERSION).unwrap();
+
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_B, 1_000_996u64);
+}
+
+#[test]
+fn xo_rejects_an_older_format_version() {
+    let mut world = setup_pair();
+
+    let result = xo_with_version(&mut world, FORMAT_VERSION - 1);
+
+    assert_eq!(
+        result.unwrap_err().message,
+        format!(
+            "D: Unsupported format version {}, expected {FORMAT_VERSION}",
+            FORMAT_VERSION - 1
+        )
+    );
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_A, 1_000_000u64);
+}
@@ -0,0 +1,84 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+
+use aggregator::config::Config;
+use aggregator::storage::Storage;
+use common::*;
+
+const SWAP_A_TO_B: Row = (0, 1, 0, 0, 0, 0);
+
+/// Pair deployed, referral 1 at 1% and a 10% global static fee
+fn setup_fees(free_mode: bool) -> ScenarioWorld {
+    let mut world = setup_pair();
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.add_referral(REFERRER.to_managed_address(), 100);
+            sc.set_static_fee(1_000);
+            sc.set_free_mode(free_mode);
+        });
+    world
+}
+
+fn swap_with_referral(world: &mut ScenarioWorld) {
+    xo_call(
+        world,
+        (TOKEN_A, 1_000),
+        0,
+        1,
+        1,
+        &[TOKEN_A, TOKEN_B],
+        &[PAIR],
+        &[],
+        &[SWAP_A_TO_B],
+    )
+    .unwrap();
+}
+
+/// (admin, referral 1) fee balances of B
+fn fee_balances(world: &mut ScenarioWorld) -> (u64, u64) {
+    let mut out = (0, 0);
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            let id = token_id(TOKEN_B);
+            out = (
+                sc.admin_fees()
+                    .get(&id)
+                    .unwrap_or_default()
+                    .to_u64()
+                    .unwrap(),
+                sc.referrer_balances(1)
+                    .get(&id)
+                    .unwrap_or_default()
+                    .to_u64()
+                    .unwrap(),
+            );
+        });
+    out
+}
+
+#[test]
+fn free_mode_takes_no_fee_despite_a_static_fee() {
+    let mut world = setup_fees(true);
+
+    swap_with_referral(&mut world);
+
+    assert_eq!(fee_balances(&mut world), (0, 0));
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_B, 1_000_996u64);
+}
+
+#[test]
+fn the_same_swap_outside_free_mode_pays_the_fees() {
+    let mut world = setup_fees(false);
+
+    swap_with_referral(&mut world);
+
+    assert_eq!(fee_balances(&mut world), (99, 9));
+}
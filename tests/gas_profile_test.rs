@@ -0,0 +1,75 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::Log;
+
+use aggregator::config::Config;
+use aggregator::Aggregator;
+use common::*;
+
+const SWAP_A_TO_B: Row = (0, 1, 0, 0, 0, 0);
+const SWAP_PREV_B_TO_A: Row = (0, 0, 1, 1, 0, 0);
+
+/// Swap A to B and back, returning the `gasProfile` events as (index, action, gas_used)
+fn round_trip(world: &mut ScenarioWorld) -> Vec<(u32, u8, u64)> {
+    let logs: Vec<Log> = world
+        .tx()
+        .from(USER)
+        .to(AGG)
+        .esdt(TestEsdtTransfer(TOKEN_A, 0, 1_000))
+        .returns(ReturnsLogs)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.aggregate(
+                aggregator::constants::FORMAT_VERSION,
+                BigUint::from(1u64),
+                0,
+                0,
+                registry_tokens(&[TOKEN_A, TOKEN_B]),
+                registry_addresses(&[PAIR]),
+                registry_amounts(&[]),
+                instruction_rows(&[SWAP_A_TO_B, SWAP_PREV_B_TO_A]),
+            );
+        });
+    logs.iter()
+        .filter(|log| log.topics[0] == b"gasProfile")
+        .map(|log| {
+            (
+                u32::top_decode(log.topics[1].as_slice()).unwrap(),
+                u8::top_decode(log.topics[2].as_slice()).unwrap(),
+                u64::top_decode(log.data[0].as_slice()).unwrap(),
+            )
+        })
+        .collect()
+}
+
+fn set_gas_profiling(world: &mut ScenarioWorld, enabled: bool) {
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.set_gas_profiling(enabled);
+        });
+}
+
+#[test]
+fn profiling_records_gas_per_instruction() {
+    let mut world = setup_pair();
+    set_gas_profiling(&mut world, true);
+
+    let profile = round_trip(&mut world);
+
+    // The debug VM meters no gas, so only the per-instruction records are checked
+    let steps: Vec<(u32, u8)> = profile
+        .iter()
+        .map(|(index, action, _)| (*index, *action))
+        .collect();
+    assert_eq!(steps, vec![(0, 0), (1, 0)]);
+}
+
+#[test]
+fn profiling_is_off_by_default() {
+    let mut world = setup_pair();
+
+    assert!(round_trip(&mut world).is_empty());
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+
+use common::*;
+
+/// Compound all LP_TOKEN hTokens in the vault
+const COMPOUND: Row = (33, 1, 0, 255, 0, 255);
+
+/// Hatom market for A, holding A to redeem and with the user holding hTokens
+fn setup_market() -> ScenarioWorld {
+    let mut world = setup();
+    deploy_hatom_market(&mut world);
+    world.set_esdt_balance(HATOM_MARKET, TOKEN_A.as_bytes(), 1_000_000u64);
+    world.set_esdt_balance(USER, LP_TOKEN.as_bytes(), 1_000u64);
+    world
+        .tx()
+        .from(OWNER)
+        .to(HATOM_MARKET)
+        .whitebox(mock_pair::contract_obj, |sc| {
+            use mock_pair::MockPair;
+            sc.first_token_id().set(esdt(TOKEN_A));
+        });
+    world
+}
+
+#[test]
+fn compound_returns_fresh_htokens_and_keeps_no_underlying() {
+    let mut world = setup_market();
+
+    xo_call(
+        &mut world,
+        (LP_TOKEN, 1_000),
+        1_000,
+        1,
+        0,
+        &[TOKEN_A, LP_TOKEN],
+        &[],
+        &[],
+        &[COMPOUND],
+    )
+    .unwrap();
+
+    // The redeemed A went straight back into the market
+    world.check_account(USER).esdt_balance(LP_TOKEN, 1_000u64);
+    world.check_account(AGG).esdt_balance(TOKEN_A, 0u64);
+    world.check_account(AGG).esdt_balance(LP_TOKEN, 0u64);
+    world
+        .check_account(HATOM_MARKET)
+        .esdt_balance(TOKEN_A, 1_000_000u64);
+}
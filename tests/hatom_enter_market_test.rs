@@ -0,0 +1,90 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::TxResponseStatus;
+
+use aggregator::config::Config;
+use common::*;
+
+/// Supply A to the market for LP_TOKEN hTokens, then enter the market with them
+const SUPPLY_A: Row = (24, 1, 0, 0, 0, 255);
+const ENTER_MARKET: Row = (34, 255, 1, 255, 0, 255);
+
+fn supply_and_enter(
+    world: &mut ScenarioWorld,
+    min_amount_out: u64,
+    referral_id: u64,
+) -> Result<(), TxResponseStatus> {
+    xo_call(
+        world,
+        (TOKEN_A, 1_000),
+        min_amount_out,
+        1,
+        referral_id,
+        &[TOKEN_A, LP_TOKEN],
+        &[],
+        &[],
+        &[SUPPLY_A, ENTER_MARKET],
+    )
+}
+
+fn collateral_of_user(world: &mut ScenarioWorld) -> u64 {
+    let mut collateral = 0u64;
+    world
+        .query()
+        .to(protocol_address(&aggregator::constants::HATOM_CONTROLLER))
+        .whitebox(mock_pair::contract_obj, |sc| {
+            use mock_pair::MockPair;
+            collateral = sc
+                .collateral(&USER.to_managed_address())
+                .get()
+                .to_u64()
+                .unwrap();
+        });
+    collateral
+}
+
+#[test]
+fn supplied_htokens_are_entered_as_collateral_for_the_caller() {
+    let mut world = setup();
+    deploy_hatom_market(&mut world);
+
+    supply_and_enter(&mut world, 1_000, 0).unwrap();
+
+    assert_eq!(collateral_of_user(&mut world), 1_000);
+    world.check_account(USER).esdt_balance(LP_TOKEN, 0u64);
+    world.check_account(AGG).esdt_balance(LP_TOKEN, 0u64);
+}
+
+#[test]
+fn entered_htokens_are_checked_against_the_minimum() {
+    let mut world = setup();
+    deploy_hatom_market(&mut world);
+
+    let message = supply_and_enter(&mut world, 1_001, 0)
+        .expect_err("path should revert")
+        .message;
+
+    assert_eq!(message, "E: LP output below minimum: have 1000, need 1001");
+    assert_eq!(collateral_of_user(&mut world), 0);
+}
+
+#[test]
+fn enter_market_is_rejected_when_output_fees_apply() {
+    let mut world = setup();
+    deploy_hatom_market(&mut world);
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.add_referral(REFERRER.to_managed_address(), 100);
+        });
+
+    let message = supply_and_enter(&mut world, 0, 1)
+        .expect_err("path should revert")
+        .message;
+
+    assert_eq!(message, aggregator::errors::ERR_LP_TO_CALLER_CHARGES_FEES);
+    assert_eq!(collateral_of_user(&mut world), 0);
+}
@@ -0,0 +1,33 @@
+mod common;
+
+use common::*;
+
+/// Stake all WEGLD in the vault with Hatom
+const STAKE_WEGLD: Row = (22, 0, 0, 255, 0, 255);
+
+#[test]
+fn hatom_staking_unwraps_a_wegld_input() {
+    let mut world = setup();
+    fund_wrapper(&mut world, 1_000_000);
+    deploy_hatom_staking(&mut world);
+    world.set_esdt_balance(USER, WEGLD.as_bytes(), 1_000);
+
+    xo_call(
+        &mut world,
+        (WEGLD, 1_000),
+        1_000,
+        1,
+        0,
+        &[WEGLD, TOKEN_D],
+        &[],
+        &[],
+        &[STAKE_WEGLD],
+    )
+    .unwrap();
+
+    world.check_account(USER).esdt_balance(WEGLD, 0u64);
+    world.check_account(USER).esdt_balance(TOKEN_D, 1_000u64);
+    world
+        .check_account(protocol_address(&aggregator::constants::HATOM_STAKING))
+        .balance(1_000u64);
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::TxResponseStatus;
+
+use aggregator::config::Config;
+use aggregator::types::ActionCategory;
+use common::*;
+
+/// A -> B swap of everything in the vault
+const SWAP_A_TO_B: Row = (0, 1, 0, 0, 0, 0);
+/// Hatom staking of all native EGLD in the vault
+const STAKE_EGLD: Row = (22, 254, 0, 255, 0, 255);
+
+fn set_internal_min_output(world: &mut ScenarioWorld, category: ActionCategory, min: u64) {
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.set_internal_min_output(category, BigUint::from(min));
+        });
+}
+
+/// Swap 1,000 A for 996 B on the 1M/1M pair
+fn swap(world: &mut ScenarioWorld) -> Result<(), TxResponseStatus> {
+    xo_call(
+        world,
+        (TOKEN_A, 1_000),
+        0,
+        1,
+        0,
+        &[TOKEN_A, TOKEN_B],
+        &[PAIR],
+        &[],
+        &[SWAP_A_TO_B],
+    )
+}
+
+/// Stake 1,000 EGLD for 1,000 D
+fn stake(world: &mut ScenarioWorld) -> Result<(), TxResponseStatus> {
+    xo_call_egld(world, 1_000, 0, 0, 0, &[TOKEN_D], &[], &[], &[STAKE_EGLD])
+}
+
+#[test]
+fn the_swap_minimum_reaches_swap_pool_calls() {
+    let mut world = setup_pair();
+    set_internal_min_output(&mut world, ActionCategory::Swap, 997);
+
+    let result = swap(&mut world);
+    assert_eq!(
+        result.unwrap_err().message,
+        "E: Sub-call failed for action 0: error code 1"
+    );
+
+    set_internal_min_output(&mut world, ActionCategory::Swap, 996);
+    swap(&mut world).unwrap();
+
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_B, 1_000_996u64);
+}
+
+#[test]
+fn the_liquidity_minimum_leaves_swaps_alone() {
+    let mut world = setup_pair();
+    set_internal_min_output(&mut world, ActionCategory::Liquidity, 1_000_000);
+
+    swap(&mut world).unwrap();
+
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_B, 1_000_996u64);
+}
+
+#[test]
+fn staking_ignores_the_swap_minimum() {
+    let mut world = setup();
+    deploy_hatom_staking(&mut world);
+    set_internal_min_output(&mut world, ActionCategory::Swap, 1_000_000);
+
+    stake(&mut world).unwrap();
+
+    world.check_account(USER).esdt_balance(TOKEN_D, 1_000u64);
+}
@@ -0,0 +1,147 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::TxResponseStatus;
+
+use aggregator::config::Config;
+use aggregator::types::ActionCategory;
+use common::*;
+
+/// Jex stable swap of all the A input to B
+const STABLE_SWAP_ALL: Row = (15, 1, 0, 0, 0, 0);
+/// Jex stable swap of amounts[0] A to B
+const STABLE_SWAP_FIXED_0: Row = (15, 1, 0, 2, 0, 0);
+/// Jex stable add of all A and all B
+const STABLE_ADD_BOTH: Row = (16, 0, 1, 255, 0, 0);
+/// Jex stable add of all A only
+const STABLE_ADD_SINGLE: Row = (16, 0, 255, 255, 0, 0);
+
+fn setup_jex_stable(multiplier: u64, input_ppm: u32) -> ScenarioWorld {
+    let mut world = setup();
+    deploy_stable_pool(&mut world, 1_000_000);
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.set_jex_stable_min_multiplier(multiplier);
+            sc.set_jex_stable_min_input_ppm(input_ppm);
+        });
+    world
+}
+
+fn run(
+    world: &mut ScenarioWorld,
+    amount_in: u64,
+    token_out: u8,
+    amounts: &[u64],
+    rows: &[Row],
+) -> Result<(), TxResponseStatus> {
+    xo_call(
+        world,
+        (TOKEN_A, amount_in),
+        0,
+        token_out,
+        0,
+        &[TOKEN_A, TOKEN_B, STABLE_LP],
+        &[STABLE_POOL],
+        amounts,
+        rows,
+    )
+}
+
+fn last_mins(world: &mut ScenarioWorld) -> (u64, u64) {
+    let mut mins = (0, 0);
+    world
+        .query()
+        .to(STABLE_POOL)
+        .whitebox(mock_stable::contract_obj, |sc| {
+            use mock_stable::MockStable;
+            mins = (
+                sc.last_swap_min().get().to_u64().unwrap(),
+                sc.last_add_min().get().to_u64().unwrap(),
+            );
+        });
+    mins
+}
+
+#[test]
+fn tiny_stable_swap_reverts_without_the_input_cap() {
+    // Internal minimum 1 times 1000 is above the 50 B the swap returns
+    let mut world = setup_jex_stable(1_000, 0);
+
+    let err = run(&mut world, 50, 1, &[], &[STABLE_SWAP_ALL]).unwrap_err();
+    assert_eq!(
+        err.message,
+        "E: Sub-call failed for action 15: error code 1"
+    );
+}
+
+#[test]
+fn tiny_stable_swap_passes_with_the_input_cap() {
+    let mut world = setup_jex_stable(1_000, 990_000);
+
+    run(&mut world, 50, 1, &[], &[STABLE_SWAP_ALL]).unwrap();
+
+    assert_eq!(last_mins(&mut world).0, 49);
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_B, 1_000_050u64);
+}
+
+#[test]
+fn input_cap_never_goes_below_the_internal_minimum() {
+    let mut world = setup_jex_stable(1_000, 990_000);
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.set_internal_min_output(ActionCategory::Swap, BigUint::from(60u64));
+        });
+
+    // The cap of 49 is raised to the configured internal minimum of 60
+    let err = run(&mut world, 50, 1, &[], &[STABLE_SWAP_ALL]).unwrap_err();
+    assert_eq!(
+        err.message,
+        "E: Sub-call failed for action 15: error code 1"
+    );
+}
+
+#[test]
+fn input_cap_applies_to_single_input_adds_only() {
+    // 0.1% of 300 A is below the internal minimum, so a capped add floors at 1
+    let mut world = setup_jex_stable(1_000, 1_000);
+    run(&mut world, 300, 2, &[], &[STABLE_ADD_SINGLE]).unwrap();
+    assert_eq!(last_mins(&mut world).1, 1);
+
+    // A and B amounts are not summed into one input: the two-token add keeps min * 2
+    let mut world = setup_jex_stable(1_000, 1_000);
+    run(
+        &mut world,
+        300,
+        2,
+        &[150],
+        &[STABLE_SWAP_FIXED_0, STABLE_ADD_BOTH],
+    )
+    .unwrap();
+    assert_eq!(last_mins(&mut world).1, 2);
+    world.check_account(USER).esdt_balance(STABLE_LP, 300u64);
+}
+
+#[test]
+fn a_pool_multiplier_takes_precedence_over_the_global_one() {
+    // The global multiplier alone would revert the swap, as above
+    let mut world = setup_jex_stable(1_000, 0);
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.set_jex_stable_pool_min_multiplier(STABLE_POOL.to_managed_address(), 5);
+        });
+
+    run(&mut world, 50, 1, &[], &[STABLE_SWAP_ALL]).unwrap();
+
+    assert_eq!(last_mins(&mut world).0, 5);
+}
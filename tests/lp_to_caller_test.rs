@@ -0,0 +1,128 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::TxResponseStatus;
+
+use aggregator::config::Config;
+use common::*;
+
+const SWAP_A_TO_B: Row = (0, 1, 0, 2, 0, 0);
+const LP_TO_CALLER: Row = (39, 0, 0, 0, 0, 255);
+const ADD_ALL: Row = (1, 0, 0, 1, 0, 0);
+
+fn lp_path(
+    world: &mut ScenarioWorld,
+    min_amount_out: u64,
+    referral_id: u64,
+) -> Result<(), TxResponseStatus> {
+    xo_call(
+        world,
+        (TOKEN_A, 2_000),
+        min_amount_out,
+        2,
+        referral_id,
+        &[TOKEN_A, TOKEN_B, LP_TOKEN],
+        &[PAIR],
+        &[1_000],
+        &[SWAP_A_TO_B, LP_TO_CALLER, ADD_ALL],
+    )
+}
+
+/// Same route split over two adds: the first takes 500 A and half the B
+fn split_lp_path(world: &mut ScenarioWorld, min_amount_out: u64) -> Result<(), TxResponseStatus> {
+    xo_call(
+        world,
+        (TOKEN_A, 2_000),
+        min_amount_out,
+        2,
+        0,
+        &[TOKEN_A, TOKEN_B, LP_TOKEN],
+        &[PAIR],
+        &[1_000, 500, 500_000],
+        &[
+            SWAP_A_TO_B,
+            LP_TO_CALLER,
+            (1, 0, 3, 1, 130, 0),
+            LP_TO_CALLER,
+            ADD_ALL,
+        ],
+    )
+}
+
+fn error_message(result: Result<(), TxResponseStatus>) -> String {
+    result.expect_err("path should revert").message
+}
+
+#[test]
+fn lp_to_caller_sends_the_lp_to_the_caller() {
+    let mut world = setup_pair();
+
+    lp_path(&mut world, 997, 0).unwrap();
+
+    world.check_account(USER).esdt_balance(LP_TOKEN, 997u64);
+    world.check_account(AGG).esdt_balance(LP_TOKEN, 0u64);
+}
+
+#[test]
+fn lp_to_caller_checks_the_minimum() {
+    let mut world = setup_pair();
+
+    let message = error_message(lp_path(&mut world, 998, 0));
+
+    assert_eq!(message, "E: LP output below minimum: have 997, need 998");
+}
+
+#[test]
+fn lp_to_caller_checks_the_minimum_on_the_split_total() {
+    let mut world = setup_pair();
+
+    // Neither add mints 994 on its own
+    split_lp_path(&mut world, 994).unwrap();
+    world.check_account(USER).esdt_balance(LP_TOKEN, 994u64);
+
+    let message = error_message(split_lp_path(&mut world, 1_000));
+    assert!(
+        message.starts_with("E: LP output below minimum"),
+        "{message}"
+    );
+}
+
+#[test]
+fn lp_to_caller_is_rejected_when_output_fees_apply() {
+    let mut world = setup_pair();
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.add_referral(REFERRER.to_managed_address(), 100);
+        });
+
+    let message = error_message(lp_path(&mut world, 0, 1));
+
+    assert_eq!(message, aggregator::errors::ERR_LP_TO_CALLER_CHARGES_FEES);
+    world.check_account(USER).esdt_balance(LP_TOKEN, 0u64);
+}
+
+#[test]
+fn lp_to_caller_is_allowed_when_fees_are_taken_from_the_input() {
+    let mut world = setup_pair();
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.add_referral(REFERRER.to_managed_address(), 100);
+            sc.set_fee_on_input(true);
+        });
+
+    lp_path(&mut world, 0, 1).unwrap();
+
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            use aggregator::storage::Storage;
+            assert!(sc.referrer_balances(1).get(&token_id(TOKEN_A)).is_some());
+        });
+}
@@ -0,0 +1,110 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+
+use aggregator::config::Config;
+use aggregator::storage::Storage;
+use common::*;
+
+const ALLOW_FAILURE: Row = (37, 0, 0, 0, 0, 255);
+/// A -> B swap of amounts[0]
+const SWAP_FIXED_0: Row = (0, 1, 0, 2, 0, 0);
+/// A -> B swaps of amounts[1] and amounts[2], too small for the pool to produce any output
+const SWAP_FIXED_1: Row = (0, 1, 0, 3, 0, 0);
+const SWAP_FIXED_2: Row = (0, 1, 0, 4, 0, 0);
+
+/// Swap 1,000 of 2,000 A and skip two failing swaps, leaving B and two refunds of A
+fn path_with_two_refunds(world: &mut ScenarioWorld) {
+    xo_call(
+        world,
+        (TOKEN_A, 2_000),
+        0,
+        1,
+        0,
+        &[TOKEN_A, TOKEN_B],
+        &[PAIR],
+        &[1_000, 1, 1],
+        &[
+            SWAP_FIXED_0,
+            ALLOW_FAILURE,
+            SWAP_FIXED_1,
+            ALLOW_FAILURE,
+            SWAP_FIXED_2,
+        ],
+    )
+    .unwrap();
+}
+
+fn set_max_output_tokens(world: &mut ScenarioWorld, max_output_tokens: u32) {
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.set_max_output_tokens(max_output_tokens);
+        });
+}
+
+fn admin_fee_of_a(world: &mut ScenarioWorld) -> u64 {
+    let mut fee = 0u64;
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            fee = sc
+                .admin_fees()
+                .get(&token_id(TOKEN_A))
+                .unwrap_or_default()
+                .to_u64()
+                .unwrap();
+        });
+    fee
+}
+
+#[test]
+fn every_refund_is_returned_without_a_cap() {
+    let mut world = setup_pair();
+
+    path_with_two_refunds(&mut world);
+
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_A, 1_000_000u64 - 2_000 + 2);
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_B, 1_000_996u64);
+    assert_eq!(admin_fee_of_a(&mut world), 998);
+}
+
+#[test]
+fn refunds_over_the_cap_are_kept_as_dust() {
+    let mut world = setup_pair();
+    set_max_output_tokens(&mut world, 2);
+
+    path_with_two_refunds(&mut world);
+
+    // The output and one refund fit the cap, the other refund joins the dust
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_A, 1_000_000u64 - 2_000 + 1);
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_B, 1_000_996u64);
+    assert_eq!(admin_fee_of_a(&mut world), 999);
+}
+
+#[test]
+fn a_cap_of_one_returns_only_the_output() {
+    let mut world = setup_pair();
+    set_max_output_tokens(&mut world, 1);
+
+    path_with_two_refunds(&mut world);
+
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_A, 1_000_000u64 - 2_000);
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_B, 1_000_996u64);
+    assert_eq!(admin_fee_of_a(&mut world), 1_000);
+}
@@ -0,0 +1,49 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::TxResponseStatus;
+
+use aggregator::config::Config;
+use common::*;
+
+/// A -> B swap of amounts[0]
+const SWAP_FIXED_0: Row = (0, 1, 0, 2, 0, 0);
+
+/// Split 900 A into `splits` swaps of 300 A, with at most 2 splits per token
+fn split_swap(splits: usize) -> Result<(), TxResponseStatus> {
+    let mut world = setup_pair();
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.set_max_splits_per_token(2);
+        });
+
+    xo_call(
+        &mut world,
+        (TOKEN_A, 900),
+        0,
+        1,
+        0,
+        &[TOKEN_A, TOKEN_B],
+        &[PAIR],
+        &[300],
+        &vec![SWAP_FIXED_0; splits],
+    )
+}
+
+#[test]
+fn splits_at_the_limit_are_accepted() {
+    split_swap(2).unwrap();
+}
+
+#[test]
+fn splits_above_the_limit_name_the_token() {
+    let err = split_swap(3).unwrap_err();
+
+    assert_eq!(
+        err.message,
+        "D: Split limit exceeded for token TOKA-000001: 3 withdrawals, max 2"
+    );
+}
@@ -0,0 +1,69 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::TxResponseStatus;
+
+use aggregator::config::Config;
+use aggregator::storage::Storage;
+use common::*;
+
+const SWAP_A_TO_B: Row = (0, 1, 0, 0, 0, 0);
+
+fn swap(world: &mut ScenarioWorld, min_amount_out: u64) -> Result<(), TxResponseStatus> {
+    xo_call(
+        world,
+        (TOKEN_A, 1_000),
+        min_amount_out,
+        1,
+        0,
+        &[TOKEN_A, TOKEN_B],
+        &[PAIR],
+        &[],
+        &[SWAP_A_TO_B],
+    )
+}
+
+/// (total aggregations, unique callers, USER known)
+fn metrics(world: &mut ScenarioWorld) -> (u64, u64, bool) {
+    let mut out = (0, 0, false);
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            out = (
+                sc.total_aggregations().get(),
+                sc.unique_callers().get(),
+                sc.known_caller(&USER.to_managed_address()).get(),
+            );
+        });
+    out
+}
+
+#[test]
+fn aggregations_are_counted_but_reverts_are_not() {
+    let mut world = setup_pair();
+
+    swap(&mut world, 0).unwrap();
+    swap(&mut world, 0).unwrap();
+    assert!(swap(&mut world, 1_000_000).is_err());
+
+    // Unique caller tracking is off by default: no per-caller entry is written
+    assert_eq!(metrics(&mut world), (2, 0, false));
+}
+
+#[test]
+fn unique_callers_are_counted_once_when_enabled() {
+    let mut world = setup_pair();
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.set_unique_caller_tracking(true);
+        });
+
+    swap(&mut world, 0).unwrap();
+    swap(&mut world, 0).unwrap();
+
+    assert_eq!(metrics(&mut world), (2, 1, true));
+}
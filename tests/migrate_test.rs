@@ -0,0 +1,134 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+
+use aggregator::config::Config;
+use aggregator::storage::Storage;
+use aggregator::Aggregator;
+use common::*;
+
+const AGG_V2: TestSCAddress = TestSCAddress::new("aggregator-v2");
+
+/// Admin fees of 100 A, referral 1 owed 50 B and referral 2 owed 30 B, plus a new
+/// aggregator at AGG_V2 accepting balances from AGG
+fn setup_migration() -> ScenarioWorld {
+    let mut world = setup();
+    world.set_esdt_balance(AGG, TOKEN_A.as_bytes(), 100u64);
+    world.set_esdt_balance(AGG, TOKEN_B.as_bytes(), 80u64);
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.admin_fees()
+                .insert(token_id(TOKEN_A), BigUint::from(100u64));
+            for owed in [50u64, 30] {
+                let id = sc.add_referral(REFERRER.to_managed_address(), 100);
+                sc.referrer_balances(id)
+                    .insert(token_id(TOKEN_B), BigUint::from(owed));
+            }
+        });
+
+    world
+        .tx()
+        .from(OWNER)
+        .raw_deploy()
+        .code(CODE)
+        .new_address(AGG_V2)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.init();
+            sc.set_migration_source(AGG.to_managed_address());
+        });
+    world
+}
+
+fn migrate(world: &mut ScenarioWorld, from: u64, count: u64) {
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.migrate_balances(AGG_V2.to_managed_address(), from, count);
+        });
+}
+
+#[test]
+fn migrate_balances_moves_admin_fees_and_referrals() {
+    let mut world = setup_migration();
+
+    migrate(&mut world, 1, 10);
+
+    world.check_account(AGG).esdt_balance(TOKEN_A, 0u64);
+    world.check_account(AGG).esdt_balance(TOKEN_B, 0u64);
+    world.check_account(AGG_V2).esdt_balance(TOKEN_A, 100u64);
+    world.check_account(AGG_V2).esdt_balance(TOKEN_B, 80u64);
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            assert!(sc.admin_fees().is_empty());
+            assert!(sc.referrer_balances(1).is_empty());
+            assert!(sc.referrer_balances(2).is_empty());
+        });
+    world
+        .query()
+        .to(AGG_V2)
+        .whitebox(aggregator::contract_obj, |sc| {
+            assert_eq!(
+                sc.admin_fees().get(&token_id(TOKEN_A)),
+                Some(BigUint::from(100u64))
+            );
+            for (id, owed) in [(1u64, 50u64), (2, 30)] {
+                let config = sc.referral_config(id).get();
+                assert_eq!(config.owner, REFERRER.to_managed_address());
+                assert_eq!(config.fee, 100);
+                assert_eq!(
+                    sc.referrer_balances(id).get(&token_id(TOKEN_B)),
+                    Some(BigUint::from(owed))
+                );
+            }
+            assert_eq!(sc.referral_id_counter().get(), 2);
+        });
+}
+
+#[test]
+fn migrate_balances_pages_through_the_referrals() {
+    let mut world = setup_migration();
+
+    migrate(&mut world, 1, 1);
+    world.check_account(AGG_V2).esdt_balance(TOKEN_B, 50u64);
+    world
+        .query()
+        .to(AGG_V2)
+        .whitebox(aggregator::contract_obj, |sc| {
+            assert!(sc.referral_config(2).is_empty());
+        });
+
+    // A page past the last referral stops at the last id
+    migrate(&mut world, 2, 100);
+    world.check_account(AGG_V2).esdt_balance(TOKEN_B, 80u64);
+    world
+        .query()
+        .to(AGG_V2)
+        .whitebox(aggregator::contract_obj, |sc| {
+            assert_eq!(
+                sc.referrer_balances(2).get(&token_id(TOKEN_B)),
+                Some(BigUint::from(30u64))
+            );
+        });
+}
+
+#[test]
+fn migrated_balances_are_only_accepted_from_the_migration_source() {
+    let mut world = setup_migration();
+
+    world
+        .tx()
+        .from(USER)
+        .to(AGG_V2)
+        .esdt(TestEsdtTransfer(TOKEN_A, 0, 10))
+        .returns(ExpectMessage(aggregator::errors::ERR_NOT_MIGRATION_SOURCE))
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.accept_migrated_admin_fees();
+        });
+}
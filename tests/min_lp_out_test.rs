@@ -0,0 +1,115 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::TxResponseStatus;
+
+use common::*;
+
+/// Minimum LP for the next add taken from amounts[0]
+const SET_MIN_LP_0: Row = (30, 0, 0, 0, 0, 255);
+/// xExchange swap of amounts[1] A to B, then an add of all A and all B
+const SWAP_FIXED_1: Row = (0, 1, 0, 3, 0, 0);
+const XEXCHANGE_ADD_ALL: Row = (1, 0, 0, 1, 0, 0);
+/// AshSwap V1 and Jex stable adds of all A only
+const ASH_ADD_ALL: Row = (4, 0, 255, 255, 0, 0);
+const JEX_STABLE_ADD_ALL: Row = (16, 0, 255, 255, 0, 0);
+
+fn xexchange_add(world: &mut ScenarioWorld, min_lp_out: u64) -> Result<(), TxResponseStatus> {
+    xo_call(
+        world,
+        (TOKEN_A, 2_000),
+        0,
+        2,
+        0,
+        &[TOKEN_A, TOKEN_B, LP_TOKEN],
+        &[PAIR],
+        &[min_lp_out, 1_000],
+        &[SWAP_FIXED_1, SET_MIN_LP_0, XEXCHANGE_ADD_ALL],
+    )
+}
+
+fn stable_add(
+    world: &mut ScenarioWorld,
+    add: Row,
+    min_lp_out: u64,
+) -> Result<(), TxResponseStatus> {
+    xo_call(
+        world,
+        (TOKEN_A, 300),
+        0,
+        2,
+        0,
+        &[TOKEN_A, TOKEN_B, STABLE_LP],
+        &[STABLE_POOL],
+        &[min_lp_out],
+        &[SET_MIN_LP_0, add],
+    )
+}
+
+fn stable_pool_world() -> ScenarioWorld {
+    let mut world = setup();
+    deploy_stable_pool(&mut world, 1_000_000);
+    world
+}
+
+fn last_add_min(world: &mut ScenarioWorld) -> u64 {
+    let mut min = 0;
+    world
+        .query()
+        .to(STABLE_POOL)
+        .whitebox(mock_stable::contract_obj, |sc| {
+            use mock_stable::MockStable;
+            min = sc.last_add_min().get().to_u64().unwrap();
+        });
+    min
+}
+
+#[test]
+fn xexchange_add_passes_a_met_min_lp_out() {
+    let mut world = setup_pair();
+
+    xexchange_add(&mut world, 997).unwrap();
+
+    world.check_account(USER).esdt_balance(LP_TOKEN, 997u64);
+}
+
+#[test]
+fn xexchange_add_reverts_below_min_lp_out() {
+    let mut world = setup_pair();
+
+    let result = xexchange_add(&mut world, 998);
+
+    assert_eq!(
+        result.unwrap_err().message,
+        "E: LP output below minimum: have 997, need 998"
+    );
+}
+
+#[test]
+fn stable_adds_forward_min_lp_out_to_the_pool() {
+    for add in [ASH_ADD_ALL, JEX_STABLE_ADD_ALL] {
+        let mut world = stable_pool_world();
+
+        stable_add(&mut world, add, 300).unwrap();
+
+        assert_eq!(last_add_min(&mut world), 300);
+        world.check_account(USER).esdt_balance(STABLE_LP, 300u64);
+    }
+}
+
+#[test]
+fn stable_adds_revert_below_min_lp_out() {
+    for add in [ASH_ADD_ALL, JEX_STABLE_ADD_ALL] {
+        let mut world = stable_pool_world();
+
+        let result = stable_add(&mut world, add, 301);
+
+        assert_eq!(
+            result.unwrap_err().message,
+            format!("E: Sub-call failed for action {}: error code 1", add.0)
+        );
+        world
+            .check_account(USER)
+            .esdt_balance(TOKEN_A, 1_000_000u64);
+    }
+}
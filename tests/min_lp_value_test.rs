@@ -0,0 +1,68 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::TxResponseStatus;
+
+use common::*;
+
+const SWAP_A_TO_B: Row = (0, 1, 0, 2, 0, 0);
+/// At least amounts[1] LP shares from the next add
+const MIN_LP_SHARES: Row = (30, 1, 0, 0, 0, 255);
+/// The next add must be worth at least amounts[2] of A
+const MIN_LP_VALUE_IN_A: Row = (40, 0, 0, 2, 0, 255);
+const ADD_ALL: Row = (1, 0, 0, 1, 0, 0);
+
+/// Swap half of 2000 A to B and add both sides, asking for 400 shares worth 1900 A
+fn guarded_add(world: &mut ScenarioWorld) -> Result<(), TxResponseStatus> {
+    xo_call(
+        world,
+        (TOKEN_A, 2_000),
+        1,
+        2,
+        0,
+        &[TOKEN_A, TOKEN_B, LP_TOKEN],
+        &[PAIR],
+        &[1_000, 400, 1_900],
+        &[SWAP_A_TO_B, MIN_LP_SHARES, MIN_LP_VALUE_IN_A, ADD_ALL],
+    )
+}
+
+/// Pair minting only `mint_ppm` of the fair LP amount, 0 for a fair mint
+fn setup_minting_pair(mint_ppm: u32) -> ScenarioWorld {
+    let mut world = setup_pair();
+    world
+        .tx()
+        .from(OWNER)
+        .to(PAIR)
+        .whitebox(mock_pair::contract_obj, |sc| {
+            use mock_pair::MockPair;
+            sc.lp_mint_ppm().set(mint_ppm);
+        });
+    world
+}
+
+#[test]
+fn fair_add_meets_both_minimums() {
+    let mut world = setup_minting_pair(0);
+
+    guarded_add(&mut world).unwrap();
+
+    world.check_account(USER).esdt_balance(LP_TOKEN, 997u64);
+}
+
+#[test]
+fn off_value_add_reverts_despite_meeting_the_share_minimum() {
+    let mut world = setup_minting_pair(500_000);
+
+    let err = guarded_add(&mut world).unwrap_err();
+
+    assert!(
+        err.message.starts_with("E: LP value below minimum"),
+        "{}",
+        err.message
+    );
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_A, 1_000_000u64);
+    world.check_account(USER).esdt_balance(LP_TOKEN, 0u64);
+}
@@ -0,0 +1,95 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+
+use aggregator::config::Config;
+use aggregator::storage::Storage;
+use common::*;
+
+const SWAP_FIXED_0: Row = (0, 1, 0, 2, 0, 0);
+const ZAP_ADD_ALL: Row = (1, 0, 0, 1, 0, 0);
+
+/// Swap 1,000 of 2,000 A to B, then ZAP add the 1,000 A and 996 B left, which the
+/// moved pool balances with a pre-swap of a few wei of A
+fn near_balanced_zap(world: &mut ScenarioWorld) {
+    xo_call(
+        world,
+        (TOKEN_A, 2_000),
+        0,
+        2,
+        0,
+        &[TOKEN_A, TOKEN_B, LP_TOKEN],
+        &[PAIR],
+        &[1_000],
+        &[SWAP_FIXED_0, ZAP_ADD_ALL],
+    )
+    .unwrap();
+}
+
+fn set_min_zap_swap(world: &mut ScenarioWorld, min_amount: u64) {
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.set_min_zap_swap(token_id(TOKEN_A), BigUint::from(min_amount));
+        });
+}
+
+/// Admin dust booked in (A, B)
+fn admin_dust(world: &mut ScenarioWorld) -> (u64, u64) {
+    let mut dust = (0, 0);
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            let booked = |token| {
+                sc.admin_fees()
+                    .get(&token_id(token))
+                    .unwrap_or_default()
+                    .to_u64()
+                    .unwrap()
+            };
+            dust = (booked(TOKEN_A), booked(TOKEN_B));
+        });
+    dust
+}
+
+#[test]
+fn a_zap_pre_swap_below_the_minimum_is_skipped() {
+    let mut world = setup_pair();
+    set_min_zap_swap(&mut world, 100);
+
+    near_balanced_zap(&mut world);
+
+    // The add takes the 996 B as is; the A the pool ratio cannot take is admin dust
+    world
+        .check_account(PAIR)
+        .esdt_balance(TOKEN_A, 1_001_997u64);
+    assert_eq!(admin_dust(&mut world), (3, 0));
+}
+
+#[test]
+fn a_zap_pre_swap_reaching_the_minimum_still_runs() {
+    let mut world = setup_pair();
+    set_min_zap_swap(&mut world, 1);
+
+    near_balanced_zap(&mut world);
+
+    world
+        .check_account(PAIR)
+        .esdt_balance(TOKEN_A, 1_002_000u64);
+    assert_eq!(admin_dust(&mut world), (0, 1));
+}
+
+#[test]
+fn without_a_minimum_any_imbalance_is_pre_swapped() {
+    let mut world = setup_pair();
+
+    near_balanced_zap(&mut world);
+
+    world
+        .check_account(PAIR)
+        .esdt_balance(TOKEN_A, 1_002_000u64);
+    assert_eq!(admin_dust(&mut world), (0, 1));
+}
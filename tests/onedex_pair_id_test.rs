@@ -0,0 +1,58 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+
+use common::*;
+
+/// Router with pairs 1 to 3
+fn setup_router() -> ScenarioWorld {
+    let mut world = setup();
+    set_protocol_storage(
+        &mut world,
+        &aggregator::constants::ONE_DEX_ROUTER,
+        b"last_pair_id",
+        &[3],
+    );
+    world
+}
+
+fn onedex_add(world: &mut ScenarioWorld, pair_id: u16) -> String {
+    xo_call(
+        world,
+        (TOKEN_A, 1_000),
+        0,
+        1,
+        0,
+        &[TOKEN_A, TOKEN_B],
+        &[],
+        &[],
+        &[(10, 0, 1, 0, 0, pair_id)],
+    )
+    .expect_err("path should revert")
+    .message
+}
+
+#[test]
+fn onedex_pair_ids_past_the_last_pair_are_rejected() {
+    let mut world = setup_router();
+
+    assert_eq!(
+        onedex_add(&mut world, 4),
+        "D: OneDex pair_id 4 out of range"
+    );
+    assert_eq!(
+        onedex_add(&mut world, 0),
+        "D: OneDex pair_id 0 out of range"
+    );
+}
+
+#[test]
+fn onedex_pair_ids_up_to_the_last_pair_pass_decoding() {
+    let mut world = setup_router();
+
+    // The id decodes; the add then fails on the missing pair data, not the id
+    for pair_id in [1, 3] {
+        let message = onedex_add(&mut world, pair_id);
+        assert!(!message.contains("out of range"), "{message}");
+    }
+}
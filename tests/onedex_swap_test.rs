@@ -0,0 +1,54 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+
+use common::*;
+
+/// OneDex swap of all A into B through the router
+const ONEDEX_SWAP_A_TO_B: Row = (9, 1, 0, 0, 0, 255);
+
+/// World from `setup` with the OneDex router pricing A/B like the standard pair
+fn setup_router() -> ScenarioWorld {
+    let mut world = setup();
+    let router = protocol_address(&aggregator::constants::ONE_DEX_ROUTER);
+    for token in [TOKEN_A, TOKEN_B] {
+        world.set_esdt_balance(router.clone(), token.as_bytes(), 1_000_000u64);
+    }
+    world
+        .tx()
+        .from(OWNER)
+        .to(router)
+        .whitebox(mock_pair::contract_obj, |sc| {
+            use mock_pair::MockPair;
+            sc.first_token_id().set(esdt(TOKEN_A));
+            sc.second_token_id().set(esdt(TOKEN_B));
+            sc.reserve(&esdt(TOKEN_A)).set(BigUint::from(1_000_000u64));
+            sc.reserve(&esdt(TOKEN_B)).set(BigUint::from(1_000_000u64));
+            sc.total_fee_percent().set(300);
+        });
+    world
+}
+
+#[test]
+fn onedex_swap_sends_a_single_hop_path_for_its_one_input() {
+    let mut world = setup_router();
+
+    // The router rejects any path other than [input, output]
+    xo_call(
+        &mut world,
+        (TOKEN_A, 1_000),
+        996,
+        1,
+        0,
+        &[TOKEN_A, TOKEN_B],
+        &[],
+        &[],
+        &[ONEDEX_SWAP_A_TO_B],
+    )
+    .unwrap();
+
+    world.check_account(USER).esdt_balance(TOKEN_A, 999_000u64);
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_B, 1_000_996u64);
+}
@@ -0,0 +1,173 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::TxResponseStatus;
+
+use aggregator::config::Config;
+use aggregator::errors::ERR_POOL_ADDRESS_UNRESOLVED;
+use aggregator::storage::Storage;
+use common::*;
+
+const PAIR_BC: TestSCAddress = TestSCAddress::new("pair-bc");
+const LP_BC: TestTokenIdentifier = TestTokenIdentifier::new("LPBC-000009");
+
+/// A -> B swap of everything in the vault
+const SWAP_A_TO_B: Row = (0, 1, 0, 0, 0, 0);
+/// B -> C swap of everything in the vault
+const SWAP_B_TO_C: Row = (0, 2, 1, 0, 0, 1);
+/// Hatom redeem of all LP_TOKEN (the hToken) in the vault
+const REDEEM: Row = (23, 1, 0, 255, 0, 255);
+
+/// A/B pair at PAIR and B/C pair at PAIR_BC, both with their overrides dropped
+fn setup_pairs() -> ScenarioWorld {
+    let mut world = setup_pair();
+    deploy_pair_at(
+        &mut world,
+        PAIR_BC,
+        (TOKEN_B, TOKEN_C),
+        LP_BC,
+        (1_000_000, 1_000_000),
+        1_000_000,
+        300,
+    );
+    set_pair_overrides(
+        &mut world,
+        &[(TOKEN_A, TOKEN_B, None), (TOKEN_B, TOKEN_C, None)],
+    );
+    world
+}
+
+/// Write pair overrides in one batch; a `None` pair drops the override
+fn set_pair_overrides(
+    world: &mut ScenarioWorld,
+    entries: &[(
+        TestTokenIdentifier,
+        TestTokenIdentifier,
+        Option<TestSCAddress>,
+    )],
+) {
+    let entries = entries.to_vec();
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            let mut batch = MultiValueEncoded::new();
+            for (first, second, pair) in entries {
+                let pair = pair
+                    .map(|pair| pair.to_managed_address())
+                    .unwrap_or_else(ManagedAddress::zero);
+                batch.push((esdt(first), esdt(second), pair).into());
+            }
+            sc.set_pair_overrides_batch(batch);
+        });
+}
+
+/// Point the LP_TOKEN hToken at `market`, or drop its override when `None`
+fn set_market_override(world: &mut ScenarioWorld, market: Option<TestSCAddress>) {
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            let market = market
+                .map(|market| market.to_managed_address())
+                .unwrap_or_else(ManagedAddress::zero);
+            let mut batch = MultiValueEncoded::new();
+            batch.push((esdt(LP_TOKEN), market).into());
+            sc.set_market_overrides_batch(batch);
+        });
+}
+
+/// Swap 1,000 A to C through B
+fn swap_a_to_c(world: &mut ScenarioWorld) -> Result<(), TxResponseStatus> {
+    xo_call(
+        world,
+        (TOKEN_A, 1_000),
+        0,
+        2,
+        0,
+        &[TOKEN_A, TOKEN_B, TOKEN_C],
+        &[PAIR, PAIR_BC],
+        &[],
+        &[SWAP_A_TO_B, SWAP_B_TO_C],
+    )
+}
+
+/// Redeem 1,000 LP_TOKEN hTokens for A at whichever market the override names
+fn redeem(world: &mut ScenarioWorld) -> Result<(), TxResponseStatus> {
+    xo_call(
+        world,
+        (LP_TOKEN, 1_000),
+        0,
+        0,
+        0,
+        &[TOKEN_A, LP_TOKEN],
+        &[],
+        &[],
+        &[REDEEM],
+    )
+}
+
+#[test]
+fn a_zero_pair_drops_the_override() {
+    let mut world = setup_pairs();
+
+    let result = swap_a_to_c(&mut world);
+
+    assert_eq!(result.unwrap_err().message, ERR_POOL_ADDRESS_UNRESOLVED);
+}
+
+#[test]
+fn batched_pair_overrides_route_swaps_in_either_token_order() {
+    let mut world = setup_pairs();
+
+    set_pair_overrides(
+        &mut world,
+        &[
+            (TOKEN_B, TOKEN_A, Some(PAIR)),
+            (TOKEN_B, TOKEN_C, Some(PAIR_BC)),
+        ],
+    );
+    swap_a_to_c(&mut world).unwrap();
+
+    world
+        .check_account(PAIR)
+        .esdt_balance(TOKEN_A, 1_001_000u64);
+    world
+        .check_account(PAIR_BC)
+        .esdt_balance(TOKEN_B, 1_000_996u64);
+    world.check_account(USER).esdt_balance(TOKEN_C, 992u64);
+}
+
+#[test]
+fn batched_market_overrides_route_hatom_calls() {
+    let mut world = setup();
+    deploy_hatom_market(&mut world);
+    world.set_esdt_balance(HATOM_MARKET, TOKEN_A.as_bytes(), 1_000_000u64);
+    world.set_esdt_balance(USER, LP_TOKEN.as_bytes(), 1_000u64);
+    world
+        .tx()
+        .from(OWNER)
+        .to(HATOM_MARKET)
+        .whitebox(mock_pair::contract_obj, |sc| {
+            use mock_pair::MockPair;
+            sc.first_token_id().set(esdt(TOKEN_A));
+        });
+
+    set_market_override(&mut world, None);
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            assert!(sc.market_override(&esdt(LP_TOKEN)).is_empty());
+        });
+
+    set_market_override(&mut world, Some(HATOM_MARKET));
+    redeem(&mut world).unwrap();
+
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_A, 1_001_000u64);
+    world.check_account(USER).esdt_balance(LP_TOKEN, 0u64);
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::TxResponseStatus;
+
+use aggregator::config::Config;
+use aggregator::storage::Storage;
+use aggregator::Aggregator;
+use common::*;
+
+const TREASURY: TestAddress = TestAddress::new("treasury");
+const SWAP_A_TO_B: Row = (0, 1, 0, 0, 0, 0);
+
+/// Pair deployed, referral 1 at 1%, 10% static fee on B, and the owner holding A
+fn setup_owner() -> ScenarioWorld {
+    let mut world = setup_pair();
+    world.account(TREASURY).nonce(1);
+    world.set_esdt_balance(OWNER, TOKEN_A.as_bytes(), 1_000_000u64);
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.add_referral(REFERRER.to_managed_address(), 100);
+            sc.set_static_fee_override(token_id(TOKEN_B), 1_000);
+        });
+    world
+}
+
+fn xo_owner(world: &mut ScenarioWorld) -> Result<(), TxResponseStatus> {
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .esdt(TestEsdtTransfer(TOKEN_A, 0, 1_000))
+        .returns(ReturnsHandledOrError::new())
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.aggregate_owner(
+                aggregator::constants::FORMAT_VERSION,
+                TREASURY.to_managed_address(),
+                BigUint::from(996u64),
+                1,
+                registry_tokens(&[TOKEN_A, TOKEN_B]),
+                registry_addresses(&[PAIR]),
+                registry_amounts(&[]),
+                instruction_rows(&[SWAP_A_TO_B]),
+            );
+        })
+}
+
+#[test]
+fn owner_path_takes_no_fee_and_pays_the_treasury() {
+    let mut world = setup_owner();
+
+    xo_owner(&mut world).unwrap();
+
+    world.check_account(TREASURY).esdt_balance(TOKEN_B, 996u64);
+    world.check_account(AGG).esdt_balance(TOKEN_B, 0u64);
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            assert!(sc.admin_fees().get(&token_id(TOKEN_B)).is_none());
+        });
+}
+
+#[test]
+fn the_same_path_through_xo_takes_the_fee() {
+    let mut world = setup_owner();
+
+    xo_call(
+        &mut world,
+        (TOKEN_A, 1_000),
+        0,
+        1,
+        1,
+        &[TOKEN_A, TOKEN_B],
+        &[PAIR],
+        &[],
+        &[SWAP_A_TO_B],
+    )
+    .unwrap();
+
+    let mut admin_fee = 0;
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            admin_fee = sc
+                .admin_fees()
+                .get(&token_id(TOKEN_B))
+                .unwrap_or_default()
+                .to_u64()
+                .unwrap();
+        });
+    assert_eq!(admin_fee, 99);
+}
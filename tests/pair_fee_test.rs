@@ -0,0 +1,160 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+
+use aggregator::types::PairFee;
+use aggregator::Aggregator;
+use common::*;
+
+fn pair_fee(world: &mut ScenarioWorld, pool: TestSCAddress, opt_pair_id: Option<usize>) -> PairFee {
+    let mut fee = PairFee::Percent04;
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            fee = sc.get_pair_fee(pool.to_managed_address(), opt_pair_id.into());
+        });
+    fee
+}
+
+fn pair_fee_error(world: &mut ScenarioWorld, pool: TestSCAddress) -> String {
+    let result = world
+        .query()
+        .to(AGG)
+        .returns(ReturnsHandledOrError::new())
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.get_pair_fee(pool.to_managed_address(), OptionalValue::None);
+        });
+    result.unwrap_err().message
+}
+
+fn deploy_jex_pool(world: &mut ScenarioWorld, lp_fees: u8, platform_fees: u8) {
+    deploy_mock_pool(world, JEX_POOL);
+    set_pool_storage(world, JEX_POOL, b"liq_providers_fees", &[lp_fees]);
+    set_pool_storage(world, JEX_POOL, b"platform_fees", &[platform_fees]);
+}
+
+#[test]
+fn pair_fee_of_xexchange_pairs_for_each_tier() {
+    for (fee, tier) in [
+        (400, PairFee::Percent04),
+        (600, PairFee::Percent06),
+        (1000, PairFee::Percent10),
+    ] {
+        let mut world = setup();
+        deploy_pair(&mut world, 1_000_000, 1_000_000, 1_000_000, fee);
+
+        assert_eq!(pair_fee(&mut world, PAIR, None), tier);
+    }
+}
+
+#[test]
+fn pair_fee_of_the_standard_xexchange_pair_matches_no_tier() {
+    let mut world = setup_pair();
+
+    assert_eq!(
+        pair_fee_error(&mut world, PAIR),
+        aggregator::errors::ERR_PAIR_FEE_NO_TIER
+    );
+}
+
+#[test]
+fn pair_fee_of_an_xexchange_pair_is_not_rounded_into_a_tier() {
+    let mut world = setup();
+    // 0.409% would truncate to 40 bps under a base-10,000 conversion
+    deploy_pair(&mut world, 1_000_000, 1_000_000, 1_000_000, 409);
+
+    assert_eq!(
+        pair_fee_error(&mut world, PAIR),
+        aggregator::errors::ERR_PAIR_FEE_NO_TIER
+    );
+}
+
+#[test]
+fn pair_fee_of_a_jex_pool_sums_lp_and_platform_fees() {
+    let mut world = setup();
+    deploy_jex_pool(&mut world, 40, 20);
+
+    assert_eq!(pair_fee(&mut world, JEX_POOL, None), PairFee::Percent06);
+}
+
+#[test]
+fn pair_fee_of_onedex_pools_for_each_tier() {
+    let mut world = setup();
+    for (pair_id, tier) in [(1u32, 0u8), (2, 1), (3, 2)] {
+        let mut key = b"pair_fee".to_vec();
+        key.extend_from_slice(&pair_id.to_be_bytes());
+        set_protocol_storage(
+            &mut world,
+            &aggregator::constants::ONE_DEX_ROUTER,
+            &key,
+            &[tier],
+        );
+    }
+
+    assert_eq!(pair_fee(&mut world, PAIR, Some(1)), PairFee::Percent04);
+    assert_eq!(pair_fee(&mut world, PAIR, Some(2)), PairFee::Percent06);
+    assert_eq!(pair_fee(&mut world, PAIR, Some(3)), PairFee::Percent10);
+}
+
+#[test]
+fn pair_fee_of_an_unknown_pool_reverts() {
+    let mut world = setup();
+    deploy_mock_pool(&mut world, JEX_POOL);
+
+    assert_eq!(
+        pair_fee_error(&mut world, JEX_POOL),
+        aggregator::errors::ERR_PAIR_FEE_NOT_FOUND
+    );
+}
+
+#[test]
+fn pair_fee_tiers_map_to_their_fee_fraction() {
+    assert_eq!(PairFee::Percent04.as_fee_fraction(), (40, 10_000));
+    assert_eq!(PairFee::Percent06.as_fee_fraction(), (60, 10_000));
+    assert_eq!(PairFee::Percent10.as_fee_fraction(), (100, 10_000));
+}
+
+#[test]
+fn pair_fee_fraction_reproduces_the_pool_output() {
+    for tier in [PairFee::Percent04, PairFee::Percent06, PairFee::Percent10] {
+        let mut world = setup();
+        // The mock pool charges total_fee_percent over 100,000
+        let (fee_num, fee_denom) = tier.as_fee_fraction();
+        deploy_pair(
+            &mut world,
+            1_000_000,
+            2_000_000,
+            1_000_000,
+            fee_num * 100_000 / fee_denom,
+        );
+
+        let mut pool_out = 0;
+        world
+            .query()
+            .to(PAIR)
+            .whitebox(mock_pair::contract_obj, |sc| {
+                use mock_pair::MockPair;
+                let out = sc.get_amount_out(esdt(TOKEN_A), BigUint::from(10_000u64));
+                pool_out = out.to_u64().unwrap();
+            });
+
+        let mut zap_out = 0;
+        world
+            .query()
+            .to(AGG)
+            .whitebox(aggregator::contract_obj, |_| {
+                let (out, _, _) = aggregator::zap::simulate_swap_output(
+                    &BigUint::<DebugApi>::from(10_000u64),
+                    &BigUint::from(1_000_000u64),
+                    &BigUint::from(2_000_000u64),
+                    fee_num,
+                    fee_denom,
+                    aggregator::zap::FeeMode::OnInput { special_fee_num: 0 },
+                );
+                zap_out = out.to_u64().unwrap();
+            });
+
+        assert_eq!(zap_out, pool_out, "{tier:?}");
+    }
+}
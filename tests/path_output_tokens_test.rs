@@ -0,0 +1,53 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+
+use aggregator::types::PathOutput;
+use aggregator::Aggregator;
+use common::*;
+
+/// A -> B swap of amounts[0]
+const SWAP_FIXED_0: Row = (0, 1, 0, 2, 0, 0);
+/// ZAP add of all A and B
+const ADD_LIQUIDITY: Row = (1, 0, 0, 1, 0, 0);
+const CLEAR_PREV: Row = (25, 0, 0, 0, 0, 255);
+/// Wrap all EGLD
+const WRAP: Row = (18, 254, 0, 255, 0, 255);
+
+#[test]
+fn path_output_tokens_lists_each_known_output_once() {
+    let mut world = setup();
+
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            let outputs: Vec<PathOutput<DebugApi>> = sc
+                .path_output_tokens(
+                    aggregator::constants::FORMAT_VERSION,
+                    registry_tokens(&[TOKEN_A, TOKEN_B]),
+                    registry_addresses(&[PAIR]),
+                    registry_amounts(&[1_000]),
+                    instruction_rows(&[
+                        SWAP_FIXED_0,
+                        SWAP_FIXED_0,
+                        ADD_LIQUIDITY,
+                        CLEAR_PREV,
+                        WRAP,
+                    ]),
+                )
+                .into_iter()
+                .collect();
+
+            // The repeated swap is listed once, the LP mint is tagged with its action
+            // byte and the control action produces nothing
+            assert!(
+                outputs
+                    == vec![
+                        PathOutput::Token(esdt(TOKEN_B)),
+                        PathOutput::Unknown(1),
+                        PathOutput::Token(esdt(WEGLD)),
+                    ]
+            );
+        });
+}
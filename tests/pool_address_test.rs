@@ -0,0 +1,64 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+
+use aggregator::Aggregator;
+use common::*;
+
+/// A -> B swap of everything in the vault
+const SWAP_ALL: Row = (0, 1, 0, 0, 0, 0);
+/// ZAP add of all A and B
+const ADD_LIQUIDITY: Row = (1, 0, 0, 1, 0, 0);
+
+#[test]
+fn a_swap_without_a_pair_reports_the_unresolved_pool() {
+    // No A/B pair is registered
+    let mut world = setup();
+
+    let result = xo_call(
+        &mut world,
+        (TOKEN_A, 1_000),
+        0,
+        1,
+        0,
+        &[TOKEN_A, TOKEN_B],
+        &[PAIR],
+        &[],
+        &[SWAP_ALL],
+    );
+
+    assert_eq!(
+        result.unwrap_err().message,
+        aggregator::errors::ERR_POOL_ADDRESS_UNRESOLVED
+    );
+}
+
+#[test]
+fn an_add_liquidity_without_a_pair_reports_the_unresolved_pool() {
+    let mut world = setup();
+
+    let result = world
+        .tx()
+        .from(USER)
+        .to(AGG)
+        .esdt(TestEsdtTransfer(TOKEN_A, 0, 1_000u64))
+        .esdt(TestEsdtTransfer(TOKEN_B, 0, 1_000u64))
+        .returns(ReturnsHandledOrError::new())
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.aggregate(
+                aggregator::constants::FORMAT_VERSION,
+                BigUint::zero(),
+                2,
+                0,
+                registry_tokens(&[TOKEN_A, TOKEN_B, LP_TOKEN]),
+                registry_addresses(&[PAIR]),
+                registry_amounts(&[]),
+                instruction_rows(&[ADD_LIQUIDITY]),
+            );
+        });
+
+    assert_eq!(
+        result.unwrap_err().message,
+        aggregator::errors::ERR_POOL_ADDRESS_UNRESOLVED
+    );
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+
+use aggregator::types::AmountMode;
+use aggregator::utils::Utils;
+use common::*;
+
+/// PPM mode reading amounts[0]
+const PPM_0: u8 = 128;
+
+/// Amount mode decoded from `PPM_0` with `ppm` as amounts[0]
+fn decode_ppm(world: &mut ScenarioWorld, ppm: num_bigint::BigUint) -> Result<u32, String> {
+    let mut decoded = 0;
+    world
+        .query()
+        .to(AGG)
+        .returns(ReturnsHandledOrError::new())
+        .whitebox(aggregator::contract_obj, |sc| {
+            let mut amounts = ManagedVec::new();
+            amounts.push(BigUint::from_bytes_be(&ppm.to_bytes_be()));
+            let mode = aggregator::types::CompactMode::from_u8(PPM_0);
+            match sc.compact_mode_to_amount_mode(&mode, &amounts) {
+                AmountMode::Ppm(value) => decoded = value,
+                _ => panic!("PPM mode decoded to another amount mode"),
+            }
+        })
+        .map(|_| decoded)
+        .map_err(|err| err.message)
+}
+
+#[test]
+fn ppm_of_one_million_is_accepted() {
+    let mut world = setup();
+
+    assert_eq!(decode_ppm(&mut world, 1_000_000u64.into()), Ok(1_000_000));
+}
+
+#[test]
+fn ppm_above_one_million_is_rejected() {
+    let mut world = setup();
+
+    assert_eq!(
+        decode_ppm(&mut world, 1_000_001u64.into()),
+        Err(aggregator::errors::ERR_INSTRUCTION_PPM_EXCEEDS_100_PERCENT.to_string())
+    );
+}
+
+#[test]
+fn ppm_that_would_truncate_to_zero_is_rejected() {
+    let mut world = setup();
+
+    // 2^32 wraps to 0 as a u32, 2^64 decodes to 0 through an unchecked u64
+    for ppm in [
+        num_bigint::BigUint::from(1u64 << 32),
+        num_bigint::BigUint::from(1u8) << 64,
+    ] {
+        assert_eq!(
+            decode_ppm(&mut world, ppm),
+            Err(aggregator::errors::ERR_INSTRUCTION_PPM_EXCEEDS_100_PERCENT.to_string())
+        );
+    }
+}
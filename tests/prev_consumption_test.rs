@@ -0,0 +1,50 @@
+mod common;
+
+use common::*;
+
+/// Swap amounts[0] of A to B, producing a previous result of B
+const SWAP_FIXED_TO_B: Row = (0, 1, 0, 2, 0, 0);
+/// Swap the previous result of B back to A
+const SWAP_PREV_TO_A: Row = (0, 0, 1, 1, 0, 0);
+
+#[test]
+fn each_producer_feeds_one_prev_amount() {
+    let mut world = setup_pair();
+
+    // Every swap refills the previous result the next one spends
+    xo_call(
+        &mut world,
+        (TOKEN_A, 1_000),
+        0,
+        1,
+        0,
+        &[TOKEN_A, TOKEN_B],
+        &[PAIR],
+        &[500],
+        &[SWAP_FIXED_TO_B, SWAP_PREV_TO_A, (0, 1, 0, 1, 0, 0)],
+    )
+    .unwrap();
+}
+
+#[test]
+fn a_second_prev_amount_without_a_producer_reverts() {
+    let mut world = setup_pair();
+
+    // The B leg spends the swap result, leaving nothing for the A leg
+    let result = xo_call(
+        &mut world,
+        (TOKEN_A, 1_000),
+        0,
+        2,
+        0,
+        &[TOKEN_A, TOKEN_B, LP_TOKEN],
+        &[PAIR],
+        &[500],
+        &[SWAP_FIXED_TO_B, (1, 1, 1, 0, 1, 0)],
+    );
+
+    assert_eq!(result.unwrap_err().message, "E: PrevAmount not available");
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_A, 1_000_000u64);
+}
@@ -0,0 +1,30 @@
+mod common;
+
+use common::*;
+
+/// Swap amounts[0] of A to B, producing a previous result of B
+const SWAP_FIXED_TO_B: Row = (0, 1, 0, 2, 0, 0);
+/// Swap the previous result as A, although the last swap produced B
+const SWAP_PREV_A_TO_B: Row = (0, 1, 0, 1, 0, 0);
+
+#[test]
+fn a_prev_amount_of_the_wrong_token_names_both_tokens() {
+    let mut world = setup_pair();
+
+    let result = xo_call(
+        &mut world,
+        (TOKEN_A, 1_000),
+        0,
+        1,
+        0,
+        &[TOKEN_A, TOKEN_B],
+        &[PAIR],
+        &[500],
+        &[SWAP_FIXED_TO_B, SWAP_PREV_A_TO_B],
+    );
+
+    assert_eq!(
+        result.unwrap_err().message,
+        "E: PrevAmount token mismatch: expected TOKA-000001, got TOKB-000002"
+    );
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+
+use aggregator::Aggregator;
+use common::*;
+
+/// Remove all the paid LP
+const REMOVE_LIQUIDITY: Row = (2, 2, 0, 255, 0, 0);
+
+fn preview(world: &mut ScenarioWorld, lp_amount: u64) -> ((Vec<u8>, u64), (Vec<u8>, u64)) {
+    let mut amounts = Default::default();
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            let (first, second) = sc
+                .preview_remove_liquidity(PAIR.to_managed_address(), BigUint::from(lp_amount))
+                .into_tuple();
+            let entry = |payment: EsdtTokenPayment<DebugApi>| {
+                (
+                    payment.token_identifier.as_managed_buffer().to_vec(),
+                    payment.amount.to_u64().unwrap(),
+                )
+            };
+            amounts = (entry(first), entry(second));
+        });
+    amounts
+}
+
+#[test]
+fn preview_matches_the_amounts_a_removal_pays_out() {
+    let mut world = setup();
+    deploy_pair(&mut world, 3_000, 7_000, 1_000, 300);
+    world.set_esdt_balance(USER, LP_TOKEN.as_bytes(), 333u64);
+
+    // 333 of 1,000 LP is 999 of 3,000 A and 2,331 of 7,000 B, rounded down
+    assert_eq!(
+        preview(&mut world, 333),
+        (
+            (TOKEN_A.as_bytes().to_vec(), 999),
+            (TOKEN_B.as_bytes().to_vec(), 2_331)
+        )
+    );
+
+    xo_call(
+        &mut world,
+        (LP_TOKEN, 333),
+        0,
+        0,
+        0,
+        &[TOKEN_A, TOKEN_B, LP_TOKEN],
+        &[PAIR],
+        &[],
+        &[REMOVE_LIQUIDITY],
+    )
+    .unwrap();
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_A, 1_000_999u64);
+    world.check_account(PAIR).esdt_balance(TOKEN_A, 2_001u64);
+    world.check_account(PAIR).esdt_balance(TOKEN_B, 4_669u64);
+}
+
+#[test]
+fn preview_of_an_empty_pair_is_zero() {
+    let mut world = setup();
+    deploy_pair(&mut world, 0, 0, 0, 300);
+
+    assert_eq!(
+        preview(&mut world, 333),
+        (
+            (TOKEN_A.as_bytes().to_vec(), 0),
+            (TOKEN_B.as_bytes().to_vec(), 0)
+        )
+    );
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+
+use aggregator::utils::Utils;
+use common::*;
+
+const SWAP_A_TO_B: Row = (0, 1, 0, 0, 0, 0);
+const SWAP_PREV_B_TO_A: Row = (0, 0, 1, 1, 0, 0);
+
+/// Quote 1,000 A through `rows` into `token_out`
+fn quote(world: &mut ScenarioWorld, token_out: TestTokenIdentifier, rows: &[Row]) -> u64 {
+    let rows = rows.to_vec();
+    let mut quoted = 0;
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            let mut payment = ManagedVec::new();
+            payment.push(Payment::new(
+                token_id(TOKEN_A),
+                0,
+                BigUint::from(1_000u64).into_non_zero().unwrap(),
+            ));
+            quoted = sc
+                .quote_swap_path(
+                    &payment,
+                    &token_id(token_out),
+                    &registry_tokens(&[TOKEN_A, TOKEN_B]).to_vec(),
+                    &registry_addresses(&[PAIR]).to_vec(),
+                    &registry_amounts(&[]).to_vec(),
+                    instruction_rows(&rows),
+                )
+                .to_u64()
+                .unwrap();
+        });
+    quoted
+}
+
+fn execute(world: &mut ScenarioWorld, token_out: u8, rows: &[Row]) {
+    xo_call(
+        world,
+        (TOKEN_A, 1_000),
+        0,
+        token_out,
+        0,
+        &[TOKEN_A, TOKEN_B],
+        &[PAIR],
+        &[],
+        rows,
+    )
+    .unwrap();
+}
+
+#[test]
+fn a_single_hop_quote_from_the_pair_view_matches_execution() {
+    let mut world = setup_pair();
+
+    let quoted = quote(&mut world, TOKEN_B, &[SWAP_A_TO_B]);
+    execute(&mut world, 1, &[SWAP_A_TO_B]);
+
+    assert_eq!(quoted, 996);
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_B, 1_000_000u64 + quoted);
+}
+
+#[test]
+fn a_second_hop_on_the_same_pair_quoted_from_moved_reserves_matches_execution() {
+    let mut world = setup_pair();
+
+    let quoted = quote(&mut world, TOKEN_A, &[SWAP_A_TO_B, SWAP_PREV_B_TO_A]);
+    execute(&mut world, 0, &[SWAP_A_TO_B, SWAP_PREV_B_TO_A]);
+
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_A, 1_000_000u64 - 1_000 + quoted);
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::TxResponseStatus;
+
+use aggregator::config::Config;
+use aggregator::errors::ERR_REFERRAL_CAMPAIGN_WINDOW;
+use aggregator::utils::Utils;
+use common::*;
+
+fn set_campaign(
+    world: &mut ScenarioWorld,
+    start_epoch: u64,
+    end_epoch: u64,
+) -> Result<(), TxResponseStatus> {
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .returns(ReturnsHandledOrError::new())
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.set_referral_campaign(1, 500, start_epoch, end_epoch);
+        })
+}
+
+/// Referral 1 at a flat 1%, boosted to 5% from epoch 10 to epoch 20
+fn setup_campaign() -> ScenarioWorld {
+    let mut world = setup();
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.add_referral(REFERRER.to_managed_address(), 100);
+        });
+    set_campaign(&mut world, 10, 20).unwrap();
+    world
+}
+
+/// Referral share of a trade of 10,000 B through referral 1 at `epoch`
+fn referral_share_at(world: &mut ScenarioWorld, epoch: u64) -> u64 {
+    world.current_block().block_epoch(epoch);
+    let mut share = 0;
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            let (_, referral) = sc.compute_fees(
+                &token_id(TOKEN_B),
+                &token_id(TOKEN_B),
+                1,
+                &BigUint::from(10_000u64),
+            );
+            share = referral.to_u64().unwrap();
+        });
+    share
+}
+
+#[test]
+fn the_flat_fee_applies_before_the_window() {
+    let mut world = setup_campaign();
+
+    assert_eq!(referral_share_at(&mut world, 9), 100);
+}
+
+#[test]
+fn the_campaign_fee_applies_throughout_the_window() {
+    let mut world = setup_campaign();
+
+    assert_eq!(referral_share_at(&mut world, 10), 500);
+    assert_eq!(referral_share_at(&mut world, 15), 500);
+    assert_eq!(referral_share_at(&mut world, 20), 500);
+}
+
+#[test]
+fn the_flat_fee_applies_after_the_window() {
+    let mut world = setup_campaign();
+
+    assert_eq!(referral_share_at(&mut world, 21), 100);
+}
+
+#[test]
+fn clearing_the_campaign_ends_it_early() {
+    let mut world = setup_campaign();
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.clear_referral_campaign(1);
+        });
+
+    assert_eq!(referral_share_at(&mut world, 15), 100);
+}
+
+#[test]
+fn a_window_ending_before_it_starts_is_rejected() {
+    let mut world = setup_campaign();
+
+    let result = set_campaign(&mut world, 20, 10);
+
+    assert_eq!(result.unwrap_err().message, ERR_REFERRAL_CAMPAIGN_WINDOW);
+}
@@ -0,0 +1,82 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::TxResponseStatus;
+
+use aggregator::config::Config;
+use aggregator::errors::{ERR_REFERRAL_FEE_EXCEEDS_50, ERR_REFERRAL_NOT_FOUND};
+use aggregator::storage::Storage;
+use common::*;
+
+/// Referrals 1 to 3, all at 1%
+fn setup_referrals() -> ScenarioWorld {
+    let mut world = setup();
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            for _ in 0..3 {
+                sc.add_referral(REFERRER.to_managed_address(), 100);
+            }
+        });
+    world
+}
+
+fn set_batch(world: &mut ScenarioWorld, fees: &[(u64, u32)]) -> Result<(), TxResponseStatus> {
+    let fees = fees.to_vec();
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .returns(ReturnsHandledOrError::new())
+        .whitebox(aggregator::contract_obj, |sc| {
+            let mut encoded = MultiValueEncoded::new();
+            for (id, fee) in &fees {
+                encoded.push((*id, *fee).into());
+            }
+            sc.set_referral_fee_batch(encoded);
+        })
+}
+
+fn referral_fees(world: &mut ScenarioWorld) -> Vec<u32> {
+    let mut fees = Vec::new();
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            for id in 1..=3 {
+                fees.push(sc.referral_config(id).get().fee);
+            }
+        });
+    fees
+}
+
+#[test]
+fn a_batch_updates_every_listed_referral() {
+    let mut world = setup_referrals();
+
+    set_batch(&mut world, &[(1, 50), (3, 300)]).unwrap();
+
+    assert_eq!(referral_fees(&mut world), vec![50, 100, 300]);
+}
+
+#[test]
+fn an_unknown_id_reverts_the_whole_batch() {
+    let mut world = setup_referrals();
+
+    let result = set_batch(&mut world, &[(1, 50), (4, 300)]);
+
+    assert_eq!(result.unwrap_err().message, ERR_REFERRAL_NOT_FOUND);
+    assert_eq!(referral_fees(&mut world), vec![100, 100, 100]);
+}
+
+#[test]
+fn a_fee_above_half_reverts_the_whole_batch() {
+    let mut world = setup_referrals();
+
+    let result = set_batch(&mut world, &[(1, 50), (2, 5_001)]);
+
+    assert_eq!(result.unwrap_err().message, ERR_REFERRAL_FEE_EXCEEDS_50);
+    assert_eq!(referral_fees(&mut world), vec![100, 100, 100]);
+}
@@ -0,0 +1,54 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+
+use aggregator::config::Config;
+use common::*;
+
+/// Four referrals with fees 100, 200, 300 and 400
+fn setup_referrals() -> ScenarioWorld {
+    let mut world = setup();
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            for fee in [100, 200, 300, 400] {
+                sc.add_referral(REFERRER.to_managed_address(), fee);
+            }
+        });
+    world
+}
+
+/// (id, fee) of every referral on the page `from..=to`
+fn page(world: &mut ScenarioWorld, from: u64, to: u64) -> Vec<(u64, u32)> {
+    let mut out = Vec::new();
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            for entry in sc.get_all_referrals(from, to) {
+                let (id, config) = entry.into_tuple();
+                assert_eq!(config.owner, REFERRER.to_managed_address());
+                out.push((id, config.fee));
+            }
+        });
+    out
+}
+
+#[test]
+fn a_page_lists_the_referrals_in_its_range() {
+    let mut world = setup_referrals();
+
+    assert_eq!(page(&mut world, 2, 3), vec![(2, 200), (3, 300)]);
+}
+
+#[test]
+fn a_page_is_clamped_to_the_created_ids() {
+    let mut world = setup_referrals();
+
+    // Id 0 is never assigned and ids past the counter do not exist
+    assert_eq!(page(&mut world, 0, 1), vec![(1, 100)]);
+    assert_eq!(page(&mut world, 3, 100), vec![(3, 300), (4, 400)]);
+    assert!(page(&mut world, 5, 10).is_empty());
+}
@@ -0,0 +1,79 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+
+use aggregator::config::Config;
+use aggregator::utils::Utils;
+use common::*;
+
+/// Referral 1 at 1% that only shares fees from trades of at least 10,000
+fn setup_min_trade() -> ScenarioWorld {
+    let mut world = setup();
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.add_referral(REFERRER.to_managed_address(), 100);
+            sc.set_referral_min_trade(1, BigUint::from(10_000u64));
+        });
+    world
+}
+
+/// (admin, referral) fee shares of a trade of `amount` B through referral 1
+fn fee_shares(world: &mut ScenarioWorld, amount: u64) -> (u64, u64) {
+    let mut shares = (0, 0);
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            let (admin, referral) = sc.compute_fees(
+                &token_id(TOKEN_B),
+                &token_id(TOKEN_B),
+                1,
+                &BigUint::from(amount),
+            );
+            shares = (admin.to_u64().unwrap(), referral.to_u64().unwrap());
+        });
+    shares
+}
+
+#[test]
+fn a_trade_at_the_minimum_shares_the_referral_fee() {
+    let mut world = setup_min_trade();
+
+    let (_, referral) = fee_shares(&mut world, 10_000);
+
+    assert_eq!(referral, 100);
+}
+
+#[test]
+fn a_trade_below_the_minimum_sends_the_referral_share_to_admin() {
+    let mut world = setup_min_trade();
+    let (admin_at_min, referral_at_min) = fee_shares(&mut world, 10_000);
+
+    let (admin, referral) = fee_shares(&mut world, 9_999);
+
+    assert_eq!(referral, 0);
+    // Same fee as a shared trade of that size, all of it booked to admin
+    assert_eq!(admin, (admin_at_min + referral_at_min) * 9_999 / 10_000);
+}
+
+#[test]
+fn min_trade_of_an_unknown_referral_is_rejected() {
+    let mut world = setup();
+
+    let result = world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .returns(ReturnsHandledOrError::new())
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.set_referral_min_trade(1, BigUint::from(10_000u64));
+        });
+
+    assert_eq!(
+        result.unwrap_err().message,
+        aggregator::errors::ERR_REFERRAL_NOT_FOUND
+    );
+}
@@ -0,0 +1,113 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::TxResponseStatus;
+
+use aggregator::config::Config;
+use aggregator::storage::Storage;
+use common::*;
+
+const TREASURY: TestAddress = TestAddress::new("treasury");
+const SWAP_A_TO_B: Row = (0, 1, 0, 0, 0, 0);
+
+/// Referral 1 at 1%, swept after 10 idle epochs
+fn setup_referral() -> ScenarioWorld {
+    let mut world = setup_pair();
+    world.account(TREASURY).nonce(1);
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.add_referral(REFERRER.to_managed_address(), 100);
+            sc.set_referral_stale_epochs(10);
+        });
+    world
+}
+
+fn swap_at(world: &mut ScenarioWorld, epoch: u64) {
+    world.current_block().block_epoch(epoch);
+    xo_call(
+        world,
+        (TOKEN_A, 1_000),
+        0,
+        1,
+        1,
+        &[TOKEN_A, TOKEN_B],
+        &[PAIR],
+        &[],
+        &[SWAP_A_TO_B],
+    )
+    .unwrap();
+}
+
+fn sweep_at(world: &mut ScenarioWorld, epoch: u64) -> Result<(), TxResponseStatus> {
+    world.current_block().block_epoch(epoch);
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .returns(ReturnsHandledOrError::new())
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.sweep_stale_referral(1, TREASURY.to_managed_address());
+        })
+}
+
+fn referral_state(world: &mut ScenarioWorld) -> (u64, u64) {
+    let mut state = (0, 0);
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            let balance = sc
+                .referrer_balances(1)
+                .get(&token_id(TOKEN_B))
+                .unwrap_or_default();
+            state = (
+                sc.referral_last_activity(1).get(),
+                balance.to_u64().unwrap(),
+            );
+        });
+    state
+}
+
+#[test]
+fn accruals_move_the_last_activity_once_per_epoch() {
+    let mut world = setup_referral();
+
+    swap_at(&mut world, 5);
+    let (activity, first_balance) = referral_state(&mut world);
+    assert_eq!(activity, 5);
+    assert!(first_balance > 0);
+
+    swap_at(&mut world, 5);
+    assert_eq!(referral_state(&mut world).0, 5);
+
+    swap_at(&mut world, 8);
+    assert_eq!(referral_state(&mut world).0, 8);
+}
+
+#[test]
+fn stale_referral_is_swept_to_the_treasury() {
+    let mut world = setup_referral();
+    swap_at(&mut world, 1);
+    let (_, balance) = referral_state(&mut world);
+
+    assert!(sweep_at(&mut world, 11).is_err());
+    sweep_at(&mut world, 12).unwrap();
+
+    world.check_account(TREASURY).esdt_balance(TOKEN_B, balance);
+    assert_eq!(referral_state(&mut world).1, 0);
+}
+
+#[test]
+fn active_referral_is_not_swept() {
+    let mut world = setup_referral();
+    swap_at(&mut world, 1);
+    swap_at(&mut world, 8);
+
+    let err = sweep_at(&mut world, 12).unwrap_err();
+
+    assert_eq!(err.message, aggregator::errors::ERR_REFERRAL_NOT_STALE);
+    world.check_account(TREASURY).esdt_balance(TOKEN_B, 0u64);
+}
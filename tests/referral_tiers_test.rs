@@ -0,0 +1,94 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::TxResponseStatus;
+
+use aggregator::config::Config;
+use aggregator::utils::Utils;
+use common::*;
+
+fn set_tiers(world: &mut ScenarioWorld, tiers: &[(u64, u32)]) -> Result<(), TxResponseStatus> {
+    let tiers = tiers.to_vec();
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .returns(ReturnsHandledOrError::new())
+        .whitebox(aggregator::contract_obj, |sc| {
+            let mut encoded = MultiValueEncoded::new();
+            for (min_output, fee) in &tiers {
+                encoded.push((BigUint::from(*min_output), *fee).into());
+            }
+            sc.set_referral_fee_tiers(1, encoded);
+        })
+}
+
+/// Referral 1 at a flat 1%, raised to 2% from 1,000 and 3% from 10,000
+fn setup_tiers() -> ScenarioWorld {
+    let mut world = setup();
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.add_referral(REFERRER.to_managed_address(), 100);
+        });
+    set_tiers(&mut world, &[(1_000, 200), (10_000, 300)]).unwrap();
+    world
+}
+
+/// Referral share of a trade of `amount` B through referral 1
+fn referral_share(world: &mut ScenarioWorld, amount: u64) -> u64 {
+    let mut share = 0;
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            let (_, referral) = sc.compute_fees(
+                &token_id(TOKEN_B),
+                &token_id(TOKEN_B),
+                1,
+                &BigUint::from(amount),
+            );
+            share = referral.to_u64().unwrap();
+        });
+    share
+}
+
+#[test]
+fn trades_below_the_first_tier_pay_the_flat_fee() {
+    let mut world = setup_tiers();
+
+    assert_eq!(referral_share(&mut world, 999), 9);
+}
+
+#[test]
+fn each_tier_applies_from_its_threshold() {
+    let mut world = setup_tiers();
+
+    assert_eq!(referral_share(&mut world, 1_000), 20);
+    assert_eq!(referral_share(&mut world, 9_999), 199);
+    assert_eq!(referral_share(&mut world, 10_000), 300);
+    assert_eq!(referral_share(&mut world, 100_000), 3_000);
+}
+
+#[test]
+fn clearing_the_tiers_restores_the_flat_fee() {
+    let mut world = setup_tiers();
+
+    set_tiers(&mut world, &[]).unwrap();
+
+    assert_eq!(referral_share(&mut world, 10_000), 100);
+}
+
+#[test]
+fn tiers_out_of_order_are_rejected() {
+    let mut world = setup_tiers();
+
+    let result = set_tiers(&mut world, &[(10_000, 300), (1_000, 200)]);
+
+    assert_eq!(
+        result.unwrap_err().message,
+        "Referral fee tier thresholds must be ascending"
+    );
+}
@@ -0,0 +1,175 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::TxResponseStatus;
+
+use aggregator::config::Config;
+use aggregator::storage::Storage;
+use common::*;
+
+fn setup_restricted() -> ScenarioWorld {
+    let mut world = setup();
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.add_referral(REFERRER.to_managed_address(), 100);
+            let mut tokens = MultiValueEncoded::new();
+            tokens.push(token_id(TOKEN_A));
+            tokens.push(token_id(TOKEN_B));
+            sc.add_referral_allowed_tokens(1, tokens);
+        });
+    world
+}
+
+fn remove(
+    world: &mut ScenarioWorld,
+    removed: &[TestTokenIdentifier],
+) -> Result<(), TxResponseStatus> {
+    let removed = removed.to_vec();
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .returns(ReturnsHandledOrError::new())
+        .whitebox(aggregator::contract_obj, |sc| {
+            let mut tokens = MultiValueEncoded::new();
+            for token in &removed {
+                tokens.push(token_id(*token));
+            }
+            sc.remove_referral_allowed_tokens(1, tokens);
+        })
+}
+
+fn allowed_count(world: &mut ScenarioWorld) -> usize {
+    let mut count = 0;
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            count = sc.referral_allowed_tokens(1).len();
+        });
+    count
+}
+
+#[test]
+fn removing_the_last_allowed_token_is_rejected() {
+    let mut world = setup_restricted();
+
+    remove(&mut world, &[TOKEN_A]).unwrap();
+    let err = remove(&mut world, &[TOKEN_B]).unwrap_err();
+
+    assert_eq!(err.message, aggregator::errors::ERR_LAST_ALLOWED_TOKEN);
+    assert_eq!(allowed_count(&mut world), 1);
+}
+
+#[test]
+fn removing_every_allowed_token_at_once_is_rejected() {
+    let mut world = setup_restricted();
+
+    let err = remove(&mut world, &[TOKEN_A, TOKEN_B]).unwrap_err();
+
+    assert_eq!(err.message, aggregator::errors::ERR_LAST_ALLOWED_TOKEN);
+    assert_eq!(allowed_count(&mut world), 2);
+}
+
+#[test]
+fn clearing_lifts_the_restriction() {
+    let mut world = setup_restricted();
+
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.clear_referral_allowed_tokens(1);
+        });
+
+    assert_eq!(allowed_count(&mut world), 0);
+    // Nothing is restricted, so removing from the empty set is a no-op
+    remove(&mut world, &[TOKEN_A]).unwrap();
+}
+
+#[test]
+fn removing_tokens_of_an_unknown_referral_is_rejected() {
+    let mut world = setup();
+
+    let err = remove(&mut world, &[TOKEN_A]).unwrap_err();
+
+    assert_eq!(err.message, aggregator::errors::ERR_REFERRAL_NOT_FOUND);
+}
+
+/// Referral 1 at 1% restricted to `allowed`, optionally charging fees on the input
+fn setup_allowed_only(allowed: TestTokenIdentifier, fee_on_input: bool) -> ScenarioWorld {
+    let mut world = setup_pair();
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.add_referral(REFERRER.to_managed_address(), 100);
+            let mut tokens = MultiValueEncoded::new();
+            tokens.push(token_id(allowed));
+            sc.add_referral_allowed_tokens(1, tokens);
+            sc.set_fee_on_input(fee_on_input);
+        });
+    world
+}
+
+/// Swap 1,000 A to B under referral 1, returning the (admin, referral) fees in `fee_token`
+fn swap_a_to_b(world: &mut ScenarioWorld, fee_token: TestTokenIdentifier) -> (u64, u64) {
+    xo_call(
+        world,
+        (TOKEN_A, 1_000),
+        0,
+        1,
+        1,
+        &[TOKEN_A, TOKEN_B],
+        &[PAIR],
+        &[],
+        &[(0, 1, 0, 0, 0, 0)],
+    )
+    .unwrap();
+    let mut balances = (0, 0);
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            let token = token_id(fee_token);
+            let admin = sc.admin_fees().get(&token).unwrap_or_default();
+            let referral = sc.referrer_balances(1).get(&token).unwrap_or_default();
+            balances = (admin.to_u64().unwrap(), referral.to_u64().unwrap());
+        });
+    balances
+}
+
+#[test]
+fn the_referral_earns_when_token_out_is_allowed() {
+    let mut world = setup_allowed_only(TOKEN_B, false);
+
+    // 1% of the 996 B output
+    assert_eq!(swap_a_to_b(&mut world, TOKEN_B), (0, 9));
+}
+
+#[test]
+fn the_referral_share_goes_to_admin_when_only_the_input_is_allowed() {
+    let mut world = setup_allowed_only(TOKEN_A, false);
+
+    assert_eq!(swap_a_to_b(&mut world, TOKEN_B), (9, 0));
+}
+
+#[test]
+fn fee_on_input_gates_the_referral_on_token_out() {
+    let mut world = setup_allowed_only(TOKEN_B, true);
+
+    // 1% of the 1,000 A payment, earned because B is the aggregation's output
+    assert_eq!(swap_a_to_b(&mut world, TOKEN_A), (0, 10));
+}
+
+#[test]
+fn fee_on_input_ignores_an_allowed_payment_token() {
+    let mut world = setup_allowed_only(TOKEN_A, true);
+
+    assert_eq!(swap_a_to_b(&mut world, TOKEN_A), (10, 0));
+}
@@ -0,0 +1,54 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+
+use common::*;
+
+/// Run a one-row path on the standard pair and return the revert message
+fn revert_message(
+    tokens: &[TestTokenIdentifier],
+    addresses: &[TestSCAddress],
+    amounts: &[u64],
+    row: Row,
+) -> String {
+    let mut world = setup_pair();
+    xo_call(
+        &mut world,
+        (TOKEN_A, 1_000),
+        0,
+        1,
+        0,
+        tokens,
+        addresses,
+        amounts,
+        &[row],
+    )
+    .expect_err("path should revert")
+    .message
+}
+
+#[test]
+fn a_short_amounts_registry_is_reported() {
+    // The row swaps amounts[0], but the client counted no amounts
+    assert_eq!(
+        revert_message(&[TOKEN_A, TOKEN_B], &[PAIR], &[], (0, 1, 0, 2, 0, 0)),
+        "D: Registry length mismatch: amounts index 0 but only 0 entries"
+    );
+}
+
+#[test]
+fn a_short_tokens_registry_is_reported() {
+    assert_eq!(
+        revert_message(&[TOKEN_A], &[PAIR], &[], (0, 1, 0, 0, 0, 0)),
+        "D: Registry length mismatch: tokens index 1 but only 1 entries"
+    );
+}
+
+#[test]
+fn a_short_addresses_registry_is_reported() {
+    // AshSwap V1 swaps go to the listed pool address
+    assert_eq!(
+        revert_message(&[TOKEN_A, TOKEN_B], &[PAIR], &[], (3, 1, 0, 0, 0, 1)),
+        "D: Registry length mismatch: addresses index 1 but only 1 entries"
+    );
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::TxResponseStatus;
+
+use common::*;
+
+const REQUIRE_FULL_INPUT: Row = (38, 0, 0, 0, 0, 255);
+const ALLOW_FAILURE: Row = (37, 0, 0, 0, 0, 255);
+/// A -> B swap of all A
+const SWAP_ALL: Row = (0, 1, 0, 0, 0, 0);
+/// A -> B swap of amounts[0]
+const SWAP_FIXED_0: Row = (0, 1, 0, 2, 0, 0);
+/// A -> B swap of amounts[1]
+const SWAP_FIXED_1: Row = (0, 1, 0, 3, 0, 0);
+
+fn swap(world: &mut ScenarioWorld, amounts: &[u64], rows: &[Row]) -> Result<(), TxResponseStatus> {
+    xo_call(
+        world,
+        (TOKEN_A, 2_000),
+        0,
+        1,
+        0,
+        &[TOKEN_A, TOKEN_B],
+        &[PAIR],
+        amounts,
+        rows,
+    )
+}
+
+#[test]
+fn full_input_routed_passes() {
+    let mut world = setup_pair();
+
+    swap(&mut world, &[], &[REQUIRE_FULL_INPUT, SWAP_ALL]).unwrap();
+}
+
+#[test]
+fn leftover_input_reverts() {
+    let mut world = setup_pair();
+
+    let err = swap(&mut world, &[1_000], &[REQUIRE_FULL_INPUT, SWAP_FIXED_0]).unwrap_err();
+    assert_eq!(
+        err.message,
+        "E: Input token TOKA-000001 not fully consumed: 1000 left"
+    );
+}
+
+#[test]
+fn refunded_input_of_a_skipped_swap_reverts() {
+    let mut world = setup_pair();
+
+    // 1 A is too little for the pool, so the best-effort swap is skipped and refunded
+    let err = swap(
+        &mut world,
+        &[1_999, 1],
+        &[
+            REQUIRE_FULL_INPUT,
+            SWAP_FIXED_0,
+            ALLOW_FAILURE,
+            SWAP_FIXED_1,
+        ],
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.message,
+        "E: Input token TOKA-000001 not fully consumed: 1 left"
+    );
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_A, 1_000_000u64);
+}
@@ -0,0 +1,55 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::TxResponseStatus;
+
+use common::*;
+
+fn swap_a_to_b(world: &mut ScenarioWorld, row: Row) -> Result<(), TxResponseStatus> {
+    xo_call(
+        world,
+        (TOKEN_A, 1_000),
+        0,
+        1,
+        0,
+        &[TOKEN_A, TOKEN_B],
+        &[PAIR],
+        &[],
+        &[row],
+    )
+}
+
+#[test]
+fn a_jex_swap_without_an_address_is_rejected() {
+    let mut world = setup_pair();
+
+    let result = swap_a_to_b(&mut world, (12, 1, 0, 0, 0, 255));
+
+    assert_eq!(
+        result.unwrap_err().message,
+        "D: Action 12 requires an explicit address"
+    );
+}
+
+#[test]
+fn an_ash_swap_without_an_address_is_rejected() {
+    let mut world = setup_pair();
+
+    let result = swap_a_to_b(&mut world, (3, 1, 0, 0, 0, 255));
+
+    assert_eq!(
+        result.unwrap_err().message,
+        "D: Action 3 requires an explicit address"
+    );
+}
+
+#[test]
+fn an_xexchange_swap_resolves_its_own_pair() {
+    let mut world = setup_pair();
+
+    swap_a_to_b(&mut world, (0, 1, 0, 0, 0, 255)).unwrap();
+
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_B, 1_000_996u64);
+}
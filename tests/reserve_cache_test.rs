@@ -0,0 +1,55 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+
+use aggregator::storage::Storage;
+use aggregator::types::{ActionType, Instruction};
+use aggregator::utils::Utils;
+use aggregator::vault::Vault;
+use common::*;
+
+#[test]
+fn cached_reserves_match_the_pool_after_each_zap_add() {
+    let mut world = setup_pair();
+    world.set_esdt_balance(AGG, TOKEN_A.as_bytes(), 2_000u64);
+    world.set_esdt_balance(AGG, TOKEN_B.as_bytes(), 20u64);
+
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            let instr = Instruction {
+                action: ActionType::XExchangeAddLiquidity,
+                inputs: None,
+                address: None,
+            };
+            let pool = PAIR.to_managed_address();
+            let cache_key = sc.reserve_cache_key(&instr.action, &pool);
+            let mut vault = Vault::new();
+
+            // The second ZAP starts from the reserves the first one cached
+            for _ in 0..2 {
+                let mut payments = ManagedVec::new();
+                payments.push(Payment::new(
+                    token_id(TOKEN_A),
+                    0,
+                    BigUint::from(1_000u64).into_non_zero().unwrap(),
+                ));
+                payments.push(Payment::new(
+                    token_id(TOKEN_B),
+                    0,
+                    BigUint::from(10u64).into_non_zero().unwrap(),
+                ));
+                sc.pre_balance_and_add_liquidity(
+                    &mut vault,
+                    &instr,
+                    &payments,
+                    &token_id(LP_TOKEN),
+                );
+
+                let cached = vault.cached_reserves(&cache_key);
+                assert!(cached == Some(sc.get_reserves(&instr.action, &pool)));
+            }
+        });
+}
@@ -0,0 +1,141 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+
+use aggregator::storage::Storage;
+use aggregator::types::ActionType;
+use common::*;
+
+const ONEDEX_ROUTER: TestSCAddress = TestSCAddress::new("onedex-router");
+
+#[derive(Clone, Copy)]
+enum Pool {
+    XExchange,
+    OneDex,
+    Jex,
+}
+
+fn reserves(world: &mut ScenarioWorld, dex: Pool, pool: TestSCAddress) -> (u64, u64) {
+    let mut reserves = (0, 0);
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            let action = match dex {
+                Pool::XExchange => ActionType::XExchangeAddLiquidity,
+                Pool::OneDex => ActionType::OneDexAddLiquidity(1),
+                Pool::Jex => ActionType::JexAddLiquidity,
+            };
+            let (first, second) = sc.get_reserves(&action, &pool.to_managed_address());
+            reserves = (first.to_u64().unwrap(), second.to_u64().unwrap());
+        });
+    reserves
+}
+
+/// A/B mock pool at `pool` whose swap reserves (and views) hold `reserves`
+fn deploy_view_pool(world: &mut ScenarioWorld, pool: TestSCAddress, reserves: (u64, u64)) {
+    world
+        .account(pool)
+        .nonce(1)
+        .code(MOCK_PAIR_CODE)
+        .owner(OWNER);
+    world
+        .tx()
+        .from(OWNER)
+        .to(pool)
+        .whitebox(mock_pair::contract_obj, |sc| {
+            use mock_pair::MockPair;
+            sc.first_token_id().set(esdt(TOKEN_A));
+            sc.second_token_id().set(esdt(TOKEN_B));
+            sc.reserve(&esdt(TOKEN_A)).set(BigUint::from(reserves.0));
+            sc.reserve(&esdt(TOKEN_B)).set(BigUint::from(reserves.1));
+        });
+}
+
+fn report_reserves(world: &mut ScenarioWorld, pool: TestSCAddress, reserves: (u64, u64)) {
+    world
+        .tx()
+        .from(OWNER)
+        .to(pool)
+        .whitebox(mock_pair::contract_obj, |sc| {
+            use mock_pair::MockPair;
+            sc.reported_reserves()
+                .set((BigUint::from(reserves.0), BigUint::from(reserves.1)));
+        });
+}
+
+fn set_reserve(world: &mut ScenarioWorld, pool: TestSCAddress, key: &[u8], value: u64) {
+    set_pool_storage(
+        world,
+        pool,
+        key,
+        &num_bigint::BigUint::from(value).to_bytes_be(),
+    );
+}
+
+#[test]
+fn xexchange_reserves_are_read_from_storage() {
+    let mut world = setup();
+    deploy_pair(&mut world, 1_000, 2_000, 1_000, 300);
+    report_reserves(&mut world, PAIR, (1, 1));
+
+    assert_eq!(reserves(&mut world, Pool::XExchange, PAIR), (1_000, 2_000));
+}
+
+#[test]
+fn xexchange_empty_storage_reserve_falls_back_to_the_view() {
+    let mut world = setup();
+    deploy_pair(&mut world, 0, 2_000, 1_000, 300);
+    report_reserves(&mut world, PAIR, (1_000, 2_000));
+
+    assert_eq!(reserves(&mut world, Pool::XExchange, PAIR), (1_000, 2_000));
+}
+
+#[test]
+fn onedex_reserves_fall_back_to_the_router_views() {
+    let mut world = setup();
+    deploy_view_pool(&mut world, ONEDEX_ROUTER, (3_000, 4_000));
+
+    assert_eq!(
+        reserves(&mut world, Pool::OneDex, ONEDEX_ROUTER),
+        (3_000, 4_000)
+    );
+}
+
+#[test]
+fn onedex_reserves_are_read_from_router_storage() {
+    let mut world = setup();
+    deploy_view_pool(&mut world, ONEDEX_ROUTER, (3_000, 4_000));
+    let pair_id = 1u32.to_be_bytes();
+    for (key, value) in [
+        (&b"pair_first_token_reserve"[..], 500),
+        (&b"pair_second_token_reserve"[..], 600),
+    ] {
+        let mut key = key.to_vec();
+        key.extend_from_slice(&pair_id);
+        set_reserve(&mut world, ONEDEX_ROUTER, &key, value);
+    }
+
+    assert_eq!(
+        reserves(&mut world, Pool::OneDex, ONEDEX_ROUTER),
+        (500, 600)
+    );
+}
+
+#[test]
+fn jex_reserves_fall_back_to_the_pair_views() {
+    let mut world = setup();
+    deploy_view_pool(&mut world, JEX_POOL, (5_000, 6_000));
+
+    assert_eq!(reserves(&mut world, Pool::Jex, JEX_POOL), (5_000, 6_000));
+}
+
+#[test]
+fn jex_reserves_are_read_from_storage() {
+    let mut world = setup();
+    deploy_view_pool(&mut world, JEX_POOL, (5_000, 6_000));
+    set_reserve(&mut world, JEX_POOL, b"first_token_reserve", 500);
+    set_reserve(&mut world, JEX_POOL, b"second_token_reserve", 600);
+
+    assert_eq!(reserves(&mut world, Pool::Jex, JEX_POOL), (500, 600));
+}
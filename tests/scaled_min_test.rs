@@ -0,0 +1,138 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::TxResponseStatus;
+
+use aggregator::config::Config;
+use aggregator::storage::Storage;
+use aggregator::Aggregator;
+use common::*;
+
+const SWAP_A_TO_B: Row = (0, 1, 0, 0, 0, 0);
+
+fn cache_decimals(world: &mut ScenarioWorld, token: TestTokenIdentifier, decimals: u32) {
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            let mut entries = MultiValueEncoded::new();
+            entries.push((token_id(token), decimals).into());
+            sc.set_token_decimals(entries);
+        });
+}
+
+/// Swap 1000 A for B (996 out) with a scaled minimum
+fn swap_scaled(
+    world: &mut ScenarioWorld,
+    min_amount_out: u64,
+    precision: u32,
+) -> Result<(), TxResponseStatus> {
+    world
+        .tx()
+        .from(USER)
+        .to(AGG)
+        .esdt(TestEsdtTransfer(TOKEN_A, 0, 1_000u64))
+        .returns(ReturnsHandledOrError::new())
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.aggregate_scaled(
+                aggregator::constants::FORMAT_VERSION,
+                BigUint::from(min_amount_out),
+                precision,
+                1,
+                0,
+                registry_tokens(&[TOKEN_A, TOKEN_B]),
+                registry_addresses(&[PAIR]),
+                registry_amounts(&[]),
+                instruction_rows(&[SWAP_A_TO_B]),
+            );
+        })
+}
+
+#[test]
+fn scaled_minimum_uses_the_cached_decimals() {
+    // B has 3 decimals: 0.997 B is 997 raw, one above the 996 a swap returns
+    let mut world = setup_pair();
+    cache_decimals(&mut world, TOKEN_B, 3);
+    assert!(swap_scaled(&mut world, 997, 3).is_err());
+
+    let mut world = setup_pair();
+    cache_decimals(&mut world, TOKEN_B, 3);
+    swap_scaled(&mut world, 996, 3).unwrap();
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_B, 1_000_996u64);
+}
+
+#[test]
+fn scaled_minimum_of_whole_units() {
+    // 1 whole B at 3 decimals is 1000 raw
+    let mut world = setup_pair();
+    cache_decimals(&mut world, TOKEN_B, 3);
+    assert!(swap_scaled(&mut world, 1, 0).is_err());
+
+    let mut world = setup_pair();
+    cache_decimals(&mut world, TOKEN_B, 2);
+    swap_scaled(&mut world, 9, 0).unwrap();
+}
+
+#[test]
+fn scaled_minimum_follows_the_output_token_decimals() {
+    // A has 6 decimals and B none: 996 whole B is 996 raw, not 996 * 10^6
+    let mut world = setup_pair();
+    cache_decimals(&mut world, TOKEN_A, 6);
+    cache_decimals(&mut world, TOKEN_B, 0);
+    swap_scaled(&mut world, 996, 0).unwrap();
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_B, 1_000_996u64);
+
+    // Swapped around, 1 whole B at 6 decimals is far above the output
+    let mut world = setup_pair();
+    cache_decimals(&mut world, TOKEN_A, 0);
+    cache_decimals(&mut world, TOKEN_B, 6);
+    assert!(swap_scaled(&mut world, 1, 0).is_err());
+}
+
+#[test]
+fn scaled_minimum_rejects_uncached_tokens_and_excess_precision() {
+    let mut world = setup_pair();
+    let err = swap_scaled(&mut world, 1, 0).unwrap_err();
+    assert_eq!(err.message, "E: Decimals unknown for token TOKB-000002");
+
+    cache_decimals(&mut world, TOKEN_B, 3);
+    let err = swap_scaled(&mut world, 1, 4).unwrap_err();
+    assert_eq!(
+        err.message,
+        aggregator::errors::ERR_PRECISION_EXCEEDS_DECIMALS
+    );
+}
+
+#[test]
+fn decimals_cache_is_filled_and_cleared_by_the_owner() {
+    let mut world = setup();
+    cache_decimals(&mut world, TOKEN_B, 6);
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            assert_eq!(sc.get_decimals(&token_id(TOKEN_B)), 6);
+            assert_eq!(sc.get_decimals(&TokenId::from(b"EGLD-000000")), 18);
+        });
+
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            let mut tokens = MultiValueEncoded::new();
+            tokens.push(token_id(TOKEN_B));
+            sc.remove_token_decimals(tokens);
+        });
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            assert!(sc.token_decimals().get(&token_id(TOKEN_B)).is_none());
+        });
+}
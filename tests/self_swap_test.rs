@@ -0,0 +1,56 @@
+mod common;
+
+use common::*;
+
+#[test]
+fn a_swap_into_its_own_input_token_is_rejected() {
+    let mut world = setup_pair();
+
+    let result = xo_call(
+        &mut world,
+        (TOKEN_A, 1_000),
+        1,
+        0,
+        0,
+        &[TOKEN_A, TOKEN_B],
+        &[PAIR],
+        &[],
+        &[(0, 0, 0, 0, 0, 0)],
+    );
+
+    assert_eq!(
+        result.unwrap_err().message,
+        "D: Instruction swaps token TOKA-000001 to itself"
+    );
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_A, 1_000_000u64);
+}
+
+#[test]
+fn a_chained_swap_into_its_own_input_token_is_rejected() {
+    let mut world = setup_pair();
+
+    let result = xo_call(
+        &mut world,
+        (TOKEN_A, 1_000),
+        1,
+        1,
+        0,
+        &[TOKEN_A, TOKEN_B],
+        &[PAIR],
+        &[],
+        &[(0, 1, 0, 0, 0, 0), (0, 1, 1, 1, 0, 0)],
+    );
+
+    assert_eq!(
+        result.unwrap_err().message,
+        "D: Instruction swaps token TOKB-000002 to itself"
+    );
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_A, 1_000_000u64);
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_B, 1_000_000u64);
+}
@@ -0,0 +1,80 @@
+use multiversx_sc_scenario::imports::*;
+
+use aggregator::zap::{simulate_swap_output, FeeMode};
+
+/// (output, amount_out_leaving, amount_in_to_reserves) of 10,000 in on a 1M/2M pool
+fn simulate(fee_num: u64, fee_denom: u64, fee_mode: FeeMode) -> (u64, u64, u64) {
+    DebugApi::dummy();
+
+    let (output, leaving, to_reserves) = simulate_swap_output(
+        &BigUint::<DebugApi>::from(10_000u64),
+        &BigUint::from(1_000_000u64),
+        &BigUint::from(2_000_000u64),
+        fee_num,
+        fee_denom,
+        fee_mode,
+    );
+    (
+        output.to_u64().unwrap(),
+        leaving.to_u64().unwrap(),
+        to_reserves.to_u64().unwrap(),
+    )
+}
+
+#[test]
+fn valid_fee_params_simulate_the_swap() {
+    let (output, _, to_reserves) = simulate(
+        300,
+        100_000,
+        FeeMode::OnInput {
+            special_fee_num: 50,
+        },
+    );
+
+    assert_eq!(output, 19_743);
+    assert_eq!(to_reserves, 9_995);
+}
+
+#[test]
+fn a_fee_above_its_denominator_simulates_no_output() {
+    assert_eq!(
+        simulate(100_001, 100_000, FeeMode::OnInput { special_fee_num: 0 }),
+        (0, 0, 0)
+    );
+    assert_eq!(
+        simulate(100_001, 100_000, FeeMode::OnOutput { lp_fee_num: 0 }),
+        (0, 0, 0)
+    );
+}
+
+#[test]
+fn a_split_fee_above_the_denominator_simulates_no_output() {
+    assert_eq!(
+        simulate(
+            300,
+            100_000,
+            FeeMode::OnInput {
+                special_fee_num: 100_001
+            }
+        ),
+        (0, 0, 0)
+    );
+    assert_eq!(
+        simulate(
+            300,
+            100_000,
+            FeeMode::OnOutput {
+                lp_fee_num: 100_001
+            }
+        ),
+        (0, 0, 0)
+    );
+}
+
+#[test]
+fn a_zero_denominator_simulates_no_output() {
+    assert_eq!(
+        simulate(0, 0, FeeMode::OnInput { special_fee_num: 0 }),
+        (0, 0, 0)
+    );
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::TxResponseStatus;
+
+use aggregator::config::Config;
+use aggregator::Aggregator;
+use common::*;
+
+const SWAP_A_TO_B: Row = (0, 1, 0, 0, 0, 0);
+
+/// Pair at 1:1 and a floor of 90% of the spot quote
+fn setup_floor() -> ScenarioWorld {
+    let mut world = setup_pair();
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.set_min_output_ppm(900_000);
+        });
+    world
+}
+
+fn swap(world: &mut ScenarioWorld, min_amount_out: u64) -> Result<(), TxResponseStatus> {
+    xo_call(
+        world,
+        (TOKEN_A, 1_000),
+        min_amount_out,
+        1,
+        0,
+        &[TOKEN_A, TOKEN_B],
+        &[PAIR],
+        &[],
+        &[SWAP_A_TO_B],
+    )
+}
+
+#[test]
+fn minimum_below_the_floor_is_rejected() {
+    let mut world = setup_floor();
+
+    let err = swap(&mut world, 899).unwrap_err();
+
+    assert_eq!(err.message, "E: Slippage floor not met: min 899, floor 900");
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_A, 1_000_000u64);
+}
+
+#[test]
+fn minimum_at_the_floor_is_accepted() {
+    let mut world = setup_floor();
+
+    swap(&mut world, 900).unwrap();
+
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_B, 1_000_996u64);
+}
+
+#[test]
+fn floor_rejects_a_call_without_payment() {
+    let mut world = setup_floor();
+
+    let err = world
+        .tx()
+        .from(USER)
+        .to(AGG)
+        .returns(ReturnsHandledOrError::new())
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.aggregate(
+                aggregator::constants::FORMAT_VERSION,
+                BigUint::from(1u64),
+                1,
+                0,
+                registry_tokens(&[TOKEN_A, TOKEN_B]),
+                registry_addresses(&[PAIR]),
+                registry_amounts(&[]),
+                instruction_rows(&[SWAP_A_TO_B]),
+            );
+        })
+        .unwrap_err();
+
+    assert_eq!(err.message, aggregator::errors::ERR_NO_INPUT_PAYMENT);
+}
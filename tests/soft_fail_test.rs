@@ -0,0 +1,93 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::TxResponseStatus;
+
+use aggregator::Aggregator;
+use common::*;
+
+/// A -> B swap of everything in the vault
+const SWAP_ALL: Row = (0, 1, 0, 0, 0, 0);
+/// ZAP add of all A and B, which cannot be quoted
+const ADD_LIQUIDITY: Row = (1, 0, 0, 1, 0, 0);
+
+fn xo_soft_call(
+    world: &mut ScenarioWorld,
+    amount_in: u64,
+    min_amount_out: u64,
+    rows: &[Row],
+) -> Result<bool, TxResponseStatus> {
+    let rows = rows.to_vec();
+    let mut executed = false;
+    world
+        .tx()
+        .from(USER)
+        .to(AGG)
+        .esdt(TestEsdtTransfer(TOKEN_A, 0, amount_in))
+        .returns(ReturnsHandledOrError::new())
+        .whitebox(aggregator::contract_obj, |sc| {
+            executed = sc.aggregate_soft(
+                aggregator::constants::FORMAT_VERSION,
+                BigUint::from(min_amount_out),
+                1,
+                0,
+                registry_tokens(&[TOKEN_A, TOKEN_B]),
+                registry_addresses(&[PAIR]),
+                registry_amounts(&[]),
+                instruction_rows(&rows),
+            );
+        })
+        .map(|_| executed)
+}
+
+#[test]
+fn soft_fail_returns_the_payment_when_the_quote_is_too_low() {
+    let mut world = setup_pair();
+
+    // 1,000 A quotes 996 B
+    let executed = xo_soft_call(&mut world, 1_000, 997, &[SWAP_ALL]).unwrap();
+
+    assert!(!executed);
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_A, 1_000_000u64);
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_B, 1_000_000u64);
+    world
+        .check_account(PAIR)
+        .esdt_balance(TOKEN_A, 1_000_000u64);
+    world
+        .check_account(PAIR)
+        .esdt_balance(TOKEN_B, 1_000_000u64);
+}
+
+#[test]
+fn soft_fail_executes_the_path_when_the_quote_meets_the_minimum() {
+    let mut world = setup_pair();
+
+    let executed = xo_soft_call(&mut world, 1_000, 996, &[SWAP_ALL]).unwrap();
+
+    assert!(executed);
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_A, 1_000_000u64 - 1_000);
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_B, 1_000_996u64);
+}
+
+#[test]
+fn soft_fail_rejects_a_path_it_cannot_quote() {
+    let mut world = setup_pair();
+
+    let result = xo_soft_call(&mut world, 1_000, 0, &[ADD_LIQUIDITY]);
+
+    assert_eq!(
+        result.unwrap_err().message,
+        aggregator::errors::ERR_SOFT_FAIL_SWAP_ONLY
+    );
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_A, 1_000_000u64);
+}
@@ -0,0 +1,99 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+
+use aggregator::config::Config;
+use aggregator::storage::Storage;
+use common::*;
+
+const SWAP_A_TO_B: Row = (0, 1, 0, 0, 0, 0);
+const SWAP_B_TO_A: Row = (0, 0, 1, 0, 0, 0);
+
+/// Pair deployed, referral 1 taking no share, 0.5% global fee and 10% on B
+fn setup_override() -> ScenarioWorld {
+    let mut world = setup_pair();
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.add_referral(REFERRER.to_managed_address(), 0);
+            sc.set_static_fee(50);
+            sc.set_static_fee_override(token_id(TOKEN_B), 1_000);
+        });
+    world
+}
+
+/// Swap 1,000 of `payment` with referral 1 and return the admin fee booked in `token_out`
+fn admin_fee_of_swap(
+    world: &mut ScenarioWorld,
+    payment: TestTokenIdentifier,
+    token_out: TestTokenIdentifier,
+    row: Row,
+) -> u64 {
+    let out_index = if token_out == TOKEN_A { 0 } else { 1 };
+    xo_call(
+        world,
+        (payment, 1_000),
+        0,
+        out_index,
+        1,
+        &[TOKEN_A, TOKEN_B],
+        &[PAIR],
+        &[],
+        &[row],
+    )
+    .unwrap();
+    let mut fee = 0;
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            fee = sc
+                .admin_fees()
+                .get(&token_id(token_out))
+                .unwrap_or_default()
+                .to_u64()
+                .unwrap();
+        });
+    fee
+}
+
+#[test]
+fn an_overridden_output_token_pays_its_own_fee() {
+    let mut world = setup_override();
+
+    // 10% of the 996 B output
+    assert_eq!(
+        admin_fee_of_swap(&mut world, TOKEN_A, TOKEN_B, SWAP_A_TO_B),
+        99
+    );
+}
+
+#[test]
+fn other_output_tokens_pay_the_global_fee() {
+    let mut world = setup_override();
+
+    // 0.5% of the 996 A output
+    assert_eq!(
+        admin_fee_of_swap(&mut world, TOKEN_B, TOKEN_A, SWAP_B_TO_A),
+        4
+    );
+}
+
+#[test]
+fn a_cleared_override_falls_back_to_the_global_fee() {
+    let mut world = setup_override();
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.clear_static_fee_override(token_id(TOKEN_B));
+        });
+
+    assert_eq!(
+        admin_fee_of_swap(&mut world, TOKEN_A, TOKEN_B, SWAP_A_TO_B),
+        4
+    );
+}
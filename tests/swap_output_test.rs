@@ -0,0 +1,49 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+
+use common::*;
+
+/// Make the pair hand swap inputs back untouched, like a router without liquidity
+fn return_input(world: &mut ScenarioWorld) {
+    world
+        .tx()
+        .from(OWNER)
+        .to(PAIR)
+        .whitebox(mock_pair::contract_obj, |sc| {
+            use mock_pair::MockPair;
+            sc.return_input().set(true);
+        });
+}
+
+#[test]
+fn a_swap_returning_its_input_reverts_naming_the_pool() {
+    let mut world = setup_pair();
+    return_input(&mut world);
+
+    let result = xo_call(
+        &mut world,
+        (TOKEN_A, 1_000),
+        0,
+        0,
+        0,
+        &[TOKEN_A, TOKEN_B],
+        &[PAIR],
+        &[],
+        &[(0, 1, 0, 0, 0, 0), (0, 0, 1, 1, 0, 0)],
+    );
+
+    let pool: String = PAIR
+        .to_address()
+        .as_bytes()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    assert_eq!(
+        result.unwrap_err().message,
+        format!("E: Swap produced no output (no liquidity?) at pool {pool}")
+    );
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_A, 1_000_000u64);
+}
@@ -0,0 +1,88 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::TxResponseStatus;
+
+use common::*;
+
+/// Deposit the WEGLD outputs of the next call as EGLD
+const UNWRAP_OUTPUTS: Row = (35, 0, 0, 0, 0, 255);
+/// Remove all the paid WEGLD/B LP
+const REMOVE_LIQUIDITY: Row = (2, 2, 0, 255, 0, 0);
+
+fn setup_wegld_lp() -> ScenarioWorld {
+    let mut world = setup();
+    deploy_pair_at(
+        &mut world,
+        WEGLD_PAIR,
+        (WEGLD, TOKEN_B),
+        WEGLD_LP,
+        (1_000_000, 1_000_000),
+        1_000_000,
+        300,
+    );
+    fund_wrapper(&mut world, 1_000_000);
+    world.set_esdt_balance(USER, WEGLD_LP.as_bytes(), 1_000u64);
+    world
+}
+
+fn remove_liquidity(
+    world: &mut ScenarioWorld,
+    token_out: u8,
+    rows: &[Row],
+) -> Result<(), TxResponseStatus> {
+    xo_call(
+        world,
+        (WEGLD_LP, 1_000),
+        1_000,
+        token_out,
+        0,
+        &[WEGLD, TOKEN_B, WEGLD_LP],
+        &[WEGLD_PAIR],
+        &[],
+        rows,
+    )
+}
+
+#[test]
+fn unwrapped_outputs_reach_an_egld_token_out_as_native_egld() {
+    let mut world = setup_wegld_lp();
+
+    remove_liquidity(&mut world, 254, &[UNWRAP_OUTPUTS, REMOVE_LIQUIDITY]).unwrap();
+
+    world.check_account(USER).balance(1_001_000u64);
+    world.check_account(USER).esdt_balance(WEGLD, 0u64);
+    world.check_account(USER).esdt_balance(WEGLD_LP, 0u64);
+    world
+        .check_account(AGG)
+        .balance(0u64)
+        .esdt_balance(WEGLD, 0u64);
+}
+
+#[test]
+fn outputs_stay_wrapped_without_the_flag() {
+    let mut world = setup_wegld_lp();
+
+    remove_liquidity(&mut world, 0, &[REMOVE_LIQUIDITY]).unwrap();
+
+    world
+        .check_account(USER)
+        .balance(1_000_000u64)
+        .esdt_balance(WEGLD, 1_000u64);
+}
+
+#[test]
+fn an_egld_token_out_without_the_flag_finds_no_egld() {
+    let mut world = setup_wegld_lp();
+
+    let result = remove_liquidity(&mut world, 254, &[REMOVE_LIQUIDITY]);
+    assert_eq!(
+        result.unwrap_err().message,
+        "E: Token not found in vault: EGLD-000000"
+    );
+
+    world
+        .check_account(USER)
+        .balance(1_000_000u64)
+        .esdt_balance(WEGLD_LP, 1_000u64);
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+
+use aggregator::constants::{DEFAULT_CALLBACK_GAS, JEX_STABLE_MIN_MULTIPLIER, MIN_INTERNAL_OUTPUT};
+use aggregator::storage::Storage;
+use aggregator::types::ActionCategory;
+use aggregator::Aggregator;
+use common::*;
+
+fn upgrade(world: &mut ScenarioWorld) {
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.upgrade();
+        });
+}
+
+#[test]
+fn upgrade_from_a_bare_state_populates_the_built_in_defaults() {
+    let mut world = setup();
+
+    upgrade(&mut world);
+
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            for category in [ActionCategory::Swap, ActionCategory::Liquidity] {
+                assert_eq!(
+                    sc.internal_min_output(category).get(),
+                    BigUint::from(MIN_INTERNAL_OUTPUT)
+                );
+            }
+            assert_eq!(
+                sc.jex_stable_min_multiplier().get(),
+                JEX_STABLE_MIN_MULTIPLIER
+            );
+            assert_eq!(sc.callback_gas_limit().get(), DEFAULT_CALLBACK_GAS);
+            assert_eq!(sc.max_splits_per_token().get(), 0);
+            assert_eq!(sc.min_output_ppm().get(), 0);
+            assert!(!sc.drain_mode().get());
+        });
+}
+
+#[test]
+fn repeated_upgrades_keep_operator_values() {
+    let mut world = setup();
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.internal_min_output(ActionCategory::Swap)
+                .set(BigUint::from(50u64));
+            sc.jex_stable_min_multiplier().set(5);
+            sc.max_splits_per_token().set(3);
+        });
+
+    upgrade(&mut world);
+    upgrade(&mut world);
+
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            assert_eq!(
+                sc.internal_min_output(ActionCategory::Swap).get(),
+                BigUint::from(50u64)
+            );
+            assert_eq!(
+                sc.internal_min_output(ActionCategory::Liquidity).get(),
+                BigUint::from(MIN_INTERNAL_OUTPUT)
+            );
+            assert_eq!(sc.jex_stable_min_multiplier().get(), 5);
+            assert_eq!(sc.max_splits_per_token().get(), 3);
+        });
+}
@@ -0,0 +1,162 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::TxResponseStatus;
+
+use aggregator::constants::FORMAT_VERSION;
+use aggregator::Aggregator;
+use common::*;
+
+/// A -> B swap of the Ppm share in amounts[0] / amounts[1] of the current A balance
+const SWAP_PPM_0: Row = (0, 1, 0, 128, 0, 0);
+const SWAP_PPM_1: Row = (0, 1, 0, 129, 0, 0);
+
+fn validate(
+    world: &mut ScenarioWorld,
+    format_version: u8,
+    token_out: u8,
+    tokens: &[TestTokenIdentifier],
+    amounts: &[u64],
+    rows: &[Row],
+) -> Result<(), TxResponseStatus> {
+    let tokens = tokens.to_vec();
+    let amounts = amounts.to_vec();
+    let rows = rows.to_vec();
+    world
+        .query()
+        .to(AGG)
+        .returns(ReturnsHandledOrError::new())
+        .whitebox(aggregator::contract_obj, |sc| {
+            let valid = sc.validate_path(
+                format_version,
+                token_out,
+                registry_tokens(&tokens),
+                registry_addresses(&[PAIR]),
+                registry_amounts(&amounts),
+                instruction_rows(&rows),
+            );
+            assert!(valid);
+        })
+}
+
+fn validation_error(
+    format_version: u8,
+    token_out: u8,
+    tokens: &[TestTokenIdentifier],
+    amounts: &[u64],
+    rows: &[Row],
+) -> String {
+    let mut world = setup();
+    validate(&mut world, format_version, token_out, tokens, amounts, rows)
+        .expect_err("path should be rejected")
+        .message
+}
+
+#[test]
+fn validate_path_accepts_sequential_ppm_legs() {
+    let mut world = setup();
+
+    // Half of A, then all of what is left, as `xo` executes them
+    validate(
+        &mut world,
+        FORMAT_VERSION,
+        1,
+        &[TOKEN_A, TOKEN_B],
+        &[500_000, 1_000_000],
+        &[SWAP_PPM_0, SWAP_PPM_1],
+    )
+    .unwrap();
+}
+
+#[test]
+fn validate_path_rejects_a_ppm_above_100_percent() {
+    let message = validation_error(
+        FORMAT_VERSION,
+        1,
+        &[TOKEN_A, TOKEN_B],
+        &[1_000_001],
+        &[SWAP_PPM_0],
+    );
+
+    assert_eq!(
+        message,
+        aggregator::errors::ERR_INSTRUCTION_PPM_EXCEEDS_100_PERCENT
+    );
+}
+
+#[test]
+fn validate_path_rejects_an_unsupported_format_version() {
+    let message = validation_error(0, 1, &[TOKEN_A, TOKEN_B], &[], &[(0, 1, 0, 0, 0, 0)]);
+
+    assert!(
+        message.starts_with("D: Unsupported format version 0"),
+        "{message}"
+    );
+}
+
+#[test]
+fn validate_path_rejects_a_duplicate_registry_token() {
+    let message = validation_error(
+        FORMAT_VERSION,
+        1,
+        &[TOKEN_A, TOKEN_A],
+        &[],
+        &[(0, 1, 0, 0, 0, 0)],
+    );
+
+    assert!(message.starts_with("D: "), "{message}");
+    assert!(message.contains("TOKA-000001"), "{message}");
+}
+
+#[test]
+fn validate_path_rejects_an_unresolvable_token_out() {
+    let message = validation_error(FORMAT_VERSION, 7, &[TOKEN_A, TOKEN_B], &[], &[]);
+
+    assert!(message.starts_with("D: "), "{message}");
+}
+
+#[test]
+fn validate_path_rejects_an_unknown_action() {
+    let message = validation_error(
+        FORMAT_VERSION,
+        1,
+        &[TOKEN_A, TOKEN_B],
+        &[],
+        &[(200, 1, 0, 0, 0, 0)],
+    );
+
+    assert_eq!(message, "D: Invalid action type: 200");
+}
+
+#[test]
+fn validate_path_rejects_an_out_of_range_amount_index() {
+    let message = validation_error(FORMAT_VERSION, 1, &[TOKEN_A, TOKEN_B], &[], &[SWAP_PPM_0]);
+
+    assert!(message.starts_with("D: "), "{message}");
+}
+
+#[test]
+fn validate_path_rejects_a_split_above_the_limit() {
+    let mut world = setup();
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            use aggregator::storage::Storage;
+            sc.max_splits_per_token().set(1);
+        });
+
+    let message = validate(
+        &mut world,
+        FORMAT_VERSION,
+        1,
+        &[TOKEN_A, TOKEN_B],
+        &[500_000, 1_000_000],
+        &[SWAP_PPM_0, SWAP_PPM_1],
+    )
+    .unwrap_err()
+    .message;
+
+    assert!(message.starts_with("D: "), "{message}");
+}
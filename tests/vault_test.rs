@@ -0,0 +1,109 @@
+use multiversx_sc_scenario::imports::*;
+
+use aggregator::vault::Vault;
+
+fn token(name: &str) -> TokenId<DebugApi> {
+    TokenId::from(name.as_bytes())
+}
+
+fn amount(value: u64) -> NonZeroBigUint<DebugApi> {
+    BigUint::from(value).into_non_zero().unwrap()
+}
+
+fn sorted_tokens(vault: &Vault<DebugApi>) -> Vec<TokenId<DebugApi>> {
+    vault
+        .get_all_payments_sorted()
+        .iter()
+        .map(|payment| payment.token_identifier.clone())
+        .collect()
+}
+
+#[test]
+fn sorted_payments_ignore_deposit_order() {
+    DebugApi::dummy();
+
+    let mut forward = Vault::<DebugApi>::new();
+    for name in ["TOKA-000001", "TOKB-000002", "TOKC-000003"] {
+        forward.deposit(&token(name), &amount(10));
+    }
+
+    // Same balances reached through a different history, with a token removed and re-added
+    let mut shuffled = Vault::<DebugApi>::new();
+    for name in ["TOKC-000003", "TOKA-000001", "TOKB-000002"] {
+        shuffled.deposit(&token(name), &amount(10));
+    }
+    shuffled.withdraw_all(&token("TOKA-000001"));
+    shuffled.deposit(&token("TOKA-000001"), &amount(10));
+
+    let expected = vec![
+        token("TOKA-000001"),
+        token("TOKB-000002"),
+        token("TOKC-000003"),
+    ];
+    assert_eq!(sorted_tokens(&forward), expected);
+    assert_eq!(sorted_tokens(&shuffled), expected);
+    assert_ne!(
+        shuffled.get_all_payments().get(0).token_identifier,
+        token("TOKA-000001")
+    );
+}
+
+#[test]
+fn sorted_payments_keep_amounts() {
+    DebugApi::dummy();
+
+    let mut vault = Vault::<DebugApi>::new();
+    vault.deposit(&token("WEGLD-000007"), &amount(7));
+    vault.deposit(&token("EGLD-000000"), &amount(3));
+
+    let payments = vault.get_all_payments_sorted();
+    assert_eq!(payments.len(), 2);
+    assert_eq!(payments.get(0).token_identifier, token("EGLD-000000"));
+    assert_eq!(*payments.get(0).amount.as_big_uint(), 3u64);
+    assert_eq!(payments.get(1).token_identifier, token("WEGLD-000007"));
+    assert_eq!(*payments.get(1).amount.as_big_uint(), 7u64);
+}
+
+#[test]
+fn from_payment_seeds_egld_and_esdts_merging_repeated_tokens() {
+    DebugApi::dummy();
+
+    let mut payments = PaymentVec::<DebugApi>::new();
+    for (name, value) in [
+        ("EGLD-000000", 5),
+        ("TOKA-000001", 10),
+        ("TOKB-000002", 20),
+        ("TOKA-000001", 1),
+    ] {
+        payments.push(Payment::new(token(name), 0u64, amount(value)));
+    }
+
+    let vault = Vault::<DebugApi>::from_payment(&payments);
+
+    assert_eq!(vault.len(), 3);
+    assert_eq!(vault.balance_of(&token("EGLD-000000")), 5u64);
+    assert_eq!(vault.balance_of(&token("TOKA-000001")), 11u64);
+    assert_eq!(vault.balance_of(&token("TOKB-000002")), 20u64);
+    assert_eq!(
+        sorted_tokens(&vault),
+        vec![
+            token("EGLD-000000"),
+            token("TOKA-000001"),
+            token("TOKB-000002"),
+        ]
+    );
+}
+
+#[test]
+fn a_prev_result_is_consumed_once() {
+    DebugApi::dummy();
+
+    let mut vault = Vault::<DebugApi>::new();
+    let payment = Payment::new(token("TOKB-000002"), 0, amount(996));
+    vault.set_prev_result(&payment);
+
+    assert!(vault.peek_prev_result().is_some());
+    assert_eq!(vault.take_prev_result(), Some(payment));
+    assert!(vault.take_prev_result().is_none());
+    assert!(vault.peek_prev_result().is_none());
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+
+use aggregator::storage::Storage;
+use aggregator::Aggregator;
+use common::*;
+
+const SWAP_FIXED_0: Row = (0, 1, 0, 2, 0, 0);
+const ZAP_ADD_ALL: Row = (1, 0, 0, 1, 0, 0);
+
+#[test]
+fn estimated_zap_dust_matches_the_dust_of_the_add() {
+    let mut world = setup_pair();
+
+    // Estimated for the vault and pool the add below sees: 1,000 A and 996 B after
+    // the swap moved the pool to 1,001,000 A / 999,004 B, at the 0.3% xExchange fee
+    let mut estimate = (0, 0);
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            let (dust_first, dust_second) = sc
+                .estimate_zap_dust(
+                    BigUint::from(1_000u64),
+                    BigUint::from(996u64),
+                    BigUint::from(1_001_000u64),
+                    BigUint::from(999_004u64),
+                    300,
+                    aggregator::constants::XEXCHANGE_FEE_DENOM,
+                    false,
+                    0,
+                )
+                .into_tuple();
+            estimate = (dust_first.to_u64().unwrap(), dust_second.to_u64().unwrap());
+        });
+
+    xo_call(
+        &mut world,
+        (TOKEN_A, 2_000),
+        0,
+        2,
+        0,
+        &[TOKEN_A, TOKEN_B, LP_TOKEN],
+        &[PAIR],
+        &[1_000],
+        &[SWAP_FIXED_0, ZAP_ADD_ALL],
+    )
+    .unwrap();
+
+    // ZAP dust is booked as admin fees
+    let mut dust = (0, 0);
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            let booked = |token| {
+                sc.admin_fees()
+                    .get(&token_id(token))
+                    .unwrap_or_default()
+                    .to_u64()
+                    .unwrap()
+            };
+            dust = (booked(TOKEN_A), booked(TOKEN_B));
+        });
+    assert!(dust.0 + dust.1 > 0);
+    assert_eq!(estimate, dust);
+}
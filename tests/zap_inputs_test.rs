@@ -0,0 +1,51 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::TxResponseStatus;
+
+use common::*;
+
+const SWAP_A_TO_B: Row = (0, 1, 0, 2, 0, 0);
+
+/// Swap half of 2,000 A to B, then ZAP add both with the given add row
+fn swap_and_add(world: &mut ScenarioWorld, add: Row) -> Result<(), TxResponseStatus> {
+    xo_call(
+        world,
+        (TOKEN_A, 2_000),
+        0,
+        2,
+        0,
+        &[TOKEN_A, TOKEN_B, LP_TOKEN],
+        &[PAIR],
+        &[1_000],
+        &[SWAP_A_TO_B, add],
+    )
+}
+
+#[test]
+fn mis_ordered_zap_inputs_are_lined_up_with_the_pool() {
+    let mut in_order = setup_pair();
+    swap_and_add(&mut in_order, (1, 0, 0, 1, 0, 0)).unwrap();
+
+    // Same add with B listed first
+    let mut reversed = setup_pair();
+    swap_and_add(&mut reversed, (1, 1, 0, 0, 0, 0)).unwrap();
+
+    for world in [&mut in_order, &mut reversed] {
+        world.check_account(USER).esdt_balance(LP_TOKEN, 997u64);
+        world
+            .check_account(USER)
+            .esdt_balance(TOKEN_B, 1_000_000u64);
+    }
+}
+
+#[test]
+fn a_single_input_zap_add_reverts_before_resolving_the_pool() {
+    let mut world = setup_pair();
+
+    let message = swap_and_add(&mut world, (1, 0, 0, 255, 0, 0))
+        .expect_err("path should revert")
+        .message;
+
+    assert_eq!(message, aggregator::errors::ERR_ZAP_TWO_INPUTS);
+}
@@ -0,0 +1,50 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+
+use aggregator::constants::{TOTAL_FEE, XEXCHANGE_FEE_DENOM};
+use aggregator::types::CompactAction;
+use aggregator::zap::MAX_BINARY_SEARCH_ITERATIONS;
+use aggregator::Aggregator;
+use common::*;
+
+#[test]
+fn zap_math_reports_the_compiled_constants() {
+    let mut world = setup();
+
+    world
+        .query()
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            let math = sc.get_zap_math();
+            assert_eq!(
+                math.max_binary_search_iterations,
+                MAX_BINARY_SEARCH_ITERATIONS
+            );
+            let venues: Vec<(u8, bool, u64)> = math
+                .venues
+                .iter()
+                .map(|venue| (venue.action, venue.fee_on_output, venue.fee_denom))
+                .collect();
+            assert_eq!(
+                venues,
+                vec![
+                    (
+                        CompactAction::XExchangeAddLiquidity.to_u8(),
+                        false,
+                        XEXCHANGE_FEE_DENOM
+                    ),
+                    (
+                        CompactAction::OneDexAddLiquidity.to_u8(),
+                        false,
+                        TOTAL_FEE as u64
+                    ),
+                    (
+                        CompactAction::JexAddLiquidity.to_u8(),
+                        true,
+                        TOTAL_FEE as u64
+                    ),
+                ]
+            );
+        });
+}
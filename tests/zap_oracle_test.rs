@@ -0,0 +1,71 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::TxResponseStatus;
+
+use aggregator::config::Config;
+use common::*;
+
+const SWAP_A_TO_B: Row = (0, 1, 0, 2, 0, 0);
+const ADD_ALL: Row = (1, 0, 0, 1, 0, 0);
+
+/// Standard pair that also serves as the oracle, quoting at `safe_price_ppm`, with a 1%
+/// tolerance
+fn setup_oracle(safe_price_ppm: u32) -> ScenarioWorld {
+    let mut world = setup_pair();
+    world
+        .tx()
+        .from(OWNER)
+        .to(PAIR)
+        .whitebox(mock_pair::contract_obj, |sc| {
+            use mock_pair::MockPair;
+            sc.safe_price_ppm().set(safe_price_ppm);
+        });
+    world
+        .tx()
+        .from(OWNER)
+        .to(AGG)
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.set_zap_oracle(PAIR.to_managed_address(), 10_000);
+        });
+    world
+}
+
+fn zap(world: &mut ScenarioWorld) -> Result<(), TxResponseStatus> {
+    xo_call(
+        world,
+        (TOKEN_A, 2_000),
+        0,
+        2,
+        0,
+        &[TOKEN_A, TOKEN_B, LP_TOKEN],
+        &[PAIR],
+        &[1_000],
+        &[SWAP_A_TO_B, ADD_ALL],
+    )
+}
+
+#[test]
+fn zap_passes_when_reserves_agree_with_the_oracle() {
+    // The swap leaves 1,001,000 A against 999,004 B; the oracle prices A just under 1 B
+    let mut world = setup_oracle(998_000);
+
+    zap(&mut world).unwrap();
+
+    world.check_account(USER).esdt_balance(LP_TOKEN, 997u64);
+}
+
+#[test]
+fn zap_reverts_when_reserves_deviate_from_the_oracle() {
+    let mut world = setup_oracle(1_100_000);
+
+    let result = zap(&mut world);
+
+    assert_eq!(
+        result.unwrap_err().message,
+        "E: ZAP reserves deviate from oracle: spot 999004, twap 1101100"
+    );
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_A, 1_000_000u64);
+}
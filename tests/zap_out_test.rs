@@ -0,0 +1,64 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::TxResponseStatus;
+
+use common::*;
+
+/// Remove all the paid LP and receive only A
+const ZAP_OUT_TO_A: Row = (26, 0, 2, 0, 0, 0);
+/// Declare A and B as the outputs of the next call
+const EXPECT_A_B: Row = (32, 0, 1, 255, 255, 255);
+/// Swap the previous result of A to B
+const SWAP_PREV_TO_B: Row = (0, 1, 255, 1, 0, 0);
+
+fn setup_with_lp() -> ScenarioWorld {
+    let mut world = setup_pair();
+    world.set_esdt_balance(USER, LP_TOKEN.as_bytes(), 1_000u64);
+    world
+}
+
+fn zap_out(world: &mut ScenarioWorld, token_out: u8, rows: &[Row]) -> Result<(), TxResponseStatus> {
+    xo_call(
+        world,
+        (LP_TOKEN, 1_000),
+        0,
+        token_out,
+        0,
+        &[TOKEN_A, TOKEN_B, LP_TOKEN],
+        &[PAIR],
+        &[],
+        rows,
+    )
+}
+
+#[test]
+fn zap_out_delivers_only_the_target_token() {
+    let mut world = setup_with_lp();
+
+    zap_out(&mut world, 0, &[ZAP_OUT_TO_A]).unwrap();
+
+    // 1,000 A from the burn plus the 1,000 B leg swapped to 996 A
+    world.check_account(USER).esdt_balance(LP_TOKEN, 0u64);
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_A, 1_001_996u64);
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_B, 1_000_000u64);
+    world.check_account(AGG).esdt_balance(TOKEN_A, 0u64);
+    world.check_account(AGG).esdt_balance(TOKEN_B, 0u64);
+}
+
+#[test]
+fn zap_out_consumes_pending_output_modifiers() {
+    let mut world = setup_with_lp();
+
+    // Declared outputs leaking into the swap would make it revert for a missing A
+    zap_out(&mut world, 1, &[EXPECT_A_B, ZAP_OUT_TO_A, SWAP_PREV_TO_B]).unwrap();
+
+    world.check_account(USER).esdt_balance(LP_TOKEN, 0u64);
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_A, 1_000_000u64);
+}
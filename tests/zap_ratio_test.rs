@@ -0,0 +1,55 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::TxResponseStatus;
+
+use common::*;
+
+const SWAP_A_TO_B: Row = (0, 1, 0, 2, 0, 0);
+/// Ratio bound from amounts[1] with the tolerance in amounts[2]
+const ZAP_RATIO: Row = (31, 1, 0, 2, 0, 255);
+const ADD_ALL: Row = (1, 0, 0, 1, 0, 0);
+
+/// 1 B per A, scaled by RATIO_PRECISION
+const ONE_TO_ONE: u64 = 1_000_000_000_000_000_000;
+
+/// ZAP 2,000 A into LP expecting a 1:1 pool within 1%, with only 1 LP required
+fn zap_expecting_parity(world: &mut ScenarioWorld) -> Result<(), TxResponseStatus> {
+    xo_call(
+        world,
+        (TOKEN_A, 2_000),
+        1,
+        2,
+        0,
+        &[TOKEN_A, TOKEN_B, LP_TOKEN],
+        &[PAIR],
+        &[1_000, ONE_TO_ONE, 10_000],
+        &[SWAP_A_TO_B, ZAP_RATIO, ADD_ALL],
+    )
+}
+
+#[test]
+fn zap_into_a_pool_at_the_expected_ratio_mints() {
+    let mut world = setup_pair();
+
+    zap_expecting_parity(&mut world).unwrap();
+
+    world.check_account(USER).esdt_balance(LP_TOKEN, 997u64);
+}
+
+#[test]
+fn zap_into_an_off_ratio_pool_reverts_despite_meeting_the_lp_minimum() {
+    let mut world = setup();
+    // 1.2 B per A, 20% off the expected parity
+    deploy_pair(&mut world, 1_000_000, 1_200_000, 1_000_000, 300);
+
+    let message = zap_expecting_parity(&mut world).unwrap_err().message;
+
+    assert!(
+        message.starts_with("E: Pool ratio off expected: have 1197"),
+        "{message}"
+    );
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN_A, 1_000_000u64);
+}
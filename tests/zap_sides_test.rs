@@ -0,0 +1,67 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+use multiversx_sc_scenario::scenario_model::TxResponseStatus;
+
+use aggregator::utils::Utils;
+use common::*;
+
+/// Check balanced ZAP amounts of A and B the way the add does
+fn require_zap_sides(world: &mut ScenarioWorld, sides: (u64, u64)) -> Result<(), TxResponseStatus> {
+    world
+        .tx()
+        .from(USER)
+        .to(AGG)
+        .returns(ReturnsHandledOrError::new())
+        .whitebox(aggregator::contract_obj, |sc| {
+            sc.require_zap_sides(
+                &token_id(TOKEN_A),
+                &BigUint::from(sides.0),
+                &token_id(TOKEN_B),
+                &BigUint::from(sides.1),
+            );
+        })
+}
+
+#[test]
+fn an_emptied_first_side_is_rejected_by_name() {
+    let mut world = setup();
+
+    let result = require_zap_sides(&mut world, (0, 996));
+
+    assert_eq!(
+        result.unwrap_err().message,
+        "E: ZAP left an empty side: TOKA-000001 0, TOKB-000002 996"
+    );
+}
+
+#[test]
+fn an_emptied_second_side_is_rejected_by_name() {
+    let mut world = setup();
+
+    let result = require_zap_sides(&mut world, (1_000, 0));
+
+    assert_eq!(
+        result.unwrap_err().message,
+        "E: ZAP left an empty side: TOKA-000001 1000, TOKB-000002 0"
+    );
+}
+
+#[test]
+fn a_zap_with_both_sides_filled_adds_liquidity() {
+    let mut world = setup_pair();
+    require_zap_sides(&mut world, (1, 1)).unwrap();
+
+    xo_call(
+        &mut world,
+        (TOKEN_A, 2_000),
+        1,
+        2,
+        0,
+        &[TOKEN_A, TOKEN_B, LP_TOKEN],
+        &[PAIR],
+        &[1_000],
+        &[(0, 1, 0, 2, 0, 0), (1, 0, 0, 1, 0, 0)],
+    )
+    .unwrap();
+}
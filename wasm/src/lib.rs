@@ -6,9 +6,9 @@
 
 // Init:                                 1
 // Upgrade:                              1
-// Endpoints:                           14
+// Endpoints:                          112
 // Async Callback (empty):               1
-// Total number of exported functions:  17
+// Total number of exported functions: 115
 
 #![no_std]
 
@@ -21,19 +21,117 @@ multiversx_sc_wasm_adapter::endpoints! {
         init => init
         upgrade => upgrade
         xo => aggregate
+        xoScaled => aggregate_scaled
+        xoCallback => aggregate_with_callback
+        xoOwner => aggregate_owner
+        xoSoft => aggregate_soft
+        xoArb => aggregate_arb
+        estimateZapDust => estimate_zap_dust
+        getZapMath => get_zap_math
+        pathOutputTokens => path_output_tokens
+        validatePath => validate_path
+        getPairFee => get_pair_fee
+        previewRemoveLiquidity => preview_remove_liquidity
+        bestCpmmPoolFor => best_cpmm_pool_for
+        getPairOverride => pair_override
+        getMarketOverride => market_override
         getReferralIdCounter => referral_id_counter
         getReferralConfig => referral_config
+        getReferralMinTrade => referral_min_trade
+        getReferralFeeTiers => referral_fee_tiers
+        getReferralCampaign => referral_campaign
+        getReferralAllowedTokens => referral_allowed_tokens
+        getReferralLastActivity => referral_last_activity
+        getReferralStaleEpochs => referral_stale_epochs
+        getCallerDefaultReferral => caller_default_referral
         getStaticFee => static_fee
+        getCallbackWhitelist => callback_whitelist
+        isFreeMode => free_mode
+        isFeeOnInput => fee_on_input
+        isDrainMode => drain_mode
+        isGasProfiling => gas_profiling
+        getStaticFeeOverride => static_fee_override
+        getEgldFeeLiability => egld_fee_liability
+        getEgldLiabilitySeedCursor => egld_liability_seed_cursor
+        isEgldLiabilitySeeded => egld_liability_seeded
+        getActionGasLimit => action_gas_limit
+        getInternalMinOutput => internal_min_output
+        getMaxSplitsPerToken => max_splits_per_token
+        getMinOutputPpm => min_output_ppm
+        getZapOracleAddress => zap_oracle_address
+        getZapOracleTolerance => zap_oracle_tolerance_ppm
+        getMinZapSwap => min_zap_swap
+        getAutoStakeAction => auto_stake_action
+        getJexStableMinMultiplier => jex_stable_min_multiplier
+        getJexStablePoolMinMultiplier => jex_stable_pool_min_multiplier
+        getJexStableMinInputPpm => jex_stable_min_input_ppm
+        isAutoWrapEnabled => auto_wrap_enabled
+        getFeeOnTransferTokens => fee_on_transfer_tokens
+        getTokenDecimals => token_decimals
+        getMigrationSource => migration_source
+        getTotalAggregations => total_aggregations
+        isUniqueCallerTrackingEnabled => unique_caller_tracking
+        getUniqueCallers => unique_callers
+        isKnownCaller => known_caller
         addReferral => add_referral
         setReferralFee => set_referral_fee
+        setReferralFeeBatch => set_referral_fee_batch
         setReferralActive => set_referral_active
         setReferralOwner => set_referral_owner
+        setReferralMinTrade => set_referral_min_trade
+        setReferralFeeTiers => set_referral_fee_tiers
+        setReferralCampaign => set_referral_campaign
+        clearReferralCampaign => clear_referral_campaign
+        addReferralAllowedTokens => add_referral_allowed_tokens
+        removeReferralAllowedTokens => remove_referral_allowed_tokens
+        clearReferralAllowedTokens => clear_referral_allowed_tokens
         setStaticFee => set_static_fee
+        setStaticFeeOverride => set_static_fee_override
+        clearStaticFeeOverride => clear_static_fee_override
+        setPairOverridesBatch => set_pair_overrides_batch
+        setMarketOverridesBatch => set_market_overrides_batch
+        addCallbackTargets => add_callback_targets
+        removeCallbackTargets => remove_callback_targets
+        setFreeMode => set_free_mode
+        setFeeOnInput => set_fee_on_input
+        setDrainMode => set_drain_mode
+        setGasProfiling => set_gas_profiling
+        setAutoWrapEnabled => set_auto_wrap_enabled
+        setUniqueCallerTracking => set_unique_caller_tracking
+        addFeeOnTransferTokens => add_fee_on_transfer_tokens
+        removeFeeOnTransferTokens => remove_fee_on_transfer_tokens
+        setTokenDecimals => set_token_decimals
+        removeTokenDecimals => remove_token_decimals
+        setActionGasLimit => set_action_gas_limit
+        setInternalMinOutput => set_internal_min_output
+        setMaxSplitsPerToken => set_max_splits_per_token
+        setMinOutputPpm => set_min_output_ppm
+        setZapOracle => set_zap_oracle
+        clearZapOracle => clear_zap_oracle
+        setMinZapSwap => set_min_zap_swap
+        setAutoStake => set_auto_stake
+        clearAutoStake => clear_auto_stake
+        setJexStableMinMultiplier => set_jex_stable_min_multiplier
+        setJexStablePoolMinMultiplier => set_jex_stable_pool_min_multiplier
+        setJexStableMinInputPpm => set_jex_stable_min_input_ppm
+        setReferralStaleEpochs => set_referral_stale_epochs
+        setMyReferral => set_my_referral
         claimReferralFees => claim_referral_fees
+        sweepStaleReferral => sweep_stale_referral
         claimAdminFees => claim_admin_fees
+        claimAdminFeesAboveThreshold => claim_admin_fees_above_threshold
+        seedEgldFeeLiability => seed_egld_fee_liability
+        rescueEgld => rescue_egld
+        migrateBalances => migrate_balances
+        setMigrationSource => set_migration_source
+        acceptMigratedAdminFees => accept_migrated_admin_fees
+        acceptMigratedReferral => accept_migrated_referral
         getReferrerBalances => get_referrer_balances
+        getReferrerBalance => get_referrer_balance
         getReferrerTokens => get_referrer_tokens
+        getAllReferrals => get_all_referrals
         getAdminFees => get_admin_fees_view
+        getAdminFee => get_admin_fee
     )
 }
 